@@ -0,0 +1,50 @@
+//! Counts heap allocations made by tokenizing and parsing each workload, which timings alone can hide.
+
+mod workloads;
+
+use emdrive::{parse_statement, tokenize};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+struct CountingAllocator;
+
+static ALLOCATION_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATION_COUNT.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATION_COUNT.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn count_allocations<T>(operation: impl FnOnce() -> T) -> usize {
+    let count_before = ALLOCATION_COUNT.load(Ordering::Relaxed);
+    let result = operation();
+    let count = ALLOCATION_COUNT.load(Ordering::Relaxed) - count_before;
+    drop(result);
+    count
+}
+
+fn main() {
+    println!(
+        "{:<20} {:>12} {:>16}",
+        "workload", "tokenize", "parse_statement"
+    );
+    for (name, statement) in &workloads::all() {
+        let tokenize_count = count_allocations(|| tokenize(statement).unwrap());
+        let parse_count = count_allocations(|| parse_statement(statement).unwrap());
+        println!("{:<20} {:>12} {:>16}", name, tokenize_count, parse_count);
+    }
+}
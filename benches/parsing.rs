@@ -0,0 +1,25 @@
+mod workloads;
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use emdrive::{parse_statement, tokenize};
+
+fn bench_workloads(c: &mut Criterion) {
+    let workloads = workloads::all();
+    let mut tokenize_group = c.benchmark_group("tokenize");
+    for (name, statement) in &workloads {
+        tokenize_group.bench_function(*name, |b| {
+            b.iter(|| tokenize(black_box(statement)).unwrap())
+        });
+    }
+    tokenize_group.finish();
+    let mut parse_group = c.benchmark_group("parse_statement");
+    for (name, statement) in &workloads {
+        parse_group.bench_function(*name, |b| {
+            b.iter(|| parse_statement(black_box(statement)).unwrap())
+        });
+    }
+    parse_group.finish();
+}
+
+criterion_group!(benches, bench_workloads);
+criterion_main!(benches);
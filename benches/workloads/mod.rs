@@ -0,0 +1,73 @@
+//! Statements representative of parse-heavy workloads, shared by the benchmarks.
+
+/// CREATE TABLE with many columns, each with a type and some with modifiers.
+pub fn wide_create_table() -> String {
+    let columns: Vec<String> = (0..100)
+        .map(|index| match index % 4 {
+            0 => format!("column_{} UINT64 DEFAULT {}", index, index),
+            1 => format!("column_{} NULLABLE(STRING)", index),
+            2 => format!("column_{} TIMESTAMP DEFAULT NOW()", index),
+            _ => format!("column_{} UUID UNIQUE", index),
+        })
+        .collect();
+    format!(
+        "CREATE TABLE wide (id UUID PRIMARY KEY, {});",
+        columns.join(", ")
+    )
+}
+
+/// INSERT of a single row with a long list of values.
+pub fn long_insert() -> String {
+    let column_names: Vec<String> = (0..500).map(|index| format!("c{}", index)).collect();
+    let values: Vec<String> = (0..500)
+        .map(|index| match index % 3 {
+            0 => index.to_string(),
+            1 => format!("'value {}'", index),
+            _ => "NULL".to_string(),
+        })
+        .collect();
+    format!(
+        "INSERT INTO long ({}) VALUES ({});",
+        column_names.join(", "),
+        values.join(", ")
+    )
+}
+
+/// SELECT with a WHERE clause combining all kinds of predicates, nested with parentheses.
+pub fn complex_select() -> String {
+    let conditions: Vec<String> = (0..50)
+        .map(|index| match index % 5 {
+            0 => format!("(a = {} OR b IS NULL)", index),
+            1 => format!("c IN ({}, {}, {})", index, index + 1, index + 2),
+            2 => format!("d BETWEEN {} AND {}", index, index * 2),
+            3 => format!("e LIKE '%{}%'", index),
+            _ => format!("(f IS NOT NULL AND (g = {} OR h = 'x'))", index),
+        })
+        .collect();
+    format!(
+        "SELECT a, b, c FROM complex WHERE {} ORDER BY a DESC LIMIT 10;",
+        conditions.join(" AND ")
+    )
+}
+
+/// SELECT of a long column list, with a long IN list and ORDER BY, all being comma-separated without enclosure.
+pub fn wide_select() -> String {
+    let column_names: Vec<String> = (0..300).map(|index| format!("c{}", index)).collect();
+    let values: Vec<String> = (0..300).map(|index| index.to_string()).collect();
+    format!(
+        "SELECT {} FROM wide WHERE c0 IN ({}) ORDER BY {};",
+        column_names.join(", "),
+        values.join(", "),
+        column_names.join(", ")
+    )
+}
+
+/// Named workloads, each stressing a different construct.
+pub fn all() -> [(&'static str, String); 4] {
+    [
+        ("wide_create_table", wide_create_table()),
+        ("long_insert", long_insert()),
+        ("complex_select", complex_select()),
+        ("wide_select", wide_select()),
+    ]
+}
@@ -1,4 +1,10 @@
-use std::{env, fmt, str};
+use serde::Deserialize;
+use std::{env, fmt, fs, io, net, path::Path, str, str::FromStr, thread};
+use thiserror::Error;
+use tracing::Level;
+
+use crate::constructs::components::{NameCharacters, NamePolicy};
+use crate::sql::{Dialect, ParseLimits, TrailingCommas};
 
 /// DBMS configuration.
 #[derive(Debug, Clone)]
@@ -9,6 +15,101 @@ pub struct Config {
     pub tcp_listen_host: String,
     /// TCP interface listener port. `8824` by default.
     pub tcp_listen_port: u16,
+    /// Addresses to listen on, each one an IP address with a port. If any are set, `tcp_listen_host`
+    /// and `tcp_listen_port` are not used. Empty by default.
+    pub tcp_listen_addresses: Vec<net::SocketAddr>,
+    /// Maximum size of an HTTP request body in bytes. `4194304` (4 MiB) by default.
+    pub max_request_bytes: u64,
+    /// Time after which a statement's execution is no longer awaited, in milliseconds. `30000` by default.
+    pub query_timeout_ms: u64,
+    /// Path to the write-ahead log of INSERTs, which makes them survive a crash. If not set, there's no WAL.
+    /// Not set by default.
+    pub wal_path: Option<String>,
+    /// When the write-ahead log is flushed to disk – after every write (`always`) or at most once per
+    /// `wal_sync_interval_ms` (`periodic`). `always` by default.
+    pub wal_sync_mode: WalSyncMode,
+    /// Minimum time between flushes of the write-ahead log in `periodic` sync mode, in milliseconds.
+    /// `1000` by default.
+    pub wal_sync_interval_ms: u64,
+    /// Whether HTTP/1.1 connections are kept alive between requests. `true` by default.
+    pub http_keep_alive: bool,
+    /// Maximum number of connections served at once. Further connections wait for a slot. `1024` by default.
+    pub max_connections: u64,
+    /// Time within which a client must send the whole request head, in milliseconds, otherwise the
+    /// connection is closed. `10000` by default.
+    pub header_read_timeout_ms: u64,
+    /// Path to the PEM file with the TLS certificate (chain). If set along with `tls_private_key_path`,
+    /// the server only accepts TLS connections. Not set by default.
+    pub tls_certificate_path: Option<String>,
+    /// Path to the PEM file with the private key of the TLS certificate. Not set by default.
+    pub tls_private_key_path: Option<String>,
+    /// Token that requests to the SQL endpoint must carry in an `Authorization: Bearer <token>` header.
+    /// If not set, no authentication is required. Not set by default.
+    pub auth_token: Option<String>,
+    /// Origins (e.g. `https://admin.example.com`) from which browsers may query the server, or `*` for any.
+    /// Empty by default, which means no cross-origin requests.
+    pub cors_allowed_origins: Vec<String>,
+    /// SQL dialect of queries – `strict` for canonical type names only, or `aliases` for ANSI-style
+    /// type names (e.g. `BIGINT`) as well. `strict` by default.
+    pub sql_dialect: Dialect,
+    /// Whether a list in parentheses may end with a comma – `strict` for no, or `lenient` for yes.
+    /// `strict` by default.
+    pub sql_trailing_commas: TrailingCommas,
+    /// Maximum number of tokens in a query, across all of its statements. `100000` by default.
+    pub max_query_tokens: u64,
+    /// Maximum depth of nested parentheses in a query. `64` by default.
+    pub max_query_nesting_depth: u64,
+    /// Maximum number of characters in names of tables and columns, at most `63`. `63` by default.
+    pub max_name_length: u64,
+    /// Characters that names of tables and columns may consist of – `lenient` for any but control characters
+    /// in quoted identifiers, or `strict` for letters, digits and underscores only. `lenient` by default.
+    pub name_characters: NameCharacters,
+    /// How long shutdown waits for in-flight requests to finish before abandoning them, in milliseconds.
+    /// `30000` by default.
+    pub shutdown_drain_timeout_ms: u64,
+    /// Number of worker threads of the runtime serving requests. The number of CPUs by default.
+    pub worker_threads: u64,
+    /// Lowest level of log messages output – `error`, `warn`, `info`, `debug` or `trace`. `debug` by default.
+    pub log_level: Level,
+    /// Format of log output – `human` for readable lines, or `json` for one JSON object per line, as log
+    /// pipelines ingest. `human` by default.
+    pub log_format: LogFormat,
+}
+
+/// Policy of flushing the write-ahead log to disk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WalSyncMode {
+    /// Flush after every write, so that no acknowledged write is lost.
+    Always,
+    /// Flush at most once per interval, trading durability of the latest writes for speed.
+    Periodic,
+}
+
+impl fmt::Display for WalSyncMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Always => write!(f, "always"),
+            Self::Periodic => write!(f, "periodic"),
+        }
+    }
+}
+
+/// Format of log output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    /// Readable lines, e.g. for a terminal.
+    Human,
+    /// One JSON object per line, e.g. for ingestion into a log pipeline.
+    Json,
+}
+
+impl fmt::Display for LogFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Human => write!(f, "human"),
+            Self::Json => write!(f, "json"),
+        }
+    }
 }
 
 impl Default for Config {
@@ -17,6 +118,30 @@ impl Default for Config {
             data_directory: "/var/lib/emdrive/data".to_string(),
             tcp_listen_host: "127.0.0.1".to_string(),
             tcp_listen_port: 8824,
+            tcp_listen_addresses: Vec::new(),
+            max_request_bytes: 4 * 1024 * 1024,
+            query_timeout_ms: 30_000,
+            wal_path: None,
+            wal_sync_mode: WalSyncMode::Always,
+            wal_sync_interval_ms: 1000,
+            http_keep_alive: true,
+            max_connections: 1024,
+            header_read_timeout_ms: 10_000,
+            tls_certificate_path: None,
+            tls_private_key_path: None,
+            auth_token: None,
+            cors_allowed_origins: Vec::new(),
+            sql_dialect: Dialect::Strict,
+            sql_trailing_commas: TrailingCommas::Strict,
+            max_query_tokens: 100_000,
+            max_query_nesting_depth: 64,
+            max_name_length: NamePolicy::MAX_SAFE_LENGTH as u64,
+            name_characters: NameCharacters::Lenient,
+            shutdown_drain_timeout_ms: 30_000,
+            worker_threads: thread::available_parallelism()
+                .map_or(1, |cpu_count| cpu_count.get() as u64),
+            log_level: Level::DEBUG,
+            log_format: LogFormat::Human,
         }
     }
 }
@@ -25,49 +150,1001 @@ impl fmt::Display for Config {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "{}={:?}\n{}={:?}\n{}={:?}",
+            "{}={:?}\n{}={:?}\n{}={:?}\n{}={:?}\n{}={:?}\n{}={:?}\n{}={:?}\n{}={:?}\n{}={:?}\n{}={:?}\n{}={:?}\n{}={:?}\n{}={:?}\n{}={:?}\n{}={:?}\n{}={:?}\n{}={:?}\n{}={:?}\n{}={:?}\n{}={:?}\n{}={:?}\n{}={:?}\n{}={:?}\n{}={:?}\n{}={:?}\n{}={:?}",
             envify_config_key("data_directory"),
             self.data_directory,
             envify_config_key("tcp_listen_host"),
             self.tcp_listen_host,
             envify_config_key("tcp_listen_port"),
-            self.tcp_listen_port
+            self.tcp_listen_port,
+            envify_config_key("tcp_listen_addresses"),
+            self.tcp_listen_addresses
+                .iter()
+                .map(|address| address.to_string())
+                .collect::<Vec<_>>()
+                .join(","),
+            envify_config_key("max_request_bytes"),
+            self.max_request_bytes,
+            envify_config_key("query_timeout_ms"),
+            self.query_timeout_ms,
+            envify_config_key("wal_path"),
+            self.wal_path.as_deref().unwrap_or_default(),
+            envify_config_key("wal_sync_mode"),
+            self.wal_sync_mode.to_string(),
+            envify_config_key("wal_sync_interval_ms"),
+            self.wal_sync_interval_ms,
+            envify_config_key("http_keep_alive"),
+            self.http_keep_alive,
+            envify_config_key("max_connections"),
+            self.max_connections,
+            envify_config_key("header_read_timeout_ms"),
+            self.header_read_timeout_ms,
+            envify_config_key("tls_certificate_path"),
+            self.tls_certificate_path.as_deref().unwrap_or_default(),
+            envify_config_key("tls_private_key_path"),
+            self.tls_private_key_path.as_deref().unwrap_or_default(),
+            envify_config_key("auth_token"),
+            // The token is a secret, so it's only shown whether it's set
+            if self.auth_token.is_some() { "<redacted>" } else { "" },
+            envify_config_key("cors_allowed_origins"),
+            self.cors_allowed_origins.join(","),
+            envify_config_key("sql_dialect"),
+            self.sql_dialect.to_string(),
+            envify_config_key("sql_trailing_commas"),
+            self.sql_trailing_commas.to_string(),
+            envify_config_key("max_query_tokens"),
+            self.max_query_tokens,
+            envify_config_key("max_query_nesting_depth"),
+            self.max_query_nesting_depth,
+            envify_config_key("max_name_length"),
+            self.max_name_length,
+            envify_config_key("name_characters"),
+            self.name_characters.to_string(),
+            envify_config_key("shutdown_drain_timeout_ms"),
+            self.shutdown_drain_timeout_ms,
+            envify_config_key("worker_threads"),
+            self.worker_threads,
+            envify_config_key("log_level"),
+            self.log_level.to_string().to_lowercase(),
+            envify_config_key("log_format"),
+            self.log_format.to_string()
         )
     }
 }
 
+/// Reason a configuration couldn't be loaded.
+#[derive(Error, Debug)]
+pub enum ConfigError {
+    #[error("Failed to read config file {path}: {source}")]
+    Io { path: String, source: io::Error },
+    #[error("Failed to parse config file {path}: {source}")]
+    Parse {
+        path: String,
+        source: toml::de::Error,
+    },
+    #[error("Invalid {key} value {value:?}: {reason}")]
+    InvalidValue {
+        key: String,
+        value: String,
+        reason: String,
+    },
+}
+
+/// Config file contents. All settings are optional, falling back to their defaults.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConfigFile {
+    data_directory: Option<String>,
+    tcp_listen_host: Option<String>,
+    // Deserialized as a wider integer, so that out-of-range ports can be reported clearly
+    tcp_listen_port: Option<i64>,
+    tcp_listen_addresses: Option<Vec<String>>,
+    max_request_bytes: Option<i64>,
+    query_timeout_ms: Option<i64>,
+    wal_path: Option<String>,
+    wal_sync_mode: Option<String>,
+    wal_sync_interval_ms: Option<i64>,
+    http_keep_alive: Option<bool>,
+    max_connections: Option<i64>,
+    header_read_timeout_ms: Option<i64>,
+    tls_certificate_path: Option<String>,
+    tls_private_key_path: Option<String>,
+    auth_token: Option<String>,
+    cors_allowed_origins: Option<Vec<String>>,
+    sql_dialect: Option<String>,
+    sql_trailing_commas: Option<String>,
+    max_query_tokens: Option<i64>,
+    max_query_nesting_depth: Option<i64>,
+    max_name_length: Option<i64>,
+    name_characters: Option<String>,
+    shutdown_drain_timeout_ms: Option<i64>,
+    worker_threads: Option<i64>,
+    log_level: Option<String>,
+    log_format: Option<String>,
+}
+
 impl Config {
-    pub fn from_env() -> Config {
+    /// Load config from a TOML file. Settings omitted in the file get their default values.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Config, ConfigError> {
+        let path = path.as_ref();
+        let contents = fs::read_to_string(path).map_err(|source| ConfigError::Io {
+            path: path.display().to_string(),
+            source,
+        })?;
+        let file: ConfigFile = toml::from_str(&contents).map_err(|source| ConfigError::Parse {
+            path: path.display().to_string(),
+            source,
+        })?;
         let default = Config::default();
-        Config {
-            data_directory: get_env_or("data_directory", default.data_directory),
-            tcp_listen_host: get_env_or("tcp_listen_host", default.tcp_listen_host),
-            tcp_listen_port: get_env_cast_or("tcp_listen_port", default.tcp_listen_port),
+        Ok(Config {
+            data_directory: file.data_directory.unwrap_or(default.data_directory),
+            tcp_listen_host: match file.tcp_listen_host {
+                Some(host) => validate_host("tcp_listen_host", host)?,
+                None => default.tcp_listen_host,
+            },
+            tcp_listen_port: match file.tcp_listen_port {
+                Some(port) => validate_port("tcp_listen_port", &port.to_string())?,
+                None => default.tcp_listen_port,
+            },
+            tcp_listen_addresses: match file.tcp_listen_addresses {
+                Some(addresses) => addresses
+                    .into_iter()
+                    .map(|address| validate_socket_address("tcp_listen_addresses", address))
+                    .collect::<Result<_, _>>()?,
+                None => default.tcp_listen_addresses,
+            },
+            max_request_bytes: match file.max_request_bytes {
+                Some(bytes) => validate_positive_integer("max_request_bytes", &bytes.to_string())?,
+                None => default.max_request_bytes,
+            },
+            query_timeout_ms: match file.query_timeout_ms {
+                Some(ms) => validate_positive_integer("query_timeout_ms", &ms.to_string())?,
+                None => default.query_timeout_ms,
+            },
+            wal_path: file.wal_path.or(default.wal_path),
+            wal_sync_mode: match file.wal_sync_mode {
+                Some(mode) => validate_wal_sync_mode("wal_sync_mode", mode)?,
+                None => default.wal_sync_mode,
+            },
+            wal_sync_interval_ms: match file.wal_sync_interval_ms {
+                Some(ms) => validate_positive_integer("wal_sync_interval_ms", &ms.to_string())?,
+                None => default.wal_sync_interval_ms,
+            },
+            http_keep_alive: file.http_keep_alive.unwrap_or(default.http_keep_alive),
+            max_connections: match file.max_connections {
+                Some(count) => validate_positive_integer("max_connections", &count.to_string())?,
+                None => default.max_connections,
+            },
+            header_read_timeout_ms: match file.header_read_timeout_ms {
+                Some(ms) => validate_positive_integer("header_read_timeout_ms", &ms.to_string())?,
+                None => default.header_read_timeout_ms,
+            },
+            tls_certificate_path: file.tls_certificate_path.or(default.tls_certificate_path),
+            tls_private_key_path: file.tls_private_key_path.or(default.tls_private_key_path),
+            auth_token: match file.auth_token {
+                Some(token) => Some(validate_auth_token("auth_token", token)?),
+                None => default.auth_token,
+            },
+            cors_allowed_origins: match file.cors_allowed_origins {
+                Some(origins) => origins
+                    .into_iter()
+                    .map(|origin| validate_cors_origin("cors_allowed_origins", origin))
+                    .collect::<Result<_, _>>()?,
+                None => default.cors_allowed_origins,
+            },
+            sql_dialect: match file.sql_dialect {
+                Some(dialect) => validate_dialect("sql_dialect", dialect)?,
+                None => default.sql_dialect,
+            },
+            sql_trailing_commas: match file.sql_trailing_commas {
+                Some(mode) => validate_trailing_commas("sql_trailing_commas", mode)?,
+                None => default.sql_trailing_commas,
+            },
+            max_query_tokens: match file.max_query_tokens {
+                Some(count) => validate_positive_integer("max_query_tokens", &count.to_string())?,
+                None => default.max_query_tokens,
+            },
+            max_query_nesting_depth: match file.max_query_nesting_depth {
+                Some(depth) => {
+                    validate_nesting_depth("max_query_nesting_depth", &depth.to_string())?
+                }
+                None => default.max_query_nesting_depth,
+            },
+            max_name_length: match file.max_name_length {
+                Some(length) => validate_name_length("max_name_length", &length.to_string())?,
+                None => default.max_name_length,
+            },
+            name_characters: match file.name_characters {
+                Some(policy) => validate_name_characters("name_characters", policy)?,
+                None => default.name_characters,
+            },
+            shutdown_drain_timeout_ms: match file.shutdown_drain_timeout_ms {
+                Some(ms) => {
+                    validate_positive_integer("shutdown_drain_timeout_ms", &ms.to_string())?
+                }
+                None => default.shutdown_drain_timeout_ms,
+            },
+            worker_threads: match file.worker_threads {
+                Some(count) => validate_positive_integer("worker_threads", &count.to_string())?,
+                None => default.worker_threads,
+            },
+            log_level: match file.log_level {
+                Some(level) => validate_log_level("log_level", level)?,
+                None => default.log_level,
+            },
+            log_format: match file.log_format {
+                Some(format) => validate_log_format("log_format", format)?,
+                None => default.log_format,
+            },
+        })
+    }
+
+    /// Load config from the environment, with default values for settings that aren't set.
+    pub fn from_env() -> Result<Config, ConfigError> {
+        Config::default().with_env_overrides()
+    }
+
+    /// Load config from the TOML file if there's one, with the environment taking precedence over it.
+    pub fn load(path: Option<&Path>) -> Result<Config, ConfigError> {
+        match path {
+            Some(path) => Config::from_file(path)?,
+            None => Config::default(),
         }
+        .with_env_overrides()
+    }
+
+    /// Override settings with values from the environment, where set.
+    pub fn with_env_overrides(mut self) -> Result<Config, ConfigError> {
+        if let Some(data_directory) = get_env("data_directory")? {
+            self.data_directory = data_directory;
+        }
+        if let Some(host) = get_env("tcp_listen_host")? {
+            self.tcp_listen_host = validate_host(&envify_config_key("tcp_listen_host"), host)?;
+        }
+        if let Some(port) = get_env("tcp_listen_port")? {
+            self.tcp_listen_port = validate_port(&envify_config_key("tcp_listen_port"), &port)?;
+        }
+        // Multiple addresses are separated with commas in the environment
+        if let Some(addresses) = get_env("tcp_listen_addresses")? {
+            self.tcp_listen_addresses = addresses
+                .split(',')
+                .map(str::trim)
+                .filter(|address| !address.is_empty())
+                .map(|address| {
+                    validate_socket_address(
+                        &envify_config_key("tcp_listen_addresses"),
+                        address.to_string(),
+                    )
+                })
+                .collect::<Result<_, _>>()?;
+        }
+        if let Some(bytes) = get_env("max_request_bytes")? {
+            self.max_request_bytes =
+                validate_positive_integer(&envify_config_key("max_request_bytes"), &bytes)?;
+        }
+        if let Some(ms) = get_env("query_timeout_ms")? {
+            self.query_timeout_ms =
+                validate_positive_integer(&envify_config_key("query_timeout_ms"), &ms)?;
+        }
+        // An empty value disables the WAL
+        if let Some(path) = get_env("wal_path")? {
+            self.wal_path = Some(path).filter(|path| !path.is_empty());
+        }
+        if let Some(mode) = get_env("wal_sync_mode")? {
+            self.wal_sync_mode = validate_wal_sync_mode(&envify_config_key("wal_sync_mode"), mode)?;
+        }
+        if let Some(ms) = get_env("wal_sync_interval_ms")? {
+            self.wal_sync_interval_ms =
+                validate_positive_integer(&envify_config_key("wal_sync_interval_ms"), &ms)?;
+        }
+        if let Some(keep_alive) = get_env("http_keep_alive")? {
+            self.http_keep_alive =
+                validate_boolean(&envify_config_key("http_keep_alive"), keep_alive)?;
+        }
+        if let Some(count) = get_env("max_connections")? {
+            self.max_connections =
+                validate_positive_integer(&envify_config_key("max_connections"), &count)?;
+        }
+        if let Some(ms) = get_env("header_read_timeout_ms")? {
+            self.header_read_timeout_ms =
+                validate_positive_integer(&envify_config_key("header_read_timeout_ms"), &ms)?;
+        }
+        // Empty values disable TLS
+        if let Some(path) = get_env("tls_certificate_path")? {
+            self.tls_certificate_path = Some(path).filter(|path| !path.is_empty());
+        }
+        if let Some(path) = get_env("tls_private_key_path")? {
+            self.tls_private_key_path = Some(path).filter(|path| !path.is_empty());
+        }
+        // An empty value disables authentication
+        if let Some(token) = get_env("auth_token")? {
+            self.auth_token = if token.is_empty() {
+                None
+            } else {
+                Some(validate_auth_token(
+                    &envify_config_key("auth_token"),
+                    token,
+                )?)
+            };
+        }
+        // Multiple origins are separated with commas in the environment
+        if let Some(origins) = get_env("cors_allowed_origins")? {
+            self.cors_allowed_origins = origins
+                .split(',')
+                .map(str::trim)
+                .filter(|origin| !origin.is_empty())
+                .map(|origin| {
+                    validate_cors_origin(
+                        &envify_config_key("cors_allowed_origins"),
+                        origin.to_string(),
+                    )
+                })
+                .collect::<Result<_, _>>()?;
+        }
+        if let Some(dialect) = get_env("sql_dialect")? {
+            self.sql_dialect = validate_dialect(&envify_config_key("sql_dialect"), dialect)?;
+        }
+        if let Some(mode) = get_env("sql_trailing_commas")? {
+            self.sql_trailing_commas =
+                validate_trailing_commas(&envify_config_key("sql_trailing_commas"), mode)?;
+        }
+        if let Some(count) = get_env("max_query_tokens")? {
+            self.max_query_tokens =
+                validate_positive_integer(&envify_config_key("max_query_tokens"), &count)?;
+        }
+        if let Some(depth) = get_env("max_query_nesting_depth")? {
+            self.max_query_nesting_depth =
+                validate_nesting_depth(&envify_config_key("max_query_nesting_depth"), &depth)?;
+        }
+        if let Some(length) = get_env("max_name_length")? {
+            self.max_name_length =
+                validate_name_length(&envify_config_key("max_name_length"), &length)?;
+        }
+        if let Some(policy) = get_env("name_characters")? {
+            self.name_characters =
+                validate_name_characters(&envify_config_key("name_characters"), policy)?;
+        }
+        if let Some(ms) = get_env("shutdown_drain_timeout_ms")? {
+            self.shutdown_drain_timeout_ms =
+                validate_positive_integer(&envify_config_key("shutdown_drain_timeout_ms"), &ms)?;
+        }
+        if let Some(count) = get_env("worker_threads")? {
+            self.worker_threads =
+                validate_positive_integer(&envify_config_key("worker_threads"), &count)?;
+        }
+        if let Some(level) = get_env("log_level")? {
+            self.log_level = validate_log_level(&envify_config_key("log_level"), level)?;
+        }
+        if let Some(format) = get_env("log_format")? {
+            self.log_format = validate_log_format(&envify_config_key("log_format"), format)?;
+        }
+        Ok(self)
+    }
+
+    /// Limits of parsing queries, as configured.
+    pub fn parse_limits(&self) -> ParseLimits {
+        ParseLimits {
+            max_token_count: usize::try_from(self.max_query_tokens).unwrap_or(usize::MAX),
+            max_nesting_depth: usize::try_from(self.max_query_nesting_depth).unwrap_or(usize::MAX),
+        }
+    }
+
+    /// Rules for names of tables and columns, as configured.
+    pub fn name_policy(&self) -> NamePolicy {
+        NamePolicy {
+            max_length: usize::try_from(self.max_name_length).unwrap_or(usize::MAX),
+            characters: self.name_characters,
+        }
+    }
+
+    /// Addresses on which the server listens – `tcp_listen_addresses` if any are set, otherwise
+    /// `tcp_listen_host` with `tcp_listen_port`.
+    pub fn listen_addresses(&self) -> Vec<net::SocketAddr> {
+        if !self.tcp_listen_addresses.is_empty() {
+            return self.tcp_listen_addresses.clone();
+        }
+        vec![net::SocketAddr::new(
+            net::IpAddr::from_str(&self.tcp_listen_host).unwrap(),
+            self.tcp_listen_port,
+        )]
     }
 }
 
+/// Make sure that the token can be sent in an HTTP header.
+fn validate_auth_token(key: &str, token: String) -> Result<String, ConfigError> {
+    if !token.is_empty() && token.bytes().all(|byte| byte.is_ascii_graphic()) {
+        return Ok(token);
+    }
+    Err(ConfigError::InvalidValue {
+        key: key.to_string(),
+        // Not echoing the secret back
+        value: "<redacted>".to_string(),
+        reason: "not a non-empty string of visible ASCII characters".to_string(),
+    })
+}
+
+/// Make sure that the origin is `*` or a scheme with a host, e.g. `https://example.com:8080`.
+/// Browsers send origins without a path, so one with a trailing slash would never match.
+fn validate_cors_origin(key: &str, origin: String) -> Result<String, ConfigError> {
+    let is_valid = origin == "*"
+        || origin.split_once("://").is_some_and(|(scheme, host)| {
+            !scheme.is_empty()
+                && !host.is_empty()
+                && !host.contains('/')
+                && origin.bytes().all(|byte| byte.is_ascii_graphic())
+        });
+    if is_valid {
+        return Ok(origin);
+    }
+    Err(ConfigError::InvalidValue {
+        key: key.to_string(),
+        value: origin,
+        reason: "not `*` or an origin such as `https://example.com`".to_string(),
+    })
+}
+
+/// Make sure that the host is an IP address.
+fn validate_host(key: &str, host: String) -> Result<String, ConfigError> {
+    match net::IpAddr::from_str(&host) {
+        Ok(_) => Ok(host),
+        Err(_) => Err(ConfigError::InvalidValue {
+            key: key.to_string(),
+            value: host,
+            reason: "not an IP address".to_string(),
+        }),
+    }
+}
+
+/// Make sure that the address is an IP address with a port, e.g. `127.0.0.1:8824` or `[::1]:8824`.
+fn validate_socket_address(key: &str, address: String) -> Result<net::SocketAddr, ConfigError> {
+    match net::SocketAddr::from_str(&address) {
+        Ok(socket_address) => Ok(socket_address),
+        Err(_) => Err(ConfigError::InvalidValue {
+            key: key.to_string(),
+            value: address,
+            reason: "not an IP address with a port".to_string(),
+        }),
+    }
+}
+
+/// Make sure that the port is a number between 1 and 65535.
+fn validate_port(key: &str, port: &str) -> Result<u16, ConfigError> {
+    match u16::from_str(port) {
+        Ok(port) if port > 0 => Ok(port),
+        _ => Err(ConfigError::InvalidValue {
+            key: key.to_string(),
+            value: port.to_string(),
+            reason: "not a port number between 1 and 65535".to_string(),
+        }),
+    }
+}
+
+/// Make sure that the value is an integer greater than 0.
+fn validate_positive_integer(key: &str, value: &str) -> Result<u64, ConfigError> {
+    match u64::from_str(value) {
+        Ok(value) if value > 0 => Ok(value),
+        _ => Err(ConfigError::InvalidValue {
+            key: key.to_string(),
+            value: value.to_string(),
+            reason: "not a positive integer".to_string(),
+        }),
+    }
+}
+
+/// Make sure that the value is an integer between 1 and the depth that's safe to evaluate.
+fn validate_nesting_depth(key: &str, value: &str) -> Result<u64, ConfigError> {
+    match u64::from_str(value) {
+        Ok(depth) if depth > 0 && depth <= ParseLimits::MAX_SAFE_NESTING_DEPTH as u64 => Ok(depth),
+        _ => Err(ConfigError::InvalidValue {
+            key: key.to_string(),
+            value: value.to_string(),
+            reason: format!(
+                "not an integer between 1 and {}",
+                ParseLimits::MAX_SAFE_NESTING_DEPTH
+            ),
+        }),
+    }
+}
+
+/// Make sure that the value is an integer between 1 and the name length that fits in a directory name.
+fn validate_name_length(key: &str, value: &str) -> Result<u64, ConfigError> {
+    match u64::from_str(value) {
+        Ok(length) if length > 0 && length <= NamePolicy::MAX_SAFE_LENGTH as u64 => Ok(length),
+        _ => Err(ConfigError::InvalidValue {
+            key: key.to_string(),
+            value: value.to_string(),
+            reason: format!(
+                "not an integer between 1 and {}",
+                NamePolicy::MAX_SAFE_LENGTH
+            ),
+        }),
+    }
+}
+
+/// Make sure that the value is `true` or `false`.
+fn validate_boolean(key: &str, value: String) -> Result<bool, ConfigError> {
+    match value.to_lowercase().as_str() {
+        "true" => Ok(true),
+        "false" => Ok(false),
+        _ => Err(ConfigError::InvalidValue {
+            key: key.to_string(),
+            value,
+            reason: "not `true` or `false`".to_string(),
+        }),
+    }
+}
+
+/// Make sure that the WAL sync mode is one of the supported ones.
+fn validate_wal_sync_mode(key: &str, mode: String) -> Result<WalSyncMode, ConfigError> {
+    match mode.to_lowercase().as_str() {
+        "always" => Ok(WalSyncMode::Always),
+        "periodic" => Ok(WalSyncMode::Periodic),
+        _ => Err(ConfigError::InvalidValue {
+            key: key.to_string(),
+            value: mode,
+            reason: "not `always` or `periodic`".to_string(),
+        }),
+    }
+}
+
+/// Make sure that the log level is one of the supported ones.
+fn validate_log_level(key: &str, level: String) -> Result<Level, ConfigError> {
+    // Levels can be parsed from numbers too, but only names are documented
+    match level.to_lowercase().as_str() {
+        "error" | "warn" | "info" | "debug" | "trace" => Ok(Level::from_str(&level).unwrap()),
+        _ => Err(ConfigError::InvalidValue {
+            key: key.to_string(),
+            value: level,
+            reason: "not one of: error, warn, info, debug, trace".to_string(),
+        }),
+    }
+}
+
+/// Make sure that the log format is one of the supported ones.
+fn validate_log_format(key: &str, format: String) -> Result<LogFormat, ConfigError> {
+    match format.to_lowercase().as_str() {
+        "human" => Ok(LogFormat::Human),
+        "json" => Ok(LogFormat::Json),
+        _ => Err(ConfigError::InvalidValue {
+            key: key.to_string(),
+            value: format,
+            reason: "not `human` or `json`".to_string(),
+        }),
+    }
+}
+
+/// Make sure that the dialect is one of the supported ones.
+fn validate_dialect(key: &str, dialect: String) -> Result<Dialect, ConfigError> {
+    Dialect::from_name(&dialect).ok_or_else(|| ConfigError::InvalidValue {
+        key: key.to_string(),
+        value: dialect,
+        reason: format!("not one of: {}", Dialect::SUPPORTED_NAMES),
+    })
+}
+
+/// Make sure that the trailing comma mode is one of the supported ones.
+fn validate_trailing_commas(key: &str, mode: String) -> Result<TrailingCommas, ConfigError> {
+    TrailingCommas::from_name(&mode).ok_or_else(|| ConfigError::InvalidValue {
+        key: key.to_string(),
+        value: mode,
+        reason: format!("not one of: {}", TrailingCommas::SUPPORTED_NAMES),
+    })
+}
+
+/// Make sure that the name character policy is one of the supported ones.
+fn validate_name_characters(key: &str, policy: String) -> Result<NameCharacters, ConfigError> {
+    NameCharacters::from_name(&policy).ok_or_else(|| ConfigError::InvalidValue {
+        key: key.to_string(),
+        value: policy,
+        reason: format!("not one of: {}", NameCharacters::SUPPORTED_NAMES),
+    })
+}
+
 // Format internal config key to environment variable name.
 fn envify_config_key(key: &str) -> String {
     format!("EMDRIVE_{}", &key.to_uppercase())
 }
 
-fn get_env(key: &str) -> Result<String, env::VarError> {
-    env::var(envify_config_key(key))
+fn get_env(key: &str) -> Result<Option<String>, ConfigError> {
+    let env_key = envify_config_key(key);
+    match env::var(&env_key) {
+        Ok(value) => Ok(Some(value)),
+        Err(env::VarError::NotPresent) => Ok(None),
+        Err(env::VarError::NotUnicode(value)) => Err(ConfigError::InvalidValue {
+            key: env_key,
+            value: value.to_string_lossy().into(),
+            reason: "not valid Unicode".to_string(),
+        }),
+    }
 }
 
-fn get_env_or(key: &str, default: String) -> String {
-    get_env(key).unwrap_or(default)
-}
+#[cfg(test)]
+mod config_tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use rand::distributions::Alphanumeric;
+    use rand::{thread_rng, Rng};
+    use std::path::PathBuf;
+    use std::sync::{Mutex, MutexGuard};
+
+    /// The environment is shared by all tests, so tests that modify it must not run concurrently.
+    static ENV_LOCK: Mutex<()> = Mutex::new(());
+
+    /// Sets environment variables for the duration of a test, restoring previous values on drop.
+    struct EnvGuard {
+        previous_values: Vec<(String, Option<String>)>,
+        _lock: MutexGuard<'static, ()>,
+    }
+
+    impl EnvGuard {
+        fn set(variables: &[(&str, &str)]) -> Self {
+            let lock = ENV_LOCK
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            let previous_values = variables
+                .iter()
+                .map(|(key, value)| {
+                    let previous_value = env::var(key).ok();
+                    env::set_var(key, value);
+                    (key.to_string(), previous_value)
+                })
+                .collect();
+            EnvGuard {
+                previous_values,
+                _lock: lock,
+            }
+        }
+    }
+
+    impl Drop for EnvGuard {
+        fn drop(&mut self) {
+            for (key, previous_value) in &self.previous_values {
+                match previous_value {
+                    Some(value) => env::set_var(key, value),
+                    None => env::remove_var(key),
+                }
+            }
+        }
+    }
+
+    /// Write the contents to a uniquely named file in the temporary directory.
+    fn write_temp_config(contents: &str) -> PathBuf {
+        let file_name: String = thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(10)
+            .map(char::from)
+            .collect();
+        let path = Path::new(env!("TMPDIR")).join(format!("{}.toml", file_name));
+        fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn loading_valid_file_works() {
+        let path = write_temp_config(
+            r#"
+tcp_listen_host = "0.0.0.0"
+tcp_listen_port = 9000
+"#,
+        );
+        let config = Config::from_file(&path).unwrap();
+        assert_eq!(config.tcp_listen_host, "0.0.0.0");
+        assert_eq!(config.tcp_listen_port, 9000);
+        assert_eq!(config.data_directory, Config::default().data_directory);
+    }
+
+    #[test]
+    fn loading_file_with_bad_port_fails() {
+        let path = write_temp_config("tcp_listen_port = 70000\n");
+        match Config::from_file(&path).unwrap_err() {
+            ConfigError::InvalidValue { key, value, .. } => {
+                assert_eq!(key, "tcp_listen_port");
+                assert_eq!(value, "70000");
+            }
+            error => panic!("Unexpected error: {}", error),
+        }
+    }
+
+    #[test]
+    fn loading_file_with_bad_host_fails() {
+        let path = write_temp_config("tcp_listen_host = \"localhost\"\n");
+        match Config::from_file(&path).unwrap_err() {
+            ConfigError::InvalidValue { key, .. } => assert_eq!(key, "tcp_listen_host"),
+            error => panic!("Unexpected error: {}", error),
+        }
+    }
 
-fn get_env_cast_or<T: str::FromStr + fmt::Display>(key: &str, default: T) -> T {
-    let value_raw = get_env(key);
-    if let Ok(value_raw) = value_raw {
-        match T::from_str(&value_raw) {
-            Ok(value) => value,
-            Err(_) => panic!("{} is not a valid {} value!", value_raw, key),
+    #[test]
+    fn loading_missing_file_fails() {
+        let path = Path::new(env!("TMPDIR")).join("this-config-does-not-exist.toml");
+        assert!(matches!(
+            Config::from_file(&path).unwrap_err(),
+            ConfigError::Io { .. }
+        ));
+    }
+
+    #[test]
+    fn env_takes_precedence_over_file_and_defaults() {
+        let path = write_temp_config(
+            r#"
+tcp_listen_host = "0.0.0.0"
+tcp_listen_port = 9000
+"#,
+        );
+        let _guard = EnvGuard::set(&[("EMDRIVE_TCP_LISTEN_PORT", "9001")]);
+        let config = Config::load(Some(&path)).unwrap();
+        assert_eq!(config.tcp_listen_port, 9001);
+        assert_eq!(config.tcp_listen_host, "0.0.0.0");
+        assert_eq!(config.data_directory, Config::default().data_directory);
+    }
+
+    #[test]
+    fn invalid_env_value_fails() {
+        let _guard = EnvGuard::set(&[("EMDRIVE_TCP_LISTEN_PORT", "eighty")]);
+        match Config::from_env().unwrap_err() {
+            ConfigError::InvalidValue { key, value, .. } => {
+                assert_eq!(key, "EMDRIVE_TCP_LISTEN_PORT");
+                assert_eq!(value, "eighty");
+            }
+            error => panic!("Unexpected error: {}", error),
         }
-    } else {
-        default
+    }
+
+    #[test]
+    fn listen_addresses_can_be_listed() {
+        let path = write_temp_config(
+            r#"
+tcp_listen_addresses = ["127.0.0.1:9000", "[::1]:9000"]
+"#,
+        );
+        let config = Config::from_file(&path).unwrap();
+        assert_eq!(
+            config.listen_addresses(),
+            vec![
+                "127.0.0.1:9000".parse::<net::SocketAddr>().unwrap(),
+                "[::1]:9000".parse::<net::SocketAddr>().unwrap()
+            ]
+        );
+        let _guard = EnvGuard::set(&[("EMDRIVE_TCP_LISTEN_ADDRESSES", "0.0.0.0:80, 127.0.0.1")]);
+        match Config::load(Some(&path)).unwrap_err() {
+            ConfigError::InvalidValue { key, value, .. } => {
+                assert_eq!(key, "EMDRIVE_TCP_LISTEN_ADDRESSES");
+                assert_eq!(value, "127.0.0.1");
+            }
+            error => panic!("Unexpected error: {}", error),
+        }
+    }
+
+    #[test]
+    fn wal_can_be_configured() {
+        let path = write_temp_config(
+            r#"
+wal_path = "/tmp/emdrive.wal"
+wal_sync_mode = "periodic"
+"#,
+        );
+        let config = Config::from_file(&path).unwrap();
+        assert_eq!(config.wal_path.as_deref(), Some("/tmp/emdrive.wal"));
+        assert_eq!(config.wal_sync_mode, WalSyncMode::Periodic);
+        assert_eq!(config.wal_sync_interval_ms, 1000);
+        let path = write_temp_config("wal_sync_mode = \"sometimes\"\n");
+        match Config::from_file(&path).unwrap_err() {
+            ConfigError::InvalidValue { key, value, .. } => {
+                assert_eq!(key, "wal_sync_mode");
+                assert_eq!(value, "sometimes");
+            }
+            error => panic!("Unexpected error: {}", error),
+        }
+    }
+
+    #[test]
+    fn connection_handling_can_be_configured() {
+        let path = write_temp_config(
+            r#"
+http_keep_alive = false
+max_connections = 16
+"#,
+        );
+        let config = Config::from_file(&path).unwrap();
+        assert!(!config.http_keep_alive);
+        assert_eq!(config.max_connections, 16);
+        assert_eq!(config.header_read_timeout_ms, 10_000);
+        let _guard = EnvGuard::set(&[("EMDRIVE_HTTP_KEEP_ALIVE", "yes")]);
+        match Config::load(Some(&path)).unwrap_err() {
+            ConfigError::InvalidValue { key, value, .. } => {
+                assert_eq!(key, "EMDRIVE_HTTP_KEEP_ALIVE");
+                assert_eq!(value, "yes");
+            }
+            error => panic!("Unexpected error: {}", error),
+        }
+    }
+
+    #[test]
+    fn auth_token_can_be_configured() {
+        let path = write_temp_config(
+            r#"
+auth_token = "s3cret"
+"#,
+        );
+        let config = Config::from_file(&path).unwrap();
+        assert_eq!(config.auth_token.as_deref(), Some("s3cret"));
+        assert!(!config.to_string().contains("s3cret"));
+        let _guard = EnvGuard::set(&[("EMDRIVE_AUTH_TOKEN", "")]);
+        assert_eq!(Config::load(Some(&path)).unwrap().auth_token, None);
+        let invalid_path = write_temp_config(
+            r#"
+auth_token = "two words"
+"#,
+        );
+        match Config::from_file(&invalid_path).unwrap_err() {
+            ConfigError::InvalidValue { key, value, .. } => {
+                assert_eq!(key, "auth_token");
+                assert_eq!(value, "<redacted>");
+            }
+            error => panic!("Unexpected error: {}", error),
+        }
+    }
+
+    #[test]
+    fn cors_allowed_origins_can_be_configured() {
+        let path = write_temp_config(
+            r#"
+cors_allowed_origins = ["https://admin.example.com", "http://localhost:3000"]
+"#,
+        );
+        assert_eq!(
+            Config::from_file(&path).unwrap().cors_allowed_origins,
+            vec!["https://admin.example.com", "http://localhost:3000"]
+        );
+        let _guard = EnvGuard::set(&[("EMDRIVE_CORS_ALLOWED_ORIGINS", "*, https://example.com/")]);
+        match Config::load(Some(&path)).unwrap_err() {
+            ConfigError::InvalidValue { key, value, .. } => {
+                assert_eq!(key, "EMDRIVE_CORS_ALLOWED_ORIGINS");
+                assert_eq!(value, "https://example.com/");
+            }
+            error => panic!("Unexpected error: {}", error),
+        }
+    }
+
+    #[test]
+    fn sql_dialect_can_be_configured() {
+        let path = write_temp_config("sql_dialect = \"aliases\"\n");
+        assert_eq!(
+            Config::from_file(&path).unwrap().sql_dialect,
+            Dialect::Aliases
+        );
+        let _guard = EnvGuard::set(&[("EMDRIVE_SQL_DIALECT", "ansi")]);
+        match Config::load(Some(&path)).unwrap_err() {
+            ConfigError::InvalidValue { key, value, .. } => {
+                assert_eq!(key, "EMDRIVE_SQL_DIALECT");
+                assert_eq!(value, "ansi");
+            }
+            error => panic!("Unexpected error: {}", error),
+        }
+    }
+
+    #[test]
+    fn sql_trailing_commas_can_be_configured() {
+        let path = write_temp_config("sql_trailing_commas = \"Lenient\"\n");
+        assert_eq!(
+            Config::from_file(&path).unwrap().sql_trailing_commas,
+            TrailingCommas::Lenient
+        );
+        let _guard = EnvGuard::set(&[("EMDRIVE_SQL_TRAILING_COMMAS", "allowed")]);
+        match Config::load(Some(&path)).unwrap_err() {
+            ConfigError::InvalidValue { key, value, .. } => {
+                assert_eq!(key, "EMDRIVE_SQL_TRAILING_COMMAS");
+                assert_eq!(value, "allowed");
+            }
+            error => panic!("Unexpected error: {}", error),
+        }
+    }
+
+    #[test]
+    fn name_policy_can_be_configured() {
+        assert_eq!(Config::default().name_policy(), NamePolicy::default());
+        let path = write_temp_config("max_name_length = 20\nname_characters = \"Strict\"\n");
+        assert_eq!(
+            Config::from_file(&path).unwrap().name_policy(),
+            NamePolicy {
+                max_length: 20,
+                characters: NameCharacters::Strict
+            }
+        );
+        let long_path = write_temp_config("max_name_length = 64\n");
+        match Config::from_file(&long_path).unwrap_err() {
+            ConfigError::InvalidValue { key, value, reason } => {
+                assert_eq!(key, "max_name_length");
+                assert_eq!(value, "64");
+                assert_eq!(reason, "not an integer between 1 and 63");
+            }
+            error => panic!("Unexpected error: {}", error),
+        }
+        let _guard = EnvGuard::set(&[("EMDRIVE_NAME_CHARACTERS", "ascii")]);
+        match Config::load(Some(&path)).unwrap_err() {
+            ConfigError::InvalidValue { key, value, .. } => {
+                assert_eq!(key, "EMDRIVE_NAME_CHARACTERS");
+                assert_eq!(value, "ascii");
+            }
+            error => panic!("Unexpected error: {}", error),
+        }
+    }
+
+    #[test]
+    fn logging_can_be_configured() {
+        let path = write_temp_config("log_level = \"WARN\"\nlog_format = \"json\"\n");
+        let config = Config::from_file(&path).unwrap();
+        assert_eq!(config.log_level, Level::WARN);
+        assert_eq!(config.log_format, LogFormat::Json);
+        let _guard = EnvGuard::set(&[("EMDRIVE_LOG_LEVEL", "3")]);
+        match Config::load(Some(&path)).unwrap_err() {
+            ConfigError::InvalidValue { key, value, .. } => {
+                assert_eq!(key, "EMDRIVE_LOG_LEVEL");
+                assert_eq!(value, "3");
+            }
+            error => panic!("Unexpected error: {}", error),
+        }
+    }
+
+    #[test]
+    fn query_limits_can_be_configured() {
+        let path = write_temp_config("max_query_tokens = 500\nmax_query_nesting_depth = 8\n");
+        assert_eq!(
+            Config::from_file(&path).unwrap().parse_limits(),
+            ParseLimits {
+                max_token_count: 500,
+                max_nesting_depth: 8
+            }
+        );
+        let safest_path = write_temp_config("max_query_nesting_depth = 128\n");
+        assert_eq!(
+            Config::from_file(&safest_path)
+                .unwrap()
+                .max_query_nesting_depth,
+            ParseLimits::MAX_SAFE_NESTING_DEPTH as u64
+        );
+        let deep_path = write_temp_config("max_query_nesting_depth = 129\n");
+        match Config::from_file(&deep_path).unwrap_err() {
+            ConfigError::InvalidValue { key, value, reason } => {
+                assert_eq!(key, "max_query_nesting_depth");
+                assert_eq!(value, "129");
+                assert_eq!(reason, "not an integer between 1 and 128");
+            }
+            error => panic!("Unexpected error: {}", error),
+        }
+        let _guard = EnvGuard::set(&[("EMDRIVE_MAX_QUERY_NESTING_DEPTH", "0")]);
+        match Config::load(Some(&path)).unwrap_err() {
+            ConfigError::InvalidValue { key, value, .. } => {
+                assert_eq!(key, "EMDRIVE_MAX_QUERY_NESTING_DEPTH");
+                assert_eq!(value, "0");
+            }
+            error => panic!("Unexpected error: {}", error),
+        }
+    }
+
+    #[test]
+    fn worker_threads_can_be_configured() {
+        assert_eq!(
+            Config::default().worker_threads,
+            thread::available_parallelism().unwrap().get() as u64
+        );
+        let path = write_temp_config("worker_threads = 3\n");
+        assert_eq!(Config::from_file(&path).unwrap().worker_threads, 3);
+        let _guard = EnvGuard::set(&[("EMDRIVE_WORKER_THREADS", "0")]);
+        match Config::load(Some(&path)).unwrap_err() {
+            ConfigError::InvalidValue { key, value, .. } => {
+                assert_eq!(key, "EMDRIVE_WORKER_THREADS");
+                assert_eq!(value, "0");
+            }
+            error => panic!("Unexpected error: {}", error),
+        }
+    }
+
+    #[test]
+    fn listen_addresses_fall_back_to_host_and_port() {
+        assert_eq!(
+            Config::default().listen_addresses(),
+            vec!["127.0.0.1:8824".parse::<net::SocketAddr>().unwrap()]
+        );
     }
 }
@@ -1,13 +1,16 @@
-use serde::Serialize;
-use std::{collections::HashSet, str::FromStr};
-use time::OffsetDateTime;
+use serde::{Serialize, Serializer};
+use std::{
+    cmp::Ordering, collections::HashSet, convert::TryFrom, fmt, ops::RangeInclusive, str::FromStr,
+};
+use time::{format_description::well_known::Rfc3339, OffsetDateTime, Time};
 use uuid::Uuid;
 
-use crate::sql::ValidationError;
+use crate::sql::{Dialect, ValidationError};
 
 use super::functions::Function;
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[non_exhaustive]
 pub enum DataTypeRaw {
     UInt8,
     UInt16,
@@ -20,6 +23,82 @@ pub enum DataTypeRaw {
     String,
 }
 
+impl DataTypeRaw {
+    /// Array of all types, which is what they're recognized from.
+    pub const ALL: [Self; 9] = [
+        Self::UInt8,
+        Self::UInt16,
+        Self::UInt32,
+        Self::UInt64,
+        Self::UInt128,
+        Self::Bool,
+        Self::Timestamp,
+        Self::Uuid,
+        Self::String,
+    ];
+
+    /// The type as written in SQL.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::UInt8 => "UINT8",
+            Self::UInt16 => "UINT16",
+            Self::UInt32 => "UINT32",
+            Self::UInt64 => "UINT64",
+            Self::UInt128 => "UINT128",
+            Self::Bool => "BOOL",
+            Self::Timestamp => "TIMESTAMP",
+            Self::Uuid => "UUID",
+            Self::String => "STRING",
+        }
+    }
+
+    /// Hint at the type that a name which doesn't refer to one was probably meant to be, based on edit distance
+    /// from canonical type names and from aliases of the `aliases` dialect, e.g. `UINT64` for `uint_64`.
+    /// Returns `None` if no type is close enough.
+    pub fn suggestion_for(candidate: &str) -> Option<String> {
+        let candidate = candidate.to_uppercase();
+        let canonical_names = Self::ALL
+            .iter()
+            .map(|data_type| (data_type.name(), *data_type));
+        let alias_names = Dialect::TYPE_ALIASES
+            .iter()
+            .map(|alias| (*alias, Dialect::Aliases.resolve_type_alias(alias).unwrap()));
+        let (closest_name, closest_type, distance) = canonical_names
+            .chain(alias_names)
+            .map(|(name, data_type)| (name, data_type, edit_distance(&candidate, name)))
+            .min_by_key(|(_, _, distance)| *distance)?;
+        // Past a third of the name, the match is more likely to be a coincidence than a typo
+        if distance > (candidate.chars().count() / 3).max(1) {
+            return None;
+        }
+        Some(if distance == 0 {
+            format!(
+                "Did you mean `{}`? `{}` is only an alias of it in the `aliases` dialect.",
+                closest_type, closest_name
+            )
+        } else {
+            format!("Did you mean `{}`?", closest_type)
+        })
+    }
+
+    /// Whether values of the type have a meaningful order, so that ranges of them make sense.
+    pub fn is_orderable(&self) -> bool {
+        !matches!(self, Self::Bool | Self::Uuid)
+    }
+
+    /// Values that fit in the type, if it's an integer type.
+    pub fn integer_range(&self) -> Option<RangeInclusive<u128>> {
+        match self {
+            Self::UInt8 => Some(0..=u128::from(u8::MAX)),
+            Self::UInt16 => Some(0..=u128::from(u16::MAX)),
+            Self::UInt32 => Some(0..=u128::from(u32::MAX)),
+            Self::UInt64 => Some(0..=u128::from(u64::MAX)),
+            Self::UInt128 => Some(0..=u128::MAX),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct DataType {
     pub raw_type: DataTypeRaw,
@@ -30,26 +109,84 @@ impl FromStr for DataTypeRaw {
     type Err = String;
 
     fn from_str(candidate: &str) -> std::result::Result<Self, Self::Err> {
-        match candidate.to_lowercase().as_str() {
-            "uint8" => Ok(Self::UInt8),
-            "uint16" => Ok(Self::UInt16),
-            "uint32" => Ok(Self::UInt32),
-            "uint64" => Ok(Self::UInt64),
-            "uint128" => Ok(Self::UInt128),
-            "bool" => Ok(Self::Bool),
-            "timestamp" => Ok(Self::Timestamp),
-            "uuid" => Ok(Self::Uuid),
-            "string" => Ok(Self::String),
-            _ => Err(format!(
-                "`{}` does not refer to a supported type",
-                candidate
-            )),
+        Self::ALL
+            .iter()
+            .find(|data_type| data_type.name().eq_ignore_ascii_case(candidate))
+            .copied()
+            .ok_or_else(|| match Self::suggestion_for(candidate) {
+                Some(suggestion) => format!(
+                    "`{}` does not refer to a supported type. {}",
+                    candidate, suggestion
+                ),
+                None => format!("`{}` does not refer to a supported type", candidate),
+            })
+    }
+}
+
+/// Number of single-character insertions, deletions or substitutions needed to turn one string into the other.
+fn edit_distance(left: &str, right: &str) -> usize {
+    let right: Vec<char> = right.chars().collect();
+    // Distances from the prefix of `left` processed so far to each prefix of `right`
+    let mut distances: Vec<usize> = (0..=right.len()).collect();
+    for (left_index, left_character) in left.chars().enumerate() {
+        let mut previous_diagonal = distances[0];
+        distances[0] = left_index + 1;
+        for (right_index, right_character) in right.iter().enumerate() {
+            let substitution = previous_diagonal + usize::from(left_character != *right_character);
+            previous_diagonal = distances[right_index + 1];
+            distances[right_index + 1] = substitution
+                .min(distances[right_index] + 1)
+                .min(previous_diagonal + 1);
+        }
+    }
+    distances[right.len()]
+}
+
+impl fmt::Display for DataTypeRaw {
+    /// The type in canonical SQL syntax, e.g. `UINT64`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for DataType {
+    type Err = String;
+
+    /// Parse the type from SQL syntax, e.g. `UINT64` or `NULLABLE(UINT64)`.
+    fn from_str(candidate: &str) -> std::result::Result<Self, Self::Err> {
+        const NULLABLE_PREFIX: &str = "NULLABLE(";
+        let trimmed_candidate = candidate.trim();
+        let nullable_inner = trimmed_candidate
+            .get(..NULLABLE_PREFIX.len())
+            .filter(|prefix| prefix.eq_ignore_ascii_case(NULLABLE_PREFIX))
+            .and_then(|_| trimmed_candidate[NULLABLE_PREFIX.len()..].strip_suffix(')'));
+        Ok(match nullable_inner {
+            Some(inner) => DataType {
+                raw_type: inner.trim().parse()?,
+                is_nullable: true,
+            },
+            None => DataType {
+                raw_type: trimmed_candidate.parse()?,
+                is_nullable: false,
+            },
+        })
+    }
+}
+
+impl fmt::Display for DataType {
+    /// The type in canonical SQL syntax, with nullable types wrapped, e.g. `NULLABLE(UINT64)`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_nullable {
+            write!(f, "NULLABLE({})", self.raw_type)
+        } else {
+            write!(f, "{}", self.raw_type)
         }
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Serialize)]
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Serialize)]
 #[serde(untagged)]
+#[non_exhaustive]
 pub enum DataInstanceRaw {
     UInt8(u8),
     UInt16(u16),
@@ -57,12 +194,249 @@ pub enum DataInstanceRaw {
     UInt64(u64),
     UInt128(u128),
     Bool(bool),
+    #[serde(serialize_with = "serialize_timestamp")]
     Timestamp(OffsetDateTime),
     Uuid(Uuid),
     String(String),
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Serialize)]
+impl DataInstanceRaw {
+    /// Widen the value to `u128` if it's an unsigned integer.
+    pub fn as_u128(&self) -> Option<u128> {
+        match self {
+            Self::UInt8(value) => Some(u128::from(*value)),
+            Self::UInt16(value) => Some(u128::from(*value)),
+            Self::UInt32(value) => Some(u128::from(*value)),
+            Self::UInt64(value) => Some(u128::from(*value)),
+            Self::UInt128(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Type of the value.
+    pub fn raw_type(&self) -> DataTypeRaw {
+        match self {
+            Self::UInt8(_) => DataTypeRaw::UInt8,
+            Self::UInt16(_) => DataTypeRaw::UInt16,
+            Self::UInt32(_) => DataTypeRaw::UInt32,
+            Self::UInt64(_) => DataTypeRaw::UInt64,
+            Self::UInt128(_) => DataTypeRaw::UInt128,
+            Self::Bool(_) => DataTypeRaw::Bool,
+            Self::Timestamp(_) => DataTypeRaw::Timestamp,
+            Self::Uuid(_) => DataTypeRaw::Uuid,
+            Self::String(_) => DataTypeRaw::String,
+        }
+    }
+
+    /// Compare two values by their meaning, regardless of integer width.
+    /// Returns `None` if the values are not comparable (e.g. a string and a number).
+    pub fn compare(&self, other: &Self) -> Option<Ordering> {
+        if let (Some(left), Some(right)) = (self.as_u128(), other.as_u128()) {
+            return Some(left.cmp(&right));
+        }
+        match (self, other) {
+            (Self::Bool(left), Self::Bool(right)) => Some(left.cmp(right)),
+            (Self::Timestamp(left), Self::Timestamp(right)) => Some(left.cmp(right)),
+            (Self::Uuid(left), Self::Uuid(right)) => Some(left.cmp(right)),
+            // UUIDs are written as string literals
+            (Self::Uuid(left), Self::String(right)) => {
+                parse_uuid(right).ok().map(|right| left.cmp(&right))
+            }
+            (Self::String(left), Self::Uuid(right)) => {
+                parse_uuid(left).ok().map(|left| left.cmp(right))
+            }
+            // So are timestamps
+            (Self::Timestamp(left), Self::String(right)) => {
+                parse_timestamp(right).ok().map(|right| left.cmp(&right))
+            }
+            (Self::String(left), Self::Timestamp(right)) => {
+                parse_timestamp(left).ok().map(|left| left.cmp(right))
+            }
+            (Self::String(left), Self::String(right)) => Some(left.cmp(right)),
+            _ => None,
+        }
+    }
+
+    /// Convert this value to the specified type, as long as no information is lost.
+    /// Strings are converted to UUIDs if they're in the canonical hyphenated form,
+    /// and to timestamps if they're ISO 8601 dates, with or without time.
+    pub fn coerce(self, raw_type: DataTypeRaw) -> Result<Self, ValidationError> {
+        if let (DataTypeRaw::Uuid, Self::String(candidate)) = (raw_type, &self) {
+            return parse_uuid(candidate).map(Self::Uuid);
+        }
+        if let (DataTypeRaw::Timestamp, Self::String(candidate)) = (raw_type, &self) {
+            return parse_timestamp(candidate).map(Self::Timestamp);
+        }
+        if let (Some(value), Some(range)) = (self.as_u128(), raw_type.integer_range()) {
+            if !range.contains(&value) {
+                return Err(ValidationError(format!(
+                    "Value {} is out of range for {} ({}..={})",
+                    value,
+                    raw_type,
+                    range.start(),
+                    range.end()
+                )));
+            }
+        }
+        let coerced = match (raw_type, &self) {
+            (DataTypeRaw::UInt8, _) => self
+                .as_u128()
+                .and_then(|value| u8::try_from(value).ok())
+                .map(Self::UInt8),
+            (DataTypeRaw::UInt16, _) => self
+                .as_u128()
+                .and_then(|value| u16::try_from(value).ok())
+                .map(Self::UInt16),
+            (DataTypeRaw::UInt32, _) => self
+                .as_u128()
+                .and_then(|value| u32::try_from(value).ok())
+                .map(Self::UInt32),
+            (DataTypeRaw::UInt64, _) => self
+                .as_u128()
+                .and_then(|value| u64::try_from(value).ok())
+                .map(Self::UInt64),
+            (DataTypeRaw::UInt128, _) => self.as_u128().map(Self::UInt128),
+            (DataTypeRaw::Bool, Self::Bool(_))
+            | (DataTypeRaw::Timestamp, Self::Timestamp(_))
+            | (DataTypeRaw::Uuid, Self::Uuid(_))
+            | (DataTypeRaw::String, Self::String(_)) => Some(self.clone()),
+            _ => None,
+        };
+        coerced.ok_or_else(|| {
+            ValidationError(format!("Value {:?} cannot be used as {}", self, raw_type))
+        })
+    }
+}
+
+impl DataInstanceRaw {
+    /// Convert this value to the specified type with CAST. Unlike coercion, this converts between representations:
+    /// any value can be cast to a string (timestamps in ISO 8601), strings to other types if they're written
+    /// the way values of those types are, booleans to integers as 1 and 0, and integers of 1 and 0 to booleans.
+    /// Timestamps are cast to integers as microseconds since Unix epoch. Casts that would lose information fail.
+    pub fn cast(self, raw_type: DataTypeRaw) -> Result<Self, ValidationError> {
+        let cast_error = |value: &Self, reason: &str| {
+            ValidationError(format!(
+                "Value {:?} cannot be cast to {}{}",
+                value, raw_type, reason
+            ))
+        };
+        match (&self, raw_type) {
+            (Self::String(_), DataTypeRaw::String) => Ok(self),
+            (Self::Timestamp(timestamp), DataTypeRaw::String) => {
+                Ok(Self::String(timestamp_iso(timestamp)))
+            }
+            (Self::Uuid(uuid), DataTypeRaw::String) => Ok(Self::String(uuid.to_string())),
+            (Self::Bool(value), DataTypeRaw::String) => Ok(Self::String(value.to_string())),
+            (value, DataTypeRaw::String) => Ok(Self::String(value.as_u128().unwrap().to_string())),
+            (Self::String(candidate), _) if raw_type.integer_range().is_some() => {
+                match candidate.parse::<u128>() {
+                    Ok(value) => Self::UInt128(value).coerce(raw_type),
+                    Err(_) => Err(cast_error(&self, ", as it's not a non-negative integer")),
+                }
+            }
+            (Self::String(candidate), DataTypeRaw::Bool) => {
+                match candidate.to_ascii_lowercase().as_str() {
+                    "true" => Ok(Self::Bool(true)),
+                    "false" => Ok(Self::Bool(false)),
+                    _ => Err(cast_error(&self, ", as it's neither `true` nor `false`")),
+                }
+            }
+            (Self::Bool(value), _) if raw_type.integer_range().is_some() => {
+                Self::UInt8(u8::from(*value)).coerce(raw_type)
+            }
+            (Self::Timestamp(timestamp), _) if raw_type.integer_range().is_some() => {
+                match u128::try_from(timestamp_micros(timestamp)) {
+                    Ok(micros) => Self::UInt128(micros).coerce(raw_type),
+                    Err(_) => Err(cast_error(&self, ", as it's before Unix epoch")),
+                }
+            }
+            (value, DataTypeRaw::Bool) if value.as_u128().is_some() => {
+                match value.as_u128().unwrap() {
+                    0 => Ok(Self::Bool(false)),
+                    1 => Ok(Self::Bool(true)),
+                    _ => Err(cast_error(&self, ", as only 0 and 1 are")),
+                }
+            }
+            (Self::Bool(_), DataTypeRaw::Bool)
+            | (Self::Timestamp(_), DataTypeRaw::Timestamp)
+            | (Self::Uuid(_), DataTypeRaw::Uuid)
+            | (Self::String(_), DataTypeRaw::Timestamp | DataTypeRaw::Uuid) => {
+                self.coerce(raw_type)
+            }
+            (value, _) if value.as_u128().is_some() && raw_type.integer_range().is_some() => {
+                self.coerce(raw_type)
+            }
+            _ => Err(cast_error(&self, "")),
+        }
+    }
+}
+
+/// Parse a UUID in the canonical hyphenated form, e.g. `550e8400-e29b-41d4-a716-446655440000`.
+/// Other forms, e.g. without hyphens, are rejected, so that a UUID is always written the same way.
+fn parse_uuid(candidate: &str) -> Result<Uuid, ValidationError> {
+    match Uuid::parse_str(candidate) {
+        Ok(uuid) if candidate.len() == 36 => Ok(uuid),
+        _ => Err(ValidationError(format!(
+            "String `{}` is not a valid UUID, expected the hyphenated form of 32 hexadecimal digits, e.g. `550e8400-e29b-41d4-a716-446655440000`",
+            candidate
+        ))),
+    }
+}
+
+/// Parse an ISO 8601 timestamp, either a date and time with an offset, e.g. `2024-01-15T12:30:00Z`
+/// or `2024-01-15T13:30:00.5+01:00`, or just a date, e.g. `2024-01-15`, which means midnight UTC.
+/// The result is in UTC, with precision cut down to the microseconds that timestamps are stored in.
+pub fn parse_timestamp(candidate: &str) -> Result<OffsetDateTime, ValidationError> {
+    let is_date_only = candidate.len() == 10 && !candidate.contains('T');
+    let parsed = if is_date_only {
+        // RFC 3339 is strict about the date part, so it checks the date padded with midnight UTC
+        OffsetDateTime::parse(&format!("{}T00:00:00Z", candidate), &Rfc3339)
+    } else {
+        OffsetDateTime::parse(candidate, &Rfc3339)
+    };
+    match parsed {
+        Ok(timestamp) => {
+            let timestamp = timestamp.to_offset(time::UtcOffset::UTC);
+            let time = timestamp.time();
+            Ok(timestamp.replace_time(
+                Time::from_hms_micro(
+                    time.hour(),
+                    time.minute(),
+                    time.second(),
+                    time.microsecond(),
+                )
+                .unwrap(),
+            ))
+        }
+        Err(_) => Err(ValidationError(format!(
+            "String `{}` is not a valid timestamp, expected an ISO 8601 date and time with an offset, e.g. `2024-01-15T12:30:00Z`, or a date, e.g. `2024-01-15`",
+            candidate
+        ))),
+    }
+}
+
+/// Microseconds since Unix epoch, which is how timestamps are stored and rendered as numbers.
+pub fn timestamp_micros(timestamp: &OffsetDateTime) -> i64 {
+    // Timestamps representable by `OffsetDateTime` are within ±10000 years, so this can't overflow
+    (timestamp.unix_timestamp_nanos() / 1000) as i64
+}
+
+/// The timestamp in the RFC 3339 profile of ISO 8601, in UTC, e.g. `2024-01-15T12:30:00Z`.
+pub fn timestamp_iso(timestamp: &OffsetDateTime) -> String {
+    timestamp
+        .to_offset(time::UtcOffset::UTC)
+        .format(&Rfc3339)
+        .unwrap_or_else(|_| timestamp_micros(timestamp).to_string())
+}
+
+fn serialize_timestamp<S>(timestamp: &OffsetDateTime, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.serialize_i64(timestamp_micros(timestamp))
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Serialize)]
 #[serde(untagged)]
 pub enum DataInstance {
     Direct(DataInstanceRaw),
@@ -70,46 +444,513 @@ pub enum DataInstance {
     Null,
 }
 
+impl DataInstance {
+    /// The underlying value, or `None` if this is NULL.
+    pub fn as_raw(&self) -> Option<&DataInstanceRaw> {
+        match self {
+            Self::Direct(value) | Self::Nullable(value) => Some(value),
+            Self::Null => None,
+        }
+    }
+
+    /// Convert a literal to an instance of the specified type, wrapped according to the type's nullability.
+    pub fn coerce(self, data_type: &DataType) -> Result<Self, ValidationError> {
+        match self {
+            Self::Null if data_type.is_nullable => Ok(Self::Null),
+            Self::Null => Err(ValidationError(
+                "NULL cannot be used as a value of a non-nullable type".into(),
+            )),
+            Self::Direct(value) | Self::Nullable(value) => {
+                let value = value.coerce(data_type.raw_type)?;
+                Ok(if data_type.is_nullable {
+                    Self::Nullable(value)
+                } else {
+                    Self::Direct(value)
+                })
+            }
+        }
+    }
+}
+
+impl fmt::Display for DataInstanceRaw {
+    /// The value as an SQL literal, e.g. `42` or `'foo'`. Timestamps are in microseconds since Unix epoch.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Bool(true) => f.write_str("TRUE"),
+            Self::Bool(false) => f.write_str("FALSE"),
+            Self::Timestamp(value) => write!(f, "{}", timestamp_micros(value)),
+            Self::Uuid(value) => write!(f, "'{}'", value),
+            Self::String(value) => {
+                write!(f, "'{}'", value.replace('\\', "\\\\").replace('\'', "\\'"))
+            }
+            value => write!(f, "{}", value.as_u128().unwrap()),
+        }
+    }
+}
+
+impl fmt::Display for DataInstance {
+    /// The value as an SQL literal, e.g. `42`, `'foo'` or `NULL`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.as_raw() {
+            None => f.write_str("NULL"),
+            Some(value) => value.fmt(f),
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, Clone)]
+#[non_exhaustive]
 pub enum DataDefinition {
     // A column identifier.
     Identifier(String),
+    // A column identifier qualified with the name of the column's table, e.g. `users.id`.
+    QualifiedIdentifier {
+        table_name: String,
+        column_name: String,
+    },
     // A constant value.
     Const(DataInstance),
     // A function call.
     FunctionCall(Function),
+    // A placeholder of a parameter value, by 1-based parameter number. Replaced with a constant at bind time.
+    Parameter(usize),
+}
+
+impl DataDefinition {
+    /// Error for a parameter placeholder that made it to execution without a value bound.
+    pub fn unbound_parameter_error(number: usize) -> ValidationError {
+        ValidationError(format!("Parameter `${}` has no value bound", number))
+    }
+}
+
+impl fmt::Display for DataDefinition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Identifier(column_name) => write!(f, "`{}`", column_name),
+            Self::QualifiedIdentifier {
+                table_name,
+                column_name,
+            } => write!(f, "`{}`.`{}`", table_name, column_name),
+            Self::Const(value) => write!(f, "{}", value),
+            Self::FunctionCall(function) => write!(f, "{}()", function.name()),
+            Self::Parameter(number) => write!(f, "${}", number),
+        }
+    }
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
+#[non_exhaustive]
 pub enum Expression {
     Atom(DataDefinition),
     /// LHS = RHS
     Equal(Box<Self>, Box<Self>),
+    /// LHS AND RHS
+    And(Box<Self>, Box<Self>),
+    /// LHS OR RHS
+    Or(Box<Self>, Box<Self>),
+    /// Operand IS NULL
+    IsNull(Box<Self>),
+    /// Operand IS NOT NULL
+    IsNotNull(Box<Self>),
+    /// Column IN (value, ...)
+    In {
+        column: Box<Self>,
+        values: Vec<Self>,
+    },
+    /// Column LIKE pattern
+    Like {
+        column: Box<Self>,
+        pattern: Box<Self>,
+    },
+    /// Column BETWEEN low AND high
+    Between {
+        column: Box<Self>,
+        low: Box<Self>,
+        high: Box<Self>,
+    },
+    /// LHS + RHS
+    Add(Box<Self>, Box<Self>),
+    /// LHS - RHS
+    Subtract(Box<Self>, Box<Self>),
+    /// LHS * RHS
+    Multiply(Box<Self>, Box<Self>),
+    /// LHS / RHS
+    Divide(Box<Self>, Box<Self>),
+    /// CAST(operand AS data_type)
+    Cast {
+        operand: Box<Self>,
+        data_type: DataTypeRaw,
+    },
+}
+
+impl Expression {
+    /// Collect the data definitions this expression is made of, e.g. to bind parameters.
+    pub fn collect_atoms_mut<'e>(&'e mut self, atoms: &mut Vec<&'e mut DataDefinition>) {
+        match self {
+            Self::Atom(atom) => atoms.push(atom),
+            Self::Equal(lhs, rhs)
+            | Self::And(lhs, rhs)
+            | Self::Or(lhs, rhs)
+            | Self::Add(lhs, rhs)
+            | Self::Subtract(lhs, rhs)
+            | Self::Multiply(lhs, rhs)
+            | Self::Divide(lhs, rhs) => {
+                lhs.collect_atoms_mut(atoms);
+                rhs.collect_atoms_mut(atoms);
+            }
+            Self::IsNull(operand) | Self::IsNotNull(operand) | Self::Cast { operand, .. } => {
+                operand.collect_atoms_mut(atoms)
+            }
+            Self::In { column, values } => {
+                column.collect_atoms_mut(atoms);
+                for value in values {
+                    value.collect_atoms_mut(atoms);
+                }
+            }
+            Self::Like { column, pattern } => {
+                column.collect_atoms_mut(atoms);
+                pattern.collect_atoms_mut(atoms);
+            }
+            Self::Between { column, low, high } => {
+                column.collect_atoms_mut(atoms);
+                low.collect_atoms_mut(atoms);
+                high.collect_atoms_mut(atoms);
+            }
+        }
+    }
+
+    /// Operator and operands of an arithmetic operation, if this is one.
+    pub fn as_arithmetic(&self) -> Option<(&'static str, &Self, &Self)> {
+        match self {
+            Self::Add(lhs, rhs) => Some(("+", lhs, rhs)),
+            Self::Subtract(lhs, rhs) => Some(("-", lhs, rhs)),
+            Self::Multiply(lhs, rhs) => Some(("*", lhs, rhs)),
+            Self::Divide(lhs, rhs) => Some(("/", lhs, rhs)),
+            _ => None,
+        }
+    }
+
+    /// How tightly the arithmetic operator binds, if this is an arithmetic operation.
+    fn arithmetic_precedence(&self) -> Option<u8> {
+        match self {
+            Self::Add(..) | Self::Subtract(..) => Some(1),
+            Self::Multiply(..) | Self::Divide(..) => Some(2),
+            _ => None,
+        }
+    }
+
+    /// Name of a result column computed with this expression: the expression in SQL syntax, but with column names
+    /// unquoted, e.g. `price * quantity`.
+    pub fn column_name(&self) -> String {
+        ColumnName(self).to_string()
+    }
+}
+
+/// Write an arithmetic operation, grouping operands that bind looser than the operator – or on the right
+/// equally tight, as operators of the same precedence apply left to right.
+fn write_arithmetic(
+    f: &mut fmt::Formatter<'_>,
+    expression: &Expression,
+    write_operand: fn(&Expression, &mut fmt::Formatter<'_>) -> fmt::Result,
+) -> fmt::Result {
+    let (operator, lhs, rhs) = expression.as_arithmetic().unwrap();
+    let precedence = expression.arithmetic_precedence().unwrap();
+    // Operands other than arithmetic operations are atoms, which never need grouping
+    let lhs_is_grouped = lhs
+        .arithmetic_precedence()
+        .is_some_and(|lhs_precedence| lhs_precedence < precedence);
+    let rhs_is_grouped = rhs
+        .arithmetic_precedence()
+        .is_some_and(|rhs_precedence| rhs_precedence <= precedence);
+    for (index, (operand, is_grouped)) in [(lhs, lhs_is_grouped), (rhs, rhs_is_grouped)]
+        .into_iter()
+        .enumerate()
+    {
+        if index > 0 {
+            write!(f, " {} ", operator)?;
+        }
+        if is_grouped {
+            f.write_str("(")?;
+            write_operand(operand, f)?;
+            f.write_str(")")?;
+        } else {
+            write_operand(operand, f)?;
+        }
+    }
+    Ok(())
+}
+
+/// Expression displayed as a result column name.
+struct ColumnName<'e>(&'e Expression);
+
+impl fmt::Display for ColumnName<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Expression::Atom(DataDefinition::Identifier(column_name)) => f.write_str(column_name),
+            Expression::Atom(DataDefinition::QualifiedIdentifier {
+                table_name,
+                column_name,
+            }) => write!(f, "{}.{}", table_name, column_name),
+            Expression::Cast { operand, data_type } => {
+                write!(f, "CAST({} AS {})", ColumnName(operand), data_type)
+            }
+            expression if expression.as_arithmetic().is_some() => {
+                write_arithmetic(f, expression, |operand, f| {
+                    fmt::Display::fmt(&ColumnName(operand), f)
+                })
+            }
+            expression => fmt::Display::fmt(expression, f),
+        }
+    }
+}
+
+impl fmt::Display for Expression {
+    /// The expression in SQL syntax, with parentheses where precedence requires them.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Atom(atom) => write!(f, "{}", atom),
+            Self::Equal(lhs, rhs) => write!(f, "{} = {}", lhs, rhs),
+            Self::And(lhs, rhs) => {
+                // OR binds looser than AND, so it has to be grouped
+                for (index, operand) in [lhs, rhs].into_iter().enumerate() {
+                    if index > 0 {
+                        f.write_str(" AND ")?;
+                    }
+                    match operand.as_ref() {
+                        Self::Or(..) => write!(f, "({})", operand)?,
+                        operand => write!(f, "{}", operand)?,
+                    }
+                }
+                Ok(())
+            }
+            Self::Or(lhs, rhs) => write!(f, "{} OR {}", lhs, rhs),
+            Self::IsNull(operand) => write!(f, "{} IS NULL", operand),
+            Self::IsNotNull(operand) => write!(f, "{} IS NOT NULL", operand),
+            Self::In { column, values } => write!(
+                f,
+                "{} IN ({})",
+                column,
+                values
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Self::Like { column, pattern } => write!(f, "{} LIKE {}", column, pattern),
+            Self::Between { column, low, high } => {
+                write!(f, "{} BETWEEN {} AND {}", column, low, high)
+            }
+            Self::Add(..) | Self::Subtract(..) | Self::Multiply(..) | Self::Divide(..) => {
+                write_arithmetic(f, self, fmt::Display::fmt)
+            }
+            Self::Cast { operand, data_type } => write!(f, "CAST({} AS {})", operand, data_type),
+        }
+    }
 }
 
 pub trait Validatable {
     /// Make sure that this definition (self) actually makes sense.
     fn validate(&self) -> Result<(), ValidationError>;
+
+    /// Like `validate`, but with names of tables and columns held to the given policy instead of the default one.
+    /// Only definitions that name something need to implement this.
+    fn validate_with_name_policy(&self, name_policy: &NamePolicy) -> Result<(), ValidationError> {
+        let _ = name_policy;
+        self.validate()
+    }
+}
+
+impl Validatable for DataType {
+    fn validate(&self) -> Result<(), ValidationError> {
+        // Parameters of a type (e.g. a length) get checked here, so that every definition agrees on them.
+        // None of the supported types takes parameters yet, so there's nothing that can be wrong
+        match self.raw_type {
+            DataTypeRaw::UInt8
+            | DataTypeRaw::UInt16
+            | DataTypeRaw::UInt32
+            | DataTypeRaw::UInt64
+            | DataTypeRaw::UInt128
+            | DataTypeRaw::Bool
+            | DataTypeRaw::Timestamp
+            | DataTypeRaw::Uuid
+            | DataTypeRaw::String => Ok(()),
+        }
+    }
+}
+
+/// Rules for names of tables and columns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NamePolicy {
+    /// Maximum number of characters in a name.
+    pub max_length: usize,
+    /// Characters that a name may consist of.
+    pub characters: NameCharacters,
+}
+
+impl NamePolicy {
+    /// Longest that names can be allowed to be. Names of tables are also names of their directories,
+    /// and at 4 bytes per character this fits within the usual limit of 255 bytes.
+    pub const MAX_SAFE_LENGTH: usize = 63;
+}
+
+impl Default for NamePolicy {
+    /// Names of up to 63 characters, with quoted identifiers allowed to have any characters but control ones.
+    fn default() -> Self {
+        NamePolicy {
+            max_length: Self::MAX_SAFE_LENGTH,
+            characters: NameCharacters::Lenient,
+        }
+    }
+}
+
+/// Characters that names of tables and columns may consist of. Unquoted identifiers are always limited
+/// to letters, digits and underscores by the parser, so this is about quoted ones.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum NameCharacters {
+    /// Any characters other than control ones, e.g. `"my table"`.
+    #[default]
+    Lenient,
+    /// Only letters, digits and underscores, not starting with a digit, just like unquoted identifiers.
+    Strict,
+}
+
+impl NameCharacters {
+    /// Policy names accepted in config, for use in error messages.
+    pub const SUPPORTED_NAMES: &'static str = "lenient, strict";
+
+    /// Policy by its name, as in config.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "lenient" => Some(Self::Lenient),
+            "strict" => Some(Self::Strict),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for NameCharacters {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Lenient => write!(f, "lenient"),
+            Self::Strict => write!(f, "strict"),
+        }
+    }
+}
+
+/// Whether the word can be an identifier without quoting, i.e. it consists of letters, digits and underscores,
+/// and doesn't start with a digit.
+pub(crate) fn is_plain_identifier(word: &str) -> bool {
+    !word.starts_with(|character: char| character.is_ascii_digit())
+        && word
+            .chars()
+            .all(|character| character.is_alphanumeric() || character == '_')
 }
 
-#[derive(Debug, PartialEq, Eq)]
+/// Make sure that the name is short enough and consists of characters allowed by the policy.
+/// Control characters are never allowed.
+fn validate_name(kind: &str, name: &str, name_policy: &NamePolicy) -> Result<(), ValidationError> {
+    let name_length = name.chars().count();
+    if name_length > name_policy.max_length {
+        return Err(ValidationError(format!(
+            "{} name `{}` is {} characters long, over the limit of {}",
+            kind, name, name_length, name_policy.max_length
+        )));
+    }
+    if let Some(control_character) = name.chars().find(|character| character.is_control()) {
+        return Err(ValidationError(format!(
+            "{} name `{}` contains control character {:?}",
+            kind,
+            name.escape_debug(),
+            control_character
+        )));
+    }
+    if name_policy.characters == NameCharacters::Strict && !is_plain_identifier(name) {
+        return Err(ValidationError(format!(
+            "{} name `{}` must consist of letters, digits and underscores, and not start with a digit",
+            kind, name
+        )));
+    }
+    Ok(())
+}
+
+/// Bring an identifier to the form in which it's compared. Identifiers are case-insensitive,
+/// but definitions keep the original case, which is what's shown to users.
+pub fn normalize_identifier(identifier: &str) -> String {
+    identifier.to_lowercase()
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[non_exhaustive]
 pub struct ColumnDefinition {
     pub name: String,
     pub data_type: DataType,
     pub primary_key: bool,
+    /// Whether values in this column must be unique (NULLs excepted).
+    pub unique: bool,
     pub default: Option<DataDefinition>,
+    /// Free-form description of the column, for documentation only.
+    pub comment: Option<String>,
+}
+
+impl ColumnDefinition {
+    /// The DEFAULT definition, with a constant value coerced to the column's type.
+    pub fn coerced_default(&self) -> Result<Option<DataDefinition>, ValidationError> {
+        match &self.default {
+            // Types are non-nullable by default, so NULL as the DEFAULT is most likely a missing NULLABLE(...)
+            Some(DataDefinition::Const(DataInstance::Null)) if !self.data_type.is_nullable => {
+                Err(ValidationError(format!(
+                    "DEFAULT of column `{}` is NULL, but its type {} is non-nullable – make it NULLABLE({}) or drop the DEFAULT",
+                    self.name, self.data_type, self.data_type.raw_type
+                )))
+            }
+            Some(DataDefinition::Const(value)) => value
+                .clone()
+                .coerce(&self.data_type)
+                .map(|value| Some(DataDefinition::Const(value)))
+                .map_err(|error| {
+                    ValidationError(format!(
+                        "DEFAULT of column `{}` doesn't match its type: {}",
+                        self.name, error.0
+                    ))
+                }),
+            Some(identifier @ DataDefinition::Identifier(_))
+            | Some(identifier @ DataDefinition::QualifiedIdentifier { .. }) => {
+                Err(ValidationError(format!(
+                    "DEFAULT of column `{}` must be a constant or a function call, not identifier {}",
+                    self.name, identifier
+                )))
+            }
+            Some(DataDefinition::Parameter(number)) => {
+                Err(DataDefinition::unbound_parameter_error(*number))
+            }
+            default => Ok(default.clone()),
+        }
+    }
 }
 
 impl Validatable for ColumnDefinition {
     fn validate(&self) -> Result<(), ValidationError> {
+        self.validate_with_name_policy(&NamePolicy::default())
+    }
+
+    fn validate_with_name_policy(&self, name_policy: &NamePolicy) -> Result<(), ValidationError> {
         if self.name.is_empty() {
             return Err(ValidationError("A column must have a name".into()));
         }
+        validate_name("Column", &self.name, name_policy)?;
+        self.data_type.validate().map_err(|error| {
+            ValidationError(format!(
+                "Type of column `{}` is invalid: {}",
+                self.name, error.0
+            ))
+        })?;
+        self.coerced_default()?;
         Ok(())
     }
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[non_exhaustive]
 pub struct TableDefinition {
     // Table name.
     pub name: String,
@@ -118,55 +959,386 @@ pub struct TableDefinition {
 }
 
 impl TableDefinition {
-    pub fn new(name: String, columns: Vec<ColumnDefinition>) -> Self {
+    /// Define a table, making sure that at least one of the columns is in the PRIMARY KEY,
+    /// since so much of storage relies on that. Other requirements are checked by `validate`.
+    pub fn new(name: String, columns: Vec<ColumnDefinition>) -> Result<Self, ValidationError> {
+        let table = TableDefinition { name, columns };
+        table.validate_primary_key()?;
+        Ok(table)
+    }
+
+    /// Define a table as written, without even the PRIMARY KEY requirement, so that the parser can leave
+    /// every requirement to `validate`.
+    pub(crate) fn new_unchecked(name: String, columns: Vec<ColumnDefinition>) -> Self {
         TableDefinition { name, columns }
     }
 
-    pub fn get_primary_key(&self) -> &ColumnDefinition {
+    pub(crate) fn validate_primary_key(&self) -> Result<(), ValidationError> {
+        if !self.columns.iter().any(|column| column.primary_key) {
+            return Err(ValidationError(
+                "A table must have at least 1 PRIMARY KEY column".into(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Columns making up the PRIMARY KEY, in column order. More than one means a composite key.
+    pub fn get_primary_key_columns(&self) -> Vec<&ColumnDefinition> {
+        self.columns
+            .iter()
+            .filter(|column| column.primary_key)
+            .collect()
+    }
+
+    /// Find the position and definition of the column with the specified name (case-insensitively).
+    pub fn get_column(&self, name: &str) -> Option<(usize, &ColumnDefinition)> {
+        let name = normalize_identifier(name);
         self.columns
             .iter()
-            .find(|column| column.primary_key)
-            .expect("A table must have a PRIMARY KEY column")
+            .enumerate()
+            .find(|(_, column)| normalize_identifier(&column.name) == name)
     }
 }
 
 impl Validatable for TableDefinition {
     fn validate(&self) -> Result<(), ValidationError> {
+        self.validate_with_name_policy(&NamePolicy::default())
+    }
+
+    fn validate_with_name_policy(&self, name_policy: &NamePolicy) -> Result<(), ValidationError> {
         if self.name.is_empty() {
             return Err(ValidationError("A table must have a name".into()));
         }
+        validate_name("Table", &self.name, name_policy)?;
+        // The table's data lives in a directory named after the table, which must stay within the schema's directory
+        if self.name == "." || self.name == ".." || self.name.contains(['/', '\\']) {
+            return Err(ValidationError(format!(
+                "Table name `{}` can't be used as a directory name",
+                self.name
+            )));
+        }
         if self.columns.is_empty() {
             return Err(ValidationError(
                 "A table must have at least one column".into(),
             ));
         }
-        let mut primary_key_count = 0;
         let mut column_names: HashSet<String> = HashSet::new();
         for (column_index, column) in self.columns.iter().enumerate() {
-            if column_names.contains(&column.name) {
+            if !column_names.insert(normalize_identifier(&column.name)) {
                 return Err(ValidationError(format!(
                     "There is more than one column with name `{}` in table definition",
                     column.name
                 )));
             }
-            column_names.insert(column.name.clone());
-            if column.primary_key {
-                primary_key_count += 1;
-            }
-            if let Err(column_error) = column.validate() {
+            if let Err(column_error) = column.validate_with_name_policy(name_policy) {
                 return Err(ValidationError(format!(
                     "Problem at column {}: {}",
                     column_index + 1,
-                    column_error
+                    column_error.0
                 )));
             }
         }
-        if primary_key_count != 1 {
-            return Err(ValidationError(format!(
-                "A table must have exactly 1 PRIMARY KEY column, not {}",
-                primary_key_count
-            )));
+        self.validate_primary_key()
+    }
+}
+
+#[cfg(test)]
+mod components_tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn data_type_display_round_trips() {
+        for raw_type in DataTypeRaw::ALL {
+            for is_nullable in [false, true] {
+                let data_type = DataType {
+                    raw_type,
+                    is_nullable,
+                };
+                assert_eq!(data_type.to_string().parse(), Ok(data_type.clone()));
+            }
+            assert_eq!(raw_type.to_string().parse(), Ok(raw_type));
         }
-        Ok(())
+    }
+
+    #[test]
+    fn every_data_type_is_valid() {
+        // No supported type takes parameters yet, so none can be invalid
+        for raw_type in DataTypeRaw::ALL {
+            for is_nullable in [false, true] {
+                let data_type = DataType {
+                    raw_type,
+                    is_nullable,
+                };
+                assert_eq!(
+                    data_type.validate(),
+                    Ok(()),
+                    "{} should be valid",
+                    data_type
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn data_type_displays_as_sql() {
+        assert_eq!(DataTypeRaw::UInt64.to_string(), "UINT64");
+        assert_eq!(
+            DataType {
+                raw_type: DataTypeRaw::UInt64,
+                is_nullable: true
+            }
+            .to_string(),
+            "NULLABLE(UINT64)"
+        );
+        assert_eq!(
+            "nullable( string )".parse(),
+            Ok(DataType {
+                raw_type: DataTypeRaw::String,
+                is_nullable: true
+            })
+        );
+        assert_eq!(
+            "NULLABLE(NULLABLE(STRING))".parse::<DataType>(),
+            Err("`NULLABLE(STRING)` does not refer to a supported type".to_string())
+        );
+    }
+
+    #[test]
+    fn unknown_type_names_get_suggestions() {
+        assert_eq!(
+            "uint_64".parse::<DataTypeRaw>(),
+            Err("`uint_64` does not refer to a supported type. Did you mean `UINT64`?".to_string())
+        );
+        assert_eq!(
+            "varchar".parse::<DataTypeRaw>(),
+            Err("`varchar` does not refer to a supported type. Did you mean `STRING`? `VARCHAR` is only an alias of it in the `aliases` dialect.".to_string())
+        );
+        assert_eq!(
+            DataTypeRaw::suggestion_for("Strng"),
+            Some("Did you mean `STRING`?".to_string())
+        );
+        assert_eq!(
+            "foo".parse::<DataTypeRaw>(),
+            Err("`foo` does not refer to a supported type".to_string())
+        );
+    }
+
+    #[test]
+    fn edit_distance_counts_single_character_edits() {
+        assert_eq!(edit_distance("UINT64", "UINT64"), 0);
+        assert_eq!(edit_distance("UINT_64", "UINT64"), 1);
+        assert_eq!(edit_distance("UUDI", "UUID"), 2);
+        assert_eq!(edit_distance("", "BOOL"), 4);
+        assert_eq!(edit_distance("KITTEN", "SITTING"), 3);
+    }
+
+    fn table_named(name: &str) -> TableDefinition {
+        TableDefinition::new(
+            name.into(),
+            vec![ColumnDefinition {
+                name: "id".into(),
+                data_type: DataType {
+                    raw_type: DataTypeRaw::UInt64,
+                    is_nullable: false,
+                },
+                primary_key: true,
+                unique: false,
+                default: None,
+                comment: None,
+            }],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn table_validation_rejects_unfit_names() {
+        assert_eq!(
+            table_named(&"x".repeat(NamePolicy::MAX_SAFE_LENGTH)).validate(),
+            Ok(())
+        );
+        assert_eq!(
+            table_named(&"ż".repeat(NamePolicy::MAX_SAFE_LENGTH + 1)).validate(),
+            Err(ValidationError(format!(
+                "Table name `{}` is 64 characters long, over the limit of 63",
+                "ż".repeat(64)
+            )))
+        );
+        assert_eq!(
+            table_named("tab\tle").validate(),
+            Err(ValidationError(
+                "Table name `tab\\tle` contains control character '\\t'".into()
+            ))
+        );
+        assert_eq!(
+            table_named("../etc").validate(),
+            Err(ValidationError(
+                "Table name `../etc` can't be used as a directory name".into()
+            ))
+        );
+        let mut table = table_named("xyz");
+        table.columns[0].name = "i".repeat(100);
+        assert_eq!(
+            table.validate(),
+            Err(ValidationError(format!(
+                "Problem at column 1: Column name `{}` is 100 characters long, over the limit of 63",
+                "i".repeat(100)
+            )))
+        );
+    }
+
+    #[test]
+    fn table_validation_follows_name_policy() {
+        let strict_policy = NamePolicy {
+            max_length: 8,
+            characters: NameCharacters::Strict,
+        };
+        assert_eq!(
+            table_named("users_2").validate_with_name_policy(&strict_policy),
+            Ok(())
+        );
+        assert_eq!(
+            table_named("customers").validate_with_name_policy(&strict_policy),
+            Err(ValidationError(
+                "Table name `customers` is 9 characters long, over the limit of 8".into()
+            ))
+        );
+        assert_eq!(table_named("my table").validate(), Ok(()));
+        assert_eq!(
+            table_named("my table").validate_with_name_policy(&strict_policy),
+            Err(ValidationError(
+                "Table name `my table` must consist of letters, digits and underscores, and not start with a digit"
+                    .into()
+            ))
+        );
+        let mut table = table_named("xyz");
+        table.columns[0].name = "1st".into();
+        assert_eq!(
+            table.validate_with_name_policy(&strict_policy),
+            Err(ValidationError(
+                "Problem at column 1: Column name `1st` must consist of letters, digits and underscores, and not start with a digit"
+                    .into()
+            ))
+        );
+    }
+
+    #[test]
+    fn values_are_cast_between_representations() {
+        for (value, raw_type, expected) in [
+            (
+                DataInstanceRaw::UInt64(42),
+                DataTypeRaw::String,
+                DataInstanceRaw::String("42".into()),
+            ),
+            (
+                DataInstanceRaw::String("300".into()),
+                DataTypeRaw::UInt16,
+                DataInstanceRaw::UInt16(300),
+            ),
+            (
+                DataInstanceRaw::String("TRUE".into()),
+                DataTypeRaw::Bool,
+                DataInstanceRaw::Bool(true),
+            ),
+            (
+                DataInstanceRaw::Bool(true),
+                DataTypeRaw::UInt8,
+                DataInstanceRaw::UInt8(1),
+            ),
+            (
+                DataInstanceRaw::UInt128(0),
+                DataTypeRaw::Bool,
+                DataInstanceRaw::Bool(false),
+            ),
+            (
+                DataInstanceRaw::String("2024-01-15".into()),
+                DataTypeRaw::Timestamp,
+                DataInstanceRaw::Timestamp(parse_timestamp("2024-01-15").unwrap()),
+            ),
+            (
+                DataInstanceRaw::Timestamp(parse_timestamp("1970-01-01T00:00:01Z").unwrap()),
+                DataTypeRaw::UInt64,
+                DataInstanceRaw::UInt64(1_000_000),
+            ),
+        ] {
+            assert_eq!(value.cast(raw_type), Ok(expected));
+        }
+        for (value, raw_type, expected_message) in [
+            (
+                DataInstanceRaw::String("12a".into()),
+                DataTypeRaw::UInt64,
+                "Value String(\"12a\") cannot be cast to UINT64, as it's not a non-negative integer",
+            ),
+            (
+                DataInstanceRaw::UInt16(256),
+                DataTypeRaw::UInt8,
+                "Value 256 is out of range for UINT8 (0..=255)",
+            ),
+            (
+                DataInstanceRaw::UInt8(2),
+                DataTypeRaw::Bool,
+                "Value UInt8(2) cannot be cast to BOOL, as only 0 and 1 are",
+            ),
+            (
+                DataInstanceRaw::UInt8(2),
+                DataTypeRaw::Uuid,
+                "Value UInt8(2) cannot be cast to UUID",
+            ),
+        ] {
+            assert_eq!(
+                value.cast(raw_type),
+                Err(ValidationError(expected_message.into()))
+            );
+        }
+    }
+
+    #[test]
+    fn timestamps_are_parsed_from_iso_8601() {
+        assert_eq!(
+            parse_timestamp("2024-01-15T12:30:00Z"),
+            Ok(OffsetDateTime::from_unix_timestamp(1_705_321_800).unwrap())
+        );
+        // Offsets are normalized to UTC
+        assert_eq!(
+            parse_timestamp("2024-01-15T14:30:00+02:00"),
+            Ok(OffsetDateTime::from_unix_timestamp(1_705_321_800).unwrap())
+        );
+        // Beyond microseconds, precision is cut
+        assert_eq!(
+            parse_timestamp("2024-01-15T12:30:00.123456789Z")
+                .map(|timestamp| timestamp_micros(&timestamp)),
+            Ok(1_705_321_800_123_456)
+        );
+        assert_eq!(
+            parse_timestamp("2024-01-15"),
+            Ok(OffsetDateTime::from_unix_timestamp(1_705_276_800).unwrap())
+        );
+        for malformed_timestamp in [
+            "2024-01-15T12:30:00",
+            "2024-02-30",
+            "2024-1-15",
+            "15/01/2024",
+            "1705321800",
+        ] {
+            assert_eq!(
+                parse_timestamp(malformed_timestamp),
+                Err(ValidationError(format!(
+                    "String `{}` is not a valid timestamp, expected an ISO 8601 date and time with an offset, e.g. `2024-01-15T12:30:00Z`, or a date, e.g. `2024-01-15`",
+                    malformed_timestamp
+                )))
+            );
+        }
+    }
+
+    #[test]
+    fn timestamps_are_rendered_as_iso_8601() {
+        assert_eq!(
+            timestamp_iso(&OffsetDateTime::from_unix_timestamp(1_705_321_800).unwrap()),
+            "2024-01-15T12:30:00Z"
+        );
+        assert_eq!(
+            timestamp_iso(&parse_timestamp("2024-01-15T14:30:00.5+02:00").unwrap()),
+            "2024-01-15T12:30:00.5Z"
+        );
     }
 }
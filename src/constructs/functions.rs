@@ -7,12 +7,20 @@ use uuid::Uuid;
 use super::components::DataInstanceRaw;
 
 #[derive(Debug, PartialEq, Eq, Clone)]
+#[non_exhaustive]
 pub enum Function {
     Ulid,
     Now,
 }
 
 impl Function {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Ulid => "ULID",
+            Self::Now => "NOW",
+        }
+    }
+
     pub fn call(&self) -> DataInstanceRaw {
         match self {
             Self::Ulid => DataInstanceRaw::Uuid(Uuid::from(Ulid::new())),
@@ -23,14 +31,7 @@ impl Function {
 
 impl fmt::Display for Function {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            "keyword `{}`",
-            match self {
-                Self::Ulid => "ULID",
-                Self::Now => "NOW",
-            }
-        )
+        write!(f, "keyword `{}`", self.name())
     }
 }
 
@@ -48,3 +49,50 @@ impl FromStr for Function {
         }
     }
 }
+
+/// Function computing a single value out of many rows.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[non_exhaustive]
+pub enum Aggregate {
+    Count,
+    Sum,
+    Min,
+    Max,
+    Avg,
+}
+
+impl Aggregate {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Count => "COUNT",
+            Self::Sum => "SUM",
+            Self::Min => "MIN",
+            Self::Max => "MAX",
+            Self::Avg => "AVG",
+        }
+    }
+}
+
+impl fmt::Display for Aggregate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "aggregate `{}`", self.name())
+    }
+}
+
+impl FromStr for Aggregate {
+    type Err = String;
+
+    fn from_str(candidate: &str) -> std::result::Result<Self, Self::Err> {
+        match candidate.to_lowercase().as_str() {
+            "count" => Ok(Self::Count),
+            "sum" => Ok(Self::Sum),
+            "min" => Ok(Self::Min),
+            "max" => Ok(Self::Max),
+            "avg" => Ok(Self::Avg),
+            _ => Err(format!(
+                "`{}` does not refer to a supported aggregate",
+                candidate
+            )),
+        }
+    }
+}
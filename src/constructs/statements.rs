@@ -1,8 +1,12 @@
 use crate::sql::ValidationError;
 
 use super::components::*;
+use super::functions::Aggregate;
+use std::collections::HashSet;
+use std::fmt;
 
 #[derive(Debug, PartialEq, Eq)]
+#[non_exhaustive]
 pub struct CreateTableStatement {
     pub table: TableDefinition,
     pub if_not_exists: bool,
@@ -12,13 +16,19 @@ impl Validatable for CreateTableStatement {
     fn validate(&self) -> Result<(), ValidationError> {
         self.table.validate()
     }
+
+    fn validate_with_name_policy(&self, name_policy: &NamePolicy) -> Result<(), ValidationError> {
+        self.table.validate_with_name_policy(name_policy)
+    }
 }
 
 #[derive(Debug, PartialEq, Eq)]
+#[non_exhaustive]
 pub struct InsertStatement {
     pub table_name: String,
     pub column_names: Vec<String>,
-    pub values: Vec<DataInstance>,
+    /// Constants or parameters.
+    pub values: Vec<DataDefinition>,
 }
 
 impl Validatable for InsertStatement {
@@ -28,21 +38,224 @@ impl Validatable for InsertStatement {
 }
 
 #[derive(Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct TruncateTableStatement {
+    pub table_name: String,
+}
+
+impl Validatable for TruncateTableStatement {
+    fn validate(&self) -> Result<(), ValidationError> {
+        Ok(())
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct DescribeStatement {
+    pub table_name: String,
+}
+
+impl Validatable for DescribeStatement {
+    fn validate(&self) -> Result<(), ValidationError> {
+        Ok(())
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct DeleteStatement {
+    pub table_name: String,
+    /// No WHERE means that all rows are deleted.
+    pub where_clause: Option<Expression>,
+}
+
+impl Validatable for DeleteStatement {
+    fn validate(&self) -> Result<(), ValidationError> {
+        Ok(())
+    }
+}
+
+/// `column = value` of UPDATE's SET.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Assignment {
+    pub column_name: String,
+    pub value: DataDefinition,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct UpdateStatement {
+    pub table_name: String,
+    pub assignments: Vec<Assignment>,
+    /// No WHERE means that all rows are updated.
+    pub where_clause: Option<Expression>,
+}
+
+impl Validatable for UpdateStatement {
+    fn validate(&self) -> Result<(), ValidationError> {
+        Ok(()) // TODO: Add checks
+    }
+}
+
+/// What an aggregate is computed over.
+#[derive(Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum AggregateArgument {
+    /// `*`, i.e. whole rows.
+    All,
+    /// Values of a column.
+    Identifier(String),
+}
+
+impl fmt::Display for AggregateArgument {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::All => write!(f, "*"),
+            Self::Identifier(column_name) => write!(f, "{}", column_name),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum SelectColumn {
     All,
     Identifier(String),
+    /// Column qualified with the name of its table, e.g. `users.id`.
+    QualifiedIdentifier {
+        table_name: String,
+        column_name: String,
+    },
+    Aggregate {
+        function: Aggregate,
+        argument: AggregateArgument,
+    },
+    /// Value computed for each row, e.g. `price * quantity`.
+    Expression(Expression),
+}
+
+/// Item of the SELECT list: a column, along with the name it's output under if given with `AS`.
+#[derive(Debug, PartialEq, Eq)]
+pub struct SelectItem {
+    pub column: SelectColumn,
+    pub alias: Option<String>,
+}
+
+impl From<SelectColumn> for SelectItem {
+    fn from(column: SelectColumn) -> Self {
+        Self {
+            column,
+            alias: None,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum OrderDirection {
+    Asc,
+    Desc,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct OrderBy {
+    pub column_name: String,
+    pub direction: OrderDirection,
+}
+
+/// `JOIN table ON condition` of SELECT. Only INNER JOIN is supported, i.e. pairs of rows that don't satisfy
+/// the condition are left out. The condition must be an equality of a column of each table.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Join {
+    pub table_name: String,
+    pub condition: Expression,
 }
 
 #[derive(Debug, PartialEq, Eq)]
+#[non_exhaustive]
 pub struct SelectStatement {
-    pub columns: Vec<SelectColumn>,
+    /// Whether rows with equal values in all columns are returned only once (NULLs are equal to each other here).
+    pub distinct: bool,
+    pub columns: Vec<SelectItem>,
     /// String means table name
     pub source: String,
+    /// Another table whose rows are joined with those of `source`.
+    pub join: Option<Join>,
     pub where_clause: Option<Expression>,
+    /// Names of the columns to group rows by. Empty means that rows aren't grouped.
+    pub group_by: Vec<String>,
+    /// Empty means no particular order.
+    pub order_by: Vec<OrderBy>,
+    /// Maximum number of rows to return.
+    pub limit: Option<u64>,
+    /// Number of rows to skip.
+    pub offset: Option<u64>,
+    /// Opaque cursor of the page to return, from the previous page's result, or empty for the first page.
+    /// Pages follow the PRIMARY KEY order. Not part of SQL – it's set from the `cursor` param of a request.
+    pub cursor: Option<String>,
 }
 
 impl Validatable for SelectStatement {
     fn validate(&self) -> Result<(), ValidationError> {
-        Ok(()) // TODO: Add checks
+        let mut aliases: HashSet<String> = HashSet::new();
+        for item in &self.columns {
+            let alias = match (&item.column, &item.alias) {
+                (_, None) => continue,
+                (SelectColumn::All, Some(_)) => {
+                    return Err(ValidationError(
+                        "`*` cannot be given an alias, as it stands for multiple columns".into(),
+                    ))
+                }
+                (_, Some(alias)) => alias,
+            };
+            if !aliases.insert(normalize_identifier(alias)) {
+                return Err(ValidationError(format!(
+                    "Alias `{}` is given to more than one SELECT column",
+                    alias
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum AlterTableAction {
+    AddColumn(ColumnDefinition),
+    DropColumn(String),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct AlterTableStatement {
+    pub table_name: String,
+    pub action: AlterTableAction,
+}
+
+impl Validatable for AlterTableStatement {
+    fn validate(&self) -> Result<(), ValidationError> {
+        self.validate_with_name_policy(&NamePolicy::default())
+    }
+
+    fn validate_with_name_policy(&self, name_policy: &NamePolicy) -> Result<(), ValidationError> {
+        match &self.action {
+            AlterTableAction::AddColumn(column) => {
+                column.validate_with_name_policy(name_policy)?;
+                if column.primary_key {
+                    return Err(ValidationError(format!(
+                        "Column `{}` cannot be a PRIMARY KEY, as the table already has one",
+                        column.name
+                    )));
+                }
+                if !column.data_type.is_nullable && column.default.is_none() {
+                    return Err(ValidationError(format!(
+                        "Column `{}` must be nullable or have a DEFAULT, so that existing rows can be filled in",
+                        column.name
+                    )));
+                }
+                Ok(())
+            }
+            AlterTableAction::DropColumn(_) => Ok(()),
+        }
     }
 }
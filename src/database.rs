@@ -0,0 +1,211 @@
+//! Embedded database engine, for running SQL in-process without the HTTP server.
+
+use crate::config::Config;
+use crate::constructs::components::Validatable;
+use crate::executor::{ExecutionError, Executor, StatementOutcome};
+use crate::sql::{parse_statements_with_limits, Statement, SyntaxError, ValidationError};
+use std::io;
+use thiserror::Error;
+use tokio::runtime;
+use tracing::*;
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum EngineError {
+    /// The input can't be parsed.
+    #[error(transparent)]
+    Syntax(#[from] SyntaxError),
+    /// A statement doesn't make sense, by itself or in the context of the current database state.
+    #[error(transparent)]
+    Validation(#[from] ValidationError),
+    /// Data couldn't be read from or written to disk.
+    #[error("StorageError: {0}")]
+    Storage(String),
+}
+
+impl From<ExecutionError> for EngineError {
+    fn from(execution_error: ExecutionError) -> Self {
+        match execution_error {
+            ExecutionError::Validation(validation_error) => Self::Validation(validation_error),
+            ExecutionError::Storage(message) => Self::Storage(message),
+        }
+    }
+}
+
+/// Database operating on the data directory of the config, executing SQL synchronously.
+///
+/// `Database` is `Send` and `Sync`, so it can be shared between threads, e.g. in an `Arc`. It's locked internally
+/// just like the server's executor: each statement locks its table, shared for reads and exclusive for writes,
+/// while inputs creating or altering tables, or controlling a transaction, hold off all others until done.
+///
+/// The database runs its own Tokio runtime for I/O, so [`Database::execute`] must not be called from within
+/// an async context (that panics). There, use `tokio::task::spawn_blocking`.
+pub struct Database {
+    config: Config,
+    runtime: runtime::Runtime,
+    executor: Executor,
+}
+
+impl Database {
+    /// Open the database, loading the catalog and replaying the write-ahead log (if enabled).
+    pub fn open(config: &Config) -> Result<Self, io::Error> {
+        // Futures are polled on the threads calling `execute`, so a single worker is enough to drive I/O
+        let runtime = runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .enable_all()
+            .build()?;
+        let mut executor = Executor::new(config);
+        runtime.block_on(executor.bootstrap())?;
+        Ok(Database {
+            config: config.clone(),
+            runtime,
+            executor,
+        })
+    }
+
+    /// Parse, validate and execute the semicolon-separated statements of the input, in the configured dialect.
+    /// Like over HTTP, execution stops at the first error, and the outcome is that of the last statement.
+    pub fn execute(&self, sql: &str) -> Result<StatementOutcome, EngineError> {
+        let statements = parse_input(&self.config, sql)?;
+        validate_statements(&self.config, &statements)?;
+        Ok(self
+            .runtime
+            .block_on(self.executor.execute_batch(statements))?)
+    }
+}
+
+/// Parse the semicolon-separated statements of the input as configured, i.e. in the configured dialect,
+/// with the configured trailing commas and within the configured query limits.
+/// The server parses queries this way too, only binding parameters before validation.
+pub(crate) fn parse_input(config: &Config, sql: &str) -> Result<Vec<Statement>, SyntaxError> {
+    parse_statements_with_limits(
+        sql,
+        config.sql_dialect,
+        config.sql_trailing_commas,
+        &config.parse_limits(),
+    )
+}
+
+/// Validate the statements as configured, stopping at the first invalid one.
+pub(crate) fn validate_statements(
+    config: &Config,
+    statements: &[Statement],
+) -> Result<(), ValidationError> {
+    let name_policy = config.name_policy();
+    statements
+        .iter()
+        .try_for_each(|statement| statement.validate_with_name_policy(&name_policy))
+}
+
+impl Drop for Database {
+    fn drop(&mut self) {
+        if let Err(error) = self.runtime.block_on(self.executor.sync_wal()) {
+            error!("‼️ Failed to sync the write-ahead log: {}", error);
+        }
+    }
+}
+
+#[cfg(test)]
+mod database_tests {
+    use super::*;
+    use crate::constructs::components::{DataInstance, DataInstanceRaw, NameCharacters};
+    use crate::storage::Row;
+    use pretty_assertions::assert_eq;
+    use rand::distributions::Alphanumeric;
+    use rand::{thread_rng, Rng};
+    use std::path::Path;
+
+    /// Database operating on a fresh data directory.
+    fn get_test_database() -> Database {
+        get_test_database_with(Config::default())
+    }
+
+    /// Database with the given config, operating on a fresh data directory.
+    fn get_test_database_with(config: Config) -> Database {
+        let data_directory_name: String = thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(10)
+            .map(char::from)
+            .collect();
+        Database::open(&Config {
+            data_directory: Path::new(env!("TMPDIR"))
+                .join(data_directory_name)
+                .to_string_lossy()
+                .into(),
+            ..config
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn database_is_shareable_between_threads() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Database>();
+    }
+
+    #[test]
+    fn statements_are_executed_through_embedded_database() {
+        let database = get_test_database();
+        assert!(matches!(
+            database.execute("CREATE TABLE people (id UINT64 PRIMARY KEY, name STRING)"),
+            Ok(StatementOutcome::TableCreated(true))
+        ));
+        assert!(matches!(
+            database.execute("INSERT INTO people (id, name) VALUES (1, 'Alice'); INSERT INTO people (id, name) VALUES (2, 'Bob')"),
+            Ok(StatementOutcome::RowsAffected(1))
+        ));
+        let query_result = match database.execute("SELECT name FROM people WHERE id = 2") {
+            Ok(StatementOutcome::Rows(query_result)) => query_result,
+            outcome => panic!("Expected rows, instead got {:?}", outcome),
+        };
+        assert_eq!(query_result.column_names, ["name"]);
+        assert_eq!(
+            query_result.rows,
+            [Row(vec![DataInstance::Direct(DataInstanceRaw::String(
+                "Bob".into()
+            ))])]
+        );
+    }
+
+    #[test]
+    fn errors_are_returned_from_embedded_database() {
+        let database = get_test_database();
+        assert!(matches!(
+            database.execute("SELECT * FROM"),
+            Err(EngineError::Syntax(_))
+        ));
+        assert_eq!(
+            database.execute("SELECT * FROM people").unwrap_err(),
+            EngineError::Validation(ValidationError("Table `people` does not exist".into()))
+        );
+    }
+
+    #[test]
+    fn names_are_validated_as_configured() {
+        let database = get_test_database_with(Config {
+            max_name_length: 6,
+            name_characters: NameCharacters::Strict,
+            ..Default::default()
+        });
+        assert_eq!(
+            database
+                .execute("CREATE TABLE \"my-people\" (id UINT64 PRIMARY KEY)")
+                .unwrap_err(),
+            EngineError::Validation(ValidationError(
+                "Table name `my-people` is 9 characters long, over the limit of 6".into()
+            ))
+        );
+        assert_eq!(
+            database
+                .execute("CREATE TABLE \"my-ppl\" (id UINT64 PRIMARY KEY)")
+                .unwrap_err(),
+            EngineError::Validation(ValidationError(
+                "Table name `my-ppl` must consist of letters, digits and underscores, and not start with a digit"
+                    .into()
+            ))
+        );
+        assert!(matches!(
+            database.execute("CREATE TABLE people (id UINT64 PRIMARY KEY)"),
+            Ok(StatementOutcome::TableCreated(true))
+        ));
+    }
+}
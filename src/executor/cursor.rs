@@ -0,0 +1,189 @@
+use crate::{
+    constructs::components::{timestamp_iso, DataInstance, DataInstanceRaw, TableDefinition},
+    sql::ValidationError,
+    storage::Row,
+};
+use std::cmp::Ordering;
+
+/// Encode the PRIMARY KEY of the last row of a page as an opaque cursor of the next page.
+/// The cursor is a URL-safe Base64 JSON array of the key's values, with integers as decimal strings,
+/// as JSON numbers can't represent every `UINT128`.
+pub fn encode_cursor(primary_key: &[DataInstance]) -> String {
+    let values: Vec<serde_json::Value> = primary_key
+        .iter()
+        .map(|value| match value.as_raw() {
+            None => serde_json::Value::Null,
+            Some(DataInstanceRaw::Bool(value)) => serde_json::Value::Bool(*value),
+            Some(DataInstanceRaw::String(value)) => serde_json::Value::String(value.clone()),
+            Some(DataInstanceRaw::Uuid(value)) => serde_json::Value::String(value.to_string()),
+            Some(DataInstanceRaw::Timestamp(value)) => {
+                serde_json::Value::String(timestamp_iso(value))
+            }
+            Some(value) => serde_json::Value::String(value.as_u128().unwrap().to_string()),
+        })
+        .collect();
+    base64::encode_config(
+        serde_json::to_string(&values).unwrap(),
+        base64::URL_SAFE_NO_PAD,
+    )
+}
+
+/// Decode a cursor into the PRIMARY KEY after which the page starts, typed like the table's key columns.
+/// An empty cursor means the first page, which is signified by `None`.
+pub fn decode_cursor(
+    cursor: &str,
+    table_definition: &TableDefinition,
+) -> Result<Option<Vec<DataInstance>>, ValidationError> {
+    if cursor.is_empty() {
+        return Ok(None);
+    }
+    let invalid_cursor_error = || {
+        ValidationError(format!(
+            "Cursor `{}` is invalid for table `{}`",
+            cursor, table_definition.name
+        ))
+    };
+    let values = base64::decode_config(cursor, base64::URL_SAFE_NO_PAD)
+        .ok()
+        .and_then(|json| serde_json::from_slice::<Vec<serde_json::Value>>(&json).ok())
+        .ok_or_else(invalid_cursor_error)?;
+    let primary_key_columns = table_definition.get_primary_key_columns();
+    if values.len() != primary_key_columns.len() {
+        return Err(invalid_cursor_error());
+    }
+    values
+        .into_iter()
+        .zip(primary_key_columns)
+        .map(|(value, column)| {
+            let value = match value {
+                serde_json::Value::Null => DataInstance::Null,
+                serde_json::Value::Bool(value) => {
+                    DataInstance::Direct(DataInstanceRaw::Bool(value))
+                }
+                serde_json::Value::String(value)
+                    if column.data_type.raw_type.integer_range().is_some() =>
+                {
+                    DataInstance::Direct(DataInstanceRaw::UInt128(
+                        value.parse().map_err(|_| invalid_cursor_error())?,
+                    ))
+                }
+                serde_json::Value::String(value) => {
+                    DataInstance::Direct(DataInstanceRaw::String(value))
+                }
+                _ => return Err(invalid_cursor_error()),
+            };
+            value
+                .coerce(&column.data_type)
+                .map_err(|_| invalid_cursor_error())
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map(Some)
+}
+
+/// Values of the row's PRIMARY KEY columns, in column order.
+pub fn get_primary_key(row: &Row, table_definition: &TableDefinition) -> Vec<DataInstance> {
+    table_definition
+        .columns
+        .iter()
+        .zip(&row.0)
+        .filter(|(column, _)| column.primary_key)
+        .map(|(_, value)| value.clone())
+        .collect()
+}
+
+/// Compare two PRIMARY KEYs column by column, the same way as ORDER BY ASC does, i.e. with NULLs last.
+pub fn compare_primary_keys(key_a: &[DataInstance], key_b: &[DataInstance]) -> Ordering {
+    for (value_a, value_b) in key_a.iter().zip(key_b) {
+        let ordering = match (value_a.as_raw(), value_b.as_raw()) {
+            // Values of the same column are always comparable
+            (Some(value_a), Some(value_b)) => value_a.compare(value_b).unwrap_or(Ordering::Equal),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    Ordering::Equal
+}
+
+#[cfg(test)]
+mod cursor_tests {
+    use super::*;
+    use crate::constructs::components::{ColumnDefinition, DataType, DataTypeRaw};
+    use pretty_assertions::assert_eq;
+
+    fn get_test_column(name: &str, raw_type: DataTypeRaw, primary_key: bool) -> ColumnDefinition {
+        ColumnDefinition {
+            name: name.into(),
+            data_type: DataType {
+                raw_type,
+                is_nullable: false,
+            },
+            primary_key,
+            unique: false,
+            default: None,
+            comment: None,
+        }
+    }
+
+    fn get_test_table() -> TableDefinition {
+        TableDefinition::new(
+            "events".into(),
+            vec![
+                get_test_column("user_id", DataTypeRaw::UInt128, true),
+                get_test_column("note", DataTypeRaw::String, false),
+                get_test_column("created_at", DataTypeRaw::Timestamp, true),
+            ],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn cursor_round_trips_composite_primary_key() {
+        let table_definition = get_test_table();
+        let primary_key = vec![
+            DataInstance::Direct(DataInstanceRaw::UInt128(u128::MAX)),
+            DataInstance::Direct(
+                DataInstanceRaw::String("2024-01-15T12:30:00.000001Z".into())
+                    .coerce(DataTypeRaw::Timestamp)
+                    .unwrap(),
+            ),
+        ];
+
+        let cursor = encode_cursor(&primary_key);
+
+        assert!(cursor
+            .chars()
+            .all(|character| character.is_ascii_alphanumeric() || "-_".contains(character)));
+        assert_eq!(
+            decode_cursor(&cursor, &table_definition),
+            Ok(Some(primary_key))
+        );
+    }
+
+    #[test]
+    fn empty_cursor_means_first_page() {
+        assert_eq!(decode_cursor("", &get_test_table()), Ok(None));
+    }
+
+    #[test]
+    fn malformed_cursor_is_rejected() {
+        let table_definition = get_test_table();
+        for cursor in [
+            "!".to_string(),
+            base64::encode_config("{}", base64::URL_SAFE_NO_PAD),
+            base64::encode_config("[\"1\"]", base64::URL_SAFE_NO_PAD),
+            base64::encode_config("[\"x\",\"2024-01-15\"]", base64::URL_SAFE_NO_PAD),
+        ] {
+            assert_eq!(
+                decode_cursor(&cursor, &table_definition),
+                Err(ValidationError(format!(
+                    "Cursor `{}` is invalid for table `events`",
+                    cursor
+                )))
+            );
+        }
+    }
+}
@@ -0,0 +1,368 @@
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::ops::Bound;
+
+use crate::constructs::components::{
+    DataDefinition, DataInstance, DataInstanceRaw, Expression, TableDefinition,
+};
+use crate::storage::Row;
+
+/// Values of the PRIMARY KEY columns, in column order. Values of the same column are always comparable,
+/// so keys are totally ordered, lexicographically.
+#[derive(Debug, Clone)]
+struct IndexKey(Vec<DataInstanceRaw>);
+
+impl PartialEq for IndexKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for IndexKey {}
+
+impl PartialOrd for IndexKey {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for IndexKey {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0
+            .iter()
+            .zip(&other.0)
+            .map(|(value, other_value)| value.compare(other_value).unwrap_or(Ordering::Equal))
+            .find(|ordering| *ordering != Ordering::Equal)
+            .unwrap_or(Ordering::Equal)
+    }
+}
+
+/// In-memory index of a table's rows by PRIMARY KEY value, pointing to positions of rows in storage.
+#[derive(Debug, Default)]
+pub struct PrimaryKeyIndex {
+    positions: BTreeMap<IndexKey, usize>,
+    row_count: usize,
+}
+
+impl PrimaryKeyIndex {
+    pub fn build(table_definition: &TableDefinition, rows: &[Row]) -> Self {
+        let primary_key_indexes = primary_key_column_indexes(table_definition);
+        let mut index = PrimaryKeyIndex::default();
+        for row in rows {
+            index.push(&primary_key_of(&primary_key_indexes, row));
+        }
+        index
+    }
+
+    /// Position of the row with the specified PRIMARY KEY values, if there is one.
+    pub fn get(&self, key: &[DataInstanceRaw]) -> Option<usize> {
+        self.positions.get(&IndexKey(key.to_vec())).copied()
+    }
+
+    /// Positions of rows with PRIMARY KEY values greater than `key` (of all indexed rows if `None`),
+    /// in PRIMARY KEY order.
+    pub fn positions_after(
+        &self,
+        key: Option<&[DataInstanceRaw]>,
+    ) -> impl Iterator<Item = usize> + '_ {
+        let start = match key {
+            Some(key) => Bound::Excluded(IndexKey(key.to_vec())),
+            None => Bound::Unbounded,
+        };
+        self.positions
+            .range((start, Bound::Unbounded))
+            .map(|(_, position)| *position)
+    }
+
+    /// Number of rows in storage, including ones not indexed.
+    pub fn row_count(&self) -> usize {
+        self.row_count
+    }
+
+    /// Whether some rows aren't indexed, as there's a NULL in their PRIMARY KEY.
+    pub fn has_unindexed_rows(&self) -> bool {
+        self.positions.len() < self.row_count
+    }
+
+    /// Register the PRIMARY KEY values of a row appended to storage.
+    pub fn push(&mut self, key: &[DataInstance]) {
+        if let Some(key) = raw_primary_key(key) {
+            self.positions.insert(IndexKey(key), self.row_count);
+        }
+        self.row_count += 1;
+    }
+}
+
+/// Positions of the PRIMARY KEY columns, of which a table has at least one.
+pub fn primary_key_column_indexes(table_definition: &TableDefinition) -> Vec<usize> {
+    table_definition
+        .columns
+        .iter()
+        .enumerate()
+        .filter(|(_, column)| column.primary_key)
+        .map(|(column_index, _)| column_index)
+        .collect()
+}
+
+/// Values of the PRIMARY KEY columns at the given positions of the row.
+pub fn primary_key_of(primary_key_indexes: &[usize], row: &Row) -> Vec<DataInstance> {
+    primary_key_indexes
+        .iter()
+        .map(|column_index| row.0[*column_index].clone())
+        .collect()
+}
+
+/// PRIMARY KEY values without NULLs, or `None` if any of them is NULL.
+pub fn raw_primary_key(key: &[DataInstance]) -> Option<Vec<DataInstanceRaw>> {
+    key.iter().map(|value| value.as_raw().cloned()).collect()
+}
+
+/// If the condition is equality of every PRIMARY KEY column and a constant, i.e. `pk = value` or `value = pk`,
+/// joined with AND for composite keys, return the values coerced to the PRIMARY KEY columns' types.
+/// Such conditions can be answered with the index.
+/// `Some(None)` means that a value can't be a PRIMARY KEY value at all (e.g. NULL), so nothing matches.
+pub fn find_primary_key_lookup(
+    condition: Option<&Expression>,
+    table_definition: &TableDefinition,
+) -> Option<Option<Vec<DataInstanceRaw>>> {
+    let mut equalities = Vec::new();
+    collect_conjuncts(condition?, &mut equalities);
+    let primary_key_indexes = primary_key_column_indexes(table_definition);
+    if equalities.len() != primary_key_indexes.len() {
+        return None;
+    }
+    let mut key: Vec<Option<Option<DataInstanceRaw>>> = vec![None; primary_key_indexes.len()];
+    for equality in equalities {
+        let (lhs, rhs) = match equality {
+            Expression::Equal(lhs, rhs) => (lhs.as_ref(), rhs.as_ref()),
+            _ => return None,
+        };
+        let (column_name, value) = match (lhs, rhs) {
+            (
+                Expression::Atom(DataDefinition::Identifier(column_name)),
+                Expression::Atom(DataDefinition::Const(value)),
+            )
+            | (
+                Expression::Atom(DataDefinition::Const(value)),
+                Expression::Atom(DataDefinition::Identifier(column_name)),
+            ) => (column_name, value),
+            _ => return None,
+        };
+        let (column_index, column) = table_definition.get_column(column_name)?;
+        let key_position = primary_key_indexes
+            .iter()
+            .position(|primary_key_index| *primary_key_index == column_index)?;
+        if key[key_position].is_some() {
+            // The same column compared twice means that some other PRIMARY KEY column isn't constrained
+            return None;
+        }
+        key[key_position] = Some(
+            value
+                .as_raw()
+                .and_then(|value| value.clone().coerce(column.data_type.raw_type).ok()),
+        );
+    }
+    Some(
+        key.into_iter()
+            .collect::<Option<Vec<_>>>()?
+            .into_iter()
+            .collect(),
+    )
+}
+
+/// Flatten a chain of ANDs into the conditions joined.
+fn collect_conjuncts<'e>(condition: &'e Expression, conjuncts: &mut Vec<&'e Expression>) {
+    match condition {
+        Expression::And(lhs, rhs) => {
+            collect_conjuncts(lhs, conjuncts);
+            collect_conjuncts(rhs, conjuncts);
+        }
+        condition => conjuncts.push(condition),
+    }
+}
+
+#[cfg(test)]
+mod index_tests {
+    use super::*;
+    use crate::constructs::components::{ColumnDefinition, DataType, DataTypeRaw};
+    use pretty_assertions::assert_eq;
+
+    fn get_test_table() -> TableDefinition {
+        TableDefinition::new(
+            "test".into(),
+            vec![
+                ColumnDefinition {
+                    name: "note".into(),
+                    data_type: DataType {
+                        raw_type: DataTypeRaw::String,
+                        is_nullable: false,
+                    },
+                    primary_key: false,
+                    unique: false,
+                    default: None,
+                    comment: None,
+                },
+                ColumnDefinition {
+                    name: "id".into(),
+                    data_type: DataType {
+                        raw_type: DataTypeRaw::UInt16,
+                        is_nullable: false,
+                    },
+                    primary_key: true,
+                    unique: false,
+                    default: None,
+                    comment: None,
+                },
+            ],
+        )
+        .unwrap()
+    }
+
+    fn equal(lhs: DataDefinition, rhs: DataDefinition) -> Expression {
+        Expression::Equal(
+            Box::new(Expression::Atom(lhs)),
+            Box::new(Expression::Atom(rhs)),
+        )
+    }
+
+    #[test]
+    fn index_lookup_works() {
+        let table_definition = get_test_table();
+        let rows: Vec<Row> = [7, 3, 5]
+            .iter()
+            .map(|id| {
+                Row(vec![
+                    DataInstance::Direct(DataInstanceRaw::String("x".into())),
+                    DataInstance::Direct(DataInstanceRaw::UInt16(*id)),
+                ])
+            })
+            .collect();
+        let mut index = PrimaryKeyIndex::build(&table_definition, &rows);
+        index.push(&[DataInstance::Direct(DataInstanceRaw::UInt16(1))]);
+        assert_eq!(index.get(&[DataInstanceRaw::UInt16(3)]), Some(1));
+        assert_eq!(index.get(&[DataInstanceRaw::UInt16(1)]), Some(3));
+        assert_eq!(index.get(&[DataInstanceRaw::UInt16(4)]), None);
+    }
+
+    #[test]
+    fn index_positions_follow_key_order() {
+        let table_definition = get_test_table();
+        let rows: Vec<Row> = [7, 3, 5]
+            .iter()
+            .map(|id| {
+                Row(vec![
+                    DataInstance::Direct(DataInstanceRaw::String("x".into())),
+                    DataInstance::Direct(DataInstanceRaw::UInt16(*id)),
+                ])
+            })
+            .collect();
+        let mut index = PrimaryKeyIndex::build(&table_definition, &rows);
+        assert_eq!(index.positions_after(None).collect::<Vec<_>>(), [1, 2, 0]);
+        assert_eq!(
+            index
+                .positions_after(Some(&[DataInstanceRaw::UInt16(4)]))
+                .collect::<Vec<_>>(),
+            [2, 0]
+        );
+        assert_eq!(
+            index
+                .positions_after(Some(&[DataInstanceRaw::UInt16(5)]))
+                .collect::<Vec<_>>(),
+            [0]
+        );
+        assert!(!index.has_unindexed_rows());
+        index.push(&[DataInstance::Null]);
+        assert!(index.has_unindexed_rows());
+        assert_eq!(index.row_count(), 4);
+    }
+
+    #[test]
+    fn primary_key_lookup_is_found() {
+        let table_definition = get_test_table();
+        let id = || DataDefinition::Identifier("ID".into());
+        let five = || DataDefinition::Const(DataInstance::Direct(DataInstanceRaw::UInt32(5)));
+        assert_eq!(
+            find_primary_key_lookup(Some(&equal(id(), five())), &table_definition),
+            Some(Some(vec![DataInstanceRaw::UInt16(5)]))
+        );
+        assert_eq!(
+            find_primary_key_lookup(Some(&equal(five(), id())), &table_definition),
+            Some(Some(vec![DataInstanceRaw::UInt16(5)]))
+        );
+        assert_eq!(
+            find_primary_key_lookup(
+                Some(&equal(
+                    id(),
+                    DataDefinition::Const(DataInstance::Direct(DataInstanceRaw::UInt32(70_000)))
+                )),
+                &table_definition
+            ),
+            Some(None)
+        );
+    }
+
+    #[test]
+    fn primary_key_lookup_is_not_found_for_other_conditions() {
+        let table_definition = get_test_table();
+        let note = DataDefinition::Identifier("note".into());
+        let x = DataDefinition::Const(DataInstance::Direct(DataInstanceRaw::String("x".into())));
+        assert_eq!(find_primary_key_lookup(None, &table_definition), None);
+        assert_eq!(
+            find_primary_key_lookup(Some(&equal(note, x)), &table_definition),
+            None
+        );
+    }
+
+    #[test]
+    fn composite_primary_key_lookup_needs_every_column() {
+        let mut table_definition = get_test_table();
+        table_definition.columns[0].primary_key = true;
+        let note = || DataDefinition::Identifier("note".into());
+        let id = || DataDefinition::Identifier("id".into());
+        let x = || DataDefinition::Const(DataInstance::Direct(DataInstanceRaw::String("x".into())));
+        let five = || DataDefinition::Const(DataInstance::Direct(DataInstanceRaw::UInt32(5)));
+        let both = Expression::And(Box::new(equal(five(), id())), Box::new(equal(note(), x())));
+        assert_eq!(
+            find_primary_key_lookup(Some(&both), &table_definition),
+            Some(Some(vec![
+                DataInstanceRaw::String("x".into()),
+                DataInstanceRaw::UInt16(5)
+            ]))
+        );
+        assert_eq!(
+            find_primary_key_lookup(Some(&equal(id(), five())), &table_definition),
+            None
+        );
+        let repeated =
+            Expression::And(Box::new(equal(id(), five())), Box::new(equal(id(), five())));
+        assert_eq!(
+            find_primary_key_lookup(Some(&repeated), &table_definition),
+            None
+        );
+    }
+
+    #[test]
+    fn composite_index_lookup_works() {
+        let mut table_definition = get_test_table();
+        table_definition.columns[0].primary_key = true;
+        let rows: Vec<Row> = [("a", 1), ("a", 2), ("b", 1)]
+            .iter()
+            .map(|(note, id)| {
+                Row(vec![
+                    DataInstance::Direct(DataInstanceRaw::String(note.to_string())),
+                    DataInstance::Direct(DataInstanceRaw::UInt16(*id)),
+                ])
+            })
+            .collect();
+        let index = PrimaryKeyIndex::build(&table_definition, &rows);
+        let key = |note: &str, id| {
+            [
+                DataInstanceRaw::String(note.into()),
+                DataInstanceRaw::UInt16(id),
+            ]
+        };
+        assert_eq!(index.get(&key("a", 2)), Some(1));
+        assert_eq!(index.get(&key("b", 1)), Some(2));
+        assert_eq!(index.get(&key("b", 2)), None);
+    }
+}
@@ -0,0 +1,302 @@
+use std::collections::HashMap;
+
+use crate::constructs::components::{
+    normalize_identifier, ColumnDefinition, DataDefinition, DataInstance, DataInstanceRaw,
+    Expression, TableDefinition,
+};
+use crate::constructs::statements::{AggregateArgument, SelectColumn, SelectItem, SelectStatement};
+use crate::sql::ValidationError;
+use crate::storage::Row;
+
+/// Rewrite column references of the SELECT into plain names of columns of the relation it reads from.
+/// With a single table that's just the table's columns, and qualified references must name that table.
+/// With a join it's the combined table of `combine_tables`, where columns are only qualified if both tables have them,
+/// so unqualified references to such columns are ambiguous.
+pub fn resolve_column_references(
+    statement: &mut SelectStatement,
+    tables: &[&TableDefinition],
+) -> Result<(), ValidationError> {
+    for SelectItem {
+        column: select_column,
+        ..
+    } in &mut statement.columns
+    {
+        match select_column {
+            SelectColumn::Identifier(column_name) => {
+                *column_name = resolve_column_name(tables, None, column_name)?;
+            }
+            SelectColumn::QualifiedIdentifier {
+                table_name,
+                column_name,
+            } => {
+                *select_column = SelectColumn::Identifier(resolve_column_name(
+                    tables,
+                    Some(table_name),
+                    column_name,
+                )?);
+            }
+            SelectColumn::Aggregate {
+                argument: AggregateArgument::Identifier(column_name),
+                ..
+            } => {
+                *column_name = resolve_column_name(tables, None, column_name)?;
+            }
+            SelectColumn::Expression(expression) => {
+                resolve_expression_references(expression, tables)?;
+            }
+            SelectColumn::All
+            | SelectColumn::Aggregate {
+                argument: AggregateArgument::All,
+                ..
+            } => (),
+        }
+    }
+    if let Some(join) = &mut statement.join {
+        resolve_expression_references(&mut join.condition, tables)?;
+    }
+    if let Some(where_clause) = &mut statement.where_clause {
+        resolve_expression_references(where_clause, tables)?;
+    }
+    for column_name in &mut statement.group_by {
+        *column_name = resolve_column_name(tables, None, column_name)?;
+    }
+    for order_by in &mut statement.order_by {
+        order_by.column_name = resolve_column_name(tables, None, &order_by.column_name)?;
+    }
+    Ok(())
+}
+
+/// Rewrite column references of the expression into plain names of columns of the relation, like
+/// `resolve_column_references` does for a whole SELECT.
+pub fn resolve_expression_references(
+    expression: &mut Expression,
+    tables: &[&TableDefinition],
+) -> Result<(), ValidationError> {
+    let mut atoms = Vec::new();
+    expression.collect_atoms_mut(&mut atoms);
+    for atom in atoms {
+        resolve_data_definition_reference(atom, tables)?;
+    }
+    Ok(())
+}
+
+/// Rewrite a column reference into a plain name of a column of the relation. Other data definitions stay as they are.
+pub fn resolve_data_definition_reference(
+    data_definition: &mut DataDefinition,
+    tables: &[&TableDefinition],
+) -> Result<(), ValidationError> {
+    match data_definition {
+        DataDefinition::Identifier(column_name) => {
+            *column_name = resolve_column_name(tables, None, column_name)?;
+        }
+        DataDefinition::QualifiedIdentifier {
+            table_name,
+            column_name,
+        } => {
+            *data_definition = DataDefinition::Identifier(resolve_column_name(
+                tables,
+                Some(table_name),
+                column_name,
+            )?);
+        }
+        _ => (),
+    }
+    Ok(())
+}
+
+/// Name of the referenced column in the relation the statement reads from.
+fn resolve_column_name(
+    tables: &[&TableDefinition],
+    table_name: Option<&str>,
+    column_name: &str,
+) -> Result<String, ValidationError> {
+    let tables_in_scope: Vec<&TableDefinition> = match table_name {
+        Some(table_name) => {
+            let normalized_table_name = normalize_identifier(table_name);
+            let table = tables
+                .iter()
+                .find(|table| normalize_identifier(&table.name) == normalized_table_name)
+                .ok_or_else(|| {
+                    ValidationError(format!(
+                        "Table `{}` is not part of the query, so its columns can't be referenced",
+                        table_name
+                    ))
+                })?;
+            vec![table]
+        }
+        None => tables.to_vec(),
+    };
+    // A single table's missing column is reported where the column is used, like in any other statement
+    if let [table] = tables {
+        return Ok(table.get_column(column_name).map_or_else(
+            || column_name.to_string(),
+            |(_, column)| column.name.clone(),
+        ));
+    }
+    let matches: Vec<(&TableDefinition, &ColumnDefinition)> = tables_in_scope
+        .into_iter()
+        .filter_map(|table| {
+            table
+                .get_column(column_name)
+                .map(|(_, column)| (table, column))
+        })
+        .collect();
+    match matches.as_slice() {
+        [(table, column)] => Ok(get_combined_column_name(tables, table, column)),
+        [] => Err(ValidationError(format!(
+            "Column `{}` does not exist in {}",
+            column_name,
+            match table_name {
+                Some(table_name) => format!("table `{}`", table_name),
+                None => format!("tables {}", quote_table_names(tables)),
+            }
+        ))),
+        [(table, column), ..] => Err(ValidationError(format!(
+            "Column `{}` is ambiguous, as it exists in tables {} – qualify it with a table name, e.g. `{}.{}`",
+            column_name,
+            quote_table_names(tables),
+            table.name,
+            column.name
+        ))),
+    }
+}
+
+fn quote_table_names(tables: &[&TableDefinition]) -> String {
+    tables
+        .iter()
+        .map(|table| format!("`{}`", table.name))
+        .collect::<Vec<_>>()
+        .join(" and ")
+}
+
+/// Name of the column in the combined table. It's qualified with its table's name if the other table has
+/// a column of the same name too, e.g. `users.id`, so that names in the combined table are unique.
+fn get_combined_column_name(
+    tables: &[&TableDefinition],
+    table: &TableDefinition,
+    column: &ColumnDefinition,
+) -> String {
+    let tables_with_column_count = tables
+        .iter()
+        .filter(|table| table.get_column(&column.name).is_some())
+        .count();
+    if tables_with_column_count > 1 {
+        format!("{}.{}", table.name, column.name)
+    } else {
+        column.name.clone()
+    }
+}
+
+/// Definition of the relation made up of two joined tables: columns of the left one followed by those of the right.
+/// There's no PRIMARY KEY, as a row of either table may be paired with multiple rows of the other.
+pub fn combine_tables(left: &TableDefinition, right: &TableDefinition) -> TableDefinition {
+    let tables = [left, right];
+    TableDefinition {
+        name: format!("{} JOIN {}", left.name, right.name),
+        columns: tables
+            .iter()
+            .flat_map(|table| {
+                table.columns.iter().map(|column| ColumnDefinition {
+                    name: get_combined_column_name(&tables, table, column),
+                    primary_key: false,
+                    ..column.clone()
+                })
+            })
+            .collect(),
+    }
+}
+
+/// Positions of the columns compared by the JOIN condition within the left and right table respectively.
+/// The condition must already be resolved against the combined table.
+pub fn resolve_join_columns(
+    condition: &Expression,
+    left: &TableDefinition,
+    right: &TableDefinition,
+    combined: &TableDefinition,
+) -> Result<(usize, usize), ValidationError> {
+    let condition_error = || {
+        ValidationError(format!(
+            "JOIN condition must be an equality of a column of table `{}` and a column of table `{}`, e.g. `{}.id = {}.id`",
+            left.name, right.name, left.name, right.name
+        ))
+    };
+    let (lhs_name, rhs_name) = match condition {
+        Expression::Equal(lhs, rhs) => match (lhs.as_ref(), rhs.as_ref()) {
+            (
+                Expression::Atom(DataDefinition::Identifier(lhs_name)),
+                Expression::Atom(DataDefinition::Identifier(rhs_name)),
+            ) => (lhs_name, rhs_name),
+            _ => return Err(condition_error()),
+        },
+        _ => return Err(condition_error()),
+    };
+    let get_index = |column_name: &str| {
+        combined
+            .get_column(column_name)
+            .map(|(column_index, _)| column_index)
+            .ok_or_else(condition_error)
+    };
+    let (lhs_index, rhs_index) = (get_index(lhs_name)?, get_index(rhs_name)?);
+    let left_column_count = left.columns.len();
+    let (left_index, right_index) =
+        match (lhs_index < left_column_count, rhs_index < left_column_count) {
+            (true, false) => (lhs_index, rhs_index - left_column_count),
+            (false, true) => (rhs_index, lhs_index - left_column_count),
+            _ => return Err(condition_error()),
+        };
+    let left_type = left.columns[left_index].data_type.raw_type;
+    let right_type = right.columns[right_index].data_type.raw_type;
+    let are_types_compatible = left_type == right_type
+        || (left_type.integer_range().is_some() && right_type.integer_range().is_some());
+    if !are_types_compatible {
+        return Err(ValidationError(format!(
+            "JOIN condition compares column `{}` of type {} with column `{}` of type {}",
+            left.columns[left_index].name, left_type, right.columns[right_index].name, right_type
+        )));
+    }
+    Ok((left_index, right_index))
+}
+
+/// Pair up rows of the two tables whose join columns are equal, with a hash join: rows of the right table are
+/// hashed by their join column, and then looked up for each row of the left table. A joined row is made up of
+/// the left row's values followed by the right row's. Rows follow the order of the left table, and then the right.
+pub fn hash_join(
+    left_rows: Vec<Row>,
+    right_rows: &[Row],
+    left_index: usize,
+    right_index: usize,
+) -> Vec<Row> {
+    let mut right_positions_by_key: HashMap<DataInstanceRaw, Vec<usize>> = HashMap::new();
+    for (position, right_row) in right_rows.iter().enumerate() {
+        if let Some(key) = get_join_key(&right_row.0[right_index]) {
+            right_positions_by_key
+                .entry(key)
+                .or_default()
+                .push(position);
+        }
+    }
+    let mut joined_rows = Vec::new();
+    for left_row in left_rows {
+        let right_positions =
+            get_join_key(&left_row.0[left_index]).and_then(|key| right_positions_by_key.get(&key));
+        for position in right_positions.into_iter().flatten() {
+            joined_rows.push(Row(left_row
+                .0
+                .iter()
+                .chain(&right_rows[*position].0)
+                .cloned()
+                .collect()));
+        }
+    }
+    joined_rows
+}
+
+/// Value that a join column's value is hashed as. Integers are widened, so that columns of different widths
+/// can be joined. NULL isn't equal to anything, so it has no key.
+fn get_join_key(value: &DataInstance) -> Option<DataInstanceRaw> {
+    let value = value.as_raw()?;
+    Some(match value.as_u128() {
+        Some(integer) => DataInstanceRaw::UInt128(integer),
+        None => value.clone(),
+    })
+}
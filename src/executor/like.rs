@@ -0,0 +1,144 @@
+use crate::sql::ValidationError;
+
+/// Character that makes the following character of a LIKE pattern literal, e.g. `\%` matches `%` itself.
+const LIKE_ESCAPE_CHARACTER: char = '\\';
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum PatternElement {
+    /// A specific character.
+    Literal(char),
+    /// Any single character, written as `_`.
+    AnyCharacter,
+    /// Any sequence of characters, including an empty one, written as `%`.
+    AnySequence,
+}
+
+/// Pattern of LIKE, compiled to a sequence of elements that's matched without backtracking beyond the last `%`.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct LikePattern {
+    elements: Vec<PatternElement>,
+}
+
+impl LikePattern {
+    pub fn compile(pattern: &str) -> Result<Self, ValidationError> {
+        let mut elements = Vec::with_capacity(pattern.len());
+        let mut characters = pattern.chars();
+        while let Some(character) = characters.next() {
+            elements.push(match character {
+                '%' => {
+                    // Consecutive `%`s mean the same as a single one
+                    if elements.last() == Some(&PatternElement::AnySequence) {
+                        continue;
+                    }
+                    PatternElement::AnySequence
+                }
+                '_' => PatternElement::AnyCharacter,
+                LIKE_ESCAPE_CHARACTER => match characters.next() {
+                    Some(escaped_character) => PatternElement::Literal(escaped_character),
+                    None => {
+                        return Err(ValidationError(format!(
+                            "LIKE pattern `{}` ends with a dangling escape character `{}`",
+                            pattern, LIKE_ESCAPE_CHARACTER
+                        )))
+                    }
+                },
+                character => PatternElement::Literal(character),
+            });
+        }
+        Ok(LikePattern { elements })
+    }
+
+    /// Whether the whole candidate matches the pattern (case-sensitively).
+    pub fn matches(&self, candidate: &str) -> bool {
+        let candidate: Vec<char> = candidate.chars().collect();
+        let mut element_index = 0;
+        let mut character_index = 0;
+        // Where to resume if the current attempt fails: the element after the last `%`,
+        // and the candidate character that `%` should swallow next
+        let mut resume_point: Option<(usize, usize)> = None;
+        while character_index < candidate.len() {
+            match self.elements.get(element_index) {
+                Some(PatternElement::AnySequence) => {
+                    element_index += 1;
+                    resume_point = Some((element_index, character_index));
+                    continue;
+                }
+                Some(PatternElement::AnyCharacter) => {
+                    element_index += 1;
+                    character_index += 1;
+                    continue;
+                }
+                Some(PatternElement::Literal(expected))
+                    if *expected == candidate[character_index] =>
+                {
+                    element_index += 1;
+                    character_index += 1;
+                    continue;
+                }
+                _ => (),
+            }
+            match resume_point {
+                Some((resume_element_index, resume_character_index)) => {
+                    element_index = resume_element_index;
+                    character_index = resume_character_index + 1;
+                    resume_point = Some((resume_element_index, character_index));
+                }
+                None => return false,
+            }
+        }
+        self.elements[element_index..]
+            .iter()
+            .all(|element| *element == PatternElement::AnySequence)
+    }
+}
+
+#[cfg(test)]
+mod like_tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn matches(pattern: &str, candidate: &str) -> bool {
+        LikePattern::compile(pattern).unwrap().matches(candidate)
+    }
+
+    #[test]
+    fn matches_prefix_suffix_and_contains() {
+        assert!(matches("foo%", "foobar"));
+        assert!(matches("foo%", "foo"));
+        assert!(!matches("foo%", "barfoo"));
+        assert!(matches("%bar", "foobar"));
+        assert!(!matches("%bar", "barfoo"));
+        assert!(matches("%ob%", "foobar"));
+        assert!(!matches("%ob%", "fbo"));
+        assert!(matches("%a%a%", "banana"));
+        assert!(!matches("%a%a%a%a%", "banana"));
+    }
+
+    #[test]
+    fn matches_single_characters() {
+        assert!(matches("f_o", "foo"));
+        assert!(matches("f_o", "fło"));
+        assert!(!matches("f_o", "fo"));
+        assert!(!matches("f_o", "fooo"));
+        assert!(matches("_%_", "ab"));
+        assert!(!matches("_%_", "a"));
+    }
+
+    #[test]
+    fn matches_escaped_wildcards_literally() {
+        assert!(matches("100\\%", "100%"));
+        assert!(!matches("100\\%", "1000"));
+        assert!(matches("a\\_c", "a_c"));
+        assert!(!matches("a\\_c", "abc"));
+    }
+
+    #[test]
+    fn rejects_dangling_escape_character() {
+        assert_eq!(
+            LikePattern::compile("foo\\"),
+            Err(ValidationError(
+                "LIKE pattern `foo\\` ends with a dangling escape character `\\`".into()
+            ))
+        );
+    }
+}
@@ -1,29 +1,64 @@
+mod cursor;
+mod index;
+mod join;
+mod like;
 mod read;
 mod write;
 
-use parking_lot::Mutex;
+use parking_lot::{Mutex, RwLock};
+use std::collections::HashMap;
 use std::io;
+use std::path::Path;
 use std::sync::Arc;
 
 use crate::config;
-use crate::storage::system::SystemTable;
+use crate::sql::ValidationError;
+use crate::storage::catalog::{load_catalog, save_catalog};
+use crate::storage::filesystem::sync_table_file;
+use crate::storage::system::{SystemTable, SYSTEM_SCHEMA_NAME};
+use crate::storage::wal::WriteAheadLog;
 use crate::{
-    constructs::components::{DataInstance, DataInstanceRaw, TableDefinition},
+    constructs::components::{
+        normalize_identifier, DataInstance, DataInstanceRaw, TableDefinition,
+    },
+    constructs::statements::{
+        AlterTableStatement, DeleteStatement, DescribeStatement, InsertStatement, SelectStatement,
+        TruncateTableStatement, UpdateStatement,
+    },
     sql::Statement,
     storage::{NamedRow, Row},
 };
-use serde::{ser::SerializeSeq, Serialize, Serializer};
+use futures::stream::{FuturesUnordered, StreamExt};
+use index::PrimaryKeyIndex;
+use serde::{
+    ser::{SerializeMap, SerializeSeq},
+    Serialize, Serializer,
+};
+use thiserror::Error;
 use tokio::sync::{mpsc, oneshot};
 use tracing::*;
 
 const MAX_IN_FLIGHT_REQUESTS: usize = 100;
 
+/// Schema in which user tables live.
+pub const DEFAULT_SCHEMA_NAME: &str = "default";
+
 #[derive(Debug)]
 pub struct QueryResult {
     pub column_names: Vec<String>,
     pub rows: Vec<Row>,
 }
 
+impl QueryResult {
+    /// Result of a statement that doesn't return any data.
+    pub fn empty() -> Self {
+        QueryResult {
+            column_names: Vec::new(),
+            rows: Vec::new(),
+        }
+    }
+}
+
 impl Serialize for QueryResult {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -37,12 +72,108 @@ impl Serialize for QueryResult {
     }
 }
 
-/// Payload with a statement and a sender to return the result to.
-pub type ExecutorPayload = (Statement, oneshot::Sender<QueryResult>);
+/// What executing a statement came down to.
+#[derive(Debug)]
+pub enum StatementOutcome {
+    /// Rows returned, e.g. by SELECT.
+    Rows(QueryResult),
+    /// A page of rows returned by a SELECT with a cursor, along with the cursor of the next page,
+    /// or `None` if this is the last page.
+    Page {
+        rows: QueryResult,
+        next_cursor: Option<String>,
+    },
+    /// Number of rows inserted, updated or deleted.
+    RowsAffected(usize),
+    /// Whether CREATE TABLE actually created the table – `false` if it already existed, with IF NOT EXISTS.
+    TableCreated(bool),
+    /// Acknowledgement of a statement that neither returns nor affects rows, e.g. ALTER TABLE.
+    Done,
+}
+
+impl StatementOutcome {
+    /// The outcome as a table, with the number of affected rows under column `rows_affected`,
+    /// and whether a table was created under column `created`.
+    pub fn into_query_result(self) -> QueryResult {
+        match self {
+            Self::Rows(query_result)
+            | Self::Page {
+                rows: query_result, ..
+            } => query_result,
+            Self::RowsAffected(count) => QueryResult {
+                column_names: vec!["rows_affected".to_string()],
+                rows: vec![Row(vec![DataInstance::Direct(DataInstanceRaw::UInt64(
+                    count as u64,
+                ))])],
+            },
+            Self::TableCreated(is_created) => QueryResult {
+                column_names: vec!["created".to_string()],
+                rows: vec![Row(vec![DataInstance::Direct(DataInstanceRaw::Bool(
+                    is_created,
+                ))])],
+            },
+            Self::Done => QueryResult::empty(),
+        }
+    }
+}
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum ExecutionError {
+    /// The statement doesn't make sense in the context of the current database state.
+    #[error(transparent)]
+    Validation(#[from] ValidationError),
+    /// Data couldn't be read from or written to disk.
+    #[error("StorageError: {0}")]
+    Storage(String),
+}
+
+impl Serialize for ExecutionError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            Self::Validation(validation_error) => validation_error.serialize(serializer),
+            Self::Storage(message) => {
+                let mut map = serializer.serialize_map(Some(2))?;
+                map.serialize_entry("type", "storage")?;
+                map.serialize_entry("message", message)?;
+                map.end()
+            }
+        }
+    }
+}
+
+pub type ExecutionResult = Result<StatementOutcome, ExecutionError>;
+
+/// Payload with a batch of statements and a sender to return the result to.
+pub type ExecutorPayload = (Vec<Statement>, oneshot::Sender<ExecutionResult>);
+
+/// State of user tables at BEGIN, restored on ROLLBACK. Rows are only copied for tables that the transaction
+/// changes, just before their first change.
+struct TransactionSnapshot {
+    tables: Vec<TableDefinition>,
+    /// Rows of tables changed within the transaction, by normalized table name.
+    rows: HashMap<String, Vec<Row>>,
+    /// Result of the batch before BEGIN, which is its result again if the transaction is rolled back.
+    result_before: StatementOutcome,
+}
 
 pub struct Executor {
     config: config::Config,
-    tables: Arc<Mutex<Vec<TableDefinition>>>,
+    /// Catalog of user tables. Its lock is only held briefly, never across await points.
+    tables: Arc<RwLock<Vec<TableDefinition>>>,
+    /// PRIMARY KEY indexes of tables by normalized table name, built on first use. Statements reading a table
+    /// share its index, as it's only changed by writes, which hold the table's lock exclusively.
+    primary_key_indexes: Mutex<HashMap<String, Arc<RwLock<PrimaryKeyIndex>>>>,
+    /// Write-ahead log, if enabled. Its lock is held across await points, hence the async mutex.
+    wal: Option<tokio::sync::Mutex<WriteAheadLog>>,
+    /// Held for the whole execution of a batch – exclusive for batches that change the catalog or control
+    /// a transaction, and shared otherwise, with tables locked individually then.
+    batch_lock: tokio::sync::RwLock<()>,
+    /// Locks of user tables by normalized table name, held for a statement – shared by reads and exclusive for
+    /// writes, so that writes to different tables don't wait for one another.
+    table_locks: Mutex<HashMap<String, Arc<tokio::sync::RwLock<()>>>>,
     rx: Option<mpsc::Receiver<ExecutorPayload>>,
 }
 
@@ -50,7 +181,11 @@ impl Executor {
     pub fn new(config: &config::Config) -> Self {
         Executor {
             config: config.clone(),
-            tables: Arc::new(Mutex::new(Vec::new())),
+            tables: Arc::new(RwLock::new(Vec::new())),
+            primary_key_indexes: Mutex::new(HashMap::new()),
+            wal: None,
+            batch_lock: tokio::sync::RwLock::new(()),
+            table_locks: Mutex::new(HashMap::new()),
             rx: None,
         }
     }
@@ -65,29 +200,3146 @@ impl Executor {
         debug!("⬆️ Bootstraping the executor...");
         for table in SystemTable::ALL {
             let table_definition = table.get_definition();
-            write::ensure_table_file_exists(&self.config, &table_definition).await?;
+            write::ensure_table_file_exists(&self.config, SYSTEM_SCHEMA_NAME, &table_definition)
+                .await?;
+        }
+        let tables = load_catalog(&self.config).await?;
+        debug!(
+            "📒 Loaded {} table definitions from the catalog",
+            tables.len()
+        );
+        self.set_tables(tables);
+        if let Some(wal_path) = &self.config.wal_path {
+            let replayed_count = self.replay_wal(Path::new(wal_path)).await?;
+            debug!("📜 Replayed {} write-ahead log records", replayed_count);
+            self.wal = Some(tokio::sync::Mutex::new(
+                WriteAheadLog::open(&self.config, wal_path).await?,
+            ));
+            self.checkpoint().await?;
         }
         Ok(())
     }
 
-    pub async fn start(&mut self) -> Result<(), io::Error> {
+    /// Apply INSERTs from the write-ahead log that didn't make it to table files, returning how many were applied.
+    async fn replay_wal(&self, wal_path: &Path) -> Result<usize, io::Error> {
+        let invalid_record_error = |problem: String| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "Write-ahead log {} cannot be replayed: {}",
+                    wal_path.display(),
+                    problem
+                ),
+            )
+        };
+        let mut replayed_count = 0;
+        // Each table is read once, into an index of its rows, rather than for every record
+        let mut primary_key_indexes: HashMap<String, PrimaryKeyIndex> = HashMap::new();
+        for record in WriteAheadLog::read_records(wal_path).await? {
+            let table_definition = self
+                .get_table(record.table_name())
+                .map_err(|error| invalid_record_error(error.0))?;
+            let row = record
+                .into_row(&table_definition)
+                .map_err(invalid_record_error)?;
+            let normalized_table_name = normalize_identifier(&table_definition.name);
+            if !primary_key_indexes.contains_key(&normalized_table_name) {
+                let rows =
+                    read::read_all_rows(&self.config, DEFAULT_SCHEMA_NAME, &table_definition)
+                        .await
+                        .map_err(invalid_record_error)?;
+                primary_key_indexes.insert(
+                    normalized_table_name.clone(),
+                    PrimaryKeyIndex::build(&table_definition, &rows),
+                );
+            }
+            if write::replay_insert(
+                &self.config,
+                DEFAULT_SCHEMA_NAME,
+                &table_definition,
+                row,
+                primary_key_indexes.get_mut(&normalized_table_name).unwrap(),
+            )
+            .await
+            .map_err(invalid_record_error)?
+            {
+                replayed_count += 1;
+            }
+        }
+        Ok(replayed_count)
+    }
+
+    /// Make sure that table files are on disk, so that the write-ahead log can be emptied.
+    async fn checkpoint(&self) -> Result<(), io::Error> {
+        let wal = match &self.wal {
+            Some(wal) => wal,
+            None => return Ok(()),
+        };
+        let mut wal = wal.lock().await;
+        let table_names: Vec<String> = self
+            .tables
+            .read()
+            .iter()
+            .map(|table| table.name.clone())
+            .collect();
+        for table_name in table_names {
+            sync_table_file(&self.config, DEFAULT_SCHEMA_NAME, &table_name).await?;
+        }
+        wal.truncate().await?;
+        trace!("Checkpointed write-ahead log {}", wal.path().display());
+        Ok(())
+    }
+
+    /// Apply the catalog modified by a DDL statement, persisting it if the statement succeeded.
+    async fn commit_tables(
+        &self,
+        tables: Vec<TableDefinition>,
+        result: ExecutionResult,
+    ) -> ExecutionResult {
+        if result.is_ok() {
+            save_catalog(&self.config, &tables)
+                .await
+                .map_err(|error| ExecutionError::Storage(error.to_string()))?;
+        }
+        self.set_tables(tables);
+        result
+    }
+
+    /// Replace the catalog, keeping a lock for each table.
+    fn set_tables(&self, tables: Vec<TableDefinition>) {
+        let mut table_locks = self.table_locks.lock();
+        let normalized_table_names: Vec<String> = tables
+            .iter()
+            .map(|table| normalize_identifier(&table.name))
+            .collect();
+        table_locks.retain(|table_name, _| normalized_table_names.contains(table_name));
+        for table_name in normalized_table_names {
+            table_locks.entry(table_name).or_default();
+        }
+        *self.tables.write() = tables;
+    }
+
+    /// Lock of the user table, if it exists.
+    fn get_table_lock(&self, table_name: &str) -> Option<Arc<tokio::sync::RwLock<()>>> {
+        self.table_locks
+            .lock()
+            .get(&normalize_identifier(table_name))
+            .cloned()
+    }
+
+    /// Look up the definition of a user table.
+    fn get_table(&self, table_name: &str) -> Result<TableDefinition, ValidationError> {
+        let normalized_table_name = normalize_identifier(table_name);
+        self.tables
+            .read()
+            .iter()
+            .find(|table| normalize_identifier(&table.name) == normalized_table_name)
+            .cloned()
+            .ok_or_else(|| ValidationError(format!("Table `{}` does not exist", table_name)))
+    }
+
+    /// The table's PRIMARY KEY index, building it if it's not cached – in which case the rows read to build it
+    /// are returned too, so that the statement doesn't have to read them again. Statements reading the table
+    /// concurrently may each build the index on a cold cache, but only the first one built is kept.
+    async fn get_primary_key_index(
+        &self,
+        table_definition: &TableDefinition,
+    ) -> Result<(Arc<RwLock<PrimaryKeyIndex>>, Option<Vec<Row>>), ExecutionError> {
+        let normalized_table_name = normalize_identifier(&table_definition.name);
+        if let Some(index) = self
+            .primary_key_indexes
+            .lock()
+            .get(&normalized_table_name)
+            .cloned()
+        {
+            return Ok((index, None));
+        }
+        let rows = read::read_all_rows(&self.config, DEFAULT_SCHEMA_NAME, table_definition)
+            .await
+            .map_err(ExecutionError::Storage)?;
+        let index = self
+            .primary_key_indexes
+            .lock()
+            .entry(normalized_table_name)
+            .or_insert_with(|| {
+                Arc::new(RwLock::new(PrimaryKeyIndex::build(table_definition, &rows)))
+            })
+            .clone();
+        Ok((index, Some(rows)))
+    }
+
+    /// Replace the table's PRIMARY KEY index, e.g. after its rows have been rewritten.
+    fn put_primary_key_index(&self, table_definition: &TableDefinition, index: PrimaryKeyIndex) {
+        self.primary_key_indexes.lock().insert(
+            normalize_identifier(&table_definition.name),
+            Arc::new(RwLock::new(index)),
+        );
+    }
+
+    pub async fn execute(&self, statement: Statement) -> ExecutionResult {
+        // Statements changing the catalog have the whole database to themselves (see `execute_batch`), while
+        // others only need their tables
+        let mut table_names: Vec<String> = match &statement {
+            Statement::Insert(InsertStatement { table_name, .. })
+            | Statement::Update(UpdateStatement { table_name, .. })
+            | Statement::Delete(DeleteStatement { table_name, .. })
+            | Statement::TruncateTable(TruncateTableStatement { table_name })
+            | Statement::Describe(DescribeStatement { table_name }) => {
+                vec![normalize_identifier(table_name)]
+            }
+            Statement::Select(SelectStatement { source, join, .. }) => std::iter::once(source)
+                .chain(join.as_ref().map(|join| &join.table_name))
+                .map(|table_name| normalize_identifier(table_name))
+                .collect(),
+            _ => Vec::new(),
+        };
+        // Tables are locked in name order, so that statements locking more than one table can't deadlock
+        table_names.sort();
+        table_names.dedup();
+        let mut _shared_table_guards = Vec::new();
+        let mut _exclusive_table_guards = Vec::new();
+        for table_lock in table_names
+            .iter()
+            .filter_map(|table_name| self.get_table_lock(table_name))
+        {
+            if statement.is_mutation() {
+                _exclusive_table_guards.push(table_lock.write_owned().await);
+            } else {
+                _shared_table_guards.push(table_lock.read_owned().await);
+            }
+        }
+        // Only INSERTs are logged, and logged rows must match the table structure they're replayed into,
+        // so any other change starts afresh
+        if matches!(
+            statement,
+            Statement::CreateTable(_)
+                | Statement::AlterTable(_)
+                | Statement::TruncateTable(_)
+                | Statement::Update(_)
+                | Statement::Delete(_)
+        ) {
+            self.checkpoint()
+                .await
+                .map_err(|error| ExecutionError::Storage(error.to_string()))?;
+        }
+        match statement {
+            Statement::CreateTable(create_table) => {
+                // The catalog lock can't be held across an await point, so we work on a copy of it
+                let mut tables = self.tables.read().clone();
+                let result = write::create_table(
+                    &self.config,
+                    DEFAULT_SCHEMA_NAME,
+                    &mut tables,
+                    create_table,
+                )
+                .await;
+                self.commit_tables(tables, result).await
+            }
+            Statement::AlterTable(alter_table) => {
+                let mut tables = self.tables.read().clone();
+                let result =
+                    write::alter_table(&self.config, DEFAULT_SCHEMA_NAME, &mut tables, alter_table)
+                        .await;
+                self.commit_tables(tables, result).await
+            }
+            Statement::TruncateTable(truncate_table) => {
+                let table_definition = self.get_table(&truncate_table.table_name)?;
+                self.primary_key_indexes
+                    .lock()
+                    .remove(&normalize_identifier(&table_definition.name));
+                let result =
+                    write::truncate_table(&self.config, DEFAULT_SCHEMA_NAME, &table_definition)
+                        .await;
+                if result.is_ok() {
+                    self.put_primary_key_index(&table_definition, PrimaryKeyIndex::default());
+                }
+                result
+            }
+            Statement::Insert(insert) => {
+                let table_definition = self.get_table(&insert.table_name)?;
+                let (primary_key_index, _) = self.get_primary_key_index(&table_definition).await?;
+                let mut wal = match &self.wal {
+                    Some(wal) => Some(wal.lock().await),
+                    None => None,
+                };
+                write::insert(
+                    &self.config,
+                    DEFAULT_SCHEMA_NAME,
+                    &table_definition,
+                    insert,
+                    &primary_key_index,
+                    wal.as_deref_mut(),
+                )
+                .await
+            }
+            Statement::Select(select) if select.join.is_some() => {
+                let table_definition = self.get_table(&select.source)?;
+                let joined_table_definition =
+                    self.get_table(&select.join.as_ref().unwrap().table_name)?;
+                read::select_join(
+                    &self.config,
+                    DEFAULT_SCHEMA_NAME,
+                    &table_definition,
+                    &joined_table_definition,
+                    select,
+                )
+                .await
+            }
+            Statement::Select(select) => {
+                let table_definition = self.get_table(&select.source)?;
+                let (primary_key_index, rows_read) =
+                    self.get_primary_key_index(&table_definition).await?;
+                read::select(
+                    &self.config,
+                    DEFAULT_SCHEMA_NAME,
+                    &table_definition,
+                    select,
+                    &primary_key_index,
+                    rows_read,
+                )
+                .await
+            }
+            Statement::Update(update) => {
+                let table_definition = self.get_table(&update.table_name)?;
+                // The index is rebuilt from the rows as rewritten, and left out of the cache if that fails
+                self.primary_key_indexes
+                    .lock()
+                    .remove(&normalize_identifier(&table_definition.name));
+                let mut primary_key_index = PrimaryKeyIndex::default();
+                let result = write::update(
+                    &self.config,
+                    DEFAULT_SCHEMA_NAME,
+                    &table_definition,
+                    update,
+                    &mut primary_key_index,
+                )
+                .await;
+                if result.is_ok() {
+                    self.put_primary_key_index(&table_definition, primary_key_index);
+                }
+                result
+            }
+            Statement::Delete(delete) => {
+                let table_definition = self.get_table(&delete.table_name)?;
+                self.primary_key_indexes
+                    .lock()
+                    .remove(&normalize_identifier(&table_definition.name));
+                let mut primary_key_index = PrimaryKeyIndex::default();
+                let result = write::delete(
+                    &self.config,
+                    DEFAULT_SCHEMA_NAME,
+                    &table_definition,
+                    delete,
+                    &mut primary_key_index,
+                )
+                .await;
+                if result.is_ok() {
+                    self.put_primary_key_index(&table_definition, primary_key_index);
+                }
+                result
+            }
+            Statement::ShowTables => Ok(StatementOutcome::Rows(read::show_tables(
+                &self.tables.read(),
+            ))),
+            Statement::Describe(describe) => {
+                let table_definition = self.get_table(&describe.table_name)?;
+                Ok(StatementOutcome::Rows(read::describe(&table_definition)))
+            }
+            Statement::Begin | Statement::Commit | Statement::Rollback => Err(ValidationError(
+                "Transactions can only be controlled within a batch of statements".into(),
+            )
+            .into()),
+            Statement::Explain(explained) => match *explained {
+                Statement::Select(select) => {
+                    let table_definition = self.get_table(&select.source)?;
+                    let joined_table_definition = match &select.join {
+                        Some(join) => Some(self.get_table(&join.table_name)?),
+                        None => None,
+                    };
+                    Ok(StatementOutcome::Rows(read::explain_select(
+                        select,
+                        &table_definition,
+                        joined_table_definition.as_ref(),
+                    )?))
+                }
+                explained => Err(ValidationError(format!(
+                    "Only SELECT statements can be explained, not `{}`",
+                    explained.kind()
+                ))
+                .into()),
+            },
+        }
+    }
+
+    /// Execute statements one by one, stopping at the first error. The result is that of the last statement
+    /// other than BEGIN, COMMIT or ROLLBACK, not counting statements rolled back.
+    /// Statements between BEGIN and COMMIT are applied atomically – on ROLLBACK or on error the database
+    /// is brought back to its state at BEGIN. A transaction still open at the end of the batch is rolled back.
+    /// Changes are written as they're executed though, so a transaction interrupted by a crash isn't undone.
+    pub async fn execute_batch(&self, statements: Vec<Statement>) -> ExecutionResult {
+        // Rolling back rewrites table files, so transaction control needs exclusive access too
+        let is_catalog_kept = statements.iter().all(|statement| {
+            !matches!(
+                statement,
+                Statement::CreateTable(_)
+                    | Statement::AlterTable(_)
+                    | Statement::Begin
+                    | Statement::Commit
+                    | Statement::Rollback
+            )
+        });
+        let (_shared_guard, _exclusive_guard) = if is_catalog_kept {
+            (Some(self.batch_lock.read().await), None)
+        } else {
+            (None, Some(self.batch_lock.write().await))
+        };
+        let mut transaction: Option<TransactionSnapshot> = None;
+        let mut last_result = StatementOutcome::Done;
+        for statement in statements {
+            let result = match statement {
+                Statement::Begin if transaction.is_some() => Err(ValidationError(
+                    "A transaction is already in progress, and transactions cannot be nested"
+                        .into(),
+                )
+                .into()),
+                Statement::Begin => {
+                    transaction = Some(self.take_snapshot(std::mem::replace(
+                        &mut last_result,
+                        StatementOutcome::Done,
+                    )));
+                    continue;
+                }
+                Statement::Commit | Statement::Rollback if transaction.is_none() => {
+                    Err(ValidationError("There is no transaction in progress".into()).into())
+                }
+                Statement::Commit => {
+                    transaction = None;
+                    continue;
+                }
+                Statement::Rollback => {
+                    last_result = self.restore_snapshot(transaction.take().unwrap()).await?;
+                    continue;
+                }
+                statement => {
+                    async {
+                        if let Some(snapshot) = &mut transaction {
+                            self.snapshot_changed_table(snapshot, &statement).await?;
+                        }
+                        self.execute(statement).await
+                    }
+                    .await
+                }
+            };
+            match result {
+                Ok(result) => last_result = result,
+                Err(error) => {
+                    if let Some(snapshot) = transaction {
+                        self.restore_snapshot(snapshot).await?;
+                    }
+                    return Err(error);
+                }
+            }
+        }
+        if let Some(snapshot) = transaction {
+            debug!("↩️ Rolling back a transaction that wasn't committed");
+            last_result = self.restore_snapshot(snapshot).await?;
+        }
+        Ok(last_result)
+    }
+
+    /// Snapshot the catalog at BEGIN. Rows are added to the snapshot with `snapshot_changed_table`.
+    fn take_snapshot(&self, result_before: StatementOutcome) -> TransactionSnapshot {
+        TransactionSnapshot {
+            tables: self.tables.read().clone(),
+            rows: HashMap::new(),
+            result_before,
+        }
+    }
+
+    /// Add rows of the table that the statement changes to the snapshot, unless they're already there.
+    /// Tables created within the transaction aren't snapshotted, as they're forgotten on ROLLBACK anyway.
+    async fn snapshot_changed_table(
+        &self,
+        snapshot: &mut TransactionSnapshot,
+        statement: &Statement,
+    ) -> Result<(), ExecutionError> {
+        let table_name = match statement {
+            Statement::Insert(InsertStatement { table_name, .. })
+            | Statement::Update(UpdateStatement { table_name, .. })
+            | Statement::Delete(DeleteStatement { table_name, .. })
+            | Statement::TruncateTable(TruncateTableStatement { table_name })
+            | Statement::AlterTable(AlterTableStatement { table_name, .. }) => {
+                normalize_identifier(table_name)
+            }
+            _ => return Ok(()),
+        };
+        if snapshot.rows.contains_key(&table_name) {
+            return Ok(());
+        }
+        let table_definition = match snapshot
+            .tables
+            .iter()
+            .find(|table| normalize_identifier(&table.name) == table_name)
+        {
+            Some(table_definition) => table_definition,
+            None => return Ok(()),
+        };
+        let rows = read::read_all_rows(&self.config, DEFAULT_SCHEMA_NAME, table_definition)
+            .await
+            .map_err(ExecutionError::Storage)?;
+        snapshot.rows.insert(table_name, rows);
+        Ok(())
+    }
+
+    /// Bring tables back to the snapshotted state, returning the result of the batch from before BEGIN.
+    /// Only tables changed within the transaction are rewritten. Tables created since are forgotten,
+    /// their files being overwritten when a table of the same name is created.
+    async fn restore_snapshot(
+        &self,
+        snapshot: TransactionSnapshot,
+    ) -> Result<StatementOutcome, ExecutionError> {
+        let TransactionSnapshot {
+            tables,
+            rows,
+            result_before,
+        } = snapshot;
+        // Indexes of tables rewritten or forgotten are rebuilt on next use
+        self.primary_key_indexes.lock().retain(|table_name, _| {
+            !rows.contains_key(table_name)
+                && tables
+                    .iter()
+                    .any(|table| normalize_identifier(&table.name) == *table_name)
+        });
+        for (table_name, rows) in rows {
+            let table_definition = tables
+                .iter()
+                .find(|table| normalize_identifier(&table.name) == table_name)
+                .expect("Only tables of the snapshotted catalog have their rows snapshotted");
+            write::rewrite_all_rows(&self.config, DEFAULT_SCHEMA_NAME, table_definition, rows)
+                .await
+                .map_err(ExecutionError::Storage)?;
+        }
+        save_catalog(&self.config, &tables)
+            .await
+            .map_err(|error| ExecutionError::Storage(error.to_string()))?;
+        self.set_tables(tables);
+        // Rolled back INSERTs must not be replayed from the write-ahead log
+        self.checkpoint()
+            .await
+            .map_err(|error| ExecutionError::Storage(error.to_string()))?;
+        Ok(result_before)
+    }
+
+    /// Execute statements until `shutdown` resolves (or all senders are gone).
+    /// Statements already queued at that point are still executed, but no new ones are accepted.
+    /// Batches are executed concurrently, as far as `execute_batch` locking allows.
+    pub async fn start_until(
+        &mut self,
+        shutdown: impl std::future::Future<Output = ()>,
+    ) -> Result<(), io::Error> {
         let mut rx = self
             .rx
             .take()
-            .expect("`prepare_channel` must be ran before `start`");
-        self.bootstrap().await?;
+            .expect("`prepare_channel` must be ran before `start_until`");
         debug!("🗡 Executor engaged");
-        while let Some(payload) = rx.recv().await {
-            let (statement, tx) = payload;
-            debug!("➡️ Executing statement: {:?}", statement);
-            // TODO: Implement real query execution
-            let result = QueryResult {
-                column_names: vec!["id".to_string()],
-                rows: vec![Row(vec![DataInstance::Direct(DataInstanceRaw::UInt64(1))])],
-            };
-            tx.send(result).unwrap();
+        tokio::pin!(shutdown);
+        let mut is_shutting_down = false;
+        let mut is_channel_drained = false;
+        let mut batches_in_flight = FuturesUnordered::new();
+        let executor = &*self;
+        while !is_channel_drained || !batches_in_flight.is_empty() {
+            tokio::select! {
+                payload = rx.recv(), if !is_channel_drained => match payload {
+                    Some((statements, tx)) => batches_in_flight.push(async move {
+                        debug!("➡️ Executing statements: {:?}", statements);
+                        let result = executor.execute_batch(statements).await;
+                        if tx.send(result).is_err() {
+                            debug!("⚠️ Statement result could not be delivered, as the requester is gone");
+                        }
+                    }),
+                    None => is_channel_drained = true,
+                },
+                Some(()) = batches_in_flight.next() => {}
+                _ = &mut shutdown, if !is_shutting_down => {
+                    rx.close();
+                    is_shutting_down = true;
+                }
+            }
         }
+        // Nothing more can be written now
+        self.sync_wal().await?;
         debug!("🎗 Executor disengaged");
         Ok(())
     }
+
+    /// Make sure that the write-ahead log is on disk, if enabled. In `periodic` sync mode the log may hold
+    /// records that aren't yet.
+    pub async fn sync_wal(&self) -> Result<(), io::Error> {
+        if let Some(wal) = &self.wal {
+            wal.lock().await.sync().await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod executor_tests {
+    use super::*;
+    use crate::constructs::components::{DataInstance, DataInstanceRaw, Validatable};
+    use crate::sql::{bind_parameters, parse_statement, parse_statements};
+    use futures::future;
+    use pretty_assertions::assert_eq;
+    use rand::distributions::Alphanumeric;
+    use rand::{thread_rng, Rng};
+    use std::time::Duration;
+
+    /// Config with a fresh data directory.
+    fn get_test_config() -> config::Config {
+        let data_directory_name: String = thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(10)
+            .map(char::from)
+            .collect();
+        config::Config {
+            data_directory: Path::new(env!("TMPDIR"))
+                .join(data_directory_name)
+                .to_string_lossy()
+                .into(),
+            ..Default::default()
+        }
+    }
+
+    async fn get_bootstrapped_executor(config: &config::Config) -> Executor {
+        let mut executor = Executor::new(config);
+        executor.bootstrap().await.unwrap();
+        executor
+    }
+
+    /// Executor operating on a fresh data directory.
+    async fn get_test_executor() -> Executor {
+        get_bootstrapped_executor(&get_test_config()).await
+    }
+
+    /// Executor operating on a fresh data directory, with a write-ahead log in it.
+    async fn get_test_executor_with_wal() -> Executor {
+        let mut config = get_test_config();
+        config.wal_path = Some(
+            Path::new(&config.data_directory)
+                .join("wal")
+                .to_string_lossy()
+                .into(),
+        );
+        get_bootstrapped_executor(&config).await
+    }
+
+    /// Execute the statement, with the outcome as a table.
+    async fn execute(executor: &Executor, statement: &str) -> Result<QueryResult, ExecutionError> {
+        executor
+            .execute(parse_statement(statement).unwrap())
+            .await
+            .map(StatementOutcome::into_query_result)
+    }
+
+    #[tokio::test]
+    async fn insert_null_into_nullable_column_works() {
+        let executor = get_test_executor().await;
+        execute(
+            &executor,
+            "CREATE TABLE test (id UINT64 PRIMARY KEY, note NULLABLE(STRING))",
+        )
+        .await
+        .unwrap();
+        execute(&executor, "INSERT INTO test (id, note) VALUES (1, NULL)")
+            .await
+            .unwrap();
+        execute(&executor, "INSERT INTO test (id, note) VALUES (2, 'x')")
+            .await
+            .unwrap();
+        let result = execute(&executor, "SELECT * FROM test").await.unwrap();
+        assert_eq!(result.column_names, vec!["id", "note"]);
+        assert_eq!(
+            result.rows,
+            vec![
+                Row(vec![
+                    DataInstance::Direct(DataInstanceRaw::UInt64(1)),
+                    DataInstance::Null
+                ]),
+                Row(vec![
+                    DataInstance::Direct(DataInstanceRaw::UInt64(2)),
+                    DataInstance::Nullable(DataInstanceRaw::String("x".into()))
+                ])
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn insert_null_into_non_nullable_column_fails() {
+        let executor = get_test_executor().await;
+        execute(
+            &executor,
+            "CREATE TABLE test (id UINT64 PRIMARY KEY, note STRING)",
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            execute(&executor, "INSERT INTO test (id, note) VALUES (1, NULL)")
+                .await
+                .unwrap_err(),
+            ExecutionError::Validation(ValidationError(
+                "Problem at column `note`: NULL cannot be used as a value of a non-nullable type"
+                    .into()
+            ))
+        );
+        let result = execute(&executor, "SELECT * FROM test").await.unwrap();
+        assert_eq!(result.rows, vec![]);
+    }
+
+    #[tokio::test]
+    async fn insert_integer_out_of_range_fails() {
+        let executor = get_test_executor().await;
+        execute(
+            &executor,
+            "CREATE TABLE test (id UINT8 PRIMARY KEY, small UINT16, big UINT64)",
+        )
+        .await
+        .unwrap();
+        execute(
+            &executor,
+            "INSERT INTO test (id, small, big) VALUES (255, 65535, 18446744073709551615)",
+        )
+        .await
+        .unwrap();
+        for (statement, error_message) in [
+            (
+                "INSERT INTO test (id) VALUES (256)",
+                "Problem at column `id`: Value 256 is out of range for UINT8 (0..=255)",
+            ),
+            (
+                "INSERT INTO test (id, small) VALUES (1, 65536)",
+                "Problem at column `small`: Value 65536 is out of range for UINT16 (0..=65535)",
+            ),
+            (
+                "INSERT INTO test (id, big) VALUES (1, 18446744073709551616)",
+                "Problem at column `big`: Value 18446744073709551616 is out of range for UINT64 (0..=18446744073709551615)",
+            ),
+        ] {
+            assert_eq!(
+                execute(&executor, statement).await.unwrap_err(),
+                ExecutionError::Validation(ValidationError(error_message.into()))
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn uuid_literal_round_trips() {
+        let executor = get_test_executor().await;
+        execute(
+            &executor,
+            "CREATE TABLE test (id UUID PRIMARY KEY, parent_id NULLABLE(UUID))",
+        )
+        .await
+        .unwrap();
+        execute(
+            &executor,
+            "INSERT INTO test (id, parent_id) VALUES ('550e8400-e29b-41d4-a716-446655440000', '00000000-0000-0000-0000-00000000002A')",
+        )
+        .await
+        .unwrap();
+        let result = execute(
+            &executor,
+            "SELECT * FROM test WHERE id = '550e8400-e29b-41d4-a716-446655440000'",
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            result.rows,
+            vec![Row(vec![
+                DataInstance::Direct(DataInstanceRaw::Uuid(uuid::Uuid::from_u128(
+                    0x550e8400_e29b_41d4_a716_446655440000
+                ))),
+                DataInstance::Nullable(DataInstanceRaw::Uuid(uuid::Uuid::from_u128(42))),
+            ])]
+        );
+        let result = execute(
+            &executor,
+            "SELECT id FROM test WHERE parent_id = '00000000-0000-0000-0000-00000000002a'",
+        )
+        .await
+        .unwrap();
+        assert_eq!(result.rows.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn timestamp_literal_round_trips() {
+        let executor = get_test_executor().await;
+        execute(
+            &executor,
+            "CREATE TABLE test (id UINT8 PRIMARY KEY, seen_at TIMESTAMP)",
+        )
+        .await
+        .unwrap();
+        execute(
+            &executor,
+            "INSERT INTO test (id, seen_at) VALUES (1, '2024-01-15T13:30:00.123456+01:00')",
+        )
+        .await
+        .unwrap();
+        execute(
+            &executor,
+            "INSERT INTO test (id, seen_at) VALUES (2, '2024-01-15')",
+        )
+        .await
+        .unwrap();
+        let result = execute(
+            &executor,
+            "SELECT seen_at FROM test WHERE seen_at = '2024-01-15T12:30:00.123456Z'",
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            result.rows,
+            vec![Row(vec![DataInstance::Direct(DataInstanceRaw::Timestamp(
+                time::OffsetDateTime::from_unix_timestamp_nanos(1_705_321_800_123_456_000).unwrap()
+            ))])]
+        );
+        assert_eq!(
+            execute(
+                &executor,
+                "INSERT INTO test (id, seen_at) VALUES (3, '2024-01-15 12:30')"
+            )
+            .await
+            .unwrap_err(),
+            ExecutionError::Validation(ValidationError(
+                "Problem at column `seen_at`: String `2024-01-15 12:30` is not a valid timestamp, expected an ISO 8601 date and time with an offset, e.g. `2024-01-15T12:30:00Z`, or a date, e.g. `2024-01-15`".into()
+            ))
+        );
+    }
+
+    #[tokio::test]
+    async fn insert_malformed_uuid_fails() {
+        let executor = get_test_executor().await;
+        execute(&executor, "CREATE TABLE test (id UUID PRIMARY KEY)")
+            .await
+            .unwrap();
+        for malformed_uuid in [
+            "550e8400-e29b-41d4-a716-44665544000",
+            "550e8400e29b41d4a716446655440000",
+            "550e8400-e29b-41d4-a716-44665544000g",
+        ] {
+            assert_eq!(
+                execute(
+                    &executor,
+                    &format!("INSERT INTO test (id) VALUES ('{}')", malformed_uuid)
+                )
+                .await
+                .unwrap_err(),
+                ExecutionError::Validation(ValidationError(format!(
+                    "Problem at column `id`: String `{}` is not a valid UUID, expected the hyphenated form of 32 hexadecimal digits, e.g. `550e8400-e29b-41d4-a716-446655440000`",
+                    malformed_uuid
+                )))
+            );
+        }
+        let result = execute(&executor, "SELECT * FROM test").await.unwrap();
+        assert_eq!(result.rows, vec![]);
+    }
+
+    #[tokio::test]
+    async fn bool_literal_round_trips() {
+        let executor = get_test_executor().await;
+        execute(
+            &executor,
+            "CREATE TABLE test (id UINT8 PRIMARY KEY, active BOOL)",
+        )
+        .await
+        .unwrap();
+        execute(&executor, "INSERT INTO test (id, active) VALUES (1, TRUE)")
+            .await
+            .unwrap();
+        execute(&executor, "INSERT INTO test (id, active) VALUES (2, false)")
+            .await
+            .unwrap();
+        let result = execute(&executor, "SELECT id FROM test WHERE active = TRUE")
+            .await
+            .unwrap();
+        assert_eq!(
+            result.rows,
+            vec![Row(vec![DataInstance::Direct(DataInstanceRaw::UInt8(1))])]
+        );
+        let result = execute(&executor, "SELECT active FROM test WHERE id = 2")
+            .await
+            .unwrap();
+        assert_eq!(
+            result.rows,
+            vec![Row(vec![DataInstance::Direct(DataInstanceRaw::Bool(
+                false
+            ))])]
+        );
+    }
+
+    #[tokio::test]
+    async fn insert_non_bool_into_bool_fails() {
+        let executor = get_test_executor().await;
+        execute(
+            &executor,
+            "CREATE TABLE test (id UINT8 PRIMARY KEY, active BOOL)",
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            execute(&executor, "INSERT INTO test (id, active) VALUES (1, 1)")
+                .await
+                .unwrap_err(),
+            ExecutionError::Validation(ValidationError(
+                "Problem at column `active`: Value UInt32(1) cannot be used as BOOL".into()
+            ))
+        );
+        assert_eq!(
+            execute(
+                &executor,
+                "INSERT INTO test (id, active) VALUES (1, 'true')"
+            )
+            .await
+            .unwrap_err(),
+            ExecutionError::Validation(ValidationError(
+                "Problem at column `active`: Value String(\"true\") cannot be used as BOOL".into()
+            ))
+        );
+    }
+
+    #[tokio::test]
+    async fn select_with_null_checks_works() {
+        let executor = get_test_executor().await;
+        execute(
+            &executor,
+            "CREATE TABLE test (id UINT8 PRIMARY KEY, note NULLABLE(STRING))",
+        )
+        .await
+        .unwrap();
+        for statement in [
+            "INSERT INTO test (id, note) VALUES (1, NULL)",
+            "INSERT INTO test (id, note) VALUES (2, 'x')",
+            "INSERT INTO test (id) VALUES (3)",
+        ] {
+            execute(&executor, statement).await.unwrap();
+        }
+        let result = execute(&executor, "SELECT id FROM test WHERE note IS NULL")
+            .await
+            .unwrap();
+        assert_eq!(
+            result.rows,
+            vec![
+                Row(vec![DataInstance::Direct(DataInstanceRaw::UInt8(1))]),
+                Row(vec![DataInstance::Direct(DataInstanceRaw::UInt8(3))]),
+            ]
+        );
+        let result = execute(&executor, "SELECT id FROM test WHERE note IS NOT NULL")
+            .await
+            .unwrap();
+        assert_eq!(
+            result.rows,
+            vec![Row(vec![DataInstance::Direct(DataInstanceRaw::UInt8(2))])]
+        );
+        let result = execute(&executor, "SELECT id FROM test WHERE note = NULL")
+            .await
+            .unwrap();
+        assert_eq!(result.rows, vec![]);
+    }
+
+    #[tokio::test]
+    async fn select_with_in_list_works() {
+        let executor = get_test_executor().await;
+        execute(
+            &executor,
+            "CREATE TABLE test (id UINT8 PRIMARY KEY, name STRING)",
+        )
+        .await
+        .unwrap();
+        for statement in [
+            "INSERT INTO test (id, name) VALUES (1, 'foo')",
+            "INSERT INTO test (id, name) VALUES (2, 'bar')",
+            "INSERT INTO test (id, name) VALUES (3, 'baz')",
+        ] {
+            execute(&executor, statement).await.unwrap();
+        }
+        let result = execute(&executor, "SELECT name FROM test WHERE id IN (3, 1, 300)")
+            .await
+            .unwrap_err();
+        assert_eq!(
+            result,
+            ExecutionError::Validation(ValidationError(
+                "Problem in IN list: Value 300 is out of range for UINT8 (0..=255)".into()
+            ))
+        );
+        let result = execute(&executor, "SELECT name FROM test WHERE id IN (3, 1)")
+            .await
+            .unwrap();
+        assert_eq!(
+            result.rows,
+            vec![
+                Row(vec![DataInstance::Direct(DataInstanceRaw::String(
+                    "foo".into()
+                ))]),
+                Row(vec![DataInstance::Direct(DataInstanceRaw::String(
+                    "baz".into()
+                ))]),
+            ]
+        );
+        let result = execute(
+            &executor,
+            "SELECT id FROM test WHERE name IN ('qux', 'quux')",
+        )
+        .await
+        .unwrap();
+        assert_eq!(result.rows, vec![]);
+    }
+
+    #[tokio::test]
+    async fn select_with_between_works() {
+        let executor = get_test_executor().await;
+        execute(
+            &executor,
+            "CREATE TABLE test (id UINT8 PRIMARY KEY, active BOOL)",
+        )
+        .await
+        .unwrap();
+        for id in 1..=5 {
+            execute(
+                &executor,
+                &format!("INSERT INTO test (id, active) VALUES ({}, TRUE)", id),
+            )
+            .await
+            .unwrap();
+        }
+        let result = execute(&executor, "SELECT id FROM test WHERE id BETWEEN 2 AND 4")
+            .await
+            .unwrap();
+        assert_eq!(
+            result.rows,
+            vec![
+                Row(vec![DataInstance::Direct(DataInstanceRaw::UInt8(2))]),
+                Row(vec![DataInstance::Direct(DataInstanceRaw::UInt8(3))]),
+                Row(vec![DataInstance::Direct(DataInstanceRaw::UInt8(4))]),
+            ]
+        );
+        // A reversed range is empty
+        let result = execute(&executor, "SELECT id FROM test WHERE id BETWEEN 4 AND 2")
+            .await
+            .unwrap();
+        assert_eq!(result.rows, vec![]);
+        assert_eq!(
+            execute(&executor, "SELECT id FROM test WHERE id BETWEEN 1 AND 'x'")
+                .await
+                .unwrap_err(),
+            ExecutionError::Validation(ValidationError(
+                "Problem in BETWEEN bounds: Value String(\"x\") cannot be used as UINT8".into()
+            ))
+        );
+        assert_eq!(
+            execute(
+                &executor,
+                "SELECT id FROM test WHERE active BETWEEN FALSE AND TRUE"
+            )
+            .await
+            .unwrap_err(),
+            ExecutionError::Validation(ValidationError(
+                "BETWEEN cannot be used with Bool values, as they have no meaningful order".into()
+            ))
+        );
+    }
+
+    #[tokio::test]
+    async fn select_with_and_or_works() {
+        let executor = get_test_executor().await;
+        execute(
+            &executor,
+            "CREATE TABLE test (id UINT8 PRIMARY KEY, a UINT8, b UINT8)",
+        )
+        .await
+        .unwrap();
+        for statement in [
+            "INSERT INTO test (id, a, b) VALUES (1, 1, 1)",
+            "INSERT INTO test (id, a, b) VALUES (2, 1, 2)",
+            "INSERT INTO test (id, a, b) VALUES (3, 2, 2)",
+            "INSERT INTO test (id, a, b) VALUES (4, 2, 1)",
+        ] {
+            execute(&executor, statement).await.unwrap();
+        }
+        let select_ids = |condition: &'static str| {
+            let executor = &executor;
+            async move {
+                execute(
+                    executor,
+                    &format!("SELECT id FROM test WHERE {}", condition),
+                )
+                .await
+                .unwrap()
+                .rows
+                .into_iter()
+                .map(|row| match row.0[..] {
+                    [DataInstance::Direct(DataInstanceRaw::UInt8(id))] => id,
+                    _ => panic!("Unexpected row {:?}", row),
+                })
+                .collect::<Vec<u8>>()
+            }
+        };
+        assert_eq!(select_ids("a = 1 AND b = 2").await, vec![2]);
+        assert_eq!(select_ids("a = 1 OR b = 2").await, vec![1, 2, 3]);
+        // AND binds tighter than OR
+        assert_eq!(select_ids("id = 4 OR a = 1 AND b = 2").await, vec![2, 4]);
+        assert_eq!(select_ids("(id = 4 OR a = 1) AND b = 2").await, vec![2]);
+    }
+
+    #[tokio::test]
+    async fn select_with_like_works() {
+        let executor = get_test_executor().await;
+        execute(
+            &executor,
+            "CREATE TABLE test (id UINT8 PRIMARY KEY, name STRING)",
+        )
+        .await
+        .unwrap();
+        for statement in [
+            "INSERT INTO test (id, name) VALUES (1, 'foobar')",
+            "INSERT INTO test (id, name) VALUES (2, 'barfoo')",
+            "INSERT INTO test (id, name) VALUES (3, 'fox')",
+            "INSERT INTO test (id, name) VALUES (4, '100%')",
+            "INSERT INTO test (id, name) VALUES (5, '1000')",
+        ] {
+            execute(&executor, statement).await.unwrap();
+        }
+        let select_ids = |pattern: &'static str| {
+            let executor = &executor;
+            async move {
+                execute(
+                    executor,
+                    &format!("SELECT id FROM test WHERE name LIKE '{}'", pattern),
+                )
+                .await
+                .unwrap()
+                .rows
+                .into_iter()
+                .map(|row| match row.0[..] {
+                    [DataInstance::Direct(DataInstanceRaw::UInt8(id))] => id,
+                    _ => panic!("Unexpected row {:?}", row),
+                })
+                .collect::<Vec<u8>>()
+            }
+        };
+        assert_eq!(select_ids("foo%").await, vec![1]);
+        assert_eq!(select_ids("%foo").await, vec![2]);
+        assert_eq!(select_ids("%oo%").await, vec![1, 2]);
+        assert_eq!(select_ids("fo_").await, vec![3]);
+        assert_eq!(select_ids("100%").await, vec![4, 5]);
+        // The backslash has to be doubled, as it's also the escape character of string literals
+        assert_eq!(select_ids("100\\\\%").await, vec![4]);
+        assert_eq!(
+            execute(&executor, "SELECT id FROM test WHERE id LIKE '1%'")
+                .await
+                .unwrap_err(),
+            ExecutionError::Validation(ValidationError(
+                "LIKE can only be used with STRING values, not UINT8".into()
+            ))
+        );
+        assert_eq!(
+            execute(&executor, "SELECT id FROM test WHERE name LIKE 1")
+                .await
+                .unwrap_err(),
+            ExecutionError::Validation(ValidationError(
+                "LIKE pattern must be a STRING, not UINT32".into()
+            ))
+        );
+    }
+
+    #[tokio::test]
+    async fn select_all_follows_definition_order() {
+        let executor = get_test_executor().await;
+        execute(
+            &executor,
+            "CREATE TABLE test (zeta UINT8 PRIMARY KEY, alpha STRING, mid BOOL)",
+        )
+        .await
+        .unwrap();
+        execute(
+            &executor,
+            "INSERT INTO test (mid, alpha, zeta) VALUES (TRUE, 'foo', 1)",
+        )
+        .await
+        .unwrap();
+        for _ in 0..5 {
+            let result = execute(&executor, "SELECT * FROM test").await.unwrap();
+            assert_eq!(result.column_names, vec!["zeta", "alpha", "mid"]);
+            assert_eq!(
+                result.rows,
+                vec![Row(vec![
+                    DataInstance::Direct(DataInstanceRaw::UInt8(1)),
+                    DataInstance::Direct(DataInstanceRaw::String("foo".into())),
+                    DataInstance::Direct(DataInstanceRaw::Bool(true)),
+                ])]
+            );
+        }
+        let result = execute(&executor, "SELECT mid, *, zeta FROM test")
+            .await
+            .unwrap();
+        assert_eq!(
+            result.column_names,
+            vec!["mid", "zeta", "alpha", "mid", "zeta"]
+        );
+    }
+
+    #[tokio::test]
+    async fn select_with_where_works() {
+        let executor = get_test_executor().await;
+        execute(
+            &executor,
+            "CREATE TABLE test (id UINT8 PRIMARY KEY, name STRING)",
+        )
+        .await
+        .unwrap();
+        execute(&executor, "INSERT INTO test (id, name) VALUES (1, 'foo')")
+            .await
+            .unwrap();
+        execute(&executor, "INSERT INTO test (name, id) VALUES ('bar', 2)")
+            .await
+            .unwrap();
+        let result = execute(&executor, "SELECT name FROM test WHERE id = 2")
+            .await
+            .unwrap();
+        assert_eq!(result.column_names, vec!["name"]);
+        assert_eq!(
+            result.rows,
+            vec![Row(vec![DataInstance::Direct(DataInstanceRaw::String(
+                "bar".into()
+            ))])]
+        );
+    }
+
+    #[tokio::test]
+    async fn alter_table_add_column_works() {
+        let executor = get_test_executor().await;
+        execute(&executor, "CREATE TABLE test (id UINT8 PRIMARY KEY)")
+            .await
+            .unwrap();
+        execute(&executor, "INSERT INTO test (id) VALUES (1)")
+            .await
+            .unwrap();
+        execute(
+            &executor,
+            "ALTER TABLE test ADD COLUMN note NULLABLE(STRING)",
+        )
+        .await
+        .unwrap();
+        execute(&executor, "INSERT INTO test (id, note) VALUES (2, 'x')")
+            .await
+            .unwrap();
+        let result = execute(&executor, "SELECT * FROM test").await.unwrap();
+        assert_eq!(result.column_names, vec!["id", "note"]);
+        assert_eq!(
+            result.rows,
+            vec![
+                Row(vec![
+                    DataInstance::Direct(DataInstanceRaw::UInt8(1)),
+                    DataInstance::Null
+                ]),
+                Row(vec![
+                    DataInstance::Direct(DataInstanceRaw::UInt8(2)),
+                    DataInstance::Nullable(DataInstanceRaw::String("x".into()))
+                ])
+            ]
+        );
+        assert_eq!(
+            execute(
+                &executor,
+                "ALTER TABLE test ADD COLUMN note NULLABLE(UINT8)"
+            )
+            .await
+            .unwrap_err(),
+            ExecutionError::Validation(ValidationError(
+                "Column `note` already exists in table `test`".into()
+            ))
+        );
+    }
+
+    #[tokio::test]
+    async fn alter_table_drop_column_works() {
+        let executor = get_test_executor().await;
+        execute(
+            &executor,
+            "CREATE TABLE test (id UINT8 PRIMARY KEY, note STRING, size UINT16)",
+        )
+        .await
+        .unwrap();
+        execute(
+            &executor,
+            "INSERT INTO test (id, note, size) VALUES (1, 'x', 7)",
+        )
+        .await
+        .unwrap();
+        execute(&executor, "ALTER TABLE test DROP COLUMN note")
+            .await
+            .unwrap();
+        let result = execute(&executor, "SELECT * FROM test").await.unwrap();
+        assert_eq!(result.column_names, vec!["id", "size"]);
+        assert_eq!(
+            result.rows,
+            vec![Row(vec![
+                DataInstance::Direct(DataInstanceRaw::UInt8(1)),
+                DataInstance::Direct(DataInstanceRaw::UInt16(7))
+            ])]
+        );
+    }
+
+    #[tokio::test]
+    async fn alter_table_drop_primary_key_column_fails() {
+        let executor = get_test_executor().await;
+        execute(
+            &executor,
+            "CREATE TABLE test (id UINT8 PRIMARY KEY, note STRING)",
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            execute(&executor, "ALTER TABLE test DROP COLUMN id")
+                .await
+                .unwrap_err(),
+            ExecutionError::Validation(ValidationError(
+                "Column `id` cannot be dropped, as it's the PRIMARY KEY of table `test`".into()
+            ))
+        );
+    }
+
+    #[tokio::test]
+    async fn truncate_table_works() {
+        let executor = get_test_executor().await;
+        execute(
+            &executor,
+            "CREATE TABLE test (id UINT8 PRIMARY KEY, note STRING)",
+        )
+        .await
+        .unwrap();
+        execute(&executor, "INSERT INTO test (id, note) VALUES (1, 'x')")
+            .await
+            .unwrap();
+        execute(&executor, "INSERT INTO test (id, note) VALUES (2, 'y')")
+            .await
+            .unwrap();
+        execute(&executor, "TRUNCATE TABLE test").await.unwrap();
+        let result = execute(&executor, "SELECT * FROM test").await.unwrap();
+        assert_eq!(result.column_names, vec!["id", "note"]);
+        assert_eq!(result.rows, vec![]);
+        // The table is still usable
+        execute(&executor, "INSERT INTO test (id, note) VALUES (3, 'z')")
+            .await
+            .unwrap();
+        let result = execute(&executor, "SELECT * FROM test").await.unwrap();
+        assert_eq!(result.rows.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn truncate_nonexistent_table_fails() {
+        let executor = get_test_executor().await;
+        assert_eq!(
+            execute(&executor, "TRUNCATE TABLE test").await.unwrap_err(),
+            ExecutionError::Validation(ValidationError("Table `test` does not exist".into()))
+        );
+    }
+
+    #[tokio::test]
+    async fn show_tables_works() {
+        let executor = get_test_executor().await;
+        execute(&executor, "CREATE TABLE zoo (id UINT8 PRIMARY KEY)")
+            .await
+            .unwrap();
+        execute(&executor, "CREATE TABLE Animals (id UINT8 PRIMARY KEY)")
+            .await
+            .unwrap();
+        let result = execute(&executor, "SHOW TABLES").await.unwrap();
+        assert_eq!(result.column_names, vec!["table_name"]);
+        assert_eq!(
+            result.rows,
+            vec![
+                Row(vec![DataInstance::Direct(DataInstanceRaw::String(
+                    "Animals".into()
+                ))]),
+                Row(vec![DataInstance::Direct(DataInstanceRaw::String(
+                    "zoo".into()
+                ))]),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn describe_works() {
+        let executor = get_test_executor().await;
+        execute(
+            &executor,
+            "CREATE TABLE test (id UINT8 PRIMARY KEY, note NULLABLE(STRING) COMMENT 'Free-form, shown to users')",
+        )
+        .await
+        .unwrap();
+        let result = execute(&executor, "DESCRIBE test").await.unwrap();
+        assert_eq!(
+            result.column_names,
+            vec![
+                "column_name",
+                "data_type",
+                "is_nullable",
+                "is_primary_key",
+                "comment"
+            ]
+        );
+        let row = |name: &str,
+                   data_type: &str,
+                   is_nullable: bool,
+                   is_primary_key: bool,
+                   comment: Option<&str>| {
+            Row(vec![
+                DataInstance::Direct(DataInstanceRaw::String(name.into())),
+                DataInstance::Direct(DataInstanceRaw::String(data_type.into())),
+                DataInstance::Direct(DataInstanceRaw::Bool(is_nullable)),
+                DataInstance::Direct(DataInstanceRaw::Bool(is_primary_key)),
+                match comment {
+                    Some(comment) => {
+                        DataInstance::Nullable(DataInstanceRaw::String(comment.into()))
+                    }
+                    None => DataInstance::Null,
+                },
+            ])
+        };
+        assert_eq!(
+            result.rows,
+            vec![
+                row("id", "UINT8", false, true, None),
+                row(
+                    "note",
+                    "STRING",
+                    true,
+                    false,
+                    Some("Free-form, shown to users")
+                )
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn explain_select_reports_plan() {
+        let executor = get_test_executor().await;
+        execute(
+            &executor,
+            "CREATE TABLE test (id UINT8 PRIMARY KEY, name STRING)",
+        )
+        .await
+        .unwrap();
+        async fn plan(executor: &Executor, query: &str) -> Vec<(String, String)> {
+            let result = execute(executor, query).await.unwrap();
+            assert_eq!(result.column_names, vec!["step", "detail"]);
+            result
+                .rows
+                .into_iter()
+                .map(|row| match &row.0[..] {
+                    [DataInstance::Direct(DataInstanceRaw::String(step)), DataInstance::Direct(DataInstanceRaw::String(detail))] => {
+                        (step.clone(), detail.clone())
+                    }
+                    other => panic!("Unexpected plan row {:?}", other),
+                })
+                .collect()
+        }
+        let step = |step: &str, detail: &str| (step.to_string(), detail.to_string());
+        assert_eq!(
+            plan(&executor, "EXPLAIN SELECT name FROM test WHERE id = 1").await,
+            vec![
+                step(
+                    "access",
+                    "index lookup on PRIMARY KEY (`id`) of table `test`"
+                ),
+                step("filter", "`id` = 1"),
+                step("projection", "`name`"),
+            ]
+        );
+        assert_eq!(
+            plan(&executor, "EXPLAIN SELECT * FROM test WHERE name = 'x' AND (id = 1 OR id IS NULL) ORDER BY name DESC LIMIT 5").await,
+            vec![
+                step("access", "full scan of table `test`"),
+                step("filter", "`name` = 'x' AND (`id` = 1 OR `id` IS NULL)"),
+                step("order", "`name` DESC"),
+                step("projection", "`id`, `name`"),
+                step("limit", "5"),
+            ]
+        );
+        assert_eq!(
+            plan(&executor, "EXPLAIN SELECT DISTINCT name FROM test").await,
+            vec![
+                step("access", "full scan of table `test`"),
+                step("projection", "`name`"),
+                step("distinct", "`name`"),
+            ]
+        );
+        assert_eq!(
+            plan(
+                &executor,
+                "EXPLAIN SELECT name, COUNT(*) FROM test GROUP BY name"
+            )
+            .await,
+            vec![
+                step("access", "full scan of table `test`"),
+                step("group", "`name`"),
+                step("aggregation", "name, COUNT(*)"),
+            ]
+        );
+        assert_eq!(
+            plan(&executor, "EXPLAIN SELECT COUNT(*) FROM test").await,
+            vec![
+                step("access", "full scan of table `test`"),
+                step("aggregation", "COUNT(*)"),
+            ]
+        );
+        assert_eq!(
+            execute(&executor, "EXPLAIN DELETE FROM test")
+                .await
+                .unwrap_err(),
+            ExecutionError::Validation(ValidationError(
+                "Only SELECT statements can be explained, not `delete`".into()
+            ))
+        );
+    }
+
+    #[tokio::test]
+    async fn describe_nonexistent_table_fails() {
+        let executor = get_test_executor().await;
+        assert_eq!(
+            execute(&executor, "DESCRIBE test").await.unwrap_err(),
+            ExecutionError::Validation(ValidationError("Table `test` does not exist".into()))
+        );
+    }
+
+    #[tokio::test]
+    async fn tables_persist_across_restarts() {
+        let executor = get_test_executor().await;
+        execute(
+            &executor,
+            "CREATE TABLE test (id UINT8 PRIMARY KEY, note STRING DEFAULT 'x')",
+        )
+        .await
+        .unwrap();
+        execute(&executor, "INSERT INTO test (id) VALUES (1)")
+            .await
+            .unwrap();
+        let restarted_executor = get_bootstrapped_executor(&executor.config).await;
+        let result = execute(&restarted_executor, "SELECT * FROM test")
+            .await
+            .unwrap();
+        assert_eq!(
+            result.rows,
+            vec![Row(vec![
+                DataInstance::Direct(DataInstanceRaw::UInt8(1)),
+                DataInstance::Direct(DataInstanceRaw::String("x".into()))
+            ])]
+        );
+        assert_eq!(*restarted_executor.tables.read(), *executor.tables.read());
+    }
+
+    #[tokio::test]
+    async fn inserts_are_replayed_from_wal() {
+        let executor = get_test_executor_with_wal().await;
+        execute(
+            &executor,
+            "CREATE TABLE test (id UINT8 PRIMARY KEY, note STRING)",
+        )
+        .await
+        .unwrap();
+        execute(&executor, "INSERT INTO test (id, note) VALUES (1, 'x')")
+            .await
+            .unwrap();
+        execute(&executor, "INSERT INTO test (id, note) VALUES (2, 'y')")
+            .await
+            .unwrap();
+        // Simulating a crash in which the table file lost the rows
+        let table_definition = executor.get_table("test").unwrap();
+        write::rewrite_all_rows(
+            &executor.config,
+            DEFAULT_SCHEMA_NAME,
+            &table_definition,
+            Vec::new(),
+        )
+        .await
+        .unwrap();
+        let config = executor.config.clone();
+        drop(executor);
+        let restarted_executor = get_bootstrapped_executor(&config).await;
+        let result = execute(&restarted_executor, "SELECT * FROM test")
+            .await
+            .unwrap();
+        assert_eq!(
+            result.rows,
+            vec![
+                Row(vec![
+                    DataInstance::Direct(DataInstanceRaw::UInt8(1)),
+                    DataInstance::Direct(DataInstanceRaw::String("x".into()))
+                ]),
+                Row(vec![
+                    DataInstance::Direct(DataInstanceRaw::UInt8(2)),
+                    DataInstance::Direct(DataInstanceRaw::String("y".into()))
+                ])
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn replaying_wal_does_not_duplicate_rows() {
+        let executor = get_test_executor_with_wal().await;
+        execute(&executor, "CREATE TABLE test (id UINT8 PRIMARY KEY)")
+            .await
+            .unwrap();
+        execute(&executor, "INSERT INTO test (id) VALUES (1)")
+            .await
+            .unwrap();
+        let config = executor.config.clone();
+        drop(executor);
+        let restarted_executor = get_bootstrapped_executor(&config).await;
+        let result = execute(&restarted_executor, "SELECT * FROM test")
+            .await
+            .unwrap();
+        assert_eq!(result.rows.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn failed_insert_is_not_replayed_from_wal() {
+        let executor = get_test_executor_with_wal().await;
+        execute(
+            &executor,
+            "CREATE TABLE test (id UINT8 PRIMARY KEY, note STRING)",
+        )
+        .await
+        .unwrap();
+        let note = "x".repeat(2000);
+        let mut inserted_count = 0;
+        // Rows are inserted until the table is full, so the last INSERT fails when writing to the table file
+        let error = loop {
+            match execute(
+                &executor,
+                &format!(
+                    "INSERT INTO test (id, note) VALUES ({}, '{}')",
+                    inserted_count, note
+                ),
+            )
+            .await
+            {
+                Ok(_) => inserted_count += 1,
+                Err(error) => break error,
+            }
+        };
+        assert!(matches!(error, ExecutionError::Storage(message) if message.contains("is full")));
+        let config = executor.config.clone();
+        drop(executor);
+        let restarted_executor = get_bootstrapped_executor(&config).await;
+        let result = execute(&restarted_executor, "SELECT id FROM test")
+            .await
+            .unwrap();
+        assert_eq!(result.rows.len(), inserted_count);
+    }
+
+    #[tokio::test]
+    async fn select_by_primary_key_works() {
+        let executor = get_test_executor().await;
+        execute(
+            &executor,
+            "CREATE TABLE test (id UINT16 PRIMARY KEY, note STRING)",
+        )
+        .await
+        .unwrap();
+        for (id, note) in [(3, "c"), (1, "a"), (2, "b")] {
+            execute(
+                &executor,
+                &format!("INSERT INTO test (id, note) VALUES ({}, '{}')", id, note),
+            )
+            .await
+            .unwrap();
+        }
+        let result = execute(&executor, "SELECT note FROM test WHERE id = 1")
+            .await
+            .unwrap();
+        assert_eq!(
+            result.rows,
+            vec![Row(vec![DataInstance::Direct(DataInstanceRaw::String(
+                "a".into()
+            ))])]
+        );
+        let result = execute(&executor, "SELECT note FROM test WHERE 2 = id")
+            .await
+            .unwrap();
+        assert_eq!(
+            result.rows,
+            vec![Row(vec![DataInstance::Direct(DataInstanceRaw::String(
+                "b".into()
+            ))])]
+        );
+        let result = execute(&executor, "SELECT note FROM test WHERE id = 4")
+            .await
+            .unwrap();
+        assert_eq!(result.rows, vec![]);
+    }
+
+    #[tokio::test]
+    async fn primary_key_index_is_maintained_across_writes() {
+        let executor = get_test_executor().await;
+        execute(
+            &executor,
+            "CREATE TABLE test (id UINT16 PRIMARY KEY, note STRING)",
+        )
+        .await
+        .unwrap();
+        for (id, note) in [(1, "a"), (2, "b"), (3, "c")] {
+            execute(
+                &executor,
+                &format!("INSERT INTO test (id, note) VALUES ({}, '{}')", id, note),
+            )
+            .await
+            .unwrap();
+        }
+        let note_of = |id: u16| {
+            let executor = &executor;
+            async move {
+                execute(
+                    executor,
+                    &format!("SELECT note FROM test WHERE id = {}", id),
+                )
+                .await
+                .unwrap()
+                .rows
+            }
+        };
+        let note = |note: &str| {
+            vec![Row(vec![DataInstance::Direct(DataInstanceRaw::String(
+                note.into(),
+            ))])]
+        };
+        assert_eq!(note_of(3).await, note("c"));
+        // Rows after the deleted one shift, as do their positions in the index
+        execute(&executor, "DELETE FROM test WHERE id = 1")
+            .await
+            .unwrap();
+        assert_eq!(note_of(1).await, vec![]);
+        assert_eq!(note_of(3).await, note("c"));
+        execute(&executor, "UPDATE test SET id = 4 WHERE id = 3")
+            .await
+            .unwrap();
+        assert_eq!(note_of(3).await, vec![]);
+        assert_eq!(note_of(4).await, note("c"));
+        execute(&executor, "INSERT INTO test (id, note) VALUES (5, 'e')")
+            .await
+            .unwrap();
+        assert_eq!(note_of(5).await, note("e"));
+        execute(&executor, "TRUNCATE TABLE test").await.unwrap();
+        assert_eq!(note_of(2).await, vec![]);
+    }
+
+    #[tokio::test]
+    async fn parallel_selects_return_correct_results() {
+        let executor = get_test_executor().await;
+        execute(
+            &executor,
+            "CREATE TABLE test (id UINT16 PRIMARY KEY, note STRING)",
+        )
+        .await
+        .unwrap();
+        for (id, note) in [(1, "a"), (2, "b"), (3, "c")] {
+            execute(
+                &executor,
+                &format!("INSERT INTO test (id, note) VALUES ({}, '{}')", id, note),
+            )
+            .await
+            .unwrap();
+        }
+        let primary_key_index = executor
+            .get_primary_key_index(&executor.get_table("test").unwrap())
+            .await
+            .unwrap()
+            .0;
+        let selects = (0..60).map(|i| {
+            let statements =
+                parse_statements(&format!("SELECT note FROM test WHERE id = {}", i % 3 + 1))
+                    .unwrap();
+            executor.execute_batch(statements)
+        });
+        let results = tokio::time::timeout(Duration::from_secs(10), future::join_all(selects))
+            .await
+            .expect("Parallel SELECTs should not deadlock");
+        for (i, result) in results.into_iter().enumerate() {
+            let note = ["a", "b", "c"][i % 3];
+            assert_eq!(
+                result.unwrap().into_query_result().rows,
+                vec![Row(vec![DataInstance::Direct(DataInstanceRaw::String(
+                    note.into()
+                ))])]
+            );
+        }
+        // The index is shared by the SELECTs rather than rebuilt by each of them
+        assert!(Arc::ptr_eq(
+            &primary_key_index,
+            &executor.primary_key_indexes.lock()["test"]
+        ));
+    }
+
+    #[tokio::test]
+    async fn reads_share_batch_lock_while_catalog_changes_wait_for_it() {
+        let executor = get_test_executor().await;
+        execute(&executor, "CREATE TABLE test (id UINT8 PRIMARY KEY)")
+            .await
+            .unwrap();
+        // Standing in for a read-only batch in progress
+        let _shared_guard = executor.batch_lock.read().await;
+        for read_only_batch in ["SELECT * FROM test", "SHOW TABLES; DESCRIBE test"] {
+            tokio::time::timeout(
+                Duration::from_secs(10),
+                executor.execute_batch(parse_statements(read_only_batch).unwrap()),
+            )
+            .await
+            .expect("Reads should not wait for other reads")
+            .unwrap();
+        }
+        for catalog_changing_batch in [
+            "CREATE TABLE other (id UINT8 PRIMARY KEY)",
+            "ALTER TABLE test ADD COLUMN note NULLABLE(STRING)",
+            "BEGIN; SELECT * FROM test; ROLLBACK",
+        ] {
+            assert!(tokio::time::timeout(
+                Duration::from_millis(100),
+                executor.execute_batch(parse_statements(catalog_changing_batch).unwrap()),
+            )
+            .await
+            .is_err());
+        }
+    }
+
+    #[tokio::test]
+    async fn writes_to_different_tables_do_not_wait_for_one_another() {
+        let executor = get_test_executor().await;
+        for table_name in ["a", "b"] {
+            execute(
+                &executor,
+                &format!("CREATE TABLE {} (id UINT8 PRIMARY KEY)", table_name),
+            )
+            .await
+            .unwrap();
+        }
+        // Standing in for a long write to table `a` in progress
+        let table_lock = executor.get_table_lock("A").unwrap();
+        let exclusive_guard = table_lock.write().await;
+        tokio::time::timeout(
+            Duration::from_secs(10),
+            executor.execute_batch(parse_statements("INSERT INTO b (id) VALUES (1)").unwrap()),
+        )
+        .await
+        .expect("A write to table `b` should not wait for one to table `a`")
+        .unwrap();
+        for batch_on_a in ["INSERT INTO a (id) VALUES (1)", "SELECT * FROM a"] {
+            assert!(tokio::time::timeout(
+                Duration::from_millis(100),
+                executor.execute_batch(parse_statements(batch_on_a).unwrap()),
+            )
+            .await
+            .is_err());
+        }
+        drop(exclusive_guard);
+        // Interleaved writes to both tables all land
+        let inserts = (2..50).flat_map(|id| {
+            ["a", "b"].map(|table_name| {
+                executor.execute_batch(
+                    parse_statements(&format!("INSERT INTO {} (id) VALUES ({})", table_name, id))
+                        .unwrap(),
+                )
+            })
+        });
+        for result in tokio::time::timeout(Duration::from_secs(10), future::join_all(inserts))
+            .await
+            .expect("Parallel INSERTs should not deadlock")
+        {
+            result.unwrap();
+        }
+        for (table_name, expected_row_count) in [("a", 48), ("b", 49)] {
+            let result = execute(&executor, &format!("SELECT * FROM {}", table_name))
+                .await
+                .unwrap();
+            assert_eq!(result.rows.len(), expected_row_count);
+        }
+    }
+
+    #[tokio::test]
+    async fn insert_duplicate_primary_key_fails() {
+        let executor = get_test_executor().await;
+        execute(&executor, "CREATE TABLE test (id UINT8 PRIMARY KEY)")
+            .await
+            .unwrap();
+        execute(&executor, "INSERT INTO test (id) VALUES (1)")
+            .await
+            .unwrap();
+        assert_eq!(
+            execute(&executor, "INSERT INTO test (id) VALUES (1)")
+                .await
+                .unwrap_err(),
+            ExecutionError::Validation(ValidationError(
+                "Value 1 already exists in PRIMARY KEY column `id`".into()
+            ))
+        );
+        // The index must survive changes to the table
+        execute(
+            &executor,
+            "ALTER TABLE test ADD COLUMN note NULLABLE(STRING)",
+        )
+        .await
+        .unwrap();
+        assert!(execute(&executor, "INSERT INTO test (id) VALUES (1)")
+            .await
+            .is_err());
+        execute(&executor, "TRUNCATE TABLE test").await.unwrap();
+        execute(&executor, "INSERT INTO test (id) VALUES (1)")
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn composite_primary_key_works() {
+        let executor = get_test_executor().await;
+        execute(
+            &executor,
+            "CREATE TABLE test (user_id UINT8, day UINT16, note STRING, PRIMARY KEY (user_id, day))",
+        )
+        .await
+        .unwrap();
+        for (user_id, day, note) in [(1, 1, "a"), (1, 2, "b"), (2, 1, "c")] {
+            execute(
+                &executor,
+                &format!(
+                    "INSERT INTO test (user_id, day, note) VALUES ({}, {}, '{}')",
+                    user_id, day, note
+                ),
+            )
+            .await
+            .unwrap();
+        }
+        let result = execute(
+            &executor,
+            "SELECT note FROM test WHERE day = 1 AND user_id = 2",
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            result.rows,
+            vec![Row(vec![DataInstance::Direct(DataInstanceRaw::String(
+                "c".into()
+            ))])]
+        );
+        assert_eq!(
+            execute(
+                &executor,
+                "INSERT INTO test (user_id, day, note) VALUES (1, 2, 'd')"
+            )
+            .await
+            .unwrap_err(),
+            ExecutionError::Validation(ValidationError(
+                "Values (1, 2) already exist in PRIMARY KEY columns (`user_id`, `day`)".into()
+            ))
+        );
+        assert_eq!(
+            execute(&executor, "UPDATE test SET day = 2 WHERE note = 'a'")
+                .await
+                .unwrap_err(),
+            ExecutionError::Validation(ValidationError(
+                "Values (1, 2) already exist in PRIMARY KEY columns (`user_id`, `day`)".into()
+            ))
+        );
+        execute(&executor, "UPDATE test SET day = 3 WHERE note = 'a'")
+            .await
+            .unwrap();
+    }
+
+    async fn insert_test_rows(executor: &Executor) {
+        execute(
+            executor,
+            "CREATE TABLE test (id UINT8 PRIMARY KEY, size UINT8)",
+        )
+        .await
+        .unwrap();
+        for (id, size) in [(1, 10), (2, 20), (3, 10)] {
+            execute(
+                executor,
+                &format!("INSERT INTO test (id, size) VALUES ({}, {})", id, size),
+            )
+            .await
+            .unwrap();
+        }
+    }
+
+    fn get_ids(result: &QueryResult) -> Vec<u8> {
+        result
+            .rows
+            .iter()
+            .map(|row| match row.0[0] {
+                DataInstance::Direct(DataInstanceRaw::UInt8(id)) => id,
+                ref value => panic!("Unexpected value: {:?}", value),
+            })
+            .collect()
+    }
+
+    /// Page of the SELECT that starts after the cursor, as IDs of a `NULLABLE(UINT8)` column (with NULL as 0),
+    /// along with the cursor of the next page.
+    async fn select_page(
+        executor: &Executor,
+        query: &str,
+        cursor: &str,
+    ) -> (Vec<u8>, Option<String>) {
+        let mut statements = parse_statements(query).unwrap();
+        match &mut statements[0] {
+            Statement::Select(select) => select.cursor = Some(cursor.into()),
+            statement => panic!("Unexpected statement: {:?}", statement),
+        }
+        match executor.execute_batch(statements).await.unwrap() {
+            StatementOutcome::Page { rows, next_cursor } => {
+                let ids = rows
+                    .rows
+                    .iter()
+                    .map(|row| match row.0[0] {
+                        DataInstance::Nullable(DataInstanceRaw::UInt8(id)) => id,
+                        DataInstance::Null => 0,
+                        ref value => panic!("Unexpected value: {:?}", value),
+                    })
+                    .collect();
+                (ids, next_cursor)
+            }
+            outcome => panic!("Unexpected outcome: {:?}", outcome),
+        }
+    }
+
+    #[tokio::test]
+    async fn cursor_pages_follow_primary_key_order() {
+        let executor = get_test_executor().await;
+        execute(
+            &executor,
+            "CREATE TABLE test (id NULLABLE(UINT8) PRIMARY KEY)",
+        )
+        .await
+        .unwrap();
+        for id in [5, 1, 6, 3, 2, 4] {
+            execute(&executor, &format!("INSERT INTO test (id) VALUES ({})", id))
+                .await
+                .unwrap();
+        }
+        let query = "SELECT id FROM test WHERE id IN (1, 2, 4, 5, 6) LIMIT 2";
+        let (ids, next_cursor) = select_page(&executor, query, "").await;
+        assert_eq!(ids, [1, 2]);
+        let (ids, next_cursor) = select_page(&executor, query, &next_cursor.unwrap()).await;
+        assert_eq!(ids, [4, 5]);
+        // Exactly LIMIT rows remaining make up the last page
+        let (ids, next_cursor) = select_page(&executor, query, &next_cursor.unwrap()).await;
+        assert_eq!((ids, next_cursor), (vec![6], None));
+        // A key with NULL isn't indexed, but still comes last
+        execute(&executor, "INSERT INTO test (id) VALUES (NULL)")
+            .await
+            .unwrap();
+        let query = "SELECT id FROM test LIMIT 4";
+        let (ids, next_cursor) = select_page(&executor, query, "").await;
+        assert_eq!(ids, [1, 2, 3, 4]);
+        let (ids, next_cursor) = select_page(&executor, query, &next_cursor.unwrap()).await;
+        assert_eq!((ids, next_cursor), (vec![5, 6, 0], None));
+    }
+
+    #[tokio::test]
+    async fn update_with_where_works() {
+        let executor = get_test_executor().await;
+        insert_test_rows(&executor).await;
+        let result = execute(&executor, "UPDATE test SET size = 30 WHERE size = 10")
+            .await
+            .unwrap();
+        assert_eq!(result.column_names, vec!["rows_affected"]);
+        assert_eq!(
+            result.rows,
+            vec![Row(vec![DataInstance::Direct(DataInstanceRaw::UInt64(2))])]
+        );
+        let result = execute(&executor, "SELECT id FROM test WHERE size = 30")
+            .await
+            .unwrap();
+        assert_eq!(get_ids(&result), vec![1, 3]);
+        let result = execute(&executor, "SELECT id FROM test WHERE size = 20")
+            .await
+            .unwrap();
+        assert_eq!(get_ids(&result), vec![2]);
+    }
+
+    #[tokio::test]
+    async fn update_with_mismatched_value_fails() {
+        let executor = get_test_executor().await;
+        insert_test_rows(&executor).await;
+        assert_eq!(
+            execute(&executor, "UPDATE test SET size = 'big' WHERE id = 1")
+                .await
+                .unwrap_err(),
+            ExecutionError::Validation(ValidationError(
+                "Problem at column `size`: Value String(\"big\") cannot be used as UINT8".into()
+            ))
+        );
+    }
+
+    #[tokio::test]
+    async fn update_colliding_primary_key_fails() {
+        let executor = get_test_executor().await;
+        insert_test_rows(&executor).await;
+        assert_eq!(
+            execute(&executor, "UPDATE test SET id = 2 WHERE id = 1")
+                .await
+                .unwrap_err(),
+            ExecutionError::Validation(ValidationError(
+                "Value 2 already exists in PRIMARY KEY column `id`".into()
+            ))
+        );
+        // Changing the PRIMARY KEY to a free value is fine, and the index follows
+        execute(&executor, "UPDATE test SET id = 4 WHERE id = 1")
+            .await
+            .unwrap();
+        let result = execute(&executor, "SELECT id FROM test WHERE id = 4")
+            .await
+            .unwrap();
+        assert_eq!(get_ids(&result), vec![4]);
+    }
+
+    #[tokio::test]
+    async fn delete_with_where_works() {
+        let executor = get_test_executor().await;
+        insert_test_rows(&executor).await;
+        let result = execute(&executor, "DELETE FROM test WHERE size = 10")
+            .await
+            .unwrap();
+        assert_eq!(result.column_names, vec!["rows_affected"]);
+        assert_eq!(
+            result.rows,
+            vec![Row(vec![DataInstance::Direct(DataInstanceRaw::UInt64(2))])]
+        );
+        let result = execute(&executor, "SELECT id FROM test").await.unwrap();
+        assert_eq!(get_ids(&result), vec![2]);
+        // The PRIMARY KEY index must reflect the deletion
+        let result = execute(&executor, "SELECT id FROM test WHERE id = 2")
+            .await
+            .unwrap();
+        assert_eq!(get_ids(&result), vec![2]);
+        execute(&executor, "INSERT INTO test (id, size) VALUES (1, 30)")
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn delete_without_where_deletes_all_rows() {
+        let executor = get_test_executor().await;
+        insert_test_rows(&executor).await;
+        let result = execute(&executor, "DELETE FROM test").await.unwrap();
+        assert_eq!(
+            result.rows,
+            vec![Row(vec![DataInstance::Direct(DataInstanceRaw::UInt64(3))])]
+        );
+        let result = execute(&executor, "SELECT id FROM test").await.unwrap();
+        assert_eq!(get_ids(&result), Vec::<u8>::new());
+    }
+
+    /// Execute the statements, with the outcome as a table.
+    async fn execute_batch(
+        executor: &Executor,
+        statements: &str,
+    ) -> Result<QueryResult, ExecutionError> {
+        executor
+            .execute_batch(parse_statements(statements).unwrap())
+            .await
+            .map(StatementOutcome::into_query_result)
+    }
+
+    #[tokio::test]
+    async fn transaction_rollback_discards_changes() {
+        let executor = get_test_executor().await;
+        execute(&executor, "CREATE TABLE test (id UINT8 PRIMARY KEY)")
+            .await
+            .unwrap();
+        execute_batch(
+            &executor,
+            "BEGIN; INSERT INTO test (id) VALUES (1); CREATE TABLE other (id UINT8 PRIMARY KEY); ROLLBACK;",
+        )
+        .await
+        .unwrap();
+        let result = execute(&executor, "SELECT * FROM test").await.unwrap();
+        assert_eq!(result.rows, vec![]);
+        assert_eq!(
+            execute(&executor, "SELECT * FROM other").await.unwrap_err(),
+            ExecutionError::Validation(ValidationError("Table `other` does not exist".into()))
+        );
+        // The PRIMARY KEY index must not remember the rolled back row either
+        execute(&executor, "INSERT INTO test (id) VALUES (1)")
+            .await
+            .unwrap();
+        execute_batch(
+            &executor,
+            "BEGIN; ALTER TABLE test ADD COLUMN note NULLABLE(STRING); INSERT INTO test (id, note) VALUES (2, 'b'); ROLLBACK;",
+        )
+        .await
+        .unwrap();
+        let result = execute(&executor, "SELECT * FROM test").await.unwrap();
+        assert_eq!(result.column_names, vec!["id"]);
+        assert_eq!(get_ids(&result), vec![1]);
+    }
+
+    #[tokio::test]
+    async fn rolled_back_statements_do_not_make_the_result() {
+        let executor = get_test_executor().await;
+        execute_batch(
+            &executor,
+            "CREATE TABLE test (id UINT8 PRIMARY KEY); INSERT INTO test (id) VALUES (1); INSERT INTO test (id) VALUES (2); INSERT INTO test (id) VALUES (3)",
+        )
+        .await
+        .unwrap();
+        let result = execute_batch(
+            &executor,
+            "BEGIN; INSERT INTO test (id) VALUES (4); ROLLBACK",
+        )
+        .await
+        .unwrap();
+        assert!(result.column_names.is_empty());
+        assert!(result.rows.is_empty());
+        let result = execute_batch(
+            &executor,
+            "DELETE FROM test WHERE id = 1; BEGIN; DELETE FROM test; ROLLBACK",
+        )
+        .await
+        .unwrap();
+        assert_eq!(result.column_names, vec!["rows_affected"]);
+        assert_eq!(
+            result.rows,
+            vec![Row(vec![DataInstance::Direct(DataInstanceRaw::UInt64(1))])]
+        );
+        let result = execute(&executor, "SELECT * FROM test").await.unwrap();
+        assert_eq!(get_ids(&result), vec![2, 3]);
+    }
+
+    #[tokio::test]
+    async fn transaction_snapshots_only_changed_tables() {
+        let executor = get_test_executor().await;
+        execute_batch(
+            &executor,
+            "CREATE TABLE test (id UINT8 PRIMARY KEY); CREATE TABLE other (id UINT8 PRIMARY KEY)",
+        )
+        .await
+        .unwrap();
+        let mut snapshot = executor.take_snapshot(StatementOutcome::Done);
+        for statement in parse_statements(
+            "SELECT * FROM other; INSERT INTO test (id) VALUES (1); CREATE TABLE new (id UINT8 PRIMARY KEY); INSERT INTO new (id) VALUES (1)",
+        )
+        .unwrap()
+        {
+            executor
+                .snapshot_changed_table(&mut snapshot, &statement)
+                .await
+                .unwrap();
+        }
+        assert_eq!(snapshot.rows.keys().collect::<Vec<_>>(), vec!["test"]);
+    }
+
+    #[tokio::test]
+    async fn transaction_commit_persists_changes() {
+        let executor = get_test_executor().await;
+        execute(&executor, "CREATE TABLE test (id UINT8 PRIMARY KEY)")
+            .await
+            .unwrap();
+        let result = execute_batch(
+            &executor,
+            "BEGIN; INSERT INTO test (id) VALUES (1); INSERT INTO test (id) VALUES (2); COMMIT; SELECT * FROM test",
+        )
+        .await
+        .unwrap();
+        assert_eq!(get_ids(&result), vec![1, 2]);
+    }
+
+    #[tokio::test]
+    async fn transaction_is_rolled_back_on_error() {
+        let executor = get_test_executor().await;
+        execute(&executor, "CREATE TABLE test (id UINT8 PRIMARY KEY)")
+            .await
+            .unwrap();
+        assert_eq!(
+            execute_batch(
+                &executor,
+                "BEGIN; INSERT INTO test (id) VALUES (1); INSERT INTO test (id) VALUES (1); COMMIT",
+            )
+            .await
+            .unwrap_err(),
+            ExecutionError::Validation(ValidationError(
+                "Value 1 already exists in PRIMARY KEY column `id`".into()
+            ))
+        );
+        let result = execute(&executor, "SELECT * FROM test").await.unwrap();
+        assert_eq!(result.rows, vec![]);
+    }
+
+    #[tokio::test]
+    async fn nested_transaction_fails() {
+        let executor = get_test_executor().await;
+        execute(&executor, "CREATE TABLE test (id UINT8 PRIMARY KEY)")
+            .await
+            .unwrap();
+        assert_eq!(
+            execute_batch(
+                &executor,
+                "BEGIN; INSERT INTO test (id) VALUES (1); BEGIN; COMMIT; COMMIT"
+            )
+            .await
+            .unwrap_err(),
+            ExecutionError::Validation(ValidationError(
+                "A transaction is already in progress, and transactions cannot be nested".into()
+            ))
+        );
+        let result = execute(&executor, "SELECT * FROM test").await.unwrap();
+        assert_eq!(result.rows, vec![]);
+        assert_eq!(
+            execute_batch(&executor, "COMMIT").await.unwrap_err(),
+            ExecutionError::Validation(ValidationError(
+                "There is no transaction in progress".into()
+            ))
+        );
+    }
+
+    #[tokio::test]
+    async fn bound_parameters_are_coerced_to_column_types() {
+        let executor = get_test_executor().await;
+        execute(
+            &executor,
+            "CREATE TABLE test (id UINT8 PRIMARY KEY, note nullable(STRING))",
+        )
+        .await
+        .unwrap();
+        let mut statements = parse_statements(
+            "INSERT INTO test (id, note) VALUES (?, ?); SELECT * FROM test WHERE id = $1",
+        )
+        .unwrap();
+        bind_parameters(
+            &mut statements,
+            &[
+                DataInstance::Direct(DataInstanceRaw::UInt32(7)),
+                DataInstance::Direct(DataInstanceRaw::String("x".into())),
+            ],
+        )
+        .unwrap();
+        let result = executor
+            .execute_batch(statements)
+            .await
+            .unwrap()
+            .into_query_result();
+        assert_eq!(
+            result.rows,
+            vec![Row(vec![
+                DataInstance::Direct(DataInstanceRaw::UInt8(7)),
+                DataInstance::Nullable(DataInstanceRaw::String("x".into())),
+            ])]
+        );
+    }
+
+    #[tokio::test]
+    async fn unbound_parameter_fails() {
+        let executor = get_test_executor().await;
+        insert_test_rows(&executor).await;
+        assert_eq!(
+            execute(&executor, "DELETE FROM test WHERE id = ?")
+                .await
+                .unwrap_err(),
+            ExecutionError::Validation(ValidationError("Parameter `$1` has no value bound".into()))
+        );
+    }
+
+    #[tokio::test]
+    async fn insert_uses_column_default() {
+        let executor = get_test_executor().await;
+        execute(
+            &executor,
+            "CREATE TABLE test (id UINT8 PRIMARY KEY, size UINT16 DEFAULT 42)",
+        )
+        .await
+        .unwrap();
+        execute(&executor, "INSERT INTO test (id) VALUES (1)")
+            .await
+            .unwrap();
+        execute(&executor, "INSERT INTO test (id, size) VALUES (2, 7)")
+            .await
+            .unwrap();
+        let result = execute(&executor, "SELECT * FROM test").await.unwrap();
+        assert_eq!(
+            result.rows,
+            vec![
+                Row(vec![
+                    DataInstance::Direct(DataInstanceRaw::UInt8(1)),
+                    DataInstance::Direct(DataInstanceRaw::UInt16(42))
+                ]),
+                Row(vec![
+                    DataInstance::Direct(DataInstanceRaw::UInt8(2)),
+                    DataInstance::Direct(DataInstanceRaw::UInt16(7))
+                ])
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn create_table_with_mismatched_default_fails() {
+        let executor = get_test_executor().await;
+        let statement = "CREATE TABLE test (id UINT8 PRIMARY KEY, name STRING DEFAULT 5)";
+        assert_eq!(
+            parse_statement(statement).unwrap().validate(),
+            Err(ValidationError(
+                "Problem at column 2: DEFAULT of column `name` doesn't match its type: Value UInt32(5) cannot be used as STRING"
+                    .into()
+            ))
+        );
+        assert_eq!(
+            execute(&executor, statement).await.unwrap_err(),
+            ExecutionError::Validation(ValidationError(
+                "DEFAULT of column `name` doesn't match its type: Value UInt32(5) cannot be used as STRING"
+                    .into()
+            ))
+        );
+    }
+
+    #[tokio::test]
+    async fn null_default_requires_nullable_column() {
+        let executor = get_test_executor().await;
+        let statement = "CREATE TABLE test (id UINT8 PRIMARY KEY, size UINT64 DEFAULT NULL)";
+        let expected_message = "DEFAULT of column `size` is NULL, but its type UINT64 is non-nullable – make it NULLABLE(UINT64) or drop the DEFAULT";
+        assert_eq!(
+            parse_statement(statement).unwrap().validate(),
+            Err(ValidationError(format!(
+                "Problem at column 2: {}",
+                expected_message
+            )))
+        );
+        assert_eq!(
+            execute(&executor, statement).await.unwrap_err(),
+            ExecutionError::Validation(ValidationError(expected_message.into()))
+        );
+        execute(
+            &executor,
+            "CREATE TABLE test (id UINT8 PRIMARY KEY, size NULLABLE(UINT64) DEFAULT NULL)",
+        )
+        .await
+        .unwrap();
+        execute(&executor, "INSERT INTO test (id) VALUES (1)")
+            .await
+            .unwrap();
+        let result = execute(&executor, "SELECT size FROM test").await.unwrap();
+        assert_eq!(result.rows, vec![Row(vec![DataInstance::Null])]);
+    }
+
+    #[tokio::test]
+    async fn insert_duplicate_into_unique_column_fails() {
+        let executor = get_test_executor().await;
+        execute(
+            &executor,
+            "CREATE TABLE test (id UINT8 PRIMARY KEY, email NULLABLE(STRING) UNIQUE)",
+        )
+        .await
+        .unwrap();
+        execute(
+            &executor,
+            "INSERT INTO test (id, email) VALUES (1, 'a@b.c')",
+        )
+        .await
+        .unwrap();
+        // NULLs are allowed to repeat
+        execute(&executor, "INSERT INTO test (id, email) VALUES (2, NULL)")
+            .await
+            .unwrap();
+        execute(&executor, "INSERT INTO test (id, email) VALUES (3, NULL)")
+            .await
+            .unwrap();
+        assert_eq!(
+            execute(
+                &executor,
+                "INSERT INTO test (id, email) VALUES (4, 'a@b.c')"
+            )
+            .await
+            .unwrap_err(),
+            ExecutionError::Validation(ValidationError(
+                "Value 'a@b.c' already exists in UNIQUE column `email`".into()
+            ))
+        );
+        let result = execute(&executor, "SELECT * FROM test").await.unwrap();
+        assert_eq!(result.rows.len(), 3);
+    }
+
+    /// Values of the `size` column in the result.
+    fn get_sizes(result: &QueryResult) -> Vec<Option<u64>> {
+        result
+            .rows
+            .iter()
+            .map(|row| match row.0[0].as_raw() {
+                Some(DataInstanceRaw::UInt64(size)) => Some(*size),
+                None => None,
+                value => panic!("Unexpected value {:?}", value),
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn select_with_order_by_works() {
+        let executor = get_test_executor().await;
+        execute(
+            &executor,
+            "CREATE TABLE test (id UINT8 PRIMARY KEY, size NULLABLE(UINT64))",
+        )
+        .await
+        .unwrap();
+        for (id, size) in [(1, "20"), (2, "NULL"), (3, "5"), (4, "300")] {
+            execute(
+                &executor,
+                &format!("INSERT INTO test (id, size) VALUES ({}, {})", id, size),
+            )
+            .await
+            .unwrap();
+        }
+        let result = execute(&executor, "SELECT size FROM test ORDER BY size")
+            .await
+            .unwrap();
+        assert_eq!(get_sizes(&result), vec![Some(5), Some(20), Some(300), None]);
+        let result = execute(&executor, "SELECT size FROM test ORDER BY size DESC")
+            .await
+            .unwrap();
+        assert_eq!(get_sizes(&result), vec![Some(300), Some(20), Some(5), None]);
+    }
+
+    #[tokio::test]
+    async fn select_with_limit_and_offset_works() {
+        let executor = get_test_executor().await;
+        execute(&executor, "CREATE TABLE test (size UINT64 PRIMARY KEY)")
+            .await
+            .unwrap();
+        for size in 1..=5 {
+            execute(
+                &executor,
+                &format!("INSERT INTO test (size) VALUES ({})", size),
+            )
+            .await
+            .unwrap();
+        }
+        let result = execute(&executor, "SELECT size FROM test ORDER BY size LIMIT 2")
+            .await
+            .unwrap();
+        assert_eq!(get_sizes(&result), vec![Some(1), Some(2)]);
+        let result = execute(&executor, "SELECT size FROM test LIMIT 100")
+            .await
+            .unwrap();
+        assert_eq!(result.rows.len(), 5);
+        let result = execute(
+            &executor,
+            "SELECT size FROM test ORDER BY size DESC LIMIT 2 OFFSET 1",
+        )
+        .await
+        .unwrap();
+        assert_eq!(get_sizes(&result), vec![Some(4), Some(3)]);
+        let result = execute(&executor, "SELECT size FROM test LIMIT 2 OFFSET 10")
+            .await
+            .unwrap();
+        assert_eq!(get_sizes(&result), vec![]);
+    }
+
+    #[tokio::test]
+    async fn select_distinct_keeps_first_of_each_value() {
+        let executor = get_test_executor().await;
+        execute(
+            &executor,
+            "CREATE TABLE test (id UINT8 PRIMARY KEY, size UINT64)",
+        )
+        .await
+        .unwrap();
+        for (id, size) in [(1, 20), (2, 5), (3, 20), (4, 7), (5, 5)] {
+            execute(
+                &executor,
+                &format!("INSERT INTO test (id, size) VALUES ({}, {})", id, size),
+            )
+            .await
+            .unwrap();
+        }
+        let result = execute(&executor, "SELECT DISTINCT size FROM test")
+            .await
+            .unwrap();
+        assert_eq!(get_sizes(&result), vec![Some(20), Some(5), Some(7)]);
+        // Duplicates are removed before LIMIT and OFFSET are applied
+        let result = execute(&executor, "SELECT DISTINCT size FROM test LIMIT 2 OFFSET 1")
+            .await
+            .unwrap();
+        assert_eq!(get_sizes(&result), vec![Some(5), Some(7)]);
+        // Rows are only duplicates if all of their columns are equal
+        let result = execute(&executor, "SELECT DISTINCT size, id FROM test")
+            .await
+            .unwrap();
+        assert_eq!(result.rows.len(), 5);
+    }
+
+    #[tokio::test]
+    async fn select_distinct_treats_nulls_as_equal() {
+        let executor = get_test_executor().await;
+        execute(
+            &executor,
+            "CREATE TABLE test (id UINT8 PRIMARY KEY, size NULLABLE(UINT64))",
+        )
+        .await
+        .unwrap();
+        for (id, size) in [(1, "NULL"), (2, "3"), (3, "NULL"), (4, "3")] {
+            execute(
+                &executor,
+                &format!("INSERT INTO test (id, size) VALUES ({}, {})", id, size),
+            )
+            .await
+            .unwrap();
+        }
+        let result = execute(&executor, "SELECT DISTINCT size FROM test")
+            .await
+            .unwrap();
+        assert_eq!(get_sizes(&result), vec![None, Some(3)]);
+        let result = execute(&executor, "SELECT DISTINCT size FROM test ORDER BY size")
+            .await
+            .unwrap();
+        assert_eq!(get_sizes(&result), vec![Some(3), None]);
+    }
+
+    #[tokio::test]
+    async fn select_count_works() {
+        let executor = get_test_executor().await;
+        execute(&executor, "CREATE TABLE test (size UINT64 PRIMARY KEY)")
+            .await
+            .unwrap();
+        let result = execute(&executor, "SELECT COUNT(*) FROM test")
+            .await
+            .unwrap();
+        assert_eq!(result.column_names, vec!["COUNT(*)"]);
+        assert_eq!(get_sizes(&result), vec![Some(0)]);
+        for size in 1..=5 {
+            execute(
+                &executor,
+                &format!("INSERT INTO test (size) VALUES ({})", size),
+            )
+            .await
+            .unwrap();
+        }
+        let result = execute(&executor, "SELECT COUNT(*) FROM test WHERE size = 3")
+            .await
+            .unwrap();
+        assert_eq!(get_sizes(&result), vec![Some(1)]);
+        let result = execute(&executor, "SELECT COUNT(*) FROM test")
+            .await
+            .unwrap();
+        assert_eq!(get_sizes(&result), vec![Some(5)]);
+    }
+
+    #[tokio::test]
+    async fn select_numeric_aggregates_work() {
+        let executor = get_test_executor().await;
+        execute(
+            &executor,
+            "CREATE TABLE test (id UINT8 PRIMARY KEY, size NULLABLE(UINT64), name STRING)",
+        )
+        .await
+        .unwrap();
+        let aggregates_statement =
+            "SELECT SUM(size), MIN(size), MAX(size), AVG(size), COUNT(size) FROM test";
+        let result = execute(&executor, aggregates_statement).await.unwrap();
+        assert_eq!(
+            result.column_names,
+            vec![
+                "SUM(size)",
+                "MIN(size)",
+                "MAX(size)",
+                "AVG(size)",
+                "COUNT(size)"
+            ]
+        );
+        assert_eq!(
+            result.rows,
+            vec![Row(vec![
+                DataInstance::Null,
+                DataInstance::Null,
+                DataInstance::Null,
+                DataInstance::Null,
+                DataInstance::Direct(DataInstanceRaw::UInt64(0))
+            ])]
+        );
+        for (id, size) in [(1, "NULL"), (2, "10"), (3, "1"), (4, "4")] {
+            execute(
+                &executor,
+                &format!(
+                    "INSERT INTO test (id, size, name) VALUES ({}, {}, 'x')",
+                    id, size
+                ),
+            )
+            .await
+            .unwrap();
+        }
+        let result = execute(&executor, aggregates_statement).await.unwrap();
+        assert_eq!(
+            result.rows,
+            vec![Row(vec![
+                DataInstance::Direct(DataInstanceRaw::UInt128(15)),
+                DataInstance::Direct(DataInstanceRaw::UInt64(1)),
+                DataInstance::Direct(DataInstanceRaw::UInt64(10)),
+                // 15 / 3
+                DataInstance::Direct(DataInstanceRaw::UInt64(5)),
+                DataInstance::Direct(DataInstanceRaw::UInt64(3))
+            ])]
+        );
+        assert_eq!(
+            execute(&executor, "SELECT SUM(name) FROM test")
+                .await
+                .unwrap_err(),
+            ExecutionError::Validation(ValidationError(
+                "aggregate `SUM` can only be computed over numeric columns, but column `name` is STRING"
+                    .into()
+            ))
+        );
+    }
+
+    #[tokio::test]
+    async fn select_group_by_aggregates_each_group() {
+        let executor = get_test_executor().await;
+        execute(
+            &executor,
+            "CREATE TABLE test (id UINT8 PRIMARY KEY, country NULLABLE(STRING), size UINT64)",
+        )
+        .await
+        .unwrap();
+        for (id, country, size) in [
+            (1, "'PL'", 10),
+            (2, "'DE'", 1),
+            (3, "NULL", 7),
+            (4, "'PL'", 5),
+            (5, "'DE'", 2),
+            (6, "'AT'", 3),
+            (7, "NULL", 8),
+        ] {
+            execute(
+                &executor,
+                &format!(
+                    "INSERT INTO test (id, country, size) VALUES ({}, {}, {})",
+                    id, country, size
+                ),
+            )
+            .await
+            .unwrap();
+        }
+        let group = |country: Option<&str>, count: u64, sum: u128| {
+            Row(vec![
+                country.map_or(DataInstance::Null, |country| {
+                    DataInstance::Nullable(DataInstanceRaw::String(country.into()))
+                }),
+                DataInstance::Direct(DataInstanceRaw::UInt64(count)),
+                DataInstance::Direct(DataInstanceRaw::UInt128(sum)),
+            ])
+        };
+        let result = execute(
+            &executor,
+            "SELECT country, COUNT(*), SUM(size) FROM test GROUP BY country",
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            result.column_names,
+            vec!["country", "COUNT(*)", "SUM(size)"]
+        );
+        // Groups are sorted by the grouping columns, with the NULL group last
+        assert_eq!(
+            result.rows,
+            vec![
+                group(Some("AT"), 1, 3),
+                group(Some("DE"), 2, 3),
+                group(Some("PL"), 2, 15),
+                group(None, 2, 15),
+            ]
+        );
+        let result = execute(
+            &executor,
+            "SELECT country, COUNT(*), SUM(size) FROM test WHERE size BETWEEN 3 AND 100 GROUP BY country ORDER BY country DESC LIMIT 2",
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            result.rows,
+            vec![group(Some("PL"), 2, 15), group(Some("AT"), 1, 3)]
+        );
+    }
+
+    #[tokio::test]
+    async fn select_group_by_rejects_ungrouped_columns() {
+        let executor = get_test_executor().await;
+        execute(
+            &executor,
+            "CREATE TABLE test (id UINT8 PRIMARY KEY, country STRING, size UINT64)",
+        )
+        .await
+        .unwrap();
+        for (statement, expected_message) in [
+            (
+                "SELECT country, size, COUNT(*) FROM test GROUP BY country",
+                "Column `size` of table `test` must be in GROUP BY or be aggregated",
+            ),
+            (
+                "SELECT * FROM test GROUP BY country",
+                "`*` cannot be selected with GROUP BY",
+            ),
+            (
+                "SELECT country FROM test GROUP BY continent",
+                "Column `continent` does not exist in table `test`",
+            ),
+            (
+                "SELECT country, COUNT(*) FROM test GROUP BY country ORDER BY size",
+                "Column `size` of table `test` must be in GROUP BY to be used in ORDER BY",
+            ),
+        ] {
+            assert_eq!(
+                execute(&executor, statement).await.unwrap_err(),
+                ExecutionError::Validation(ValidationError(expected_message.into()))
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn qualified_column_references_resolve_against_table() {
+        let executor = get_test_executor().await;
+        execute(
+            &executor,
+            "CREATE TABLE test (id UINT8 PRIMARY KEY, size UINT64)",
+        )
+        .await
+        .unwrap();
+        for id in 1..=3 {
+            execute(
+                &executor,
+                &format!("INSERT INTO test (id, size) VALUES ({}, {})", id, id * 10),
+            )
+            .await
+            .unwrap();
+        }
+        execute(&executor, "UPDATE test SET size = 25 WHERE test.id = 2")
+            .await
+            .unwrap();
+        execute(&executor, "DELETE FROM TEST WHERE Test.id = 3")
+            .await
+            .unwrap();
+        let result = execute(
+            &executor,
+            "SELECT test.size FROM test WHERE test.id BETWEEN 1 AND 5",
+        )
+        .await
+        .unwrap();
+        assert_eq!(result.column_names, vec!["size"]);
+        assert_eq!(get_sizes(&result), vec![Some(10), Some(25)]);
+        for statement in [
+            "SELECT other.size FROM test",
+            "SELECT size FROM test WHERE other.id = 1",
+            "UPDATE test SET size = 1 WHERE other.id = 1",
+            "DELETE FROM test WHERE other.id = 1",
+        ] {
+            assert_eq!(
+                execute(&executor, statement).await.unwrap_err(),
+                ExecutionError::Validation(ValidationError(
+                    "Table `other` is not part of the query, so its columns can't be referenced"
+                        .into()
+                ))
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn select_join_pairs_rows_with_equal_keys() {
+        let executor = get_test_executor().await;
+        execute(
+            &executor,
+            "CREATE TABLE users (id UINT8 PRIMARY KEY, name STRING)",
+        )
+        .await
+        .unwrap();
+        execute(
+            &executor,
+            "CREATE TABLE orders (id UINT16 PRIMARY KEY, user_id NULLABLE(UINT8), amount UINT64)",
+        )
+        .await
+        .unwrap();
+        for (id, name) in [(1, "ann"), (2, "bob"), (3, "cid")] {
+            execute(
+                &executor,
+                &format!("INSERT INTO users (id, name) VALUES ({}, '{}')", id, name),
+            )
+            .await
+            .unwrap();
+        }
+        for (id, user_id, amount) in [(10, "2", 5), (11, "1", 7), (12, "2", 9), (13, "NULL", 1)] {
+            execute(
+                &executor,
+                &format!(
+                    "INSERT INTO orders (id, user_id, amount) VALUES ({}, {}, {})",
+                    id, user_id, amount
+                ),
+            )
+            .await
+            .unwrap();
+        }
+        let result = execute(
+            &executor,
+            "SELECT * FROM users JOIN orders ON users.id = orders.user_id",
+        )
+        .await
+        .unwrap();
+        // Columns present in both tables are qualified
+        assert_eq!(
+            result.column_names,
+            vec!["users.id", "name", "orders.id", "user_id", "amount"]
+        );
+        assert_eq!(result.rows.len(), 3);
+        let result = execute(
+            &executor,
+            "SELECT amount, name FROM users INNER JOIN orders ON user_id = users.id \
+            WHERE amount BETWEEN 6 AND 100 ORDER BY amount DESC",
+        )
+        .await
+        .unwrap();
+        assert_eq!(result.column_names, vec!["amount", "name"]);
+        assert_eq!(
+            result.rows,
+            vec![
+                Row(vec![
+                    DataInstance::Direct(DataInstanceRaw::UInt64(9)),
+                    DataInstance::Direct(DataInstanceRaw::String("bob".into()))
+                ]),
+                Row(vec![
+                    DataInstance::Direct(DataInstanceRaw::UInt64(7)),
+                    DataInstance::Direct(DataInstanceRaw::String("ann".into()))
+                ]),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn select_join_rejects_ambiguous_columns() {
+        let executor = get_test_executor().await;
+        execute(&executor, "CREATE TABLE a (id UINT8 PRIMARY KEY, x UINT8)")
+            .await
+            .unwrap();
+        execute(&executor, "CREATE TABLE b (id UINT8 PRIMARY KEY, y UINT8)")
+            .await
+            .unwrap();
+        for (statement, expected_message) in [
+            (
+                "SELECT id FROM a JOIN b ON a.x = b.y",
+                "Column `id` is ambiguous, as it exists in tables `a` and `b` – qualify it with a table name, e.g. `a.id`",
+            ),
+            (
+                "SELECT x FROM a JOIN b ON id = y",
+                "Column `id` is ambiguous, as it exists in tables `a` and `b` – qualify it with a table name, e.g. `a.id`",
+            ),
+            (
+                "SELECT x FROM a JOIN b ON a.x = b.z",
+                "Column `z` does not exist in table `b`",
+            ),
+            (
+                "SELECT c.x FROM a JOIN b ON a.x = b.y",
+                "Table `c` is not part of the query, so its columns can't be referenced",
+            ),
+            (
+                "SELECT x FROM a JOIN b ON a.x = a.id",
+                "JOIN condition must be an equality of a column of table `a` and a column of table `b`, e.g. `a.id = b.id`",
+            ),
+            ("SELECT * FROM a JOIN a ON a.id = a.x", "Table `a` can't be joined with itself"),
+        ] {
+            assert_eq!(
+                execute(&executor, statement).await.unwrap_err(),
+                ExecutionError::Validation(ValidationError(expected_message.into()))
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn select_computes_arithmetic_expressions() {
+        let executor = get_test_executor().await;
+        execute(
+            &executor,
+            "CREATE TABLE orders (id UINT8 PRIMARY KEY, price UINT32, quantity NULLABLE(UINT8))",
+        )
+        .await
+        .unwrap();
+        for (id, price, quantity) in [(1, 250, "4"), (2, 99, "NULL"), (3, 7, "3")] {
+            execute(
+                &executor,
+                &format!(
+                    "INSERT INTO orders (id, price, quantity) VALUES ({}, {}, {})",
+                    id, price, quantity
+                ),
+            )
+            .await
+            .unwrap();
+        }
+        let result = execute(
+            &executor,
+            "SELECT price * quantity, (price + 1) / 2 AS half FROM orders",
+        )
+        .await
+        .unwrap();
+        assert_eq!(result.column_names, vec!["price * quantity", "half"]);
+        // Results are of the wider type of the operands, and NULL operands make them NULL
+        assert_eq!(
+            result.rows,
+            vec![
+                Row(vec![
+                    DataInstance::Direct(DataInstanceRaw::UInt32(1000)),
+                    DataInstance::Direct(DataInstanceRaw::UInt32(125)),
+                ]),
+                Row(vec![
+                    DataInstance::Null,
+                    DataInstance::Direct(DataInstanceRaw::UInt32(50)),
+                ]),
+                Row(vec![
+                    DataInstance::Direct(DataInstanceRaw::UInt32(21)),
+                    DataInstance::Direct(DataInstanceRaw::UInt32(4)),
+                ]),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn select_arithmetic_errors_are_reported() {
+        let executor = get_test_executor().await;
+        execute(
+            &executor,
+            "CREATE TABLE orders (id UINT8 PRIMARY KEY, price UINT8, note STRING)",
+        )
+        .await
+        .unwrap();
+        execute(
+            &executor,
+            "INSERT INTO orders (id, price, note) VALUES (1, 200, 'x')",
+        )
+        .await
+        .unwrap();
+        for (statement, expected_message) in [
+            (
+                "SELECT price / (id - 1) FROM orders",
+                "Division by zero in `price / (id - 1)`",
+            ),
+            (
+                "SELECT price * 4294967295 FROM orders",
+                "Result of `price * 4294967295` is out of range for UINT32",
+            ),
+            (
+                "SELECT id - price FROM orders",
+                "Result of `id - price` is out of range for UINT8",
+            ),
+            (
+                "SELECT note * 2 FROM orders",
+                "Arithmetic can only be used with integer values, not String(\"x\"), in `note * 2`",
+            ),
+        ] {
+            assert_eq!(
+                execute(&executor, statement).await.unwrap_err(),
+                ExecutionError::Validation(ValidationError(expected_message.into()))
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn select_casts_values() {
+        let executor = get_test_executor().await;
+        execute(
+            &executor,
+            "CREATE TABLE events (id UINT64 PRIMARY KEY, code STRING)",
+        )
+        .await
+        .unwrap();
+        execute(
+            &executor,
+            "INSERT INTO events (id, code) VALUES (18446744073709551615, '42')",
+        )
+        .await
+        .unwrap();
+        let result = execute(
+            &executor,
+            "SELECT CAST(id AS STRING), CAST(code AS UINT64) + 1 AS next FROM events",
+        )
+        .await
+        .unwrap();
+        assert_eq!(result.column_names, vec!["CAST(id AS STRING)", "next"]);
+        assert_eq!(
+            result.rows,
+            vec![Row(vec![
+                DataInstance::Direct(DataInstanceRaw::String("18446744073709551615".into())),
+                DataInstance::Direct(DataInstanceRaw::UInt64(43)),
+            ])]
+        );
+    }
+
+    #[tokio::test]
+    async fn select_cast_errors_are_reported() {
+        let executor = get_test_executor().await;
+        execute(
+            &executor,
+            "CREATE TABLE events (id UINT16 PRIMARY KEY, code STRING)",
+        )
+        .await
+        .unwrap();
+        execute(
+            &executor,
+            "INSERT INTO events (id, code) VALUES (300, 'abc')",
+        )
+        .await
+        .unwrap();
+        for (statement, expected_message) in [
+            (
+                "SELECT CAST(code AS UINT64) FROM events",
+                "Value String(\"abc\") cannot be cast to UINT64, as it's not a non-negative integer, in `CAST(code AS UINT64)`",
+            ),
+            (
+                "SELECT CAST(id AS UINT8) FROM events",
+                "Value 300 is out of range for UINT8 (0..=255), in `CAST(id AS UINT8)`",
+            ),
+        ] {
+            assert_eq!(
+                execute(&executor, statement).await.unwrap_err(),
+                ExecutionError::Validation(ValidationError(expected_message.into()))
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn identifiers_are_case_insensitive() {
+        let executor = get_test_executor().await;
+        execute(
+            &executor,
+            "CREATE TABLE Users (Id UINT8 PRIMARY KEY, Name STRING)",
+        )
+        .await
+        .unwrap();
+        execute(&executor, "INSERT INTO USERS (id, NAME) VALUES (1, 'foo')")
+            .await
+            .unwrap();
+        let result = execute(
+            &executor,
+            "SELECT name FROM users WHERE ID = 1 ORDER BY nAmE",
+        )
+        .await
+        .unwrap();
+        // The original case is preserved in output
+        assert_eq!(result.column_names, vec!["Name".to_string()]);
+        assert_eq!(
+            result.rows,
+            vec![Row(vec![DataInstance::Direct(DataInstanceRaw::String(
+                "foo".into()
+            ))])]
+        );
+        assert_eq!(
+            execute(&executor, "CREATE TABLE USERS (id UINT8 PRIMARY KEY)")
+                .await
+                .unwrap_err(),
+            ValidationError("Table `USERS` already exists".into()).into()
+        );
+        assert_eq!(
+            parse_statement("CREATE TABLE test (id UINT8 PRIMARY KEY, ID UINT8)")
+                .unwrap()
+                .validate(),
+            Err(ValidationError(
+                "There is more than one column with name `ID` in table definition".into()
+            ))
+        );
+    }
 }
@@ -1,17 +1,38 @@
 use crate::{
     config,
-    constructs::components::TableDefinition,
-    storage::{filesystem::seek_read_decode_page, paging::Page, Row},
+    constructs::{
+        components::{
+            normalize_identifier, DataDefinition, DataInstance, DataInstanceRaw, DataTypeRaw,
+            Expression, TableDefinition,
+        },
+        functions::Aggregate,
+        statements::{
+            AggregateArgument, OrderBy, OrderDirection, SelectColumn, SelectItem, SelectStatement,
+        },
+    },
+    sql::ValidationError,
+    storage::{
+        filesystem::{seek_read_decode_page, seek_read_page},
+        paging::{decode_leaf_row, Page},
+        Row,
+    },
 };
+use parking_lot::RwLock;
+use std::cmp::Ordering;
+use std::collections::HashSet;
+
+use super::cursor::{compare_primary_keys, decode_cursor, encode_cursor, get_primary_key};
+use super::index::{find_primary_key_lookup, raw_primary_key, PrimaryKeyIndex};
+use super::join::{combine_tables, hash_join, resolve_column_references, resolve_join_columns};
+use super::like::LikePattern;
+use super::{ExecutionError, QueryResult, StatementOutcome};
 
 pub async fn read_all_rows(
     config: &config::Config,
     schema: &str,
     table_definition: &TableDefinition,
 ) -> Result<Vec<Row>, String> {
-    let meta = seek_read_decode_page(config, schema, table_definition, 0)
-        .await
-        .unwrap();
+    let meta = seek_read_decode_page(config, schema, table_definition, 0).await?;
     match meta {
         Page::Meta {
             b_tree_root_page_index,
@@ -19,8 +40,7 @@ pub async fn read_all_rows(
         } => {
             let data =
                 seek_read_decode_page(config, schema, table_definition, b_tree_root_page_index)
-                    .await
-                    .unwrap();
+                    .await?;
             match data {
                 Page::BTreeLeaf { rows, .. } => Ok(rows),
                 _ => Err("Invalid page type 1".to_string()),
@@ -30,6 +50,1244 @@ pub async fn read_all_rows(
     }
 }
 
+/// Read the B+ tree leaf holding the table's rows without decoding it, so that rows can be decoded
+/// one by one with `decode_leaf_row`.
+async fn read_leaf(
+    config: &config::Config,
+    schema: &str,
+    table_definition: &TableDefinition,
+) -> Result<Vec<u8>, String> {
+    match seek_read_decode_page(config, schema, table_definition, 0).await? {
+        Page::Meta {
+            b_tree_root_page_index,
+            ..
+        } => seek_read_page(
+            config,
+            schema,
+            &table_definition.name,
+            b_tree_root_page_index,
+        )
+        .await
+        .map_err(|error| error.to_string()),
+        _ => Err("Invalid page type 0".to_string()),
+    }
+}
+
+/// Read the row at the position among the table's rows, as pointed to by the PRIMARY KEY index,
+/// decoding only that row. `None` means that there's no row at the position.
+pub async fn read_row_at(
+    config: &config::Config,
+    schema: &str,
+    table_definition: &TableDefinition,
+    position: usize,
+) -> Result<Option<Row>, String> {
+    let leaf = read_leaf(config, schema, table_definition).await?;
+    decode_leaf_row(&leaf, table_definition, position)
+}
+
+/// Evaluate an expression in the context of a single row.
+pub fn evaluate_expression(
+    expression: &Expression,
+    table_definition: &TableDefinition,
+    row: &Row,
+) -> Result<DataInstance, ValidationError> {
+    match expression {
+        Expression::Atom(DataDefinition::Identifier(column_name)) => {
+            match table_definition.get_column(column_name) {
+                Some((column_index, _)) => Ok(row.0[column_index].clone()),
+                None => Err(ValidationError(format!(
+                    "Column `{}` does not exist in table `{}`",
+                    column_name, table_definition.name
+                ))),
+            }
+        }
+        Expression::Atom(DataDefinition::QualifiedIdentifier { .. }) => {
+            unreachable!("Qualified column references are resolved before evaluation")
+        }
+        Expression::Atom(DataDefinition::Const(value)) => Ok(value.clone()),
+        Expression::Atom(DataDefinition::FunctionCall(function)) => {
+            Ok(DataInstance::Direct(function.call()))
+        }
+        Expression::Atom(DataDefinition::Parameter(number)) => {
+            Err(DataDefinition::unbound_parameter_error(*number))
+        }
+        Expression::Equal(lhs, rhs) => {
+            let lhs = evaluate_expression(lhs, table_definition, row)?;
+            let rhs = evaluate_expression(rhs, table_definition, row)?;
+            // Comparisons involving NULL never hold
+            Ok(match (lhs.as_raw(), rhs.as_raw()) {
+                (Some(lhs), Some(rhs)) => DataInstance::Direct(DataInstanceRaw::Bool(
+                    lhs.compare(rhs) == Some(Ordering::Equal),
+                )),
+                _ => DataInstance::Null,
+            })
+        }
+        Expression::And(lhs, rhs) => {
+            let lhs = truth_value(&evaluate_expression(lhs, table_definition, row)?);
+            let rhs = truth_value(&evaluate_expression(rhs, table_definition, row)?);
+            // Unknown (NULL) operands only matter if neither operand is FALSE
+            Ok(match (lhs, rhs) {
+                (Some(false), _) | (_, Some(false)) => {
+                    DataInstance::Direct(DataInstanceRaw::Bool(false))
+                }
+                (Some(true), Some(true)) => DataInstance::Direct(DataInstanceRaw::Bool(true)),
+                _ => DataInstance::Null,
+            })
+        }
+        Expression::Or(lhs, rhs) => {
+            let lhs = truth_value(&evaluate_expression(lhs, table_definition, row)?);
+            let rhs = truth_value(&evaluate_expression(rhs, table_definition, row)?);
+            // Unknown (NULL) operands only matter if neither operand is TRUE
+            Ok(match (lhs, rhs) {
+                (Some(true), _) | (_, Some(true)) => {
+                    DataInstance::Direct(DataInstanceRaw::Bool(true))
+                }
+                (Some(false), Some(false)) => DataInstance::Direct(DataInstanceRaw::Bool(false)),
+                _ => DataInstance::Null,
+            })
+        }
+        Expression::IsNull(operand) => {
+            let operand = evaluate_expression(operand, table_definition, row)?;
+            Ok(DataInstance::Direct(DataInstanceRaw::Bool(
+                operand.as_raw().is_none(),
+            )))
+        }
+        Expression::IsNotNull(operand) => {
+            let operand = evaluate_expression(operand, table_definition, row)?;
+            Ok(DataInstance::Direct(DataInstanceRaw::Bool(
+                operand.as_raw().is_some(),
+            )))
+        }
+        Expression::In { column, values } => {
+            let lhs = evaluate_expression(column, table_definition, row)?;
+            let lhs = match lhs.as_raw() {
+                Some(lhs) => lhs,
+                None => return Ok(DataInstance::Null),
+            };
+            let column_type = referenced_column_type(column, table_definition);
+            let mut is_null_listed = false;
+            for value in values {
+                let value = match evaluate_expression(value, table_definition, row)?.as_raw() {
+                    Some(value) => value.clone(),
+                    None => {
+                        is_null_listed = true;
+                        continue;
+                    }
+                };
+                // Listed values must all be usable as values of the column
+                let value = coerce_to_column_type(value, column_type)
+                    .map_err(|error| ValidationError(format!("Problem in IN list: {}", error.0)))?;
+                if lhs.compare(&value) == Some(Ordering::Equal) {
+                    return Ok(DataInstance::Direct(DataInstanceRaw::Bool(true)));
+                }
+            }
+            // Like with equality, a NULL among the values means that a non-match is unknown
+            Ok(if is_null_listed {
+                DataInstance::Null
+            } else {
+                DataInstance::Direct(DataInstanceRaw::Bool(false))
+            })
+        }
+        Expression::Like { column, pattern } => {
+            let string_only_error = |data_type: DataTypeRaw| {
+                ValidationError(format!(
+                    "LIKE can only be used with STRING values, not {}",
+                    data_type
+                ))
+            };
+            if let Some(column_type) = referenced_column_type(column, table_definition) {
+                if column_type != DataTypeRaw::String {
+                    return Err(string_only_error(column_type));
+                }
+            }
+            let lhs = evaluate_expression(column, table_definition, row)?;
+            let pattern = evaluate_expression(pattern, table_definition, row)?;
+            match (lhs.as_raw(), pattern.as_raw()) {
+                (Some(DataInstanceRaw::String(lhs)), Some(DataInstanceRaw::String(pattern))) => {
+                    Ok(DataInstance::Direct(DataInstanceRaw::Bool(
+                        LikePattern::compile(pattern)?.matches(lhs),
+                    )))
+                }
+                (Some(DataInstanceRaw::String(_)), Some(pattern)) => Err(ValidationError(format!(
+                    "LIKE pattern must be a STRING, not {}",
+                    pattern.raw_type()
+                ))),
+                (Some(lhs), _) if !matches!(lhs, DataInstanceRaw::String(_)) => {
+                    Err(string_only_error(lhs.raw_type()))
+                }
+                _ => Ok(DataInstance::Null),
+            }
+        }
+        Expression::Between { column, low, high } => {
+            let column_type = referenced_column_type(column, table_definition);
+            if let Some(column_type) = column_type {
+                if !column_type.is_orderable() {
+                    return Err(ValidationError(format!(
+                        "BETWEEN cannot be used with {:?} values, as they have no meaningful order",
+                        column_type
+                    )));
+                }
+            }
+            let lhs = evaluate_expression(column, table_definition, row)?;
+            let low = evaluate_expression(low, table_definition, row)?;
+            let high = evaluate_expression(high, table_definition, row)?;
+            let (lhs, low, high) = match (lhs.as_raw(), low.as_raw(), high.as_raw()) {
+                (Some(lhs), Some(low), Some(high)) => (lhs, low.clone(), high.clone()),
+                _ => return Ok(DataInstance::Null),
+            };
+            let mut bound_orderings = Vec::with_capacity(2);
+            for bound in [low, high] {
+                let bound = coerce_to_column_type(bound, column_type).map_err(|error| {
+                    ValidationError(format!("Problem in BETWEEN bounds: {}", error.0))
+                })?;
+                match lhs.compare(&bound) {
+                    Some(ordering) => bound_orderings.push(ordering),
+                    None => {
+                        return Err(ValidationError(format!(
+                            "Problem in BETWEEN bounds: Value {:?} cannot be compared with {:?}",
+                            bound, lhs
+                        )))
+                    }
+                }
+            }
+            // Both ends are inclusive, and a reversed range (low > high) matches nothing
+            Ok(DataInstance::Direct(DataInstanceRaw::Bool(
+                bound_orderings[0] != Ordering::Less && bound_orderings[1] != Ordering::Greater,
+            )))
+        }
+        Expression::Add(..)
+        | Expression::Subtract(..)
+        | Expression::Multiply(..)
+        | Expression::Divide(..) => evaluate_arithmetic(expression, table_definition, row),
+        Expression::Cast { operand, data_type } => {
+            match evaluate_expression(operand, table_definition, row)?.as_raw() {
+                Some(value) => value
+                    .clone()
+                    .cast(*data_type)
+                    .map(DataInstance::Direct)
+                    .map_err(|error| {
+                        ValidationError(format!("{}, in `{}`", error.0, expression.column_name()))
+                    }),
+                // NULL is a value of every type
+                None => Ok(DataInstance::Null),
+            }
+        }
+    }
+}
+
+/// Compute an arithmetic operation on integers, with the result in the wider of the operands' types.
+/// Division truncates, as there are no fractional types. A NULL operand makes the result NULL.
+fn evaluate_arithmetic(
+    expression: &Expression,
+    table_definition: &TableDefinition,
+    row: &Row,
+) -> Result<DataInstance, ValidationError> {
+    let (_, lhs, rhs) = expression.as_arithmetic().unwrap();
+    let lhs = evaluate_expression(lhs, table_definition, row)?;
+    let rhs = evaluate_expression(rhs, table_definition, row)?;
+    let (lhs, rhs) = match (lhs.as_raw(), rhs.as_raw()) {
+        (Some(lhs), Some(rhs)) => (lhs, rhs),
+        _ => return Ok(DataInstance::Null),
+    };
+    let as_integer = |value: &DataInstanceRaw| {
+        value.as_u128().ok_or_else(|| {
+            ValidationError(format!(
+                "Arithmetic can only be used with integer values, not {:?}, in `{}`",
+                value,
+                expression.column_name()
+            ))
+        })
+    };
+    let (lhs_integer, rhs_integer) = (as_integer(lhs)?, as_integer(rhs)?);
+    let result_type = [lhs.raw_type(), rhs.raw_type()]
+        .into_iter()
+        .max_by_key(|raw_type| *raw_type.integer_range().unwrap().end())
+        .unwrap();
+    let result = match expression {
+        Expression::Add(..) => lhs_integer.checked_add(rhs_integer),
+        Expression::Subtract(..) => lhs_integer.checked_sub(rhs_integer),
+        Expression::Multiply(..) => lhs_integer.checked_mul(rhs_integer),
+        Expression::Divide(..) if rhs_integer == 0 => {
+            return Err(ValidationError(format!(
+                "Division by zero in `{}`",
+                expression.column_name()
+            )))
+        }
+        Expression::Divide(..) => lhs_integer.checked_div(rhs_integer),
+        _ => unreachable!("Only arithmetic operations are evaluated here"),
+    };
+    match result.filter(|result| result_type.integer_range().unwrap().contains(result)) {
+        Some(result) => Ok(DataInstance::Direct(
+            DataInstanceRaw::UInt128(result).coerce(result_type)?,
+        )),
+        None => Err(ValidationError(format!(
+            "Result of `{}` is out of range for {}",
+            expression.column_name(),
+            result_type
+        ))),
+    }
+}
+
+/// Truth value of a condition's outcome, or `None` if it's unknown, e.g. NULL or not a boolean at all.
+fn truth_value(outcome: &DataInstance) -> Option<bool> {
+    match outcome.as_raw() {
+        Some(DataInstanceRaw::Bool(value)) => Some(*value),
+        _ => None,
+    }
+}
+
+/// Type of the column the expression refers to, if it's a plain column reference.
+fn referenced_column_type(
+    expression: &Expression,
+    table_definition: &TableDefinition,
+) -> Option<DataTypeRaw> {
+    match expression {
+        Expression::Atom(DataDefinition::Identifier(column_name)) => table_definition
+            .get_column(column_name)
+            .map(|(_, column)| column.data_type.raw_type),
+        _ => None,
+    }
+}
+
+/// Coerce a value compared with a column to the column's type, if there's a column.
+fn coerce_to_column_type(
+    value: DataInstanceRaw,
+    column_type: Option<DataTypeRaw>,
+) -> Result<DataInstanceRaw, ValidationError> {
+    match column_type {
+        Some(column_type) => value.coerce(column_type),
+        None => Ok(value),
+    }
+}
+
+/// Whether the row satisfies the condition, i.e. the condition evaluates to TRUE.
+pub fn does_row_match(
+    condition: Option<&Expression>,
+    table_definition: &TableDefinition,
+    row: &Row,
+) -> Result<bool, ValidationError> {
+    match condition {
+        Some(condition) => Ok(matches!(
+            evaluate_expression(condition, table_definition, row)?.as_raw(),
+            Some(DataInstanceRaw::Bool(true))
+        )),
+        None => Ok(true),
+    }
+}
+
+/// Sort rows by the ORDER BY columns. NULLs are placed last, regardless of direction.
+pub fn sort_rows(
+    rows: &mut [Row],
+    order_by: &[OrderBy],
+    table_definition: &TableDefinition,
+) -> Result<(), ValidationError> {
+    let mut sort_keys: Vec<(usize, OrderDirection)> = Vec::with_capacity(order_by.len());
+    for OrderBy {
+        column_name,
+        direction,
+    } in order_by
+    {
+        match table_definition.get_column(column_name) {
+            Some((column_index, _)) => sort_keys.push((column_index, *direction)),
+            None => {
+                return Err(ValidationError(format!(
+                    "Column `{}` does not exist in table `{}`",
+                    column_name, table_definition.name
+                )))
+            }
+        }
+    }
+    rows.sort_by(|row_a, row_b| {
+        for (column_index, direction) in &sort_keys {
+            let ordering = match (
+                row_a.0[*column_index].as_raw(),
+                row_b.0[*column_index].as_raw(),
+            ) {
+                (Some(value_a), Some(value_b)) => {
+                    // Values of the same column are always comparable
+                    let ordering = value_a.compare(value_b).unwrap_or(Ordering::Equal);
+                    match direction {
+                        OrderDirection::Asc => ordering,
+                        OrderDirection::Desc => ordering.reverse(),
+                    }
+                }
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            };
+            if ordering != Ordering::Equal {
+                return ordering;
+            }
+        }
+        Ordering::Equal
+    });
+    Ok(())
+}
+
+/// Convert to `usize`, capping at `usize::MAX` on platforms where `u64` doesn't fit.
+fn saturate_to_usize(value: u64) -> usize {
+    usize::try_from(value).unwrap_or(usize::MAX)
+}
+
+/// Where a projected value comes from.
+#[derive(Debug, PartialEq, Eq)]
+pub enum ProjectedValue {
+    /// Column of the table, by position.
+    Column(usize),
+    /// Expression computed from the row.
+    Computed(Expression),
+}
+
+/// Output names and values of the projected columns.
+pub type Projection = (Vec<String>, Vec<ProjectedValue>);
+
+/// Resolve projected columns to their output names (aliases where given) and positions in the table,
+/// or expressions to compute. `*` expands to all columns in the order of the table definition,
+/// so results are always shaped the same way.
+fn resolve_projection(
+    select_items: &[SelectItem],
+    table_definition: &TableDefinition,
+) -> Result<Projection, ValidationError> {
+    let mut column_names: Vec<String> = Vec::new();
+    let mut projected_values: Vec<ProjectedValue> = Vec::new();
+    for SelectItem {
+        column: select_column,
+        alias,
+    } in select_items
+    {
+        match select_column {
+            SelectColumn::All => {
+                for (column_index, column) in table_definition.columns.iter().enumerate() {
+                    column_names.push(column.name.clone());
+                    projected_values.push(ProjectedValue::Column(column_index));
+                }
+            }
+            SelectColumn::Identifier(column_name) => {
+                match table_definition.get_column(column_name) {
+                    Some((column_index, column)) => {
+                        column_names.push(alias.clone().unwrap_or_else(|| column.name.clone()));
+                        projected_values.push(ProjectedValue::Column(column_index));
+                    }
+                    None => {
+                        return Err(ValidationError(format!(
+                            "Column `{}` does not exist in table `{}`",
+                            column_name, table_definition.name
+                        )))
+                    }
+                }
+            }
+            SelectColumn::Expression(expression) => {
+                column_names.push(alias.clone().unwrap_or_else(|| expression.column_name()));
+                projected_values.push(ProjectedValue::Computed(expression.clone()));
+            }
+            SelectColumn::Aggregate { .. } => {
+                unreachable!("Aggregates are resolved in `aggregate_rows`")
+            }
+            SelectColumn::QualifiedIdentifier { .. } => {
+                unreachable!(
+                    "Qualified column references are resolved in `resolve_column_references`"
+                )
+            }
+        }
+    }
+    Ok((column_names, projected_values))
+}
+
+/// Values of the row making up the projection.
+fn project_row(
+    row: &Row,
+    projected_values: &[ProjectedValue],
+    table_definition: &TableDefinition,
+) -> Result<Row, ValidationError> {
+    projected_values
+        .iter()
+        .map(|projected_value| match projected_value {
+            ProjectedValue::Column(column_index) => Ok(row.0[*column_index].clone()),
+            ProjectedValue::Computed(expression) => {
+                evaluate_expression(expression, table_definition, row)
+            }
+        })
+        .collect::<Result<_, _>>()
+        .map(Row)
+}
+
+/// Compute the value of an aggregate over the rows.
+/// NULLs are skipped, and if there are no values to aggregate, aggregates other than COUNT are NULL.
+fn compute_aggregate(
+    function: Aggregate,
+    argument: &AggregateArgument,
+    table_definition: &TableDefinition,
+    rows: &[Row],
+) -> Result<DataInstance, ValidationError> {
+    let (column_index, column) = match argument {
+        AggregateArgument::All if function == Aggregate::Count => {
+            return Ok(DataInstance::Direct(DataInstanceRaw::UInt64(
+                rows.len() as u64
+            )))
+        }
+        AggregateArgument::All => {
+            return Err(ValidationError(format!(
+                "{} cannot be computed over `*`",
+                function
+            )))
+        }
+        AggregateArgument::Identifier(column_name) => {
+            table_definition.get_column(column_name).ok_or_else(|| {
+                ValidationError(format!(
+                    "Column `{}` does not exist in table `{}`",
+                    column_name, table_definition.name
+                ))
+            })?
+        }
+    };
+    let is_numeric = matches!(
+        column.data_type.raw_type,
+        DataTypeRaw::UInt8
+            | DataTypeRaw::UInt16
+            | DataTypeRaw::UInt32
+            | DataTypeRaw::UInt64
+            | DataTypeRaw::UInt128
+    );
+    if matches!(function, Aggregate::Sum | Aggregate::Avg) && !is_numeric {
+        return Err(ValidationError(format!(
+            "{} can only be computed over numeric columns, but column `{}` is {}",
+            function, column.name, column.data_type.raw_type
+        )));
+    }
+    let values = rows.iter().filter_map(|row| row.0[column_index].as_raw());
+    let result = match function {
+        Aggregate::Count => Some(DataInstanceRaw::UInt64(values.count() as u64)),
+        Aggregate::Min => values
+            .min_by(|a, b| a.compare(b).unwrap_or(Ordering::Equal))
+            .cloned(),
+        Aggregate::Max => values
+            .max_by(|a, b| a.compare(b).unwrap_or(Ordering::Equal))
+            .cloned(),
+        Aggregate::Sum | Aggregate::Avg => {
+            // Integers are summed as `u128`, so that the sum of up to 2^64 `UINT64` values can't overflow
+            let mut sum: u128 = 0;
+            let mut count: u128 = 0;
+            for value in values {
+                let value = value.as_u128().expect("Numeric columns only hold integers");
+                sum = sum.checked_add(value).ok_or_else(|| {
+                    ValidationError(format!(
+                        "{} of column `{}` overflowed",
+                        function, column.name
+                    ))
+                })?;
+                count += 1;
+            }
+            match (function, count) {
+                (_, 0) => None,
+                (Aggregate::Sum, _) => Some(DataInstanceRaw::UInt128(sum)),
+                // There's no floating-point type, so the average is truncated to the column's integer type
+                _ => Some(DataInstanceRaw::UInt128(sum / count).coerce(column.data_type.raw_type)?),
+            }
+        }
+    };
+    Ok(result.map_or(DataInstance::Null, DataInstance::Direct))
+}
+
+/// Output names (aliases where given) of the aggregates and grouping columns making up the result, making sure
+/// that nothing else is selected alongside them. Without GROUP BY there are no grouping columns, so only
+/// aggregates can be selected.
+fn resolve_aggregate_column_names(
+    select_items: &[SelectItem],
+    group_by: &[String],
+    table_definition: &TableDefinition,
+) -> Result<Vec<String>, ValidationError> {
+    let group_column_indexes = resolve_group_column_indexes(group_by, table_definition)?;
+    select_items
+        .iter()
+        .map(|SelectItem { column, alias }| {
+            let column_name = resolve_aggregate_column_name(
+                column,
+                group_by,
+                &group_column_indexes,
+                table_definition,
+            )?;
+            Ok(alias.clone().unwrap_or(column_name))
+        })
+        .collect()
+}
+
+/// Name of an aggregate or grouping column, making sure that it's one of them.
+fn resolve_aggregate_column_name(
+    select_column: &SelectColumn,
+    group_by: &[String],
+    group_column_indexes: &[usize],
+    table_definition: &TableDefinition,
+) -> Result<String, ValidationError> {
+    match select_column {
+        SelectColumn::Aggregate { function, argument } => {
+            Ok(format!("{}({})", function.name(), argument))
+        }
+        SelectColumn::All if group_by.is_empty() => Err(ValidationError(
+            "`*` cannot be selected alongside aggregates".into(),
+        )),
+        SelectColumn::All => Err(ValidationError(
+            "`*` cannot be selected with GROUP BY".into(),
+        )),
+        SelectColumn::Identifier(column_name) if group_by.is_empty() => {
+            Err(ValidationError(format!(
+                "Column `{}` of table `{}` cannot be selected alongside aggregates",
+                column_name, table_definition.name
+            )))
+        }
+        SelectColumn::QualifiedIdentifier { .. } => {
+            unreachable!("Qualified column references are resolved in `resolve_column_references`")
+        }
+        SelectColumn::Expression(expression) => Err(ValidationError(format!(
+            "Expression `{}` cannot be selected alongside aggregates or with GROUP BY",
+            expression.column_name()
+        ))),
+        SelectColumn::Identifier(column_name) => match table_definition.get_column(column_name) {
+            Some((column_index, column)) if group_column_indexes.contains(&column_index) => {
+                Ok(column.name.clone())
+            }
+            Some(_) => Err(ValidationError(format!(
+                "Column `{}` of table `{}` must be in GROUP BY or be aggregated",
+                column_name, table_definition.name
+            ))),
+            None => Err(ValidationError(format!(
+                "Column `{}` does not exist in table `{}`",
+                column_name, table_definition.name
+            ))),
+        },
+    }
+}
+
+/// Indexes of the GROUP BY columns in the table.
+fn resolve_group_column_indexes(
+    group_by: &[String],
+    table_definition: &TableDefinition,
+) -> Result<Vec<usize>, ValidationError> {
+    group_by
+        .iter()
+        .map(|column_name| {
+            table_definition
+                .get_column(column_name)
+                .map(|(column_index, _)| column_index)
+                .ok_or_else(|| {
+                    ValidationError(format!(
+                        "Column `{}` does not exist in table `{}`",
+                        column_name, table_definition.name
+                    ))
+                })
+        })
+        .collect()
+}
+
+/// Collapse the rows of a group into a single one made up of grouping column values and aggregates.
+fn aggregate_group(
+    select_items: &[SelectItem],
+    table_definition: &TableDefinition,
+    rows: &[Row],
+) -> Result<Row, ValidationError> {
+    let mut values: Vec<DataInstance> = Vec::with_capacity(select_items.len());
+    for SelectItem {
+        column: select_column,
+        ..
+    } in select_items
+    {
+        match select_column {
+            SelectColumn::Aggregate { function, argument } => values.push(compute_aggregate(
+                *function,
+                argument,
+                table_definition,
+                rows,
+            )?),
+            // Grouping columns are equal in all rows of the group, which always has at least one row
+            SelectColumn::Identifier(column_name) => {
+                let (column_index, _) = table_definition.get_column(column_name).unwrap();
+                values.push(rows[0].0[column_index].clone());
+            }
+            SelectColumn::All | SelectColumn::Expression(_) => {
+                unreachable!("`*` and expressions are rejected in `resolve_aggregate_column_names`")
+            }
+            SelectColumn::QualifiedIdentifier { .. } => {
+                unreachable!(
+                    "Qualified column references are resolved in `resolve_column_references`"
+                )
+            }
+        }
+    }
+    Ok(Row(values))
+}
+
+/// Collapse the rows into a single one made up of aggregates, or with GROUP BY into one per group.
+/// Groups are sorted by ORDER BY, which can only use grouping columns, and then by the grouping columns,
+/// so that their order is deterministic. NULLs make up a group of their own, placed last.
+fn aggregate_rows(
+    statement: &SelectStatement,
+    table_definition: &TableDefinition,
+    mut rows: Vec<Row>,
+) -> Result<(Vec<String>, Vec<Row>), ValidationError> {
+    let column_names =
+        resolve_aggregate_column_names(&statement.columns, &statement.group_by, table_definition)?;
+    if statement.group_by.is_empty() {
+        let row = aggregate_group(&statement.columns, table_definition, &rows)?;
+        return Ok((column_names, vec![row]));
+    }
+    let group_column_indexes = resolve_group_column_indexes(&statement.group_by, table_definition)?;
+    for OrderBy { column_name, .. } in &statement.order_by {
+        let is_grouping_column = table_definition
+            .get_column(column_name)
+            .is_some_and(|(column_index, _)| group_column_indexes.contains(&column_index));
+        if !is_grouping_column {
+            return Err(ValidationError(format!(
+                "Column `{}` of table `{}` must be in GROUP BY to be used in ORDER BY",
+                column_name, table_definition.name
+            )));
+        }
+    }
+    let group_key_order: Vec<OrderBy> = statement
+        .group_by
+        .iter()
+        .map(|column_name| OrderBy {
+            column_name: column_name.clone(),
+            direction: OrderDirection::Asc,
+        })
+        .collect();
+    sort_rows(&mut rows, &group_key_order, table_definition)?;
+    // The sort is stable and ORDER BY columns are part of the group key, so rows of a group stay adjacent
+    sort_rows(&mut rows, &statement.order_by, table_definition)?;
+    let is_same_group = |row_a: &Row, row_b: &Row| {
+        group_column_indexes
+            .iter()
+            .all(|column_index| row_a.0[*column_index] == row_b.0[*column_index])
+    };
+    let grouped_rows = rows
+        .chunk_by(is_same_group)
+        .map(|group_rows| aggregate_group(&statement.columns, table_definition, group_rows))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok((column_names, grouped_rows))
+}
+
+/// How a SELECT finds the rows matching its WHERE clause.
+#[derive(Debug, PartialEq, Eq)]
+pub enum RowAccess {
+    /// Looking up the PRIMARY KEY value in the index. `None` means that no row can match.
+    IndexLookup(Option<Vec<DataInstanceRaw>>),
+    /// Reading every row and checking it against the WHERE clause.
+    FullScan,
+}
+
+/// Decisions about executing a SELECT, made before any row is read.
+#[derive(Debug, PartialEq, Eq)]
+pub struct SelectPlan {
+    pub access: RowAccess,
+    /// Names and values of the projected columns, or `None` if the rows are aggregated (or grouped) instead.
+    pub projection: Option<Projection>,
+}
+
+pub fn plan_select(
+    statement: &SelectStatement,
+    table_definition: &TableDefinition,
+) -> Result<SelectPlan, ValidationError> {
+    let is_aggregating = !statement.group_by.is_empty()
+        || statement
+            .columns
+            .iter()
+            .any(|SelectItem { column, .. }| matches!(column, SelectColumn::Aggregate { .. }));
+    let projection = if is_aggregating {
+        None
+    } else {
+        Some(resolve_projection(&statement.columns, table_definition)?)
+    };
+    let access = match find_primary_key_lookup(statement.where_clause.as_ref(), table_definition) {
+        Some(primary_key_value) => RowAccess::IndexLookup(primary_key_value),
+        None => RowAccess::FullScan,
+    };
+    Ok(SelectPlan { access, projection })
+}
+
+pub async fn select(
+    config: &config::Config,
+    schema: &str,
+    table_definition: &TableDefinition,
+    mut statement: SelectStatement,
+    primary_key_index: &RwLock<PrimaryKeyIndex>,
+    // Rows of the table if they've already been read, e.g. to build the index, so that they aren't read again
+    rows_read: Option<Vec<Row>>,
+) -> Result<StatementOutcome, ExecutionError> {
+    resolve_column_references(&mut statement, &[table_definition])?;
+    let SelectPlan { access, projection } = plan_select(&statement, table_definition)?;
+    if let Some(cursor) = &statement.cursor {
+        let projection = validate_paging(&statement, projection)?;
+        let page_start = decode_cursor(cursor, table_definition)?;
+        let page_rows = match rows_read {
+            Some(rows) => select_page(
+                &statement,
+                table_definition,
+                page_start,
+                &primary_key_index.read(),
+                |position| Ok(rows.get(position).cloned()),
+            )?,
+            None => {
+                let leaf = read_leaf(config, schema, table_definition)
+                    .await
+                    .map_err(ExecutionError::Storage)?;
+                select_page(
+                    &statement,
+                    table_definition,
+                    page_start,
+                    &primary_key_index.read(),
+                    |position| decode_leaf_row(&leaf, table_definition, position),
+                )?
+            }
+        };
+        return finish_page(&statement, table_definition, projection, page_rows);
+    }
+    let matching_rows: Vec<Row> = match access {
+        // With the PRIMARY KEY looked up in the index, only the row found needs to be read
+        RowAccess::IndexLookup(primary_key_value) => {
+            let position = primary_key_value.and_then(|value| primary_key_index.read().get(&value));
+            match (position, rows_read) {
+                (Some(position), Some(rows)) => rows.into_iter().nth(position),
+                (Some(position), None) => read_row_at(config, schema, table_definition, position)
+                    .await
+                    .map_err(ExecutionError::Storage)?,
+                (None, _) => None,
+            }
+            .into_iter()
+            .collect()
+        }
+        RowAccess::FullScan => {
+            let rows = match rows_read {
+                Some(rows) => rows,
+                None => read_all_rows(config, schema, table_definition)
+                    .await
+                    .map_err(ExecutionError::Storage)?,
+            };
+            filter_rows(statement.where_clause.as_ref(), table_definition, rows)?
+        }
+    };
+    Ok(finish_select(
+        &statement,
+        table_definition,
+        projection,
+        matching_rows,
+    )?)
+}
+
+/// Execute a SELECT with a JOIN, reading from pairs of rows of the two tables that satisfy the JOIN condition.
+pub async fn select_join(
+    config: &config::Config,
+    schema: &str,
+    table_definition: &TableDefinition,
+    joined_table_definition: &TableDefinition,
+    mut statement: SelectStatement,
+) -> Result<StatementOutcome, ExecutionError> {
+    if statement.cursor.is_some() {
+        return Err(ValidationError(
+            "A cursor can't be used with JOIN, as joined rows have no PRIMARY KEY".into(),
+        )
+        .into());
+    }
+    let combined_table_definition =
+        resolve_join(&mut statement, table_definition, joined_table_definition)?;
+    let join = statement
+        .join
+        .as_ref()
+        .expect("A joined table means there's a JOIN");
+    let (left_index, right_index) = resolve_join_columns(
+        &join.condition,
+        table_definition,
+        joined_table_definition,
+        &combined_table_definition,
+    )?;
+    let SelectPlan { projection, .. } = plan_select(&statement, &combined_table_definition)?;
+    let left_rows = read_all_rows(config, schema, table_definition)
+        .await
+        .map_err(ExecutionError::Storage)?;
+    let right_rows = read_all_rows(config, schema, joined_table_definition)
+        .await
+        .map_err(ExecutionError::Storage)?;
+    let joined_rows = hash_join(left_rows, &right_rows, left_index, right_index);
+    let matching_rows = filter_rows(
+        statement.where_clause.as_ref(),
+        &combined_table_definition,
+        joined_rows,
+    )?;
+    Ok(finish_select(
+        &statement,
+        &combined_table_definition,
+        projection,
+        matching_rows,
+    )?)
+}
+
+/// Resolve column references of a SELECT with a JOIN against both tables, returning their combined definition.
+fn resolve_join(
+    statement: &mut SelectStatement,
+    table_definition: &TableDefinition,
+    joined_table_definition: &TableDefinition,
+) -> Result<TableDefinition, ValidationError> {
+    // Without table aliases, columns of a table joined with itself couldn't be told apart
+    if normalize_identifier(&table_definition.name)
+        == normalize_identifier(&joined_table_definition.name)
+    {
+        return Err(ValidationError(format!(
+            "Table `{}` can't be joined with itself",
+            table_definition.name
+        )));
+    }
+    resolve_column_references(statement, &[table_definition, joined_table_definition])?;
+    Ok(combine_tables(table_definition, joined_table_definition))
+}
+
+/// Rows satisfying the condition.
+fn filter_rows(
+    condition: Option<&Expression>,
+    table_definition: &TableDefinition,
+    rows: Vec<Row>,
+) -> Result<Vec<Row>, ValidationError> {
+    let mut matching_rows = Vec::new();
+    for row in rows {
+        if does_row_match(condition, table_definition, &row)? {
+            matching_rows.push(row);
+        }
+    }
+    Ok(matching_rows)
+}
+
+/// Turn rows matching the SELECT's conditions into its result: sorted and projected, or aggregated,
+/// then deduplicated if DISTINCT, and finally cut down to the OFFSET and LIMIT.
+fn finish_select(
+    statement: &SelectStatement,
+    table_definition: &TableDefinition,
+    projection: Option<Projection>,
+    mut matching_rows: Vec<Row>,
+) -> Result<StatementOutcome, ValidationError> {
+    let (column_names, rows) = match projection {
+        Some((column_names, projected_values)) => {
+            // Sorting happens before projection, as ORDER BY columns don't have to be projected
+            sort_rows(&mut matching_rows, &statement.order_by, table_definition)?;
+            let rows = matching_rows
+                .iter()
+                .map(|row| project_row(row, &projected_values, table_definition))
+                .collect::<Result<Vec<_>, _>>()?;
+            (column_names, rows)
+        }
+        None => aggregate_rows(statement, table_definition, matching_rows)?,
+    };
+    let rows = if statement.distinct {
+        deduplicate_rows(rows)
+    } else {
+        rows
+    };
+    let rows = rows
+        .into_iter()
+        .skip(statement.offset.map_or(0, saturate_to_usize))
+        .take(statement.limit.map_or(usize::MAX, saturate_to_usize))
+        .collect();
+    Ok(StatementOutcome::Rows(QueryResult { column_names, rows }))
+}
+
+/// Remove rows equal to an earlier one in every column, keeping the first of each in place.
+/// NULLs are equal to each other here, unlike in comparisons, so there's at most one row of all NULLs.
+fn deduplicate_rows(rows: Vec<Row>) -> Vec<Row> {
+    let mut seen_rows: HashSet<Vec<DataInstance>> = HashSet::with_capacity(rows.len());
+    rows.into_iter()
+        .filter(|row| seen_rows.insert(row.0.clone()))
+        .collect()
+}
+
+/// Make sure that the SELECT can be paged through with a cursor, returning its projection.
+/// Pages follow the PRIMARY KEY order, and the cursor marks where a page starts, which rules out
+/// ORDER BY and OFFSET, while aggregation returns a single row, so there's nothing to page through.
+fn validate_paging(
+    statement: &SelectStatement,
+    projection: Option<Projection>,
+) -> Result<Projection, ValidationError> {
+    if !statement.order_by.is_empty() {
+        return Err(ValidationError(
+            "A cursor can't be used with ORDER BY, as pages follow the PRIMARY KEY order".into(),
+        ));
+    }
+    if statement.distinct {
+        return Err(ValidationError(
+            "A cursor can't be used with DISTINCT, as duplicates of a row could be on other pages"
+                .into(),
+        ));
+    }
+    if statement.offset.is_some() {
+        return Err(ValidationError(
+            "A cursor can't be used with OFFSET, as the cursor itself marks where the page starts"
+                .into(),
+        ));
+    }
+    projection.ok_or_else(|| {
+        ValidationError(
+            "A cursor can't be used with aggregate functions or GROUP BY, as they collapse rows"
+                .into(),
+        )
+    })
+}
+
+/// Select matching rows that come after the PRIMARY KEY `page_start` (from the very first row if `None`),
+/// in PRIMARY KEY order, up to one row more than the LIMIT, which tells whether there's a next page.
+/// Rows are compared by key rather than counted, so rows inserted or deleted meanwhile don't shift pages.
+/// They're visited in the index's order, with `read_row` getting each by position, so that rows past the page
+/// aren't read. Keys with NULLs aren't indexed though, so if there are any, every row is read and sorted.
+fn select_page(
+    statement: &SelectStatement,
+    table_definition: &TableDefinition,
+    page_start: Option<Vec<DataInstance>>,
+    primary_key_index: &PrimaryKeyIndex,
+    read_row: impl Fn(usize) -> Result<Option<Row>, String>,
+) -> Result<Vec<Row>, ExecutionError> {
+    let read_indexed_row = |position: usize| {
+        read_row(position)
+            .map_err(ExecutionError::Storage)?
+            .ok_or_else(|| {
+                ExecutionError::Storage(format!(
+                    "Row at position {} of table {} is missing",
+                    position, table_definition.name
+                ))
+            })
+    };
+    let raw_page_start = match &page_start {
+        Some(page_start) => raw_primary_key(page_start).map(Some),
+        None => Some(None),
+    };
+    let positions: Box<dyn Iterator<Item = usize>> = match raw_page_start {
+        Some(raw_page_start) if !primary_key_index.has_unindexed_rows() => {
+            Box::new(primary_key_index.positions_after(raw_page_start.as_deref()))
+        }
+        _ => {
+            let mut keyed_positions = Vec::new();
+            for position in 0..primary_key_index.row_count() {
+                let primary_key = get_primary_key(&read_indexed_row(position)?, table_definition);
+                let is_after_page_start = page_start.as_ref().is_none_or(|page_start| {
+                    compare_primary_keys(&primary_key, page_start) == Ordering::Greater
+                });
+                if is_after_page_start {
+                    keyed_positions.push((primary_key, position));
+                }
+            }
+            keyed_positions.sort_by(|(key_a, _), (key_b, _)| compare_primary_keys(key_a, key_b));
+            Box::new(keyed_positions.into_iter().map(|(_, position)| position))
+        }
+    };
+    let limit = statement.limit.map_or(usize::MAX, saturate_to_usize);
+    let mut page_rows = Vec::new();
+    for position in positions {
+        let row = read_indexed_row(position)?;
+        if does_row_match(statement.where_clause.as_ref(), table_definition, &row)? {
+            page_rows.push(row);
+            if page_rows.len() > limit {
+                break;
+            }
+        }
+    }
+    Ok(page_rows)
+}
+
+/// Turn rows selected by `select_page` into the page, with the cursor of the next page if there's a row past
+/// the LIMIT.
+fn finish_page(
+    statement: &SelectStatement,
+    table_definition: &TableDefinition,
+    (column_names, projected_values): Projection,
+    mut page_rows: Vec<Row>,
+) -> Result<StatementOutcome, ExecutionError> {
+    let limit = statement.limit.map_or(usize::MAX, saturate_to_usize);
+    let next_cursor = if page_rows.len() > limit {
+        page_rows.truncate(limit);
+        page_rows
+            .last()
+            .map(|row| encode_cursor(&get_primary_key(row, table_definition)))
+    } else {
+        None
+    };
+    let rows = page_rows
+        .iter()
+        .map(|row| project_row(row, &projected_values, table_definition))
+        .collect::<Result<_, _>>()?;
+    Ok(StatementOutcome::Page {
+        rows: QueryResult { column_names, rows },
+        next_cursor,
+    })
+}
+
+/// Describe the plan of the SELECT step by step, without executing it.
+pub fn explain_select(
+    mut statement: SelectStatement,
+    table_definition: &TableDefinition,
+    joined_table_definition: Option<&TableDefinition>,
+) -> Result<QueryResult, ValidationError> {
+    let combined_table_definition = match joined_table_definition {
+        Some(joined_table_definition) => Some(resolve_join(
+            &mut statement,
+            table_definition,
+            joined_table_definition,
+        )?),
+        None => {
+            resolve_column_references(&mut statement, &[table_definition])?;
+            None
+        }
+    };
+    let statement = &statement;
+    let SelectPlan { access, projection } = plan_select(
+        statement,
+        combined_table_definition
+            .as_ref()
+            .unwrap_or(table_definition),
+    )?;
+    let mut steps: Vec<(&str, String)> = Vec::new();
+    steps.push((
+        "access",
+        match access {
+            RowAccess::IndexLookup(_) => format!(
+                "index lookup on PRIMARY KEY ({}) of table `{}`",
+                table_definition
+                    .get_primary_key_columns()
+                    .iter()
+                    .map(|column| format!("`{}`", column.name))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                table_definition.name
+            ),
+            RowAccess::FullScan => format!("full scan of table `{}`", table_definition.name),
+        },
+    ));
+    if let (Some(join), Some(joined_table_definition)) = (&statement.join, joined_table_definition)
+    {
+        steps.push((
+            "join",
+            format!(
+                "hash join with table `{}` on {}",
+                joined_table_definition.name, join.condition
+            ),
+        ));
+    }
+    if let Some(where_clause) = &statement.where_clause {
+        steps.push(("filter", where_clause.to_string()));
+    }
+    if !statement.group_by.is_empty() {
+        steps.push((
+            "group",
+            statement
+                .group_by
+                .iter()
+                .map(|column_name| format!("`{}`", column_name))
+                .collect::<Vec<_>>()
+                .join(", "),
+        ));
+    }
+    if !statement.order_by.is_empty() {
+        steps.push((
+            "order",
+            statement
+                .order_by
+                .iter()
+                .map(|order_by| {
+                    format!(
+                        "`{}` {}",
+                        order_by.column_name,
+                        match order_by.direction {
+                            OrderDirection::Asc => "ASC",
+                            OrderDirection::Desc => "DESC",
+                        }
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join(", "),
+        ));
+    }
+    let (output_step, output_columns) = match projection {
+        Some((column_names, _)) => (
+            "projection",
+            column_names
+                .iter()
+                .map(|column_name| format!("`{}`", column_name))
+                .collect(),
+        ),
+        None => (
+            "aggregation",
+            resolve_aggregate_column_names(
+                &statement.columns,
+                &statement.group_by,
+                table_definition,
+            )?,
+        ),
+    };
+    steps.push((output_step, output_columns.join(", ")));
+    if statement.distinct {
+        steps.push(("distinct", output_columns.join(", ")));
+    }
+    if let Some(offset) = statement.offset {
+        steps.push(("offset", offset.to_string()));
+    }
+    if let Some(limit) = statement.limit {
+        steps.push(("limit", limit.to_string()));
+    }
+    Ok(QueryResult {
+        column_names: vec!["step".to_string(), "detail".to_string()],
+        rows: steps
+            .into_iter()
+            .map(|(step, detail)| {
+                Row(vec![
+                    DataInstance::Direct(DataInstanceRaw::String(step.to_string())),
+                    DataInstance::Direct(DataInstanceRaw::String(detail)),
+                ])
+            })
+            .collect(),
+    })
+}
+
+/// List names of the tables, sorted alphabetically.
+pub fn show_tables(tables: &[TableDefinition]) -> QueryResult {
+    let mut table_names: Vec<&str> = tables.iter().map(|table| table.name.as_str()).collect();
+    table_names.sort_unstable_by_key(|table_name| normalize_identifier(table_name));
+    QueryResult {
+        column_names: vec!["table_name".to_string()],
+        rows: table_names
+            .into_iter()
+            .map(|table_name| {
+                Row(vec![DataInstance::Direct(DataInstanceRaw::String(
+                    table_name.to_string(),
+                ))])
+            })
+            .collect(),
+    }
+}
+
+/// List columns of the table along with their properties, in the table's column order.
+pub fn describe(table_definition: &TableDefinition) -> QueryResult {
+    QueryResult {
+        column_names: vec![
+            "column_name".to_string(),
+            "data_type".to_string(),
+            "is_nullable".to_string(),
+            "is_primary_key".to_string(),
+            "comment".to_string(),
+        ],
+        rows: table_definition
+            .columns
+            .iter()
+            .map(|column| {
+                Row(vec![
+                    DataInstance::Direct(DataInstanceRaw::String(column.name.clone())),
+                    DataInstance::Direct(DataInstanceRaw::String(
+                        column.data_type.raw_type.to_string(),
+                    )),
+                    DataInstance::Direct(DataInstanceRaw::Bool(column.data_type.is_nullable)),
+                    DataInstance::Direct(DataInstanceRaw::Bool(column.primary_key)),
+                    match &column.comment {
+                        Some(comment) => {
+                            DataInstance::Nullable(DataInstanceRaw::String(comment.clone()))
+                        }
+                        None => DataInstance::Null,
+                    },
+                ])
+            })
+            .collect(),
+    }
+}
+
 #[cfg(test)]
 mod read_tests {
     use crate::{
@@ -51,7 +1309,9 @@ mod read_tests {
                         is_nullable: false,
                     },
                     primary_key: true,
+                    unique: false,
                     default: None,
+                    comment: None,
                 },
                 ColumnDefinition {
                     name: "table_name".into(),
@@ -60,10 +1320,13 @@ mod read_tests {
                         is_nullable: false,
                     },
                     primary_key: false,
+                    unique: false,
                     default: None,
+                    comment: None,
                 },
             ],
         )
+        .unwrap()
     }
 
     #[tokio::test]
@@ -1,32 +1,43 @@
+use parking_lot::RwLock;
+use std::cmp::Ordering;
 use std::io;
 
 use crate::config;
+use crate::constructs::components::{
+    normalize_identifier, ColumnDefinition, DataDefinition, DataInstance, DataInstanceRaw,
+    Expression,
+};
+use crate::constructs::statements::{
+    AlterTableAction, AlterTableStatement, Assignment, CreateTableStatement, DeleteStatement,
+    InsertStatement, UpdateStatement,
+};
+use crate::sql::ValidationError;
 use crate::storage::filesystem::{
     does_table_file_exist, seek_read_decode_page, seek_write_page, write_table_file,
 };
-use crate::storage::paging::{construct_blank_table, Page};
-use crate::storage::system::SYSTEM_SCHEMA_NAME;
+use crate::storage::paging::{construct_blank_table, does_leaf_fit, Page};
+use crate::storage::wal::{WalRecord, WriteAheadLog};
 use crate::{constructs::components::TableDefinition, storage::Row};
 use tracing::*;
 
+use super::index::{primary_key_column_indexes, primary_key_of, raw_primary_key, PrimaryKeyIndex};
+use super::join::{resolve_data_definition_reference, resolve_expression_references};
+use super::read::{does_row_match, evaluate_expression, read_all_rows};
+use super::{ExecutionError, StatementOutcome};
+
 pub async fn ensure_table_file_exists(
     config: &config::Config,
+    schema: &str,
     table_definition: &TableDefinition,
 ) -> io::Result<()> {
-    if !does_table_file_exist(&config, SYSTEM_SCHEMA_NAME, &table_definition.name).await {
+    if !does_table_file_exist(config, schema, &table_definition.name).await {
         let blank_table_blob = construct_blank_table();
-        match write_table_file(
-            &config,
-            SYSTEM_SCHEMA_NAME,
-            &table_definition.name,
-            blank_table_blob,
-        )
-        .await
-        {
-            Ok(_) => debug!("Initialized system table `{}`", table_definition.name),
+        match write_table_file(config, schema, &table_definition.name, blank_table_blob).await {
+            Ok(_) => debug!("Initialized table `{}.{}`", schema, table_definition.name),
             Err(error) => {
                 trace!(
-                    "Failed to initialize system table `{}`: {}",
+                    "Failed to initialize table `{}.{}`: {}",
+                    schema,
                     table_definition.name,
                     error
                 );
@@ -57,7 +68,16 @@ pub async fn b_tree_insert(
     let mut b_tree_root_page =
         seek_read_decode_page(config, schema, table_definition, b_tree_root_page_index).await?;
     match b_tree_root_page {
-        Page::BTreeLeaf { ref mut rows, .. } => rows.push(row),
+        Page::BTreeLeaf { ref mut rows, .. } => {
+            rows.push(row);
+            // TODO: Split the leaf instead of refusing the row
+            if !does_leaf_fit(rows) {
+                return Err(format!(
+                    "Table {}.{} is full, as B+ tree leaf splitting is not supported yet",
+                    schema, table_definition.name
+                ));
+            }
+        }
         _ => panic!(
             "Found a non-B-tree page at `b_tree_root_page_index` of table {}.{}'s data file",
             schema, table_definition.name
@@ -71,6 +91,498 @@ pub async fn b_tree_insert(
         b_tree_root_page.into(),
     )
     .await
-    .unwrap();
+    .map_err(|error| error.to_string())?;
+    Ok(())
+}
+
+/// Replace all rows of the table with the provided ones.
+pub async fn rewrite_all_rows(
+    config: &config::Config,
+    schema: &str,
+    table_definition: &TableDefinition,
+    rows: Vec<Row>,
+) -> Result<(), String> {
+    let b_tree_root_page_index =
+        match seek_read_decode_page(config, schema, table_definition, 0).await? {
+            Page::Meta {
+                b_tree_root_page_index,
+                ..
+            } => b_tree_root_page_index,
+            _ => panic!(
+                "Found a non-meta page at the beginning of table {}.{}'s data file ",
+                schema, table_definition.name
+            ),
+        };
+    // TODO: Split the leaf instead of refusing the rows
+    if !does_leaf_fit(&rows) {
+        return Err(format!(
+            "Table {}.{} is full, as B+ tree leaf splitting is not supported yet",
+            schema, table_definition.name
+        ));
+    }
+    seek_write_page(
+        config,
+        schema,
+        &table_definition.name,
+        b_tree_root_page_index,
+        Page::BTreeLeaf {
+            next_leaf_page_index: 0,
+            rows,
+        }
+        .into(),
+    )
+    .await
+    .map_err(|error| error.to_string())
+}
+
+/// Compute the column's DEFAULT value (NULL if there's no DEFAULT), coerced to the column's type.
+pub fn evaluate_default(column: &ColumnDefinition) -> Result<DataInstance, ValidationError> {
+    let value = match column.coerced_default()? {
+        None => DataInstance::Null,
+        Some(DataDefinition::Const(value)) => return Ok(value),
+        Some(DataDefinition::FunctionCall(function)) => DataInstance::Direct(function.call()),
+        Some(DataDefinition::Identifier(_))
+        | Some(DataDefinition::QualifiedIdentifier { .. })
+        | Some(DataDefinition::Parameter(_)) => {
+            unreachable!("Identifier and parameter defaults are rejected")
+        }
+    };
+    value.coerce(&column.data_type).map_err(|error| {
+        ValidationError(format!(
+            "Problem at DEFAULT of column `{}`: {}",
+            column.name, error.0
+        ))
+    })
+}
+
+/// Make sure that the column's DEFAULT is valid, storing a constant one already coerced to the column's type.
+fn with_coerced_default(mut column: ColumnDefinition) -> Result<ColumnDefinition, ValidationError> {
+    column.default = column.coerced_default()?;
+    Ok(column)
+}
+
+pub async fn create_table(
+    config: &config::Config,
+    schema: &str,
+    tables: &mut Vec<TableDefinition>,
+    statement: CreateTableStatement,
+) -> Result<StatementOutcome, ExecutionError> {
+    // Statements usually come validated, but storage must never get a table without a PRIMARY KEY
+    statement.table.validate_primary_key()?;
+    let normalized_table_name = normalize_identifier(&statement.table.name);
+    if tables
+        .iter()
+        .any(|table| normalize_identifier(&table.name) == normalized_table_name)
+    {
+        if statement.if_not_exists {
+            return Ok(StatementOutcome::TableCreated(false));
+        }
+        return Err(
+            ValidationError(format!("Table `{}` already exists", statement.table.name)).into(),
+        );
+    }
+    let mut table_definition = statement.table;
+    table_definition.columns = table_definition
+        .columns
+        .into_iter()
+        .map(with_coerced_default)
+        .collect::<Result<_, _>>()?;
+    // Any data left behind by a table of the same name is stale, so we start from a blank slate
+    write_table_file(
+        config,
+        schema,
+        &table_definition.name,
+        construct_blank_table(),
+    )
+    .await
+    .map_err(|error| ExecutionError::Storage(error.to_string()))?;
+    tables.push(table_definition);
+    Ok(StatementOutcome::TableCreated(true))
+}
+
+pub async fn alter_table(
+    config: &config::Config,
+    schema: &str,
+    tables: &mut [TableDefinition],
+    statement: AlterTableStatement,
+) -> Result<StatementOutcome, ExecutionError> {
+    let normalized_table_name = normalize_identifier(&statement.table_name);
+    let table_index = tables
+        .iter()
+        .position(|table| normalize_identifier(&table.name) == normalized_table_name)
+        .ok_or_else(|| {
+            ValidationError(format!("Table `{}` does not exist", statement.table_name))
+        })?;
+    let table_definition = &tables[table_index];
+    let mut rows = read_all_rows(config, schema, table_definition)
+        .await
+        .map_err(ExecutionError::Storage)?;
+    let mut altered_table_definition = table_definition.clone();
+    match statement.action {
+        AlterTableAction::AddColumn(column) => {
+            let column = with_coerced_default(column)?;
+            if table_definition.get_column(&column.name).is_some() {
+                return Err(ValidationError(format!(
+                    "Column `{}` already exists in table `{}`",
+                    column.name, table_definition.name
+                ))
+                .into());
+            }
+            // Existing rows get the DEFAULT value, or NULL if there's no DEFAULT
+            for row in &mut rows {
+                row.0.push(evaluate_default(&column)?);
+            }
+            altered_table_definition.columns.push(column);
+        }
+        AlterTableAction::DropColumn(column_name) => {
+            let (column_index, column) =
+                table_definition.get_column(&column_name).ok_or_else(|| {
+                    ValidationError(format!(
+                        "Column `{}` does not exist in table `{}`",
+                        column_name, table_definition.name
+                    ))
+                })?;
+            if column.primary_key {
+                return Err(ValidationError(format!(
+                    "Column `{}` cannot be dropped, as it's the PRIMARY KEY of table `{}`",
+                    column_name, table_definition.name
+                ))
+                .into());
+            }
+            for row in &mut rows {
+                row.0.remove(column_index);
+            }
+            altered_table_definition.columns.remove(column_index);
+        }
+    }
+    rewrite_all_rows(config, schema, &altered_table_definition, rows)
+        .await
+        .map_err(ExecutionError::Storage)?;
+    tables[table_index] = altered_table_definition;
+    Ok(StatementOutcome::Done)
+}
+
+pub async fn truncate_table(
+    config: &config::Config,
+    schema: &str,
+    table_definition: &TableDefinition,
+) -> Result<StatementOutcome, ExecutionError> {
+    rewrite_all_rows(config, schema, table_definition, Vec::new())
+        .await
+        .map_err(ExecutionError::Storage)?;
+    Ok(StatementOutcome::Done)
+}
+
+/// Delete rows matching the WHERE conditions, or all rows if there's no WHERE.
+/// Rows kept shift to fill the gaps, so the PRIMARY KEY index is rebuilt from them once they're rewritten.
+pub async fn delete(
+    config: &config::Config,
+    schema: &str,
+    table_definition: &TableDefinition,
+    mut statement: DeleteStatement,
+    primary_key_index: &mut PrimaryKeyIndex,
+) -> Result<StatementOutcome, ExecutionError> {
+    if let Some(where_clause) = &mut statement.where_clause {
+        resolve_expression_references(where_clause, &[table_definition])?;
+    }
+    let rows = read_all_rows(config, schema, table_definition)
+        .await
+        .map_err(ExecutionError::Storage)?;
+    let row_count = rows.len();
+    let mut kept_rows = Vec::with_capacity(row_count);
+    for row in rows {
+        if !does_row_match(statement.where_clause.as_ref(), table_definition, &row)? {
+            kept_rows.push(row);
+        }
+    }
+    let deleted_count = row_count - kept_rows.len();
+    let kept_rows_index = PrimaryKeyIndex::build(table_definition, &kept_rows);
+    rewrite_all_rows(config, schema, table_definition, kept_rows)
+        .await
+        .map_err(ExecutionError::Storage)?;
+    *primary_key_index = kept_rows_index;
+    Ok(StatementOutcome::RowsAffected(deleted_count))
+}
+
+/// Set new values in rows matching the WHERE conditions, or in all rows if there's no WHERE.
+/// PRIMARY KEY values may change, so the PRIMARY KEY index is rebuilt from the rows once they're rewritten.
+pub async fn update(
+    config: &config::Config,
+    schema: &str,
+    table_definition: &TableDefinition,
+    mut statement: UpdateStatement,
+    primary_key_index: &mut PrimaryKeyIndex,
+) -> Result<StatementOutcome, ExecutionError> {
+    if let Some(where_clause) = &mut statement.where_clause {
+        resolve_expression_references(where_clause, &[table_definition])?;
+    }
+    let mut assigned_columns: Vec<(usize, &ColumnDefinition, Expression)> =
+        Vec::with_capacity(statement.assignments.len());
+    for Assignment {
+        column_name,
+        mut value,
+    } in statement.assignments
+    {
+        resolve_data_definition_reference(&mut value, &[table_definition])?;
+        let (column_index, column) =
+            table_definition.get_column(&column_name).ok_or_else(|| {
+                ValidationError(format!(
+                    "Column `{}` does not exist in table `{}`",
+                    column_name, table_definition.name
+                ))
+            })?;
+        if assigned_columns
+            .iter()
+            .any(|(assigned_column_index, ..)| *assigned_column_index == column_index)
+        {
+            return Err(ValidationError(format!(
+                "Column `{}` is assigned more than once",
+                column_name
+            ))
+            .into());
+        }
+        assigned_columns.push((column_index, column, Expression::Atom(value)));
+    }
+    let mut rows = read_all_rows(config, schema, table_definition)
+        .await
+        .map_err(ExecutionError::Storage)?;
+    let mut updated_row_indexes: Vec<usize> = Vec::new();
+    for (row_index, row) in rows.iter_mut().enumerate() {
+        if !does_row_match(statement.where_clause.as_ref(), table_definition, row)? {
+            continue;
+        }
+        // All values are computed from the row as it was before the update
+        let mut new_values = Vec::with_capacity(assigned_columns.len());
+        for (column_index, column, value) in &assigned_columns {
+            let value = evaluate_expression(value, table_definition, row)?
+                .coerce(&column.data_type)
+                .map_err(|error| {
+                    ValidationError(format!("Problem at column `{}`: {}", column.name, error.0))
+                })?;
+            new_values.push((*column_index, value));
+        }
+        for (column_index, value) in new_values {
+            row.0[column_index] = value;
+        }
+        updated_row_indexes.push(row_index);
+    }
+    for &row_index in &updated_row_indexes {
+        let (rows_before, rows_from) = rows.split_at(row_index);
+        let (row, rows_after) = rows_from.split_first().unwrap();
+        check_uniqueness(table_definition, rows_before, row)?;
+        check_uniqueness(table_definition, rows_after, row)?;
+    }
+    let updated_rows_index = PrimaryKeyIndex::build(table_definition, &rows);
+    rewrite_all_rows(config, schema, table_definition, rows)
+        .await
+        .map_err(ExecutionError::Storage)?;
+    *primary_key_index = updated_rows_index;
+    Ok(StatementOutcome::RowsAffected(updated_row_indexes.len()))
+}
+
+/// Insert a row recovered from the write-ahead log, unless a row with the same PRIMARY KEY value is already there
+/// according to the index of the table's rows, which is kept up to date. Returns whether the row was inserted.
+pub async fn replay_insert(
+    config: &config::Config,
+    schema: &str,
+    table_definition: &TableDefinition,
+    row: Row,
+    primary_key_index: &mut PrimaryKeyIndex,
+) -> Result<bool, String> {
+    let primary_key = primary_key_of(&primary_key_column_indexes(table_definition), &row);
+    let is_already_there = raw_primary_key(&primary_key)
+        .is_some_and(|raw_primary_key| primary_key_index.get(&raw_primary_key).is_some());
+    if is_already_there {
+        return Ok(false);
+    }
+    b_tree_insert(config, schema, table_definition, row).await?;
+    primary_key_index.push(&primary_key);
+    Ok(true)
+}
+
+/// Arrange INSERT values into a row following the table's column order, coercing each value to its column's type.
+pub fn construct_row(
+    table_definition: &TableDefinition,
+    column_names: &[String],
+    values: Vec<DataInstance>,
+) -> Result<Row, ValidationError> {
+    if column_names.len() != values.len() {
+        return Err(ValidationError(format!(
+            "There are {} columns specified, but {} values",
+            column_names.len(),
+            values.len()
+        )));
+    }
+    let mut row_values: Vec<Option<DataInstance>> = vec![None; table_definition.columns.len()];
+    for (column_name, value) in column_names.iter().zip(values) {
+        let (column_index, column) = table_definition.get_column(column_name).ok_or_else(|| {
+            ValidationError(format!(
+                "Column `{}` does not exist in table `{}`",
+                column_name, table_definition.name
+            ))
+        })?;
+        if row_values[column_index].is_some() {
+            return Err(ValidationError(format!(
+                "Column `{}` is specified more than once",
+                column_name
+            )));
+        }
+        let value = value.coerce(&column.data_type).map_err(|error| {
+            ValidationError(format!("Problem at column `{}`: {}", column_name, error.0))
+        })?;
+        row_values[column_index] = Some(value);
+    }
+    table_definition
+        .columns
+        .iter()
+        .zip(row_values)
+        .map(|(column, value)| match value {
+            Some(value) => Ok(value),
+            None if column.default.is_some() || column.data_type.is_nullable => {
+                evaluate_default(column)
+            }
+            None => Err(ValidationError(format!(
+                "Column `{}` is not nullable, so a value must be specified for it",
+                column.name
+            ))),
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map(Row)
+}
+
+fn duplicate_primary_key_error(
+    table_definition: &TableDefinition,
+    primary_key: &[DataInstanceRaw],
+) -> ValidationError {
+    match (
+        primary_key,
+        table_definition.get_primary_key_columns().as_slice(),
+    ) {
+        ([value], [column]) => ValidationError(format!(
+            "Value {} already exists in PRIMARY KEY column `{}`",
+            value, column.name
+        )),
+        (primary_key, columns) => ValidationError(format!(
+            "Values ({}) already exist in PRIMARY KEY columns ({})",
+            primary_key
+                .iter()
+                .map(|value| value.to_string())
+                .collect::<Vec<_>>()
+                .join(", "),
+            columns
+                .iter()
+                .map(|column| format!("`{}`", column.name))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )),
+    }
+}
+
+/// Make sure that the row doesn't repeat the PRIMARY KEY or a value of any UNIQUE column.
+/// NULLs may repeat, as in standard SQL.
+fn check_uniqueness(
+    table_definition: &TableDefinition,
+    existing_rows: &[Row],
+    row: &Row,
+) -> Result<(), ValidationError> {
+    let primary_key_indexes = primary_key_column_indexes(table_definition);
+    if let Some(primary_key) = raw_primary_key(&primary_key_of(&primary_key_indexes, row)) {
+        let is_duplicate = existing_rows.iter().any(|existing_row| {
+            raw_primary_key(&primary_key_of(&primary_key_indexes, existing_row)).is_some_and(
+                |existing_primary_key| {
+                    existing_primary_key
+                        .iter()
+                        .zip(&primary_key)
+                        .all(|(existing_value, value)| {
+                            existing_value.compare(value) == Some(Ordering::Equal)
+                        })
+                },
+            )
+        });
+        if is_duplicate {
+            return Err(duplicate_primary_key_error(table_definition, &primary_key));
+        }
+    }
+    for (column_index, column) in table_definition.columns.iter().enumerate() {
+        if !column.unique {
+            continue;
+        }
+        let value = match row.0[column_index].as_raw() {
+            Some(value) => value,
+            None => continue,
+        };
+        let is_duplicate = existing_rows.iter().any(|existing_row| {
+            existing_row.0[column_index]
+                .as_raw()
+                .is_some_and(|existing_value| {
+                    existing_value.compare(value) == Some(Ordering::Equal)
+                })
+        });
+        if is_duplicate {
+            return Err(ValidationError(format!(
+                "Value {} already exists in UNIQUE column `{}`",
+                value, column.name
+            )));
+        }
+    }
     Ok(())
 }
+
+pub async fn insert(
+    config: &config::Config,
+    schema: &str,
+    table_definition: &TableDefinition,
+    statement: InsertStatement,
+    // Shared with reads, but only changed with the table's lock held exclusively
+    primary_key_index: &RwLock<PrimaryKeyIndex>,
+    wal: Option<&mut WriteAheadLog>,
+) -> Result<StatementOutcome, ExecutionError> {
+    let values = statement
+        .values
+        .into_iter()
+        .map(|value| match value {
+            DataDefinition::Const(value) => Ok(value),
+            DataDefinition::Parameter(number) => {
+                Err(DataDefinition::unbound_parameter_error(number))
+            }
+            _ => unreachable!("INSERT values are constants or parameters"),
+        })
+        .collect::<Result<_, _>>()?;
+    let row = construct_row(table_definition, &statement.column_names, values)?;
+    let primary_key = primary_key_of(&primary_key_column_indexes(table_definition), &row);
+    if let Some(raw_primary_key) = raw_primary_key(&primary_key) {
+        if primary_key_index.read().get(&raw_primary_key).is_some() {
+            return Err(duplicate_primary_key_error(table_definition, &raw_primary_key).into());
+        }
+    }
+    if table_definition.columns.iter().any(|column| column.unique) {
+        let existing_rows = read_all_rows(config, schema, table_definition)
+            .await
+            .map_err(ExecutionError::Storage)?;
+        check_uniqueness(table_definition, &existing_rows, &row)?;
+    }
+    // The row is logged before it's written, so that whatever is written can be recovered. If the write fails
+    // (e.g. because the table is full), the record is discarded, so that there's nothing to replay
+    let mut wal = wal;
+    let wal_length_before = match wal.as_deref_mut() {
+        Some(wal) => Some(
+            wal.append(&WalRecord::new(&table_definition.name, &row))
+                .await
+                .map_err(|error| ExecutionError::Storage(error.to_string()))?,
+        ),
+        None => None,
+    };
+    if let Err(write_error) = b_tree_insert(config, schema, table_definition, row).await {
+        if let (Some(wal), Some(wal_length_before)) = (wal, wal_length_before) {
+            wal.discard_from(wal_length_before).await.map_err(|error| {
+                ExecutionError::Storage(format!(
+                    "{}, and its write-ahead log record couldn't be discarded: {}",
+                    write_error, error
+                ))
+            })?;
+        }
+        return Err(ExecutionError::Storage(write_error));
+    }
+    primary_key_index.write().push(&primary_key);
+    Ok(StatementOutcome::RowsAffected(1))
+}
@@ -1,12 +1,76 @@
+//! Emdrive is a database management system for fast similarity search within metric spaces.
+//!
+//! Besides running the server, this crate can be used as a library – for its SQL parser, or for an
+//! embedded [`Database`] executing SQL in-process.
+//!
+//! For parsing, [`parse_statement`] turns a statement string into a [`Statement`] AST, whose nodes
+//! live in [`constructs`]:
+//!
+//! ```
+//! use emdrive::constructs::components::DataTypeRaw;
+//! use emdrive::{parse_statement, Statement};
+//!
+//! let statement =
+//!     parse_statement("CREATE TABLE users (id UINT64 PRIMARY KEY, name nullable(STRING))").unwrap();
+//! match statement {
+//!     Statement::CreateTable(create_table) => {
+//!         assert_eq!(create_table.table.name, "users");
+//!         let name_column = &create_table.table.columns[1];
+//!         assert_eq!(name_column.name, "name");
+//!         assert_eq!(name_column.data_type.raw_type, DataTypeRaw::String);
+//!         assert!(name_column.data_type.is_nullable);
+//!     }
+//!     _ => panic!("Expected a CREATE TABLE statement"),
+//! }
+//! ```
+//!
+//! Besides canonical type names, [`Dialect::Aliases`] accepts ANSI-style ones like `BIGINT` and `VARCHAR`
+//! – see [`parse_statement_in_dialect`].
+//!
+//! The token stream underlying parsing, e.g. for syntax highlighting, is available via [`tokenize`].
+//! For autocompletion, [`keywords`] and [`type_names`] list the words that SQL is made of.
+//!
+//! Errors are [`SyntaxError`] for statements that can't be parsed, and [`ValidationError`] for ones
+//! that can, but don't make sense (see [`constructs::components::Validatable`]). For editors,
+//! [`parse_statements_recovering`] keeps parsing past broken statements, collecting all errors.
+//!
+//! ## Forward compatibility
+//!
+//! The SQL dialect keeps growing, so these types are `#[non_exhaustive]` – matching on enums needs
+//! a wildcard arm, and structs can't be constructed with literals outside of this crate:
+//! - enums [`Statement`], [`TokenValue`], [`Keyword`], [`Delimiter`], [`Dialect`],
+//!   `DataTypeRaw`, `DataInstanceRaw`, `DataDefinition` and `Expression` from
+//!   [`constructs::components`], `Function` and `Aggregate` from [`constructs::functions`],
+//!   as well as `AggregateArgument`, `SelectColumn` and `AlterTableAction` from
+//!   [`constructs::statements`],
+//! - structs `ColumnDefinition` and `TableDefinition` from [`constructs::components`], and all the
+//!   `*Statement` structs from [`constructs::statements`].
+//!
+//! [`Token`], [`SyntaxError`], [`Position`], [`RecoveredStatements`] and [`ValidationError`] are exhaustive.
+
 pub mod config;
-mod constructs;
+pub mod constructs;
+pub mod database;
 mod executor;
+pub mod logging;
 pub mod server;
-mod sql;
+pub mod sql;
 pub mod storage;
 
-pub use config::Config;
-use std::io;
+pub use config::{Config, ConfigError};
+pub use database::{Database, EngineError};
+pub use executor::{ExecutionError, QueryResult, StatementOutcome};
+pub use sql::{
+    bind_parameters, is_keyword, keywords, parse_statement, parse_statement_in_dialect,
+    parse_statements, parse_statements_in_dialect, parse_statements_recovering,
+    parse_statements_recovering_in_dialect, parse_statements_with_limits, tokenize,
+    tokenize_collect_errors, tokenize_statement, tokenize_statements, type_names, Delimiter,
+    Dialect, Keyword, ParseLimits, Position, RecoveredStatements, Statement, SyntaxError, Token,
+    TokenValue, TrailingCommas, ValidationError,
+};
+use std::{env, io, path::Path};
+use tokio::runtime;
+use tokio::sync::oneshot;
 use tracing::*;
 
 pub struct Instance {
@@ -14,23 +78,110 @@ pub struct Instance {
 }
 
 impl Instance {
-    pub fn preload() -> Self {
-        Instance {
-            config: Config::from_env(),
-        }
+    /// Load launch configuration from the TOML file given as the first command-line argument (if any),
+    /// overridden by the environment.
+    pub fn preload() -> Result<Self, ConfigError> {
+        let config_path = env::args().nth(1);
+        Ok(Instance {
+            config: Config::load(config_path.as_deref().map(Path::new))?,
+        })
+    }
+
+    /// Launch configuration of the instance.
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Build the multi-threaded runtime to `run` the instance on, with the configured number of worker threads.
+    pub fn build_runtime(&self) -> Result<runtime::Runtime, io::Error> {
+        runtime_builder(&self.config).build()
     }
 
     pub async fn run(&self) -> Result<(), io::Error> {
         info!("⚙️ Launch configuration:\n{}", &self.config);
+        // Loading TLS setup first, so that a broken one fails startup instead of falling back to plaintext
+        let tls_acceptor = server::load_tls_acceptor(&self.config)?;
         let mut executor = executor::Executor::new(&self.config);
         let executor_tx = executor.prepare_channel();
-        let (executor_join_result, _) = tokio::join!(
+        // Bootstrapping while already serving, so that the server is live during a long WAL replay, but only
+        // ready after it. A failed bootstrap shuts the server down, so that e.g. a corrupt catalog doesn't go unnoticed
+        let (bootstrapped_tx, bootstrapped_rx) = oneshot::channel::<Result<(), io::Error>>();
+        // Connections abandoned on shutdown may still hold on to the channel, so the executor is stopped
+        // explicitly once the server is done
+        let (server_done_tx, server_done_rx) = oneshot::channel::<()>();
+        let (executor_join_result, server_result) = tokio::join!(
             tokio::spawn(async move {
-                executor.start().await;
+                let bootstrap_result = executor.bootstrap().await;
+                let is_bootstrapped = bootstrap_result.is_ok();
+                let _ = bootstrapped_tx.send(bootstrap_result);
+                if !is_bootstrapped {
+                    return;
+                }
+                let server_done = async {
+                    let _ = server_done_rx.await;
+                };
+                if let Err(error) = executor.start_until(server_done).await {
+                    error!("‼️ Encountered executor error: {}", error);
+                }
             }),
-            server::start_server(&self.config, executor_tx),
+            async {
+                let bootstrap = async {
+                    bootstrapped_rx.await.unwrap_or_else(|_| {
+                        Err(io::Error::other(
+                            "The executor stopped before bootstrapping",
+                        ))
+                    })
+                };
+                let server_result =
+                    server::start_server(&self.config, executor_tx, tls_acceptor, bootstrap).await;
+                let _ = server_done_tx.send(());
+                server_result
+            },
         );
         executor_join_result.expect("Failed to join executor");
-        Ok(())
+        server_result
+    }
+}
+
+/// Builder of the multi-threaded runtime serving requests, set up as configured.
+fn runtime_builder(config: &Config) -> runtime::Builder {
+    let mut builder = runtime::Builder::new_multi_thread();
+    builder
+        .worker_threads(usize::try_from(config.worker_threads).unwrap_or(usize::MAX))
+        .enable_all();
+    builder
+}
+
+#[cfg(test)]
+mod instance_tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::{thread, time};
+
+    #[test]
+    fn runtime_has_configured_worker_thread_count() {
+        let config = Config {
+            worker_threads: 3,
+            ..Default::default()
+        };
+        let started_thread_count = Arc::new(AtomicUsize::new(0));
+        let runtime = {
+            let started_thread_count = started_thread_count.clone();
+            runtime_builder(&config)
+                .on_thread_start(move || {
+                    started_thread_count.fetch_add(1, Ordering::SeqCst);
+                })
+                .build()
+                .unwrap()
+        };
+        // Workers are started when the runtime is built, each on a thread of its own
+        let deadline = time::Instant::now() + time::Duration::from_secs(5);
+        while started_thread_count.load(Ordering::SeqCst) < 3 && time::Instant::now() < deadline {
+            thread::sleep(time::Duration::from_millis(10));
+        }
+        runtime.block_on(async {});
+        assert_eq!(started_thread_count.load(Ordering::SeqCst), 3);
     }
 }
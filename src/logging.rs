@@ -0,0 +1,99 @@
+//! Log output, as configured.
+
+use crate::config::{Config, LogFormat};
+use std::io;
+use tracing::Dispatch;
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::{EnvFilter, FmtSubscriber};
+
+/// Dispatcher writing log messages of this crate to the writer, in the configured format.
+/// Messages below the configured level are left out.
+pub fn build_dispatch<W>(config: &Config, make_writer: W) -> Dispatch
+where
+    W: MakeWriter + Send + Sync + 'static,
+{
+    let builder = FmtSubscriber::builder()
+        .with_env_filter(EnvFilter::new(format!("emdrive={}", config.log_level)))
+        .with_writer(make_writer);
+    match config.log_format {
+        LogFormat::Human => Dispatch::new(builder.finish()),
+        LogFormat::Json => Dispatch::new(builder.json().finish()),
+    }
+}
+
+/// Log to stdout as configured, for the rest of the process.
+pub fn init(config: &Config) {
+    tracing::dispatcher::set_global_default(build_dispatch(config, io::stdout))
+        .expect("setting default subscriber failed");
+}
+
+#[cfg(test)]
+mod logging_tests {
+    use super::*;
+    use parking_lot::Mutex;
+    use std::sync::Arc;
+    use tracing::*;
+
+    /// Writer collecting output in memory.
+    #[derive(Clone, Default)]
+    struct CapturedOutput(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for CapturedOutput {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Lines logged with the config while running `log`.
+    fn capture_logs(config: &Config, log: impl FnOnce()) -> Vec<String> {
+        let output = CapturedOutput::default();
+        let make_writer = {
+            let output = output.clone();
+            move || output.clone()
+        };
+        tracing::dispatcher::with_default(&build_dispatch(config, make_writer), log);
+        let output = output.0.lock();
+        String::from_utf8_lossy(&output)
+            .lines()
+            .map(String::from)
+            .collect()
+    }
+
+    #[test]
+    fn configured_level_filters_out_lower_priority_messages() {
+        let config = Config {
+            log_level: Level::INFO,
+            ..Default::default()
+        };
+        let lines = capture_logs(&config, || {
+            trace!("🔬 Tracing");
+            debug!("🐛 Debugging");
+            info!("ℹ️ Informing");
+            warn!("⚠️ Warning");
+        });
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].contains("INFO") && lines[0].ends_with("ℹ️ Informing"));
+        assert!(lines[1].contains("WARN") && lines[1].ends_with("⚠️ Warning"));
+    }
+
+    #[test]
+    fn json_format_outputs_object_per_line() {
+        let config = Config {
+            log_format: LogFormat::Json,
+            ..Default::default()
+        };
+        let lines = capture_logs(&config, || {
+            debug!(statement_kind = "select", "➡️ Executing statements");
+        });
+        assert_eq!(lines.len(), 1);
+        let line: serde_json::Value = serde_json::from_str(&lines[0]).unwrap();
+        assert_eq!(line["level"], "DEBUG");
+        assert_eq!(line["fields"]["message"], "➡️ Executing statements");
+        assert_eq!(line["fields"]["statement_kind"], "select");
+    }
+}
@@ -1,10 +1,8 @@
-use std::str::FromStr;
-
-use emdrive::Instance;
+use emdrive::{logging, Config, Instance};
 use human_panic::setup_panic;
 use tracing::*;
-use tracing_subscriber::{EnvFilter, FmtSubscriber};
 
+#[allow(deprecated)] // `setup_panic!` of human-panic 1.0 expands to the deprecated `PanicInfo`
 fn main() {
     setup_panic!(Metadata {
         name: "Emdrive".into(),
@@ -12,13 +10,27 @@ fn main() {
         authors: "".into(), // Empty to disable
         homepage: env!("CARGO_PKG_REPOSITORY").into(),
     });
-    let subscriber = FmtSubscriber::builder()
-        .with_env_filter(EnvFilter::from_str("emdrive=debug").unwrap())
-        .finish();
-    tracing::subscriber::set_global_default(subscriber).expect("setting default subscriber failed");
+    let instance = match Instance::preload() {
+        Ok(instance) => instance,
+        Err(error) => {
+            // Without a valid config, logging is set up as by default
+            logging::init(&Config::default());
+            error!("‼️ {}", error);
+            std::process::exit(1);
+        }
+    };
+    logging::init(instance.config());
     info!("🔢 Starting Emdrive...");
-    let instance = Instance::preload();
-    let runtime = tokio::runtime::Runtime::new().unwrap();
-    runtime.block_on(instance.run()).unwrap();
+    let runtime = match instance.build_runtime() {
+        Ok(runtime) => runtime,
+        Err(error) => {
+            error!("‼️ Failed to start the runtime: {}", error);
+            std::process::exit(1);
+        }
+    };
+    if let Err(error) = runtime.block_on(instance.run()) {
+        error!("‼️ {}", error);
+        std::process::exit(1);
+    }
     info!("🛑 Emdrive shut down");
 }
@@ -0,0 +1,214 @@
+use flate2::write::{GzEncoder, ZlibEncoder};
+use flate2::Compression;
+use hyper::body::Bytes;
+use hyper::header::HeaderValue;
+use std::io::Write;
+use std::iter;
+
+/// Bodies smaller than this many bytes are sent as is, as compressing them would gain little or even grow them.
+const MIN_COMPRESSED_BODY_BYTES: usize = 1024;
+
+/// Compression applied to a response body.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ContentEncoding {
+    Gzip,
+    /// zlib-wrapped DEFLATE, as HTTP's `deflate` means.
+    Deflate,
+}
+
+impl ContentEncoding {
+    /// Pick a supported encoding listed in the Accept-Encoding header, gzip being preferred.
+    /// Returns `None` if the body should be sent uncompressed.
+    pub fn from_accept_encoding_header(accept_encoding: Option<&HeaderValue>) -> Option<Self> {
+        let accept_encoding = accept_encoding?.to_str().ok()?;
+        let accepted_encodings: Vec<Self> = accept_encoding
+            .split(',')
+            .filter_map(|coding| {
+                let mut coding_parts = coding.split(';');
+                let coding_name = coding_parts.next()?.trim().to_lowercase();
+                // Quality 0 means that the coding is explicitly not acceptable
+                let is_refused = coding_parts.any(|parameter| {
+                    parameter
+                        .trim()
+                        .strip_prefix("q=")
+                        .and_then(|quality| quality.trim().parse::<f32>().ok())
+                        == Some(0.0)
+                });
+                if is_refused {
+                    return None;
+                }
+                match coding_name.as_str() {
+                    "gzip" | "x-gzip" | "*" => Some(Self::Gzip),
+                    "deflate" => Some(Self::Deflate),
+                    _ => None,
+                }
+            })
+            .collect();
+        if accepted_encodings.contains(&Self::Gzip) {
+            Some(Self::Gzip)
+        } else {
+            accepted_encodings.first().copied()
+        }
+    }
+
+    /// Value of the Content-Encoding header.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Gzip => "gzip",
+            Self::Deflate => "deflate",
+        }
+    }
+}
+
+/// Compressor writing into memory, from which compressed bytes are taken as they're produced.
+enum Encoder {
+    Gzip(GzEncoder<Vec<u8>>),
+    Deflate(ZlibEncoder<Vec<u8>>),
+}
+
+impl Encoder {
+    fn new(encoding: ContentEncoding) -> Self {
+        match encoding {
+            ContentEncoding::Gzip => Self::Gzip(GzEncoder::new(Vec::new(), Compression::default())),
+            ContentEncoding::Deflate => {
+                Self::Deflate(ZlibEncoder::new(Vec::new(), Compression::default()))
+            }
+        }
+    }
+
+    /// Compress the data, returning the compressed bytes produced so far.
+    fn compress(&mut self, data: &[u8]) -> Bytes {
+        // Writing to memory can't fail
+        match self {
+            Self::Gzip(encoder) => {
+                encoder.write_all(data).unwrap();
+                Bytes::from(std::mem::take(encoder.get_mut()))
+            }
+            Self::Deflate(encoder) => {
+                encoder.write_all(data).unwrap();
+                Bytes::from(std::mem::take(encoder.get_mut()))
+            }
+        }
+    }
+
+    /// Finish the compressed stream, returning the remaining compressed bytes.
+    fn finish(&mut self) -> Bytes {
+        match self {
+            Self::Gzip(encoder) => {
+                encoder.try_finish().unwrap();
+                Bytes::from(std::mem::take(encoder.get_mut()))
+            }
+            Self::Deflate(encoder) => {
+                encoder.try_finish().unwrap();
+                Bytes::from(std::mem::take(encoder.get_mut()))
+            }
+        }
+    }
+}
+
+/// Compress the chunks of a body lazily, so that it can still be streamed. A body that turns out to be
+/// smaller than the threshold is left uncompressed. Returns the chunks along with the encoding actually applied.
+pub fn compress_chunks(
+    mut chunks: impl Iterator<Item = String> + Send + 'static,
+    encoding: Option<ContentEncoding>,
+) -> (
+    Box<dyn Iterator<Item = Bytes> + Send>,
+    Option<ContentEncoding>,
+) {
+    let encoding = match encoding {
+        Some(encoding) => encoding,
+        None => return (Box::new(chunks.map(Bytes::from)), None),
+    };
+    let mut leading_chunks = Vec::new();
+    let mut leading_byte_count = 0;
+    while leading_byte_count < MIN_COMPRESSED_BODY_BYTES {
+        match chunks.next() {
+            Some(chunk) => {
+                leading_byte_count += chunk.len();
+                leading_chunks.push(chunk);
+            }
+            None => return (Box::new(leading_chunks.into_iter().map(Bytes::from)), None),
+        }
+    }
+    let mut encoder = Encoder::new(encoding);
+    let compressed_chunks = leading_chunks
+        .into_iter()
+        .chain(chunks)
+        .map(Some)
+        .chain(iter::once(None))
+        .map(move |chunk| match chunk {
+            Some(chunk) => encoder.compress(chunk.as_bytes()),
+            None => encoder.finish(),
+        })
+        // The encoder buffers data, so not every chunk yields compressed bytes
+        .filter(|compressed_chunk| !compressed_chunk.is_empty());
+    (Box::new(compressed_chunks), Some(encoding))
+}
+
+#[cfg(test)]
+mod compression_tests {
+    use super::*;
+    use flate2::read::{GzDecoder, ZlibDecoder};
+    use pretty_assertions::assert_eq;
+    use std::io::Read;
+
+    fn negotiate(accept_encoding: &str) -> Option<ContentEncoding> {
+        ContentEncoding::from_accept_encoding_header(Some(
+            &HeaderValue::from_str(accept_encoding).unwrap(),
+        ))
+    }
+
+    #[test]
+    fn encoding_is_negotiated() {
+        assert_eq!(ContentEncoding::from_accept_encoding_header(None), None);
+        assert_eq!(negotiate("gzip, deflate, br"), Some(ContentEncoding::Gzip));
+        assert_eq!(negotiate("deflate, gzip"), Some(ContentEncoding::Gzip));
+        assert_eq!(negotiate("deflate"), Some(ContentEncoding::Deflate));
+        assert_eq!(
+            negotiate("gzip;q=0, deflate"),
+            Some(ContentEncoding::Deflate)
+        );
+        assert_eq!(negotiate("br, identity"), None);
+    }
+
+    fn get_test_chunks() -> Vec<String> {
+        (0..1000)
+            .map(|index| format!("{{\"id\":{}}}\n", index))
+            .collect()
+    }
+
+    #[test]
+    fn chunks_are_compressed() {
+        let (compressed_chunks, encoding) = compress_chunks(
+            get_test_chunks().into_iter(),
+            Some(ContentEncoding::Deflate),
+        );
+        assert_eq!(encoding, Some(ContentEncoding::Deflate));
+        let compressed: Vec<u8> = compressed_chunks.flatten().collect();
+        let mut decompressed = String::new();
+        ZlibDecoder::new(compressed.as_slice())
+            .read_to_string(&mut decompressed)
+            .unwrap();
+        assert_eq!(decompressed, get_test_chunks().concat());
+
+        let (compressed_chunks, encoding) =
+            compress_chunks(get_test_chunks().into_iter(), Some(ContentEncoding::Gzip));
+        assert_eq!(encoding, Some(ContentEncoding::Gzip));
+        let compressed: Vec<u8> = compressed_chunks.flatten().collect();
+        let mut decompressed = String::new();
+        GzDecoder::new(compressed.as_slice())
+            .read_to_string(&mut decompressed)
+            .unwrap();
+        assert_eq!(decompressed, get_test_chunks().concat());
+    }
+
+    #[test]
+    fn small_body_is_not_compressed() {
+        let (chunks, encoding) = compress_chunks(
+            vec!["[".to_string(), "]".to_string()].into_iter(),
+            Some(ContentEncoding::Gzip),
+        );
+        assert_eq!(encoding, None);
+        assert_eq!(chunks.flatten().collect::<Vec<u8>>(), b"[]");
+    }
+}
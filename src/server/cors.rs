@@ -0,0 +1,103 @@
+use hyper::header::{self, HeaderMap, HeaderValue};
+use hyper::{Body, Response, StatusCode};
+
+use crate::config;
+
+/// Methods that browsers may use in cross-origin requests.
+const ALLOWED_METHODS: &str = "GET, POST, OPTIONS";
+/// Non-simple headers that browsers may send in cross-origin requests.
+const ALLOWED_HEADERS: &str = "Authorization, Content-Type, Accept, X-Request-Id";
+/// Non-simple response headers that browsers may let scripts read.
+const EXPOSED_HEADERS: &str = "X-Request-Id, X-Next-Cursor";
+/// How long browsers may cache the result of a preflight request, in seconds.
+const PREFLIGHT_MAX_AGE_SECONDS: &str = "86400";
+
+/// The request's origin, if it's one of the allowed ones. That's the value to allow in the response.
+pub fn allowed_origin(config: &config::Config, headers: &HeaderMap) -> Option<HeaderValue> {
+    let origin = headers.get(header::ORIGIN)?;
+    config
+        .cors_allowed_origins
+        .iter()
+        .any(|allowed_origin| allowed_origin == "*" || origin == allowed_origin.as_str())
+        .then(|| origin.clone())
+}
+
+/// Attach CORS headers to the response, if the origin is allowed.
+pub fn add_cors_headers(response: &mut Response<Body>, allowed_origin: Option<HeaderValue>) {
+    let headers = response.headers_mut();
+    // Responses depend on the origin, so caches must keep them apart
+    headers.append(header::VARY, HeaderValue::from_static("Origin"));
+    if let Some(allowed_origin) = allowed_origin {
+        headers.insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, allowed_origin);
+        headers.insert(
+            header::ACCESS_CONTROL_EXPOSE_HEADERS,
+            HeaderValue::from_static(EXPOSED_HEADERS),
+        );
+    }
+}
+
+/// Response to a preflight request, to which CORS headers are yet to be attached.
+/// Without an allowed origin it lacks the allow headers, which makes the browser block the actual request.
+pub fn preflight_response(allowed_origin: Option<&HeaderValue>) -> Response<Body> {
+    let mut response = Response::builder()
+        .status(StatusCode::NO_CONTENT)
+        .body(Body::default())
+        .unwrap();
+    if allowed_origin.is_some() {
+        let headers = response.headers_mut();
+        headers.insert(
+            header::ACCESS_CONTROL_ALLOW_METHODS,
+            HeaderValue::from_static(ALLOWED_METHODS),
+        );
+        headers.insert(
+            header::ACCESS_CONTROL_ALLOW_HEADERS,
+            HeaderValue::from_static(ALLOWED_HEADERS),
+        );
+        headers.insert(
+            header::ACCESS_CONTROL_MAX_AGE,
+            HeaderValue::from_static(PREFLIGHT_MAX_AGE_SECONDS),
+        );
+    }
+    response
+}
+
+#[cfg(test)]
+mod cors_tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn headers_with_origin(origin: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::ORIGIN, HeaderValue::from_str(origin).unwrap());
+        headers
+    }
+
+    #[test]
+    fn origin_is_allowed_if_listed() {
+        let config = config::Config {
+            cors_allowed_origins: vec!["https://admin.example.com".into()],
+            ..Default::default()
+        };
+        assert_eq!(
+            allowed_origin(&config, &headers_with_origin("https://admin.example.com")),
+            Some(HeaderValue::from_static("https://admin.example.com"))
+        );
+        assert_eq!(
+            allowed_origin(&config, &headers_with_origin("https://evil.example.com")),
+            None
+        );
+        assert_eq!(allowed_origin(&config, &HeaderMap::new()), None);
+    }
+
+    #[test]
+    fn any_origin_is_allowed_with_wildcard() {
+        let config = config::Config {
+            cors_allowed_origins: vec!["*".into()],
+            ..Default::default()
+        };
+        assert_eq!(
+            allowed_origin(&config, &headers_with_origin("https://evil.example.com")),
+            Some(HeaderValue::from_static("https://evil.example.com"))
+        );
+    }
+}
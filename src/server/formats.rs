@@ -0,0 +1,672 @@
+use crate::constructs::components::{
+    timestamp_iso, timestamp_micros, DataInstance, DataInstanceRaw,
+};
+use crate::executor::{QueryResult, StatementOutcome};
+use crate::storage::{NamedRow, Row};
+use hyper::header::HeaderValue;
+use std::iter;
+
+/// Number of rows serialized into a single chunk of a streamed response body.
+const ROWS_PER_CHUNK: usize = 256;
+
+/// Format in which query results are returned.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ResponseFormat {
+    Json,
+    Csv,
+    /// Newline-delimited JSON, i.e. one JSON object per row.
+    Ndjson,
+}
+
+/// How timestamps are rendered in query results.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum TimestampFormat {
+    /// Number of microseconds since Unix epoch, e.g. `1705321800000000`.
+    #[default]
+    Micros,
+    /// ISO 8601 string in UTC, e.g. `2024-01-15T12:30:00Z`.
+    Iso,
+}
+
+impl TimestampFormat {
+    /// Names accepted in the `timestamps` param, for use in error messages.
+    pub const SUPPORTED_NAMES: &'static str = "micros, iso";
+
+    /// Timestamp format by its name, as in the `timestamps` param.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "micros" => Some(Self::Micros),
+            "iso" => Some(Self::Iso),
+            _ => None,
+        }
+    }
+}
+
+impl ResponseFormat {
+    /// Media types that can be requested, for use in error messages.
+    pub const SUPPORTED_MEDIA_TYPES: &'static str =
+        "application/json, text/csv, application/x-ndjson";
+
+    /// Names accepted in the `format` param, for use in error messages.
+    pub const SUPPORTED_NAMES: &'static str = "json, csv, ndjson";
+
+    /// Format by its name, as in the `format` param.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "json" => Some(Self::Json),
+            "csv" => Some(Self::Csv),
+            "ndjson" => Some(Self::Ndjson),
+            _ => None,
+        }
+    }
+
+    /// Pick the supported format of the highest quality listed in the Accept header (the first listed of those
+    /// with the same quality), with JSON being the default. Returns `None` if none of the listed formats is supported.
+    pub fn from_accept_header(accept: Option<&HeaderValue>) -> Option<Self> {
+        let accept = match accept {
+            Some(accept) => accept.to_str().ok()?,
+            None => return Some(Self::Json),
+        };
+        if accept.trim().is_empty() {
+            return Some(Self::Json);
+        }
+        accept
+            .split(',')
+            .filter_map(|media_range| {
+                let mut media_range_parts = media_range.split(';');
+                let media_type = media_range_parts.next()?.trim().to_lowercase();
+                let quality = media_range_parts
+                    .find_map(|parameter| parameter.trim().strip_prefix("q="))
+                    .and_then(|quality| quality.trim().parse::<f32>().ok())
+                    .unwrap_or(1.0);
+                // Quality 0 means that the media type is explicitly not acceptable
+                if quality <= 0.0 {
+                    return None;
+                }
+                let format = match media_type.as_str() {
+                    "application/json" | "application/*" | "*/*" => Self::Json,
+                    "text/csv" | "text/*" => Self::Csv,
+                    "application/x-ndjson" => Self::Ndjson,
+                    _ => return None,
+                };
+                Some((quality, format))
+            })
+            // Of formats with the same quality, `min_by` keeps the first listed
+            .min_by(|(quality, _), (other_quality, _)| other_quality.total_cmp(quality))
+            .map(|(_, format)| format)
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Self::Json => "application/json",
+            Self::Csv => "text/csv; charset=utf-8",
+            Self::Ndjson => "application/x-ndjson",
+        }
+    }
+
+    /// Serialize the outcome lazily, a chunk of rows at a time, so that a response body can be streamed
+    /// without the whole serialized result ever being held in memory. The rows themselves are all in memory
+    /// already, as the executor produces complete results, but each chunk of them is dropped once serialized.
+    /// In JSON the outcome is wrapped in an envelope naming the statement kind, e.g.
+    /// `{"statement":"insert","rows_affected":1}` or `{"statement":"select","columns":[...],"rows":[...]}`.
+    /// CSV and NDJSON are tabular, so there the number of affected rows is a `rows_affected` column,
+    /// and whether a table was created is a `created` column.
+    pub fn serialize_chunks(
+        self,
+        statement_kind: &str,
+        outcome: StatementOutcome,
+        timestamp_format: TimestampFormat,
+    ) -> impl Iterator<Item = String> {
+        let statement_kind = serde_json::to_string(statement_kind).unwrap();
+        let (opening, query_result, closing) = match (self, outcome) {
+            (Self::Json, StatementOutcome::RowsAffected(count)) => (
+                format!(
+                    "{{\"statement\":{},\"rows_affected\":{}}}",
+                    statement_kind, count
+                ),
+                QueryResult::empty(),
+                String::new(),
+            ),
+            (Self::Json, StatementOutcome::TableCreated(is_created)) => (
+                format!(
+                    "{{\"statement\":{},\"created\":{}}}",
+                    statement_kind, is_created
+                ),
+                QueryResult::empty(),
+                String::new(),
+            ),
+            (Self::Json, StatementOutcome::Done) => (
+                format!("{{\"statement\":{}}}", statement_kind),
+                QueryResult::empty(),
+                String::new(),
+            ),
+            (Self::Json, StatementOutcome::Page { rows, next_cursor }) => {
+                let opening = self.serialize_opening(&statement_kind, &rows.column_names);
+                // The cursor of the next page is only known once rows are in, so it comes after them
+                let closing = self.serialize_closing(
+                    rows.rows.len(),
+                    &format!(
+                        ",\"next_cursor\":{}",
+                        serde_json::to_string(&next_cursor).unwrap()
+                    ),
+                );
+                (opening, rows, closing)
+            }
+            (_, outcome) => {
+                let query_result = outcome.into_query_result();
+                let opening = self.serialize_opening(&statement_kind, &query_result.column_names);
+                let closing = self.serialize_closing(query_result.rows.len(), "");
+                (opening, query_result, closing)
+            }
+        };
+        let column_names = query_result.column_names;
+        let mut rows = query_result.rows.into_iter();
+        let mut are_first_rows = true;
+        let row_chunks = iter::from_fn(move || {
+            let chunk: Vec<Row> = rows.by_ref().take(ROWS_PER_CHUNK).collect();
+            if chunk.is_empty() {
+                return None;
+            }
+            let serialized = match timestamp_format {
+                TimestampFormat::Micros => {
+                    self.serialize_rows(&column_names, &chunk, are_first_rows)
+                }
+                TimestampFormat::Iso => self.serialize_rows(
+                    &column_names,
+                    &render_timestamps_iso(&chunk),
+                    are_first_rows,
+                ),
+            };
+            are_first_rows = false;
+            Some(serialized)
+        });
+        iter::once(opening)
+            .chain(row_chunks)
+            .chain(iter::once(closing))
+    }
+
+    /// What comes before rows: the envelope up to the opening bracket of the JSON rows array
+    /// (with the statement kind already serialized), or the CSV header row. Nothing in NDJSON.
+    fn serialize_opening(&self, statement_kind: &str, column_names: &[String]) -> String {
+        match self {
+            Self::Json => format!(
+                "{{\"statement\":{},\"columns\":{},\"rows\":[",
+                statement_kind,
+                serde_json::to_string(column_names).unwrap()
+            ),
+            Self::Csv => {
+                let header: Vec<String> = column_names
+                    .iter()
+                    .map(|column_name| escape_csv_field(column_name))
+                    .collect();
+                format!("{}\r\n", header.join(","))
+            }
+            Self::Ndjson => String::new(),
+        }
+    }
+
+    /// Serialize consecutive rows. In JSON each row is an object on its own line.
+    fn serialize_rows(
+        &self,
+        column_names: &[String],
+        rows: &[Row],
+        are_first_rows: bool,
+    ) -> String {
+        let mut serialized = String::new();
+        for (row_index, row) in rows.iter().enumerate() {
+            match self {
+                Self::Json => {
+                    serialized.push_str(if are_first_rows && row_index == 0 {
+                        "\n"
+                    } else {
+                        ",\n"
+                    });
+                    serialized
+                        .push_str(&serde_json::to_string(&NamedRow(column_names, &row.0)).unwrap());
+                }
+                Self::Csv => {
+                    let fields: Vec<String> = row.0.iter().map(csv_field).collect();
+                    serialized.push_str(&fields.join(","));
+                    serialized.push_str("\r\n");
+                }
+                Self::Ndjson => {
+                    serialized
+                        .push_str(&serde_json::to_string(&NamedRow(column_names, &row.0)).unwrap());
+                    serialized.push('\n');
+                }
+            }
+        }
+        serialized
+    }
+
+    /// What comes after rows: the closing brackets of the JSON rows array and envelope, or nothing in CSV and NDJSON.
+    /// `trailing_fields` are serialized fields added to the JSON envelope after rows, each preceded by a comma.
+    fn serialize_closing(&self, row_count: usize, trailing_fields: &str) -> String {
+        match self {
+            Self::Json if row_count == 0 => format!("]{}}}", trailing_fields),
+            Self::Json => format!("\n]{}}}", trailing_fields),
+            Self::Csv | Self::Ndjson => String::new(),
+        }
+    }
+}
+
+/// Replace timestamps with their ISO 8601 strings, so that they're serialized as such in every format.
+fn render_timestamps_iso(rows: &[Row]) -> Vec<Row> {
+    rows.iter()
+        .map(|row| {
+            Row(row
+                .0
+                .iter()
+                .map(|value| match value {
+                    DataInstance::Direct(DataInstanceRaw::Timestamp(timestamp)) => {
+                        DataInstance::Direct(DataInstanceRaw::String(timestamp_iso(timestamp)))
+                    }
+                    DataInstance::Nullable(DataInstanceRaw::Timestamp(timestamp)) => {
+                        DataInstance::Nullable(DataInstanceRaw::String(timestamp_iso(timestamp)))
+                    }
+                    value => value.clone(),
+                })
+                .collect())
+        })
+        .collect()
+}
+
+/// Quote the field per RFC 4180 if it contains a comma, a double quote or a line break.
+fn escape_csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Render a value as a CSV field. NULL is rendered as an empty field.
+fn csv_field(value: &DataInstance) -> String {
+    let field = match value.as_raw() {
+        None => String::new(),
+        Some(DataInstanceRaw::UInt8(value)) => value.to_string(),
+        Some(DataInstanceRaw::UInt16(value)) => value.to_string(),
+        Some(DataInstanceRaw::UInt32(value)) => value.to_string(),
+        Some(DataInstanceRaw::UInt64(value)) => value.to_string(),
+        Some(DataInstanceRaw::UInt128(value)) => value.to_string(),
+        Some(DataInstanceRaw::Bool(value)) => value.to_string(),
+        // Microseconds since Unix epoch, same as on disk
+        Some(DataInstanceRaw::Timestamp(value)) => timestamp_micros(value).to_string(),
+        Some(DataInstanceRaw::Uuid(value)) => value.to_string(),
+        Some(DataInstanceRaw::String(value)) => value.clone(),
+    };
+    escape_csv_field(&field)
+}
+
+#[cfg(test)]
+mod formats_tests {
+    use super::*;
+    use crate::storage::Row;
+    use pretty_assertions::assert_eq;
+
+    /// Format picked for the Accept header.
+    fn format_for(accept: &str) -> Option<ResponseFormat> {
+        ResponseFormat::from_accept_header(Some(&HeaderValue::from_str(accept).unwrap()))
+    }
+
+    #[test]
+    fn accept_header_is_picked_by_quality() {
+        assert_eq!(
+            format_for("text/csv;q=0.1, application/json"),
+            Some(ResponseFormat::Json)
+        );
+        assert_eq!(
+            format_for("application/json;q=0.5, application/x-ndjson;q=0.9, text/csv;q=0.9"),
+            Some(ResponseFormat::Ndjson)
+        );
+        assert_eq!(
+            format_for("text/csv, application/json"),
+            Some(ResponseFormat::Csv)
+        );
+        assert_eq!(format_for("text/csv;q=0, image/png"), None);
+    }
+
+    #[test]
+    fn csv_quotes_special_characters() {
+        let query_result = QueryResult {
+            column_names: vec!["id".into(), "text".into()],
+            rows: vec![
+                Row(vec![
+                    DataInstance::Direct(DataInstanceRaw::UInt8(1)),
+                    DataInstance::Direct(DataInstanceRaw::String("a, b".into())),
+                ]),
+                Row(vec![
+                    DataInstance::Direct(DataInstanceRaw::UInt8(2)),
+                    DataInstance::Direct(DataInstanceRaw::String("say \"hi\"".into())),
+                ]),
+                Row(vec![
+                    DataInstance::Direct(DataInstanceRaw::UInt8(3)),
+                    DataInstance::Direct(DataInstanceRaw::String("line\nbreak".into())),
+                ]),
+                Row(vec![
+                    DataInstance::Direct(DataInstanceRaw::UInt8(4)),
+                    DataInstance::Direct(DataInstanceRaw::String("plain".into())),
+                ]),
+            ],
+        };
+        assert_eq!(
+            ResponseFormat::Csv
+                .serialize_chunks(
+                    "select",
+                    StatementOutcome::Rows(query_result),
+                    TimestampFormat::Micros
+                )
+                .collect::<String>(),
+            "id,text\r\n1,\"a, b\"\r\n2,\"say \"\"hi\"\"\"\r\n3,\"line\nbreak\"\r\n4,plain\r\n"
+        );
+    }
+
+    #[test]
+    fn csv_renders_null_as_empty_and_timestamp_as_integer() {
+        let query_result = QueryResult {
+            column_names: vec!["name".into(), "created_at".into(), "active".into()],
+            rows: vec![Row(vec![
+                DataInstance::Null,
+                DataInstance::Direct(DataInstanceRaw::Timestamp(
+                    time::OffsetDateTime::from_unix_timestamp(1_600_000_000).unwrap(),
+                )),
+                DataInstance::Null,
+            ])],
+        };
+        assert_eq!(
+            ResponseFormat::Csv
+                .serialize_chunks(
+                    "select",
+                    StatementOutcome::Rows(query_result),
+                    TimestampFormat::Micros
+                )
+                .collect::<String>(),
+            "name,created_at,active\r\n,1600000000000000,\r\n"
+        );
+    }
+
+    #[test]
+    fn timestamps_are_rendered_as_iso_8601_on_request() {
+        let get_query_result = || QueryResult {
+            column_names: vec!["seen_at".into(), "deleted_at".into()],
+            rows: vec![Row(vec![
+                DataInstance::Direct(DataInstanceRaw::Timestamp(
+                    time::OffsetDateTime::from_unix_timestamp(1_705_321_800).unwrap(),
+                )),
+                DataInstance::Null,
+            ])],
+        };
+        assert_eq!(
+            ResponseFormat::Ndjson
+                .serialize_chunks(
+                    "select",
+                    StatementOutcome::Rows(get_query_result()),
+                    TimestampFormat::Micros
+                )
+                .collect::<String>(),
+            "{\"seen_at\":1705321800000000,\"deleted_at\":null}\n"
+        );
+        assert_eq!(
+            ResponseFormat::Ndjson
+                .serialize_chunks(
+                    "select",
+                    StatementOutcome::Rows(get_query_result()),
+                    TimestampFormat::Iso
+                )
+                .collect::<String>(),
+            "{\"seen_at\":\"2024-01-15T12:30:00Z\",\"deleted_at\":null}\n"
+        );
+        assert_eq!(
+            ResponseFormat::Csv
+                .serialize_chunks(
+                    "select",
+                    StatementOutcome::Rows(get_query_result()),
+                    TimestampFormat::Iso
+                )
+                .collect::<String>(),
+            "seen_at,deleted_at\r\n2024-01-15T12:30:00Z,\r\n"
+        );
+    }
+
+    #[test]
+    fn uuid_is_rendered_hyphenated() {
+        let get_query_result = || QueryResult {
+            column_names: vec!["id".into()],
+            rows: vec![Row(vec![DataInstance::Direct(DataInstanceRaw::Uuid(
+                uuid::Uuid::from_u128(0x550e8400_e29b_41d4_a716_446655440000),
+            ))])],
+        };
+        assert_eq!(
+            ResponseFormat::Csv
+                .serialize_chunks(
+                    "select",
+                    StatementOutcome::Rows(get_query_result()),
+                    TimestampFormat::Micros
+                )
+                .collect::<String>(),
+            "id\r\n550e8400-e29b-41d4-a716-446655440000\r\n"
+        );
+        assert_eq!(
+            ResponseFormat::Ndjson
+                .serialize_chunks(
+                    "select",
+                    StatementOutcome::Rows(get_query_result()),
+                    TimestampFormat::Micros
+                )
+                .collect::<String>(),
+            "{\"id\":\"550e8400-e29b-41d4-a716-446655440000\"}\n"
+        );
+    }
+
+    #[test]
+    fn rows_are_serialized_in_chunks() {
+        let get_query_result = || QueryResult {
+            column_names: vec!["id".into()],
+            rows: (0..600)
+                .map(|id| Row(vec![DataInstance::Direct(DataInstanceRaw::UInt16(id))]))
+                .collect(),
+        };
+        let json_chunks: Vec<String> = ResponseFormat::Json
+            .serialize_chunks(
+                "select",
+                StatementOutcome::Rows(get_query_result()),
+                TimestampFormat::Micros,
+            )
+            .collect();
+        // Opening, 3 chunks of rows and closing
+        assert_eq!(json_chunks.len(), 5);
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&json_chunks.concat()).unwrap()["rows"],
+            serde_json::to_value(get_query_result()).unwrap()
+        );
+        let csv_chunks: Vec<String> = ResponseFormat::Csv
+            .serialize_chunks(
+                "select",
+                StatementOutcome::Rows(get_query_result()),
+                TimestampFormat::Micros,
+            )
+            .collect();
+        assert_eq!(csv_chunks.len(), 5);
+        assert_eq!(csv_chunks.concat().lines().count(), 601);
+    }
+
+    #[test]
+    fn ndjson_lines_are_independent_objects() {
+        let query_result = QueryResult {
+            column_names: vec!["id".into(), "name".into()],
+            rows: vec![
+                Row(vec![
+                    DataInstance::Direct(DataInstanceRaw::UInt8(1)),
+                    DataInstance::Nullable(DataInstanceRaw::String("foo".into())),
+                ]),
+                Row(vec![
+                    DataInstance::Direct(DataInstanceRaw::UInt8(2)),
+                    DataInstance::Null,
+                ]),
+            ],
+        };
+        let ndjson: String = ResponseFormat::Ndjson
+            .serialize_chunks(
+                "select",
+                StatementOutcome::Rows(query_result),
+                TimestampFormat::Micros,
+            )
+            .collect();
+        let lines: Vec<serde_json::Value> = ndjson
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(
+            lines,
+            vec![
+                serde_json::json!({ "id": 1, "name": "foo" }),
+                serde_json::json!({ "id": 2, "name": null }),
+            ]
+        );
+        assert!(ndjson.ends_with('\n'));
+    }
+
+    #[test]
+    fn column_order_is_preserved() {
+        // Deliberately not alphabetical, so that no map could sort columns into this order by accident
+        let get_query_result = || QueryResult {
+            column_names: vec!["zeta".into(), "alpha".into(), "mid".into()],
+            rows: vec![Row(vec![
+                DataInstance::Direct(DataInstanceRaw::UInt8(1)),
+                DataInstance::Direct(DataInstanceRaw::UInt8(2)),
+                DataInstance::Direct(DataInstanceRaw::UInt8(3)),
+            ])],
+        };
+        assert_eq!(
+            ResponseFormat::Json
+                .serialize_chunks(
+                    "select",
+                    StatementOutcome::Rows(get_query_result()),
+                    TimestampFormat::Micros
+                )
+                .collect::<String>(),
+            "{\"statement\":\"select\",\"columns\":[\"zeta\",\"alpha\",\"mid\"],\"rows\":[\n{\"zeta\":1,\"alpha\":2,\"mid\":3}\n]}"
+        );
+        assert_eq!(
+            ResponseFormat::Csv
+                .serialize_chunks(
+                    "select",
+                    StatementOutcome::Rows(get_query_result()),
+                    TimestampFormat::Micros
+                )
+                .collect::<String>(),
+            "zeta,alpha,mid\r\n1,2,3\r\n"
+        );
+        assert_eq!(
+            ResponseFormat::Ndjson
+                .serialize_chunks(
+                    "select",
+                    StatementOutcome::Rows(get_query_result()),
+                    TimestampFormat::Micros
+                )
+                .collect::<String>(),
+            "{\"zeta\":1,\"alpha\":2,\"mid\":3}\n"
+        );
+    }
+
+    #[test]
+    fn empty_result_is_serialized_with_empty_rows() {
+        assert_eq!(
+            ResponseFormat::Json
+                .serialize_chunks(
+                    "select",
+                    StatementOutcome::Rows(QueryResult::empty()),
+                    TimestampFormat::Micros
+                )
+                .collect::<String>(),
+            r#"{"statement":"select","columns":[],"rows":[]}"#
+        );
+    }
+
+    #[test]
+    fn page_is_serialized_with_next_cursor_in_json_only() {
+        let get_page = |next_cursor: Option<&str>| StatementOutcome::Page {
+            rows: QueryResult {
+                column_names: vec!["zeta".into(), "alpha".into(), "mid".into()],
+                rows: vec![Row(vec![
+                    DataInstance::Direct(DataInstanceRaw::UInt8(1)),
+                    DataInstance::Direct(DataInstanceRaw::UInt8(2)),
+                    DataInstance::Direct(DataInstanceRaw::UInt8(3)),
+                ])],
+            },
+            next_cursor: next_cursor.map(String::from),
+        };
+        assert_eq!(
+            ResponseFormat::Json
+                .serialize_chunks("select", get_page(Some("WyIxIl0")), TimestampFormat::Micros)
+                .collect::<String>(),
+            "{\"statement\":\"select\",\"columns\":[\"zeta\",\"alpha\",\"mid\"],\"rows\":[\n{\"zeta\":1,\"alpha\":2,\"mid\":3}\n],\"next_cursor\":\"WyIxIl0\"}"
+        );
+        assert_eq!(
+            ResponseFormat::Json
+                .serialize_chunks("select", get_page(None), TimestampFormat::Micros)
+                .collect::<String>(),
+            "{\"statement\":\"select\",\"columns\":[\"zeta\",\"alpha\",\"mid\"],\"rows\":[\n{\"zeta\":1,\"alpha\":2,\"mid\":3}\n],\"next_cursor\":null}"
+        );
+        assert_eq!(
+            ResponseFormat::Csv
+                .serialize_chunks("select", get_page(Some("WyIxIl0")), TimestampFormat::Micros)
+                .collect::<String>(),
+            "zeta,alpha,mid\r\n1,2,3\r\n"
+        );
+    }
+
+    #[test]
+    fn affected_row_count_is_a_column_in_tabular_formats() {
+        assert_eq!(
+            ResponseFormat::Json
+                .serialize_chunks(
+                    "delete",
+                    StatementOutcome::RowsAffected(3),
+                    TimestampFormat::Micros
+                )
+                .collect::<String>(),
+            r#"{"statement":"delete","rows_affected":3}"#
+        );
+        assert_eq!(
+            ResponseFormat::Csv
+                .serialize_chunks(
+                    "delete",
+                    StatementOutcome::RowsAffected(3),
+                    TimestampFormat::Micros
+                )
+                .collect::<String>(),
+            "rows_affected\r\n3\r\n"
+        );
+        assert_eq!(
+            ResponseFormat::Csv
+                .serialize_chunks(
+                    "create_table",
+                    StatementOutcome::TableCreated(false),
+                    TimestampFormat::Micros
+                )
+                .collect::<String>(),
+            "created\r\nfalse\r\n"
+        );
+        assert_eq!(
+            ResponseFormat::Csv
+                .serialize_chunks("commit", StatementOutcome::Done, TimestampFormat::Micros)
+                .collect::<String>(),
+            "\r\n"
+        );
+    }
+
+    #[test]
+    fn format_names_are_case_insensitive() {
+        assert_eq!(ResponseFormat::from_name("CSV"), Some(ResponseFormat::Csv));
+        assert_eq!(
+            ResponseFormat::from_name("NDJSON"),
+            Some(ResponseFormat::Ndjson)
+        );
+        assert_eq!(
+            ResponseFormat::from_name("json"),
+            Some(ResponseFormat::Json)
+        );
+        assert_eq!(ResponseFormat::from_name("xml"), None);
+    }
+}
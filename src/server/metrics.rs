@@ -0,0 +1,146 @@
+use hyper::Method;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Upper bounds of request duration histogram buckets, in seconds.
+const DURATION_BUCKETS: [f64; 10] = [0.0001, 0.0005, 0.001, 0.005, 0.01, 0.05, 0.1, 0.5, 1.0, 5.0];
+
+/// Methods counted separately. Requests with any other method are counted as "OTHER".
+const COUNTED_METHODS: [Method; 2] = [Method::GET, Method::POST];
+
+/// Server counters, exposed in the Prometheus text format.
+#[derive(Debug, Default)]
+pub struct Metrics {
+    requests_total: AtomicU64,
+    /// Per-method request counts, indexes matching `COUNTED_METHODS`, with "OTHER" last.
+    requests_by_method: [AtomicU64; COUNTED_METHODS.len() + 1],
+    parse_errors_total: AtomicU64,
+    /// Cumulative counts are computed on render, so each bucket only counts its own range.
+    /// The last bucket is +Inf.
+    request_duration_buckets: [AtomicU64; DURATION_BUCKETS.len() + 1],
+    request_duration_sum_micros: AtomicU64,
+}
+
+impl Metrics {
+    /// Record a finished request.
+    pub fn record_request(&self, method: &Method, duration: Duration) {
+        self.requests_total.fetch_add(1, Ordering::Relaxed);
+        let method_index = COUNTED_METHODS
+            .iter()
+            .position(|counted_method| counted_method == method)
+            .unwrap_or(COUNTED_METHODS.len());
+        self.requests_by_method[method_index].fetch_add(1, Ordering::Relaxed);
+        let duration_seconds = duration.as_secs_f64();
+        let bucket_index = DURATION_BUCKETS
+            .iter()
+            .position(|upper_bound| duration_seconds <= *upper_bound)
+            .unwrap_or(DURATION_BUCKETS.len());
+        self.request_duration_buckets[bucket_index].fetch_add(1, Ordering::Relaxed);
+        self.request_duration_sum_micros
+            .fetch_add(duration.as_micros() as u64, Ordering::Relaxed);
+    }
+
+    /// Record a statement that could not be parsed.
+    pub fn record_parse_error(&self) {
+        self.parse_errors_total.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Render all metrics in the Prometheus text exposition format.
+    pub fn render(&self) -> String {
+        let mut output = String::new();
+        writeln!(
+            output,
+            "# HELP emdrive_requests_total Total number of HTTP requests handled."
+        )
+        .unwrap();
+        writeln!(output, "# TYPE emdrive_requests_total counter").unwrap();
+        writeln!(
+            output,
+            "emdrive_requests_total {}",
+            self.requests_total.load(Ordering::Relaxed)
+        )
+        .unwrap();
+        writeln!(
+            output,
+            "# HELP emdrive_requests_by_method_total Number of HTTP requests handled, by method."
+        )
+        .unwrap();
+        writeln!(output, "# TYPE emdrive_requests_by_method_total counter").unwrap();
+        for (method_index, count) in self.requests_by_method.iter().enumerate() {
+            writeln!(
+                output,
+                "emdrive_requests_by_method_total{{method=\"{}\"}} {}",
+                COUNTED_METHODS
+                    .get(method_index)
+                    .map_or("OTHER", |method| method.as_str()),
+                count.load(Ordering::Relaxed)
+            )
+            .unwrap();
+        }
+        writeln!(
+            output,
+            "# HELP emdrive_parse_errors_total Number of statements that could not be parsed."
+        )
+        .unwrap();
+        writeln!(output, "# TYPE emdrive_parse_errors_total counter").unwrap();
+        writeln!(
+            output,
+            "emdrive_parse_errors_total {}",
+            self.parse_errors_total.load(Ordering::Relaxed)
+        )
+        .unwrap();
+        writeln!(
+            output,
+            "# HELP emdrive_request_duration_seconds HTTP request handling duration."
+        )
+        .unwrap();
+        writeln!(output, "# TYPE emdrive_request_duration_seconds histogram").unwrap();
+        let mut cumulative_count = 0;
+        for (bucket_index, count) in self.request_duration_buckets.iter().enumerate() {
+            cumulative_count += count.load(Ordering::Relaxed);
+            let upper_bound = DURATION_BUCKETS
+                .get(bucket_index)
+                .map_or("+Inf".to_string(), |upper_bound| upper_bound.to_string());
+            writeln!(
+                output,
+                "emdrive_request_duration_seconds_bucket{{le=\"{}\"}} {}",
+                upper_bound, cumulative_count
+            )
+            .unwrap();
+        }
+        writeln!(
+            output,
+            "emdrive_request_duration_seconds_sum {}",
+            self.request_duration_sum_micros.load(Ordering::Relaxed) as f64 / 1_000_000.0
+        )
+        .unwrap();
+        writeln!(
+            output,
+            "emdrive_request_duration_seconds_count {}",
+            cumulative_count
+        )
+        .unwrap();
+        output
+    }
+}
+
+#[cfg(test)]
+mod metrics_tests {
+    use super::*;
+
+    #[test]
+    fn histogram_is_cumulative() {
+        let metrics = Metrics::default();
+        metrics.record_request(&Method::GET, Duration::from_micros(50));
+        metrics.record_request(&Method::DELETE, Duration::from_millis(20));
+        let rendered = metrics.render();
+        assert!(rendered.contains("emdrive_requests_by_method_total{method=\"GET\"} 1\n"));
+        assert!(rendered.contains("emdrive_requests_by_method_total{method=\"OTHER\"} 1\n"));
+        assert!(rendered.contains("emdrive_request_duration_seconds_bucket{le=\"0.0001\"} 1\n"));
+        assert!(rendered.contains("emdrive_request_duration_seconds_bucket{le=\"0.01\"} 1\n"));
+        assert!(rendered.contains("emdrive_request_duration_seconds_bucket{le=\"0.05\"} 2\n"));
+        assert!(rendered.contains("emdrive_request_duration_seconds_bucket{le=\"+Inf\"} 2\n"));
+        assert!(rendered.contains("emdrive_request_duration_seconds_count 2\n"));
+    }
+}
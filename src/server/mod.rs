@@ -1,18 +1,62 @@
 use crate::config;
-use crate::constructs::components::Validatable;
-use crate::executor::{ExecutorPayload, QueryResult};
-use crate::sql::parse_statement;
+use crate::constructs::components::{DataInstance, DataInstanceRaw};
+use crate::database::{parse_input, validate_statements};
+use crate::executor::{ExecutionError, ExecutionResult, ExecutorPayload, StatementOutcome};
+use crate::sql::{bind_parameters, Statement};
+use futures::future::BoxFuture;
+use futures::{future, stream, Future, FutureExt, StreamExt};
+use hyper::body::HttpBody;
+use hyper::header::{self, HeaderValue};
+use hyper::server::accept::{self, Accept};
+use hyper::server::conn::{AddrIncoming, AddrStream};
 use hyper::service::{make_service_fn, service_fn};
 use hyper::{Body, Method, Request, Response, Server, StatusCode};
-use serde::{ser::SerializeMap, Serialize, Serializer};
+use serde::{ser::SerializeMap, Deserialize, Serialize, Serializer};
 use std::collections::HashMap;
-use std::{convert, net, str::FromStr};
+use std::io;
+use std::panic::AssertUnwindSafe;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::{convert, net};
+use subtle::ConstantTimeEq;
 use thiserror::Error;
-use tokio::sync::{mpsc, oneshot};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::sync::{mpsc, oneshot, watch, Semaphore};
 use tokio::time;
-use tracing::*;
+use tokio_rustls::server::TlsStream;
+use tokio_rustls::TlsAcceptor;
+use tracing::{field, Instrument, *};
 use ulid::Ulid;
 
+mod compression;
+mod cors;
+mod formats;
+mod metrics;
+mod tls;
+use compression::ContentEncoding;
+use formats::{ResponseFormat, TimestampFormat};
+use metrics::Metrics;
+pub use tls::load_tls_acceptor;
+
+/// Maximum number of TLS handshakes in progress on a single listener.
+const MAX_CONCURRENT_TLS_HANDSHAKES: usize = 64;
+/// Header carrying the cursor of the next page of a paged SELECT.
+const NEXT_CURSOR_HEADER: &str = "x-next-cursor";
+/// Header carrying the ID of the request, in both directions.
+const REQUEST_ID_HEADER: &str = "x-request-id";
+/// Maximum length of a client-supplied request ID.
+const MAX_REQUEST_ID_LENGTH: usize = 128;
+/// Media type of POST bodies with the query in a form field rather than raw.
+const FORM_MEDIA_TYPE: &str = "application/x-www-form-urlencoded";
+/// Media type of POST bodies with the query and parameter values in a JSON object rather than raw.
+const JSON_MEDIA_TYPE: &str = "application/json";
+
+tokio::task_local! {
+    /// ID of the request being handled, included in error bodies.
+    static REQUEST_ID: String;
+}
+
 #[derive(Error, Debug, PartialEq)]
 #[error("ServerError: {0}")]
 pub struct ServerError(pub String);
@@ -29,142 +73,2442 @@ impl Serialize for ServerError {
     }
 }
 
-async fn process_post(
+/// State shared by all requests handled by the server.
+pub struct ServerState {
+    config: config::Config,
     executor_tx: mpsc::Sender<ExecutorPayload>,
-    body: &str,
-) -> (StatusCode, String) {
-    let statement = parse_statement(&body);
-    if let Err(parsing_error) = statement {
-        return (
-            StatusCode::BAD_REQUEST,
-            serde_json::to_string(&parsing_error).unwrap(),
-        );
+    metrics: Metrics,
+    /// Whether startup recovery – loading the catalog and replaying the write-ahead log – has finished.
+    is_ready: AtomicBool,
+}
+
+impl ServerState {
+    pub fn new(config: &config::Config, executor_tx: mpsc::Sender<ExecutorPayload>) -> Self {
+        ServerState {
+            config: config.clone(),
+            executor_tx,
+            metrics: Metrics::default(),
+            is_ready: AtomicBool::new(false),
+        }
     }
-    let statement = statement.unwrap();
-    if let Err(validation_error) = statement.validate() {
-        return (
-            StatusCode::BAD_REQUEST,
-            serde_json::to_string(&validation_error).unwrap(),
-        );
+
+    /// Mark startup recovery as finished, so that the server reports being ready to take traffic.
+    pub fn mark_ready(&self) {
+        self.is_ready.store(true, Ordering::Release);
     }
-    let (resp_tx, resp_rx) = oneshot::channel::<QueryResult>();
-    if let Err(_) = executor_tx.send((statement, resp_tx)).await {
-        // If there was an error on `send`, that means that the receiver has disconnected for some reason
-        return (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            serde_json::to_string(&ServerError("The query executor has disengaged.".into()))
-                .unwrap(),
-        );
+
+    /// Whether the server is ready to take traffic, i.e. startup recovery has finished.
+    pub fn is_ready(&self) -> bool {
+        self.is_ready.load(Ordering::Acquire)
     }
-    let query_result = resp_rx.await;
-    (
-        StatusCode::OK,
-        serde_json::to_string_pretty(&query_result.unwrap()).unwrap(),
-    )
 }
 
-async fn process_get(
-    _executor_tx: mpsc::Sender<ExecutorPayload>,
-    query: Option<&str>,
-) -> (StatusCode, String) {
-    if let Some(query_string) = query {
-        if let Ok(query_map) = serde_urlencoded::from_str::<HashMap<String, String>>(query_string) {
-            if let Some(query) = query_map.get("query") {
-                // Found SQL
-                (StatusCode::OK, query.to_string())
-                // TODO: Add statement handling
+/// Read the whole request body, unless it turns out to be larger than `limit` bytes, in which case `None` is returned.
+/// The body is checked chunk by chunk, so that an oversized one is never buffered whole.
+async fn read_body_limited(mut body: Body, limit: u64) -> Result<Option<Vec<u8>>, hyper::Error> {
+    if body.size_hint().lower() > limit {
+        return Ok(None);
+    }
+    let mut body_bytes = Vec::new();
+    while let Some(chunk) = body.data().await {
+        let chunk = chunk?;
+        if (body_bytes.len() + chunk.len()) as u64 > limit {
+            return Ok(None);
+        }
+        body_bytes.extend_from_slice(&chunk);
+    }
+    Ok(Some(body_bytes))
+}
+
+/// Response with a JSON body.
+fn json_response(status_code: StatusCode, body: String) -> Response<Body> {
+    Response::builder()
+        .header(header::CONTENT_TYPE, "application/json")
+        .status(status_code)
+        .body(Body::from(body))
+        .unwrap()
+}
+
+/// Check the request's bearer token against the configured one, if any.
+/// The tokens are compared in constant time, so that response timing gives nothing away.
+fn is_authorized(config: &config::Config, req: &Request<Body>) -> bool {
+    let expected_token = match &config.auth_token {
+        Some(expected_token) => expected_token,
+        None => return true,
+    };
+    let provided_token = req
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|authorization| authorization.to_str().ok())
+        .and_then(|authorization| authorization.strip_prefix("Bearer "));
+    match provided_token {
+        Some(provided_token) => provided_token
+            .as_bytes()
+            .ct_eq(expected_token.as_bytes())
+            .into(),
+        None => false,
+    }
+}
+
+/// Error body, i.e. the error's own fields followed by the ID of the request.
+#[derive(Serialize)]
+struct ErrorBody<'e, E: Serialize> {
+    #[serde(flatten)]
+    error: &'e E,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    request_id: Option<String>,
+}
+
+/// Response with a serialized error as the body.
+fn error_response(status_code: StatusCode, error: &impl Serialize) -> Response<Body> {
+    let body = ErrorBody {
+        error,
+        request_id: REQUEST_ID.try_with(Clone::clone).ok(),
+    };
+    json_response(status_code, serde_json::to_string(&body).unwrap())
+}
+
+/// Parse, bind parameters, validate and execute the query, returning the statement kind along with the outcome.
+async fn process_query(
+    state: &ServerState,
+    query: &str,
+    parameter_values: &[DataInstance],
+    cursor: Option<String>,
+    is_read_only: bool,
+) -> Result<(&'static str, StatementOutcome), Response<Body>> {
+    let mut statements = parse_input(&state.config, query).map_err(|parsing_error| {
+        state.metrics.record_parse_error();
+        error_response(StatusCode::BAD_REQUEST, &parsing_error)
+    })?;
+    if is_read_only {
+        if let Some(statement) = statements
+            .iter()
+            .find(|statement| !statement.is_read_only())
+        {
+            let effect = if statement.is_mutation() {
+                "modifies data"
             } else {
-                // No query param
-                (StatusCode::BAD_REQUEST, "TODO: No query param".to_string())
+                "controls a transaction"
+            };
+            let mut response = error_response(
+                StatusCode::METHOD_NOT_ALLOWED,
+                &ServerError(format!(
+                    "Statement `{}` {}, so it can only be sent with POST.",
+                    statement.kind(),
+                    effect
+                )),
+            );
+            response
+                .headers_mut()
+                .insert(header::ALLOW, HeaderValue::from_static("POST"));
+            return Err(response);
+        }
+    }
+    bind_parameters(&mut statements, parameter_values)
+        .map_err(|binding_error| error_response(StatusCode::BAD_REQUEST, &binding_error))?;
+    if let Some(cursor) = cursor {
+        match statements.as_mut_slice() {
+            [Statement::Select(select)] => select.cursor = Some(cursor),
+            _ => {
+                return Err(error_response(
+                    StatusCode::BAD_REQUEST,
+                    &ServerError(
+                        "The `cursor` parameter only applies to a single SELECT statement.".into(),
+                    ),
+                ))
             }
-        } else {
-            // Bad query string
+        }
+    }
+    let statement_kind = match statements.as_slice() {
+        [statement] => statement.kind(),
+        _ => "batch",
+    };
+    Span::current().record("statement_kind", statement_kind);
+    if let Err(validation_error) = validate_statements(&state.config, &statements) {
+        return Err(error_response(StatusCode::BAD_REQUEST, &validation_error));
+    }
+    let (resp_tx, resp_rx) = oneshot::channel::<ExecutionResult>();
+    if state.executor_tx.send((statements, resp_tx)).await.is_err() {
+        // If there was an error on `send`, that means that the receiver has disconnected for some reason
+        return Err(error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            &ServerError("The query executor has disengaged.".into()),
+        ));
+    }
+    let query_timeout = time::Duration::from_millis(state.config.query_timeout_ms);
+    // On timeout the executor isn't interrupted, the result just won't be awaited anymore
+    let execution_result = match time::timeout(query_timeout, resp_rx).await {
+        Ok(execution_result) => execution_result,
+        Err(_) => {
+            return Err(error_response(
+                StatusCode::GATEWAY_TIMEOUT,
+                &ServerError(format!(
+                    "The query exceeded its deadline of {} ms.",
+                    state.config.query_timeout_ms
+                )),
+            ))
+        }
+    };
+    match execution_result {
+        Ok(Ok(outcome)) => Ok((statement_kind, outcome)),
+        Ok(Err(ExecutionError::Validation(validation_error))) => {
+            Err(error_response(StatusCode::BAD_REQUEST, &validation_error))
+        }
+        Ok(Err(execution_error)) => Err(error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            &execution_error,
+        )),
+        Err(_) => Err(error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            &ServerError("The query executor dropped the statement.".into()),
+        )),
+    }
+}
+
+/// Parse params from the query string of the request URI.
+fn parse_query_params(query_string: Option<&str>) -> Result<HashMap<String, String>, ServerError> {
+    match query_string {
+        Some(query_string) => serde_urlencoded::from_str(query_string)
+            .map_err(|_| ServerError("The query string is malformed.".into())),
+        None => Ok(HashMap::new()),
+    }
+}
+
+/// Make sure that there are no params other than the supported ones, so that typos don't go unnoticed.
+/// `source` is where the params come from, e.g. "the query string".
+fn reject_unknown_params(
+    query_params: &HashMap<String, String>,
+    supported_params: &[&str],
+    source: &str,
+) -> Result<(), ServerError> {
+    let mut unknown_params: Vec<&str> = query_params
+        .keys()
+        .map(String::as_str)
+        .filter(|param| !supported_params.contains(param))
+        .collect();
+    if unknown_params.is_empty() {
+        return Ok(());
+    }
+    unknown_params.sort_unstable();
+    let quote_all = |params: &[&str]| {
+        params
+            .iter()
+            .map(|param| format!("`{}`", param))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    Err(ServerError(format!(
+        "Unknown parameters in {}: {}. Supported ones are: {}.",
+        source,
+        quote_all(&unknown_params),
+        quote_all(supported_params)
+    )))
+}
+
+/// Parse parameter values from the `params` param, which is a JSON array.
+/// Numbers are taken like integer literals, i.e. as `UINT32` unless they're larger.
+fn parse_parameter_values(
+    query_params: &HashMap<String, String>,
+) -> Result<Vec<DataInstance>, ServerError> {
+    let raw_values = match query_params.get("params") {
+        Some(raw_values) => {
+            serde_json::from_str::<Vec<serde_json::Value>>(raw_values).map_err(|error| {
+                ServerError(format!(
+                    "The `params` parameter must be a JSON array: {}.",
+                    error
+                ))
+            })?
+        }
+        None => return Ok(Vec::new()),
+    };
+    convert_parameter_values(raw_values)
+}
+
+/// Turn JSON values into parameter values.
+fn convert_parameter_values(
+    raw_values: Vec<serde_json::Value>,
+) -> Result<Vec<DataInstance>, ServerError> {
+    raw_values
+        .into_iter()
+        .enumerate()
+        .map(|(index, raw_value)| {
+            Ok(match raw_value {
+                serde_json::Value::Null => DataInstance::Null,
+                serde_json::Value::Bool(value) => DataInstance::Direct(DataInstanceRaw::Bool(value)),
+                serde_json::Value::String(value) => {
+                    DataInstance::Direct(DataInstanceRaw::String(value))
+                }
+                serde_json::Value::Number(ref number) if number.is_u64() => {
+                    let value = number.as_u64().unwrap();
+                    DataInstance::Direct(match u32::try_from(value) {
+                        Ok(value) => DataInstanceRaw::UInt32(value),
+                        Err(_) => DataInstanceRaw::UInt64(value),
+                    })
+                }
+                raw_value => {
+                    return Err(ServerError(format!(
+                        "Value `{}` of parameter `${}` is not supported. Supported ones are unsigned integers, booleans, strings and null.",
+                        raw_value,
+                        index + 1
+                    )))
+                }
+            })
+        })
+        .collect()
+}
+
+/// Pick the result format, with the `format` param taking precedence over the Accept header.
+fn negotiate_format(
+    query_params: &HashMap<String, String>,
+    accept: Option<&HeaderValue>,
+) -> Result<ResponseFormat, (StatusCode, ServerError)> {
+    if let Some(format_name) = query_params.get("format") {
+        return ResponseFormat::from_name(format_name).ok_or_else(|| {
             (
                 StatusCode::BAD_REQUEST,
-                "TODO: Bad query string".to_string(),
+                ServerError(format!(
+                    "Format `{}` is not supported. Supported ones are: {}.",
+                    format_name,
+                    ResponseFormat::SUPPORTED_NAMES
+                )),
             )
-        }
+        });
+    }
+    ResponseFormat::from_accept_header(accept).ok_or_else(|| {
+        (
+            StatusCode::NOT_ACCEPTABLE,
+            ServerError(format!(
+                "None of the media types in the Accept header are supported. Supported ones are: {}.",
+                ResponseFormat::SUPPORTED_MEDIA_TYPES
+            )),
+        )
+    })
+}
+
+/// Pick how timestamps are rendered from the `timestamps` param, with microseconds since Unix epoch by default.
+fn parse_timestamp_format(
+    query_params: &HashMap<String, String>,
+) -> Result<TimestampFormat, ServerError> {
+    match query_params.get("timestamps") {
+        Some(timestamp_format_name) => TimestampFormat::from_name(timestamp_format_name)
+            .ok_or_else(|| {
+                ServerError(format!(
+                    "Timestamp format `{}` is not supported. Supported ones are: {}.",
+                    timestamp_format_name,
+                    TimestampFormat::SUPPORTED_NAMES
+                ))
+            }),
+        None => Ok(TimestampFormat::default()),
+    }
+}
+
+/// Media type of the request body, lowercase and without parameters such as `charset`.
+fn body_media_type(req: &Request<Body>) -> Option<String> {
+    let content_type = req.headers().get(header::CONTENT_TYPE)?.to_str().ok()?;
+    let media_type = content_type.split(';').next().unwrap_or_default().trim();
+    Some(media_type.to_ascii_lowercase())
+}
+
+/// Query of a JSON POST body.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct JsonQuery {
+    query: String,
+    /// Values of the query's parameters, like in the `params` param.
+    params: Option<Vec<serde_json::Value>>,
+}
+
+/// Get SQL from the body of a POST request – the `query` field if the body is a form or JSON, otherwise
+/// the whole body. A JSON body may carry parameter values as well.
+async fn extract_post_query(
+    state: &ServerState,
+    req: Request<Body>,
+) -> Result<(String, Option<Vec<DataInstance>>), Response<Body>> {
+    let media_type = body_media_type(&req);
+    let body_bytes = read_post_body(state, req.into_body()).await?;
+    if media_type.as_deref() == Some(JSON_MEDIA_TYPE) {
+        let json_query = serde_json::from_slice::<JsonQuery>(&body_bytes).map_err(|error| {
+            error_response(
+                StatusCode::BAD_REQUEST,
+                &ServerError(format!("The JSON request body is malformed: {}.", error)),
+            )
+        })?;
+        let parameter_values = json_query
+            .params
+            .map(convert_parameter_values)
+            .transpose()
+            .map_err(|error| error_response(StatusCode::BAD_REQUEST, &error))?;
+        return Ok((json_query.query, parameter_values));
+    }
+    if media_type.as_deref() == Some(FORM_MEDIA_TYPE) {
+        let mut form_fields = serde_urlencoded::from_bytes::<HashMap<String, String>>(&body_bytes)
+            .map_err(|_| {
+                error_response(
+                    StatusCode::BAD_REQUEST,
+                    &ServerError("The form in the request body is malformed.".into()),
+                )
+            })?;
+        reject_unknown_params(&form_fields, &["query"], "the form")
+            .map_err(|error| error_response(StatusCode::BAD_REQUEST, &error))?;
+        return match form_fields.remove("query") {
+            Some(query) => Ok((query, None)),
+            None => Err(error_response(
+                StatusCode::BAD_REQUEST,
+                &ServerError("The `query` field is missing from the form.".into()),
+            )),
+        };
+    }
+    match String::from_utf8(body_bytes) {
+        Ok(query) => Ok((query, None)),
+        Err(_) => Err(error_response(
+            StatusCode::BAD_REQUEST,
+            &ServerError("The request body is not valid UTF-8.".into()),
+        )),
+    }
+}
+
+/// Read the body of a POST request, as long as it's within the configured size limit.
+async fn read_post_body(state: &ServerState, body: Body) -> Result<Vec<u8>, Response<Body>> {
+    match read_body_limited(body, state.config.max_request_bytes).await {
+        Ok(Some(body_bytes)) => Ok(body_bytes),
+        Ok(None) => Err(error_response(
+            StatusCode::PAYLOAD_TOO_LARGE,
+            &ServerError(format!(
+                "The request body exceeds the limit of {} bytes.",
+                state.config.max_request_bytes
+            )),
+        )),
+        Err(error) => Err(error_response(
+            StatusCode::BAD_REQUEST,
+            &ServerError(format!("The request body could not be read: {}", error)),
+        )),
+    }
+}
+
+/// Execute the query from a POST body or GET `query` param, serializing the result in the negotiated format.
+async fn handle_query(state: &ServerState, req: Request<Body>) -> Response<Body> {
+    let mut query_params = match parse_query_params(req.uri().query()) {
+        Ok(query_params) => query_params,
+        Err(error) => return error_response(StatusCode::BAD_REQUEST, &error),
+    };
+    // With POST the query is in the body
+    let supported_params: &[&str] = if req.method() == Method::POST {
+        &["format", "params", "timestamps", "cursor"]
+    } else {
+        &["query", "format", "params", "timestamps", "cursor"]
+    };
+    if let Err(error) = reject_unknown_params(&query_params, supported_params, "the query string") {
+        return error_response(StatusCode::BAD_REQUEST, &error);
+    }
+    let format = match negotiate_format(&query_params, req.headers().get(header::ACCEPT)) {
+        Ok(format) => format,
+        Err((status_code, error)) => return error_response(status_code, &error),
+    };
+    let timestamp_format = match parse_timestamp_format(&query_params) {
+        Ok(timestamp_format) => timestamp_format,
+        Err(error) => return error_response(StatusCode::BAD_REQUEST, &error),
+    };
+    let content_encoding =
+        ContentEncoding::from_accept_encoding_header(req.headers().get(header::ACCEPT_ENCODING));
+    let parameter_values = match parse_parameter_values(&query_params) {
+        Ok(parameter_values) => parameter_values,
+        Err(error) => return error_response(StatusCode::BAD_REQUEST, &error),
+    };
+    let is_read_only = req.method() == Method::GET;
+    let query = if is_read_only {
+        query_params
+            .remove("query")
+            .map(|query| (query, None))
+            .ok_or_else(|| {
+                error_response(
+                    StatusCode::BAD_REQUEST,
+                    &ServerError("The `query` parameter is missing.".into()),
+                )
+            })
     } else {
-        // No query string
-        (StatusCode::BAD_REQUEST, "TODO: No query string".to_string())
+        extract_post_query(state, req).await
+    };
+    let (query, parameter_values) = match query {
+        Ok((_, Some(_))) if query_params.contains_key("params") => {
+            return error_response(
+                StatusCode::BAD_REQUEST,
+                &ServerError(
+                    "Parameter values can't be given in both the `params` parameter and the request body."
+                        .into(),
+                ),
+            )
+        }
+        Ok((query, body_parameter_values)) => {
+            (query, body_parameter_values.unwrap_or(parameter_values))
+        }
+        Err(response) => return response,
+    };
+    let cursor = query_params.remove("cursor");
+    let query_result = process_query(state, &query, &parameter_values, cursor, is_read_only).await;
+    match query_result {
+        // The result is serialized as the body is streamed, so that large results don't need to be serialized upfront
+        Ok((statement_kind, outcome)) => {
+            // In JSON the cursor is also in the body, but CSV and NDJSON only have rows
+            let next_cursor = match &outcome {
+                StatementOutcome::Page {
+                    next_cursor: Some(next_cursor),
+                    ..
+                } => Some(next_cursor.clone()),
+                _ => None,
+            };
+            let (chunks, content_encoding) = compression::compress_chunks(
+                format.serialize_chunks(statement_kind, outcome, timestamp_format),
+                content_encoding,
+            );
+            let mut response = Response::builder()
+                .header(header::CONTENT_TYPE, format.content_type())
+                .header(header::VARY, "Accept-Encoding")
+                .status(StatusCode::OK);
+            if let Some(content_encoding) = content_encoding {
+                response = response.header(header::CONTENT_ENCODING, content_encoding.name());
+            }
+            if let Some(next_cursor) = next_cursor {
+                response = response.header(NEXT_CURSOR_HEADER, next_cursor);
+            }
+            response
+                .body(Body::wrap_stream(stream::iter(
+                    chunks.map(Ok::<_, convert::Infallible>),
+                )))
+                .unwrap()
+        }
+        Err(response) => response,
     }
 }
 
-async fn echo(
-    executor_tx: mpsc::Sender<ExecutorPayload>,
+/// ID of the request supplied by the client, if it's there and sensible: up to 128 characters
+/// that are ASCII alphanumerics, `-`, `_` or `.` – which covers ULIDs and UUIDs, among others.
+fn client_request_id(req: &Request<Body>) -> Option<String> {
+    let request_id = req.headers().get(REQUEST_ID_HEADER)?.to_str().ok()?;
+    let is_valid = !request_id.is_empty()
+        && request_id.len() <= MAX_REQUEST_ID_LENGTH
+        && request_id
+            .chars()
+            .all(|character| character.is_ascii_alphanumeric() || "-_.".contains(character));
+    is_valid.then(|| request_id.to_string())
+}
+
+async fn echo(state: Arc<ServerState>, req: Request<Body>) -> Result<Response<Body>, hyper::Error> {
+    // The client's own ID is reused, so that its calls can be correlated with server logs
+    let request_id = client_request_id(&req).unwrap_or_else(|| Ulid::new().to_string());
+    // Fields of the span are attached to every event logged while handling the request
+    let span = info_span!(
+        "request",
+        request_id = %request_id,
+        method = %req.method(),
+        path = %req.uri().path(),
+        statement_kind = field::Empty,
+    );
+    let mut response = isolate_panics(
+        &request_id,
+        REQUEST_ID.scope(request_id.clone(), route(state, req)),
+    )
+    .instrument(span)
+    .await?;
+    // The ID is made up of visible ASCII characters only, so it's always a valid header value
+    response.headers_mut().insert(
+        REQUEST_ID_HEADER,
+        HeaderValue::from_str(&request_id).unwrap(),
+    );
+    Ok(response)
+}
+
+/// Drive handling of a request, turning a panic along the way into a 500 response, rather than a dropped connection.
+async fn isolate_panics(
+    request_id: &str,
+    handling: impl Future<Output = Result<Response<Body>, hyper::Error>>,
+) -> Result<Response<Body>, hyper::Error> {
+    match AssertUnwindSafe(handling).catch_unwind().await {
+        Ok(result) => result,
+        Err(panic) => {
+            let panic_message = panic
+                .downcast_ref::<&str>()
+                .map(|message| message.to_string())
+                .or_else(|| panic.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown cause".into());
+            error!(panic = %panic_message, "💥 Panicked while handling request");
+            Ok(json_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                serde_json::json!({
+                    "type": "server",
+                    "message": "The server failed unexpectedly while handling the request.",
+                    "request_id": request_id,
+                })
+                .to_string(),
+            ))
+        }
+    }
+}
+
+async fn route(
+    state: Arc<ServerState>,
     req: Request<Body>,
 ) -> Result<Response<Body>, hyper::Error> {
     let timer = time::Instant::now();
-    let request_id = Ulid::new();
-    debug!("⚡️ Received request ID {}", request_id);
-    let response_builder = Response::builder().header("Content-Type", "application/json");
-    let result = match (req.uri().path(), req.method()) {
-        ("/", &Method::POST) => {
-            // Read-write
-            let body_bytes = hyper::body::to_bytes(req.into_body()).await?;
-            let body = String::from_utf8(body_bytes.into_iter().collect()).unwrap();
-            // Found SQL
-            let (status_code, response_string) = process_post(executor_tx, &body).await;
-            Ok(response_builder
-                .header("Content-Type", "application/json")
-                .status(status_code)
-                .body(Body::from(response_string))
-                .unwrap())
-        }
-        ("/", &Method::GET) => {
-            // Read-only
-            let query = req.uri().query();
-            let (status_code, response_string) = process_get(executor_tx, query).await;
-            Ok(response_builder
-                .status(status_code)
-                .body(Body::from(response_string))
-                .unwrap())
-        }
-        ("/", _) => Ok(response_builder
+    debug!("⚡️ Received request");
+    let method = req.method().clone();
+    let allowed_origin = cors::allowed_origin(&state.config, req.headers());
+    let mut response = match (req.uri().path(), req.method()) {
+        // Preflight requests never carry credentials, so they're answered before authorization
+        (_, &Method::OPTIONS) => cors::preflight_response(allowed_origin.as_ref()),
+        ("/", &Method::POST) | ("/", &Method::GET) if !is_authorized(&state.config, &req) => {
+            let mut response = error_response(
+                StatusCode::UNAUTHORIZED,
+                &ServerError("A valid bearer token is required.".into()),
+            );
+            response
+                .headers_mut()
+                .insert(header::WWW_AUTHENTICATE, HeaderValue::from_static("Bearer"));
+            response
+        }
+        ("/", &Method::POST) | ("/", &Method::GET) => handle_query(&state, req).await,
+        ("/health", &Method::GET) => json_response(
+            StatusCode::OK,
+            serde_json::json!({ "status": "ok" }).to_string(),
+        ),
+        ("/ready", &Method::GET) if state.is_ready() => json_response(
+            StatusCode::OK,
+            serde_json::json!({ "status": "ready" }).to_string(),
+        ),
+        ("/ready", &Method::GET) => json_response(
+            StatusCode::SERVICE_UNAVAILABLE,
+            serde_json::json!({ "status": "loading" }).to_string(),
+        ),
+        ("/metrics", &Method::GET) => Response::builder()
+            .header(header::CONTENT_TYPE, "text/plain; version=0.0.4")
+            .status(StatusCode::OK)
+            .body(Body::from(state.metrics.render()))
+            .unwrap(),
+        ("/", _) => Response::builder()
             .status(StatusCode::METHOD_NOT_ALLOWED)
             .body(Body::default())
-            .unwrap()),
-        _ => Ok(response_builder
+            .unwrap(),
+        _ => Response::builder()
             .status(StatusCode::NOT_FOUND)
             .body(Body::default())
-            .unwrap()),
+            .unwrap(),
     };
-    debug!(
-        "🪃 Finished request ID {} in {} µs",
-        request_id,
-        timer.elapsed().as_micros()
+    cors::add_cors_headers(&mut response, allowed_origin);
+    let duration = timer.elapsed();
+    state.metrics.record_request(&method, duration);
+    info!(
+        status = response.status().as_u16(),
+        duration_us = duration.as_micros() as u64,
+        "🪃 Finished request"
     );
-    result
+    Ok(response)
 }
 
+/// Wait for a signal to shut down – Ctrl+C, or on Unix also SIGTERM (as sent by container orchestrators).
 async fn shutdown_signal() {
-    tokio::signal::ctrl_c()
-        .await
-        .expect("Failed to install Ctrl+C signal handler");
-    info!("💤 Shutting down gracefully...");
+    let ctrl_c = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("Failed to install Ctrl+C signal handler");
+    };
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("Failed to install SIGTERM signal handler")
+            .recv()
+            .await;
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+    let signal_name = tokio::select! {
+        _ = ctrl_c => "Ctrl+C",
+        _ = terminate => "SIGTERM",
+    };
+    info!("💤 Received {}, shutting down gracefully...", signal_name);
 }
 
-/// Start server loop.
-pub async fn start_server(config: &config::Config, executor_tx: mpsc::Sender<ExecutorPayload>) {
-    let tcp_listen_address = net::SocketAddr::new(
-        net::IpAddr::from_str(&config.tcp_listen_host).unwrap(),
-        config.tcp_listen_port,
-    );
+/// Bind to each of the addresses. A failure is reported for its address alone,
+/// so that the server can still listen on the other ones.
+fn bind_all(addresses: &[net::SocketAddr]) -> Vec<AddrIncoming> {
+    addresses
+        .iter()
+        .filter_map(|address| match AddrIncoming::bind(address) {
+            Ok(incoming) => Some(incoming),
+            Err(error) => {
+                error!("‼️ Failed to listen on {}: {}", address, error);
+                None
+            }
+        })
+        .collect()
+}
+
+/// Incoming TLS connections. Handshakes are performed concurrently, so that a slow client doesn't hold up
+/// others, and they must finish within the timeout.
+fn accept_tls(
+    mut incoming: AddrIncoming,
+    tls_acceptor: TlsAcceptor,
+    handshake_timeout: time::Duration,
+) -> impl Accept<Conn = TlsStream<AddrStream>, Error = io::Error> {
+    let connections = stream::poll_fn(move |cx| Pin::new(&mut incoming).poll_accept(cx))
+        .filter_map(|connection| {
+            future::ready(
+                connection
+                    .map_err(|error| debug!("🔒 Failed to accept a connection: {}", error))
+                    .ok(),
+            )
+        })
+        .map(move |connection| time::timeout(handshake_timeout, tls_acceptor.accept(connection)))
+        .buffer_unordered(MAX_CONCURRENT_TLS_HANDSHAKES)
+        .filter_map(|handshake_result| {
+            future::ready(match handshake_result {
+                Ok(Ok(connection)) => Some(Ok(connection)),
+                Ok(Err(error)) => {
+                    debug!("🔒 TLS handshake failed: {}", error);
+                    None
+                }
+                Err(_) => {
+                    debug!("🔒 TLS handshake timed out");
+                    None
+                }
+            })
+        });
+    accept::from_stream(connections)
+}
 
-    let server = Server::bind(&tcp_listen_address)
-        .serve(make_service_fn(move |_conn| {
-            let executor_tx = executor_tx.clone();
+/// Serve connections from `incoming` until a shutdown is signaled, each one taking up one of the slots.
+fn serve_connections<I, IO>(
+    state: Arc<ServerState>,
+    connection_slots: Arc<Semaphore>,
+    incoming: I,
+    mut shutdown_rx: watch::Receiver<()>,
+) -> BoxFuture<'static, hyper::Result<()>>
+where
+    I: Accept<Conn = IO, Error = io::Error> + Send + 'static,
+    IO: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    Server::builder(incoming)
+        .http1_keepalive(state.config.http_keep_alive)
+        .http1_header_read_timeout(time::Duration::from_millis(
+            state.config.header_read_timeout_ms,
+        ))
+        .serve(make_service_fn(move |_conn: &IO| {
+            let state = state.clone();
+            let connection_slots = connection_slots.clone();
             async move {
-                Ok::<_, convert::Infallible>(service_fn(move |req| echo(executor_tx.clone(), req)))
+                // The connection isn't served until there's a free slot, which is held until it's closed
+                let connection_slot = connection_slots.acquire_owned().await.unwrap();
+                Ok::<_, convert::Infallible>(service_fn(move |req| {
+                    let _ = &connection_slot;
+                    echo(state.clone(), req)
+                }))
             }
         }))
-        .with_graceful_shutdown(shutdown_signal());
+        .with_graceful_shutdown(async move {
+            // An error means that the sender is gone, which is just as good a signal
+            let _ = shutdown_rx.changed().await;
+        })
+        .boxed()
+}
+
+/// Serve on all the listeners concurrently, sharing the state, until `shutdown` resolves.
+/// With a TLS acceptor, only TLS connections are accepted.
+async fn serve_all(
+    state: Arc<ServerState>,
+    listeners: Vec<AddrIncoming>,
+    tls_acceptor: Option<TlsAcceptor>,
+    shutdown: impl Future<Output = ()>,
+) {
+    let (shutdown_tx, shutdown_rx) = watch::channel(());
+    // Shared by all listeners, as the limit is on connections in total. Tokio semaphores can't hold more
+    // permits than `usize::MAX >> 3`, which is way beyond any sensible limit anyway
+    let connection_slot_count = usize::try_from(state.config.max_connections)
+        .unwrap_or(usize::MAX)
+        .min(usize::MAX >> 3);
+    let connection_slots = Arc::new(Semaphore::new(connection_slot_count));
+    let servers = listeners.into_iter().map(|incoming| {
+        let tcp_listen_address = incoming.local_addr();
+        let server = match &tls_acceptor {
+            Some(tls_acceptor) => serve_connections(
+                state.clone(),
+                connection_slots.clone(),
+                accept_tls(
+                    incoming,
+                    tls_acceptor.clone(),
+                    time::Duration::from_millis(state.config.header_read_timeout_ms),
+                ),
+                shutdown_rx.clone(),
+            ),
+            None => serve_connections(
+                state.clone(),
+                connection_slots.clone(),
+                incoming,
+                shutdown_rx.clone(),
+            ),
+        };
+        info!(
+            "👂 Server listening on {}{}...",
+            tcp_listen_address,
+            if tls_acceptor.is_some() { " (TLS)" } else { "" }
+        );
+        async move {
+            if let Err(e) = server.await {
+                error!(
+                    "‼️ Encountered server error on {}: {}",
+                    tcp_listen_address, e
+                );
+            } else {
+                debug!("⏹ Server no longer listening on {}", tcp_listen_address);
+            }
+        }
+    });
+    let all_servers = future::join_all(servers);
+    tokio::pin!(all_servers);
+    tokio::select! {
+        _ = &mut all_servers => return,
+        _ = shutdown => {}
+    }
+    let _ = shutdown_tx.send(());
+    // No new connections are accepted from now on, while requests in flight get some time to finish
+    let drain_timeout = time::Duration::from_millis(state.config.shutdown_drain_timeout_ms);
+    if time::timeout(drain_timeout, all_servers).await.is_err() {
+        // Statements already sent to the executor are still executed, only their responses are lost
+        warn!(
+            "⌛ Abandoning {} connection(s) still open after the drain timeout of {} ms",
+            connection_slot_count - connection_slots.available_permits(),
+            state.config.shutdown_drain_timeout_ms
+        );
+    }
+}
+
+/// Serve like `serve_all` while `recovery` runs, marking the server ready once it succeeds.
+/// If recovery fails instead, the server shuts down and the error is returned.
+async fn serve_recovering(
+    state: Arc<ServerState>,
+    listeners: Vec<AddrIncoming>,
+    tls_acceptor: Option<TlsAcceptor>,
+    recovery: impl Future<Output = Result<(), io::Error>>,
+    shutdown: impl Future<Output = ()>,
+) -> Result<(), io::Error> {
+    let mut recovery_error = None;
+    let shutdown_or_failed_recovery = async {
+        let recovery = async {
+            match recovery.await {
+                Ok(()) => {
+                    state.mark_ready();
+                    info!("🟢 Server ready");
+                    future::pending::<()>().await
+                }
+                Err(error) => recovery_error = Some(error),
+            }
+        };
+        tokio::select! {
+            _ = shutdown => {}
+            _ = recovery => {}
+        }
+    };
+    serve_all(
+        state.clone(),
+        listeners,
+        tls_acceptor,
+        shutdown_or_failed_recovery,
+    )
+    .await;
+    match recovery_error {
+        Some(error) => Err(error),
+        None => Ok(()),
+    }
+}
 
-    info!("👂 Server listening on {}...", tcp_listen_address);
+/// Serve until a shutdown signal. The server listens right away, so it's live (`/health`) during `recovery`,
+/// but only ready (`/ready`) once recovery succeeds – until then statements wait for the executor.
+pub async fn start_server(
+    config: &config::Config,
+    executor_tx: mpsc::Sender<ExecutorPayload>,
+    tls_acceptor: Option<TlsAcceptor>,
+    recovery: impl Future<Output = Result<(), io::Error>>,
+) -> Result<(), io::Error> {
+    let addresses = config.listen_addresses();
+    let listeners = bind_all(&addresses);
+    if listeners.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::AddrNotAvailable,
+            format!(
+                "Could not listen on any of the addresses: {}",
+                addresses
+                    .iter()
+                    .map(|address| address.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        ));
+    }
+    let state = Arc::new(ServerState::new(config, executor_tx));
+    serve_recovering(state, listeners, tls_acceptor, recovery, shutdown_signal()).await
+}
 
-    if let Err(e) = server.await {
-        error!("‼️ Encountered server error: {}", e);
-    } else {
-        debug!("⏹ Server no longer listening");
+#[cfg(test)]
+mod server_tests {
+    use super::*;
+    use crate::constructs::components::{DataInstance, DataInstanceRaw};
+    use crate::executor::{Executor, QueryResult};
+    use crate::storage::Row;
+    use pretty_assertions::{assert_eq, assert_ne};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpStream;
+    use tokio_rustls::rustls;
+
+    /// Server state with an executor channel that doesn't lead anywhere.
+    fn get_test_state_with_config(config: &config::Config) -> Arc<ServerState> {
+        let (executor_tx, _executor_rx) = mpsc::channel(1);
+        Arc::new(ServerState::new(config, executor_tx))
+    }
+
+    fn get_test_state() -> Arc<ServerState> {
+        get_test_state_with_config(&config::Config::default())
+    }
+
+    /// Request ID sent by test requests, so that error bodies are predictable.
+    const TEST_REQUEST_ID: &str = "test-request";
+
+    /// Send a request to the service, returning the response status and body.
+    /// The request is given ID `TEST_REQUEST_ID`, unless it already has one.
+    async fn request(state: &Arc<ServerState>, mut req: Request<Body>) -> (StatusCode, String) {
+        req.headers_mut()
+            .entry(REQUEST_ID_HEADER)
+            .or_insert(HeaderValue::from_static(TEST_REQUEST_ID));
+        let response = echo(state.clone(), req).await.unwrap();
+        let status = response.status();
+        let body_bytes = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        (status, String::from_utf8(body_bytes.to_vec()).unwrap())
+    }
+
+    /// Server state with an executor stand-in that returns the same result for every statement.
+    fn get_test_state_with_fixed_result() -> Arc<ServerState> {
+        let (executor_tx, mut executor_rx) = mpsc::channel::<ExecutorPayload>(1);
+        tokio::spawn(async move {
+            while let Some((_statements, resp_tx)) = executor_rx.recv().await {
+                let _ = resp_tx.send(Ok(StatementOutcome::Rows(QueryResult {
+                    column_names: vec!["id".into(), "name".into()],
+                    rows: vec![Row(vec![
+                        DataInstance::Direct(DataInstanceRaw::UInt8(1)),
+                        DataInstance::Direct(DataInstanceRaw::String("foo".into())),
+                    ])],
+                })));
+            }
+        });
+        Arc::new(ServerState::new(&config::Config::default(), executor_tx))
+    }
+
+    /// Server state with an executor stand-in that acknowledges every statement, passing the statements
+    /// on to the returned receiver.
+    fn get_test_state_recording_statements() -> (
+        Arc<ServerState>,
+        mpsc::UnboundedReceiver<Vec<crate::sql::Statement>>,
+    ) {
+        let (executor_tx, mut executor_rx) = mpsc::channel::<ExecutorPayload>(1);
+        let (statements_tx, statements_rx) = mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            while let Some((statements, resp_tx)) = executor_rx.recv().await {
+                let _ = statements_tx.send(statements);
+                let _ = resp_tx.send(Ok(StatementOutcome::Done));
+            }
+        });
+        (
+            Arc::new(ServerState::new(&config::Config::default(), executor_tx)),
+            statements_rx,
+        )
+    }
+
+    /// Send a query with the given Accept header, returning the response status, Content-Type and body.
+    async fn request_with_accept(
+        state: &Arc<ServerState>,
+        accept: Option<&str>,
+    ) -> (StatusCode, Option<String>, String) {
+        let mut req = Request::post("/").header(REQUEST_ID_HEADER, TEST_REQUEST_ID);
+        if let Some(accept) = accept {
+            req = req.header(header::ACCEPT, accept);
+        }
+        let response = echo(
+            state.clone(),
+            req.body(Body::from("SELECT * FROM test")).unwrap(),
+        )
+        .await
+        .unwrap();
+        let status = response.status();
+        let content_type = response
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .map(|content_type| content_type.to_str().unwrap().to_string());
+        let body_bytes = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        (
+            status,
+            content_type,
+            String::from_utf8(body_bytes.to_vec()).unwrap(),
+        )
+    }
+
+    #[tokio::test]
+    async fn health_check_works() {
+        let (status, body) = request(
+            &get_test_state(),
+            Request::get("/health").body(Body::default()).unwrap(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, r#"{"status":"ok"}"#);
+    }
+
+    #[tokio::test]
+    async fn query_without_statement_is_rejected() {
+        let state = get_test_state();
+        for query in ["", " \n ", "-- TODO"] {
+            let (status, body) =
+                request(&state, Request::post("/").body(Body::from(query)).unwrap()).await;
+            assert_eq!(status, StatusCode::BAD_REQUEST);
+            assert_eq!(
+                body,
+                r#"{"type":"syntax","message":"Found no statement to run, as the input is empty or only has whitespace and comments.","line":1,"column":1,"request_id":"test-request"}"#
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn form_encoded_query_is_parsed_like_raw_one() {
+        let (state, mut statements_rx) = get_test_state_recording_statements();
+        let (status, _) = request(
+            &state,
+            Request::post("/")
+                .body(Body::from(
+                    "SELECT name FROM test WHERE id = 1 AND name = 'a+b'",
+                ))
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let raw_statements = statements_rx.recv().await.unwrap();
+        let (status, _) = request(
+            &state,
+            Request::post("/")
+                .header(
+                    header::CONTENT_TYPE,
+                    "Application/X-WWW-Form-URLEncoded; charset=UTF-8",
+                )
+                .body(Body::from(
+                    "query=SELECT+name+FROM+test+WHERE+id+%3D+1+AND+name+%3D+%27a%2Bb%27",
+                ))
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(statements_rx.recv().await.unwrap(), raw_statements);
+    }
+
+    #[tokio::test]
+    async fn json_query_with_params_is_bound() {
+        let (state, mut statements_rx) = get_test_state_recording_statements();
+        let (status, _) = request(
+            &state,
+            Request::post("/")
+                .body(Body::from(
+                    "SELECT name FROM test WHERE id = 1 AND name = 'a' AND note IS NULL",
+                ))
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let literal_statements = statements_rx.recv().await.unwrap();
+        let (status, _) = request(
+            &state,
+            Request::post("/")
+                .header(header::CONTENT_TYPE, "application/json; charset=utf-8")
+                .body(Body::from(
+                    r#"{"query":"SELECT name FROM test WHERE id = ? AND name = $2 AND note IS NULL","params":[1,"a"]}"#,
+                ))
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(statements_rx.recv().await.unwrap(), literal_statements);
+    }
+
+    #[tokio::test]
+    async fn malformed_json_query_is_rejected() {
+        let state = get_test_state();
+        for (json_query, expected_message) in [
+            (
+                r#"{"query":"SHOW TABLES""#,
+                "The JSON request body is malformed: EOF while parsing an object at line 1 column 22.",
+            ),
+            (
+                r#"{"params":[]}"#,
+                "The JSON request body is malformed: missing field `query` at line 1 column 13.",
+            ),
+            (
+                r#"{"query":"SELECT * FROM test WHERE id = ?","params":[-1]}"#,
+                "Value `-1` of parameter `$1` is not supported. Supported ones are unsigned integers, booleans, strings and null.",
+            ),
+        ] {
+            let (status, body) = request(
+                &state,
+                Request::post("/")
+                    .header(header::CONTENT_TYPE, JSON_MEDIA_TYPE)
+                    .body(Body::from(json_query))
+                    .unwrap(),
+            )
+            .await;
+            assert_eq!(status, StatusCode::BAD_REQUEST);
+            assert_eq!(
+                body,
+                format!(
+                    r#"{{"type":"server","message":"{}","request_id":"test-request"}}"#,
+                    expected_message
+                )
+            );
+        }
+        let (status, body) = request(
+            &state,
+            Request::post("/?params=%5B1%5D")
+                .header(header::CONTENT_TYPE, JSON_MEDIA_TYPE)
+                .body(Body::from(
+                    r#"{"query":"SELECT * FROM test WHERE id = ?","params":[1]}"#,
+                ))
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(
+            body,
+            r#"{"type":"server","message":"Parameter values can't be given in both the `params` parameter and the request body.","request_id":"test-request"}"#
+        );
+    }
+
+    #[tokio::test]
+    async fn form_without_query_field_is_rejected() {
+        let state = get_test_state();
+        for (form, expected_message) in [
+            ("", "The `query` field is missing from the form."),
+            (
+                "qeury=SHOW+TABLES",
+                "Unknown parameters in the form: `qeury`. Supported ones are: `query`.",
+            ),
+        ] {
+            let (status, body) = request(
+                &state,
+                Request::post("/")
+                    .header(header::CONTENT_TYPE, FORM_MEDIA_TYPE)
+                    .body(Body::from(form))
+                    .unwrap(),
+            )
+            .await;
+            assert_eq!(status, StatusCode::BAD_REQUEST);
+            assert_eq!(
+                body,
+                format!(
+                    r#"{{"type":"server","message":"{}","request_id":"test-request"}}"#,
+                    expected_message
+                )
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn metrics_count_requests() {
+        let state = get_test_state();
+        request(
+            &state,
+            Request::get("/health").body(Body::default()).unwrap(),
+        )
+        .await;
+        let (status, _) = request(
+            &state,
+            Request::post("/").body(Body::from("FOO BAR")).unwrap(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let (status, body) = request(
+            &state,
+            Request::get("/metrics").body(Body::default()).unwrap(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body.contains("emdrive_requests_total 2\n"));
+        assert!(body.contains("emdrive_requests_by_method_total{method=\"GET\"} 1\n"));
+        assert!(body.contains("emdrive_requests_by_method_total{method=\"POST\"} 1\n"));
+        assert!(body.contains("emdrive_parse_errors_total 1\n"));
+        assert!(body.contains("emdrive_request_duration_seconds_count 2\n"));
+    }
+
+    #[tokio::test]
+    async fn oversized_body_is_rejected() {
+        let state = get_test_state_with_config(&config::Config {
+            max_request_bytes: 16,
+            ..Default::default()
+        });
+        let (status, body) = request(
+            &state,
+            Request::post("/")
+                .body(Body::from("SELECT * FROM a_table_with_a_long_name"))
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(
+            body,
+            r#"{"type":"server","message":"The request body exceeds the limit of 16 bytes.","request_id":"test-request"}"#
+        );
+    }
+
+    #[tokio::test]
+    async fn query_past_token_limit_is_rejected() {
+        let state = get_test_state_with_config(&config::Config {
+            max_query_tokens: 3,
+            ..Default::default()
+        });
+        let (status, body) = request(
+            &state,
+            Request::post("/")
+                .body(Body::from("SELECT * FROM test"))
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(
+            body,
+            r#"{"type":"syntax","message":"Found arbitrary `test` at line 1 past the limit of 3 tokens.","line":1,"column":15,"request_id":"test-request"}"#
+        );
+    }
+
+    #[tokio::test]
+    async fn slow_query_times_out() {
+        let (executor_tx, mut executor_rx) = mpsc::channel::<ExecutorPayload>(1);
+        // Executor stand-in which takes way longer than the timeout to come back with a result
+        tokio::spawn(async move {
+            while let Some((_statements, resp_tx)) = executor_rx.recv().await {
+                time::sleep(time::Duration::from_secs(5)).await;
+                let _ = resp_tx.send(Ok(StatementOutcome::Done));
+            }
+        });
+        let state = Arc::new(ServerState::new(
+            &config::Config {
+                query_timeout_ms: 10,
+                ..Default::default()
+            },
+            executor_tx,
+        ));
+        let (status, body) = request(
+            &state,
+            Request::post("/")
+                .body(Body::from("SELECT * FROM test"))
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::GATEWAY_TIMEOUT);
+        assert_eq!(
+            body,
+            r#"{"type":"server","message":"The query exceeded its deadline of 10 ms.","request_id":"test-request"}"#
+        );
+        let (_, metrics) = request(
+            &state,
+            Request::get("/metrics").body(Body::default()).unwrap(),
+        )
+        .await;
+        assert!(metrics.contains("emdrive_request_duration_seconds_count 1\n"));
+    }
+
+    #[tokio::test]
+    async fn accept_json_returns_json() {
+        let (status, content_type, body) = request_with_accept(
+            &get_test_state_with_fixed_result(),
+            Some("application/json"),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(content_type.as_deref(), Some("application/json"));
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&body).unwrap(),
+            serde_json::json!({
+                "statement": "select",
+                "columns": ["id", "name"],
+                "rows": [{ "id": 1, "name": "foo" }]
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn accept_csv_returns_csv() {
+        let (status, content_type, body) =
+            request_with_accept(&get_test_state_with_fixed_result(), Some("text/csv")).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(content_type.as_deref(), Some("text/csv; charset=utf-8"));
+        assert_eq!(body, "id,name\r\n1,foo\r\n");
+    }
+
+    #[tokio::test]
+    async fn accept_ndjson_returns_ndjson() {
+        let (status, content_type, body) = request_with_accept(
+            &get_test_state_with_fixed_result(),
+            Some("application/x-ndjson"),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(content_type.as_deref(), Some("application/x-ndjson"));
+        assert_eq!(body, "{\"id\":1,\"name\":\"foo\"}\n");
+    }
+
+    #[tokio::test]
+    async fn missing_accept_defaults_to_json() {
+        let (status, content_type, body) =
+            request_with_accept(&get_test_state_with_fixed_result(), None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(content_type.as_deref(), Some("application/json"));
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&body).unwrap(),
+            serde_json::json!({
+                "statement": "select",
+                "columns": ["id", "name"],
+                "rows": [{ "id": 1, "name": "foo" }]
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn accept_picks_first_supported_type() {
+        let (status, content_type, _) = request_with_accept(
+            &get_test_state_with_fixed_result(),
+            Some("application/xml, application/json;q=0, text/csv;q=0.5"),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(content_type.as_deref(), Some("text/csv; charset=utf-8"));
+    }
+
+    #[tokio::test]
+    async fn unsupported_accept_is_rejected() {
+        let (status, content_type, body) =
+            request_with_accept(&get_test_state_with_fixed_result(), Some("application/xml")).await;
+        assert_eq!(status, StatusCode::NOT_ACCEPTABLE);
+        assert_eq!(content_type.as_deref(), Some("application/json"));
+        assert_eq!(
+            body,
+            r#"{"type":"server","message":"None of the media types in the Accept header are supported. Supported ones are: application/json, text/csv, application/x-ndjson.","request_id":"test-request"}"#
+        );
+    }
+
+    #[tokio::test]
+    async fn format_param_overrides_accept() {
+        let state = get_test_state_with_fixed_result();
+        let response = echo(
+            state.clone(),
+            Request::get("/?query=SELECT+*+FROM+test&format=csv")
+                .header(header::ACCEPT, "application/json")
+                .body(Body::default())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers().get(header::CONTENT_TYPE).unwrap(),
+            "text/csv; charset=utf-8"
+        );
+        let body_bytes = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        assert_eq!(body_bytes, "id,name\r\n1,foo\r\n");
+        let (status, body) = request(
+            &state,
+            Request::get("/?query=SELECT+*+FROM+test&format=xml")
+                .body(Body::default())
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(
+            body,
+            r#"{"type":"server","message":"Format `xml` is not supported. Supported ones are: json, csv, ndjson.","request_id":"test-request"}"#
+        );
+    }
+
+    #[tokio::test]
+    async fn syntax_error_includes_position() {
+        let (status, body) = request(
+            &get_test_state(),
+            Request::post("/")
+                .body(Body::from("SELECT *\nFROM test\nWHERE = 1"))
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let error = serde_json::from_str::<serde_json::Value>(&body).unwrap();
+        assert_eq!(error["type"], "syntax");
+        assert_eq!(error["line"], 3);
+        assert_eq!(error["column"], 7);
+    }
+
+    /// Send a raw HTTP/1.1 GET request over TCP, returning the whole response.
+    async fn raw_get(address: net::SocketAddr, path: &str) -> String {
+        let mut stream = TcpStream::connect(address).await.unwrap();
+        stream
+            .write_all(
+                format!(
+                    "GET {} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\n\r\n",
+                    path, address
+                )
+                .as_bytes(),
+            )
+            .await
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        response
+    }
+
+    #[tokio::test]
+    async fn serving_on_multiple_addresses_works() {
+        let localhost_any_port: net::SocketAddr = "127.0.0.1:0".parse().unwrap();
+        let listeners = bind_all(&[localhost_any_port, localhost_any_port]);
+        let addresses: Vec<net::SocketAddr> = listeners
+            .iter()
+            .map(|listener| listener.local_addr())
+            .collect();
+        assert_eq!(addresses.len(), 2);
+        assert_ne!(addresses[0], addresses[1]);
+        let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+        let server = tokio::spawn(serve_all(get_test_state(), listeners, None, async {
+            let _ = shutdown_rx.await;
+        }));
+        for address in addresses {
+            let response = raw_get(address, "/health").await;
+            assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+            assert!(response.ends_with(r#"{"status":"ok"}"#));
+        }
+        shutdown_tx.send(()).unwrap();
+        server.await.unwrap();
+    }
+
+    /// Read from the stream until the whole health check response has been received.
+    /// Returns `None` if the connection is closed first.
+    async fn read_health_check_response(stream: &mut TcpStream) -> Option<String> {
+        let mut response = Vec::new();
+        while !response.ends_with(br#"{"status":"ok"}"#) {
+            let mut buffer = [0; 1024];
+            match stream.read(&mut buffer).await {
+                Ok(0) | Err(_) => return None,
+                Ok(read_count) => response.extend_from_slice(&buffer[..read_count]),
+            }
+        }
+        Some(String::from_utf8(response).unwrap())
+    }
+
+    /// Send two health check requests over a single connection, returning the responses received.
+    async fn get_health_twice_on_one_connection(config: config::Config) -> Vec<String> {
+        let listeners = bind_all(&["127.0.0.1:0".parse().unwrap()]);
+        let address = listeners[0].local_addr();
+        let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+        let server = tokio::spawn(serve_all(
+            get_test_state_with_config(&config),
+            listeners,
+            None,
+            async {
+                let _ = shutdown_rx.await;
+            },
+        ));
+        let mut stream = TcpStream::connect(address).await.unwrap();
+        let request = format!("GET /health HTTP/1.1\r\nHost: {}\r\n\r\n", address);
+        let mut responses = Vec::new();
+        for _ in 0..2 {
+            // If the server has closed the connection, the request fails to be written or gets no response
+            if stream.write_all(request.as_bytes()).await.is_err() {
+                break;
+            }
+            match read_health_check_response(&mut stream).await {
+                Some(response) => responses.push(response),
+                None => break,
+            }
+        }
+        drop(stream);
+        shutdown_tx.send(()).unwrap();
+        server.await.unwrap();
+        responses
+    }
+
+    #[tokio::test]
+    async fn keep_alive_reuses_connections() {
+        let responses = get_health_twice_on_one_connection(config::Config::default()).await;
+        assert_eq!(responses.len(), 2);
+        assert!(responses[1].starts_with("HTTP/1.1 200 OK\r\n"));
+    }
+
+    #[tokio::test]
+    async fn disabling_keep_alive_closes_connections() {
+        let responses = get_health_twice_on_one_connection(config::Config {
+            http_keep_alive: false,
+            ..Default::default()
+        })
+        .await;
+        assert_eq!(responses.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn serving_over_tls_works() {
+        let (certificate, config) = tls::tls_tests::write_test_certificate();
+        let tls_acceptor = load_tls_acceptor(&config).unwrap();
+        let listeners = bind_all(&["127.0.0.1:0".parse().unwrap()]);
+        let address = listeners[0].local_addr();
+        let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+        let server = tokio::spawn(serve_all(
+            get_test_state_with_config(&config),
+            listeners,
+            tls_acceptor,
+            async {
+                let _ = shutdown_rx.await;
+            },
+        ));
+        let mut root_certificates = rustls::RootCertStore::empty();
+        root_certificates.add(&certificate).unwrap();
+        let connector = tokio_rustls::TlsConnector::from(Arc::new(
+            rustls::ClientConfig::builder()
+                .with_safe_defaults()
+                .with_root_certificates(root_certificates)
+                .with_no_client_auth(),
+        ));
+        let mut stream = connector
+            .connect(
+                "localhost".try_into().unwrap(),
+                TcpStream::connect(address).await.unwrap(),
+            )
+            .await
+            .unwrap();
+        stream
+            .write_all(b"GET /health HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+            .await
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.ends_with(r#"{"status":"ok"}"#));
+        drop(stream);
+        shutdown_tx.send(()).unwrap();
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn plaintext_is_refused_over_tls() {
+        let (_, config) = tls::tls_tests::write_test_certificate();
+        let tls_acceptor = load_tls_acceptor(&config).unwrap();
+        let listeners = bind_all(&["127.0.0.1:0".parse().unwrap()]);
+        let address = listeners[0].local_addr();
+        let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+        let server = tokio::spawn(serve_all(
+            get_test_state_with_config(&config),
+            listeners,
+            tls_acceptor,
+            async {
+                let _ = shutdown_rx.await;
+            },
+        ));
+        assert!(!raw_get(address, "/health").await.contains("200 OK"));
+        shutdown_tx.send(()).unwrap();
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn server_is_ready_only_after_recovery() {
+        let listeners = bind_all(&["127.0.0.1:0".parse().unwrap()]);
+        let address = listeners[0].local_addr();
+        let (recovered_tx, recovered_rx) = oneshot::channel::<()>();
+        let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+        let server = tokio::spawn(serve_recovering(
+            get_test_state(),
+            listeners,
+            None,
+            async {
+                let _ = recovered_rx.await;
+                Ok(())
+            },
+            async {
+                let _ = shutdown_rx.await;
+            },
+        ));
+        // While loading, the server is live but not ready
+        assert!(raw_get(address, "/health")
+            .await
+            .starts_with("HTTP/1.1 200 OK\r\n"));
+        let response = raw_get(address, "/ready").await;
+        assert!(response.starts_with("HTTP/1.1 503 Service Unavailable\r\n"));
+        assert!(response.ends_with(r#"{"status":"loading"}"#));
+        recovered_tx.send(()).unwrap();
+        let mut response = raw_get(address, "/ready").await;
+        for _ in 0..100 {
+            if response.starts_with("HTTP/1.1 200 OK\r\n") {
+                break;
+            }
+            time::sleep(time::Duration::from_millis(10)).await;
+            response = raw_get(address, "/ready").await;
+        }
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.ends_with(r#"{"status":"ready"}"#));
+        shutdown_tx.send(()).unwrap();
+        assert!(server.await.unwrap().is_ok());
+    }
+
+    #[tokio::test]
+    async fn failed_recovery_shuts_server_down() {
+        let listeners = bind_all(&["127.0.0.1:0".parse().unwrap()]);
+        let result = serve_recovering(
+            get_test_state(),
+            listeners,
+            None,
+            async {
+                Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "Corrupt catalog",
+                ))
+            },
+            future::pending(),
+        )
+        .await;
+        assert_eq!(result.unwrap_err().to_string(), "Corrupt catalog");
+    }
+
+    #[tokio::test]
+    async fn failing_to_bind_one_address_keeps_the_others() {
+        let taken_listener = bind_all(&["127.0.0.1:0".parse().unwrap()]).remove(0);
+        let listeners = bind_all(&[taken_listener.local_addr(), "127.0.0.1:0".parse().unwrap()]);
+        assert_eq!(listeners.len(), 1);
+        assert_ne!(listeners[0].local_addr(), taken_listener.local_addr());
+    }
+
+    #[tokio::test]
+    async fn failing_to_bind_every_address_is_an_error() {
+        let taken_listener = bind_all(&["127.0.0.1:0".parse().unwrap()]).remove(0);
+        let config = config::Config {
+            tcp_listen_addresses: vec![taken_listener.local_addr()],
+            ..Default::default()
+        };
+        let (executor_tx, _executor_rx) = mpsc::channel(1);
+        let error = start_server(&config, executor_tx, None, async { Ok(()) })
+            .await
+            .unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::AddrNotAvailable);
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "Could not listen on any of the addresses: {}",
+                taken_listener.local_addr()
+            )
+        );
+    }
+
+    /// Start serving with an executor stand-in which takes `execution_time` to complete each statement,
+    /// then send a query over a raw connection and trigger shutdown once the statement is being executed.
+    /// Returns the connection awaiting the query's response and the server task.
+    async fn shut_down_during_query(
+        config: config::Config,
+        execution_time: time::Duration,
+    ) -> (TcpStream, tokio::task::JoinHandle<()>) {
+        let (executor_tx, mut executor_rx) = mpsc::channel::<ExecutorPayload>(1);
+        let (received_tx, received_rx) = oneshot::channel::<()>();
+        tokio::spawn(async move {
+            let mut received_tx = Some(received_tx);
+            while let Some((_statements, resp_tx)) = executor_rx.recv().await {
+                if let Some(received_tx) = received_tx.take() {
+                    let _ = received_tx.send(());
+                }
+                time::sleep(execution_time).await;
+                let _ = resp_tx.send(Ok(StatementOutcome::Done));
+            }
+        });
+        let listeners = bind_all(&["127.0.0.1:0".parse().unwrap()]);
+        let address = listeners[0].local_addr();
+        let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+        let server = tokio::spawn(serve_all(
+            Arc::new(ServerState::new(&config, executor_tx)),
+            listeners,
+            None,
+            async {
+                let _ = shutdown_rx.await;
+            },
+        ));
+        let query = "DELETE FROM test";
+        let mut stream = TcpStream::connect(address).await.unwrap();
+        stream
+            .write_all(
+                format!(
+                    "POST / HTTP/1.1\r\nHost: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    address,
+                    query.len(),
+                    query
+                )
+                .as_bytes(),
+            )
+            .await
+            .unwrap();
+        received_rx.await.unwrap();
+        shutdown_tx.send(()).unwrap();
+        (stream, server)
+    }
+
+    #[tokio::test]
+    async fn shutdown_waits_for_requests_in_flight() {
+        let (mut stream, server) =
+            shut_down_during_query(config::Config::default(), time::Duration::from_millis(300))
+                .await;
+        // The server only stops once the response is out
+        time::timeout(time::Duration::from_secs(5), server)
+            .await
+            .unwrap()
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+    }
+
+    #[tokio::test]
+    async fn shutdown_abandons_requests_after_drain_timeout() {
+        let (_stream, server) = shut_down_during_query(
+            config::Config {
+                shutdown_drain_timeout_ms: 50,
+                ..Default::default()
+            },
+            time::Duration::from_secs(5),
+        )
+        .await;
+        time::timeout(time::Duration::from_secs(1), server)
+            .await
+            .unwrap()
+            .unwrap();
+    }
+
+    /// Log output captured in memory.
+    #[derive(Clone, Default)]
+    struct CapturedLogs(Arc<parking_lot::Mutex<Vec<u8>>>);
+
+    impl std::io::Write for CapturedLogs {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl tracing_subscriber::fmt::MakeWriter for CapturedLogs {
+        type Writer = Self;
+
+        fn make_writer(&self) -> Self::Writer {
+            self.clone()
+        }
+    }
+
+    #[tokio::test]
+    async fn requests_are_logged_with_fields() {
+        let logs = CapturedLogs::default();
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer(logs.clone())
+            .with_ansi(false)
+            .finish();
+        // Tests run on a single-threaded runtime, so a thread-local default subscriber is enough
+        let _subscriber_guard = tracing::subscriber::set_default(subscriber);
+        let (status, _) = request(
+            &get_test_state_with_fixed_result(),
+            Request::post("/")
+                .body(Body::from("SELECT * FROM test"))
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let logs = String::from_utf8(logs.0.lock().clone()).unwrap();
+        let finished_line = logs
+            .lines()
+            .find(|line| line.contains("Finished request"))
+            .expect("Request should be logged");
+        assert!(finished_line.contains("request_id="));
+        assert!(finished_line.contains("method=POST"));
+        assert!(finished_line.contains("path=/"));
+        assert!(finished_line.contains("statement_kind=\"select\""));
+        assert!(finished_line.contains("status=200"));
+        assert!(finished_line.contains("duration_us="));
+    }
+
+    #[tokio::test]
+    async fn panic_while_handling_becomes_500() {
+        let logs = CapturedLogs::default();
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer(logs.clone())
+            .with_ansi(false)
+            .finish();
+        let _subscriber_guard = tracing::subscriber::set_default(subscriber);
+        let request_id = Ulid::new();
+        let span = info_span!("request", request_id = %request_id);
+        let response = isolate_panics(&request_id.to_string(), async {
+            // Stand-in for any bug deep in the engine
+            let statements: Vec<&str> = Vec::new();
+            Ok(json_response(StatusCode::OK, statements[0].to_string()))
+        })
+        .instrument(span)
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        let body_bytes = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&body_bytes).unwrap(),
+            serde_json::json!({
+                "type": "server",
+                "message": "The server failed unexpectedly while handling the request.",
+                "request_id": request_id.to_string(),
+            })
+        );
+        let logs = String::from_utf8(logs.0.lock().clone()).unwrap();
+        let panic_line = logs
+            .lines()
+            .find(|line| line.contains("Panicked while handling request"))
+            .expect("Panic should be logged");
+        assert!(panic_line.contains(&format!("request_id={}", request_id)));
+        assert!(panic_line.contains("index out of bounds"));
+    }
+
+    /// Server state with an executor stand-in and the specified auth token required.
+    fn get_test_state_with_auth_token(auth_token: Option<&str>) -> Arc<ServerState> {
+        let state = get_test_state_with_fixed_result();
+        Arc::new(ServerState::new(
+            &config::Config {
+                auth_token: auth_token.map(String::from),
+                ..Default::default()
+            },
+            state.executor_tx.clone(),
+        ))
+    }
+
+    /// Send a query with the given Authorization header, returning the response status and body.
+    async fn request_with_authorization(
+        state: &Arc<ServerState>,
+        authorization: Option<&str>,
+    ) -> (StatusCode, String) {
+        let mut req = Request::post("/");
+        if let Some(authorization) = authorization {
+            req = req.header(header::AUTHORIZATION, authorization);
+        }
+        request(state, req.body(Body::from("SELECT * FROM test")).unwrap()).await
+    }
+
+    #[tokio::test]
+    async fn request_with_correct_token_is_authorized() {
+        let state = get_test_state_with_auth_token(Some("s3cret"));
+        let (status, _) = request_with_authorization(&state, Some("Bearer s3cret")).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn request_with_missing_or_wrong_token_is_unauthorized() {
+        let state = get_test_state_with_auth_token(Some("s3cret"));
+        for authorization in [
+            None,
+            Some("Bearer s3cre"),
+            Some("Bearer s3cret2"),
+            Some("Basic s3cret"),
+            Some("s3cret"),
+        ] {
+            let (status, body) = request_with_authorization(&state, authorization).await;
+            assert_eq!(status, StatusCode::UNAUTHORIZED);
+            assert_eq!(
+                body,
+                r#"{"type":"server","message":"A valid bearer token is required.","request_id":"test-request"}"#
+            );
+        }
+        // Probes stay open
+        let (status, _) = request(
+            &state,
+            Request::get("/health").body(Body::default()).unwrap(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn request_without_token_is_authorized_when_no_token_is_configured() {
+        let state = get_test_state_with_auth_token(None);
+        let (status, _) = request_with_authorization(&state, None).await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, _) = request_with_authorization(&state, Some("Bearer whatever")).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn get_with_query_and_format_works() {
+        let (status, body) = request(
+            &get_test_state_with_fixed_result(),
+            Request::get("/?query=SELECT+*+FROM+test&format=json")
+                .body(Body::default())
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&body).unwrap(),
+            serde_json::json!({
+                "statement": "select",
+                "columns": ["id", "name"],
+                "rows": [{ "id": 1, "name": "foo" }]
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn get_with_mutation_is_rejected() {
+        let (status, body) = request(
+            &get_test_state_with_fixed_result(),
+            Request::get("/?query=SELECT+*+FROM+test%3B+INSERT+INTO+test+(id)+VALUES+(1)")
+                .body(Body::default())
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(
+            body,
+            r#"{"type":"server","message":"Statement `insert` modifies data, so it can only be sent with POST.","request_id":"test-request"}"#
+        );
+    }
+
+    #[tokio::test]
+    async fn get_with_transaction_control_is_rejected() {
+        let (status, body) = request(
+            &get_test_state_with_fixed_result(),
+            Request::get("/?query=BEGIN%3B+SELECT+*+FROM+test%3B+COMMIT")
+                .body(Body::default())
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(
+            body,
+            r#"{"type":"server","message":"Statement `begin` controls a transaction, so it can only be sent with POST.","request_id":"test-request"}"#
+        );
+    }
+
+    #[tokio::test]
+    async fn post_with_mutation_works() {
+        let (status, _) = request(
+            &get_test_state_with_fixed_result(),
+            Request::post("/")
+                .body(Body::from("INSERT INTO test (id) VALUES (1)"))
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    /// Server state with an executor stand-in, allowing CORS requests from the specified origin.
+    fn get_test_state_with_cors_allowed_origin(origin: &str) -> Arc<ServerState> {
+        let state = get_test_state_with_fixed_result();
+        Arc::new(ServerState::new(
+            &config::Config {
+                cors_allowed_origins: vec![origin.into()],
+                ..Default::default()
+            },
+            state.executor_tx.clone(),
+        ))
+    }
+
+    #[tokio::test]
+    async fn preflight_request_is_answered() {
+        let state = get_test_state_with_cors_allowed_origin("https://admin.example.com");
+        let response = echo(
+            state,
+            Request::options("/")
+                .header(header::ORIGIN, "https://admin.example.com")
+                .header(header::ACCESS_CONTROL_REQUEST_METHOD, "POST")
+                .header(header::ACCESS_CONTROL_REQUEST_HEADERS, "authorization")
+                .body(Body::default())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        let headers = response.headers();
+        assert_eq!(
+            headers[header::ACCESS_CONTROL_ALLOW_ORIGIN],
+            "https://admin.example.com"
+        );
+        assert_eq!(
+            headers[header::ACCESS_CONTROL_ALLOW_METHODS],
+            "GET, POST, OPTIONS"
+        );
+        assert_eq!(
+            headers[header::ACCESS_CONTROL_ALLOW_HEADERS],
+            "Authorization, Content-Type, Accept, X-Request-Id"
+        );
+    }
+
+    #[tokio::test]
+    async fn preflight_request_from_disallowed_origin_gets_no_allow_headers() {
+        let state = get_test_state_with_cors_allowed_origin("https://admin.example.com");
+        let response = echo(
+            state,
+            Request::options("/")
+                .header(header::ORIGIN, "https://evil.example.com")
+                .header(header::ACCESS_CONTROL_REQUEST_METHOD, "POST")
+                .body(Body::default())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        assert!(!response
+            .headers()
+            .contains_key(header::ACCESS_CONTROL_ALLOW_ORIGIN));
+        assert!(!response
+            .headers()
+            .contains_key(header::ACCESS_CONTROL_ALLOW_METHODS));
+    }
+
+    #[tokio::test]
+    async fn get_from_allowed_origin_gets_allow_origin_header() {
+        let state = get_test_state_with_cors_allowed_origin("https://admin.example.com");
+        for (origin, expected_allowed_origin) in [
+            (
+                "https://admin.example.com",
+                Some("https://admin.example.com"),
+            ),
+            ("https://evil.example.com", None),
+        ] {
+            let response = echo(
+                state.clone(),
+                Request::get("/?query=SELECT+*+FROM+test")
+                    .header(header::ORIGIN, origin)
+                    .body(Body::default())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            assert_eq!(
+                response
+                    .headers()
+                    .get(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+                    .map(|origin| origin.to_str().unwrap()),
+                expected_allowed_origin
+            );
+            assert_eq!(
+                response
+                    .headers()
+                    .get(header::ACCESS_CONTROL_EXPOSE_HEADERS)
+                    .is_some(),
+                expected_allowed_origin.is_some()
+            );
+            assert!(response
+                .headers()
+                .get_all(header::VARY)
+                .iter()
+                .any(|vary| vary == "Origin"));
+        }
+    }
+
+    /// Send an invalid query with the given request ID, returning the response's request ID header and error body.
+    async fn request_with_request_id(request_id: Option<&str>) -> (String, serde_json::Value) {
+        let mut req = Request::post("/");
+        if let Some(request_id) = request_id {
+            req = req.header(REQUEST_ID_HEADER, request_id);
+        }
+        let response = echo(get_test_state(), req.body(Body::from("FOO BAR")).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let request_id = response.headers()[REQUEST_ID_HEADER]
+            .to_str()
+            .unwrap()
+            .to_string();
+        let body_bytes = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        (request_id, serde_json::from_slice(&body_bytes).unwrap())
+    }
+
+    #[tokio::test]
+    async fn request_id_is_returned() {
+        let (request_id, body) = request_with_request_id(None).await;
+        assert!(request_id.parse::<Ulid>().is_ok());
+        assert_eq!(body["request_id"], request_id.as_str());
+        let (other_request_id, _) = request_with_request_id(None).await;
+        assert_ne!(other_request_id, request_id);
+    }
+
+    #[tokio::test]
+    async fn supplied_request_id_is_echoed() {
+        let (request_id, body) =
+            request_with_request_id(Some("550e8400-e29b-41d4-a716-446655440000")).await;
+        assert_eq!(request_id, "550e8400-e29b-41d4-a716-446655440000");
+        assert_eq!(body["request_id"], "550e8400-e29b-41d4-a716-446655440000");
+        // Anything that could mess up logs is replaced with a fresh ID
+        for invalid_request_id in ["", "foo bar", "foo\"bar", &"x".repeat(129)] {
+            let (request_id, body) = request_with_request_id(Some(invalid_request_id)).await;
+            assert!(request_id.parse::<Ulid>().is_ok());
+            assert_eq!(body["request_id"], request_id.as_str());
+        }
+    }
+
+    #[tokio::test]
+    async fn get_with_unknown_params_is_rejected() {
+        let (status, body) = request(
+            &get_test_state_with_fixed_result(),
+            Request::get("/?qeury=SELECT+*+FROM+test&frmat=csv&format=csv")
+                .body(Body::default())
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(
+            body,
+            r#"{"type":"server","message":"Unknown parameters in the query string: `frmat`, `qeury`. Supported ones are: `query`, `format`, `params`, `timestamps`, `cursor`.","request_id":"test-request"}"#
+        );
+    }
+
+    #[tokio::test]
+    async fn get_with_wrong_parameter_count_is_rejected() {
+        let (status, body) = request(
+            &get_test_state_with_fixed_result(),
+            Request::get("/?query=SELECT+*+FROM+test+WHERE+id+%3D+%3F&params=%5B1%2C2%5D")
+                .body(Body::default())
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(
+            body,
+            r#"{"type":"validation","message":"Number of parameter values (2) doesn't match the number of parameters in the query (1)","request_id":"test-request"}"#
+        );
+    }
+
+    #[tokio::test]
+    async fn get_with_unsupported_parameter_value_is_rejected() {
+        let (status, body) = request(
+            &get_test_state_with_fixed_result(),
+            Request::get("/?query=SELECT+*+FROM+test+WHERE+id+%3D+%3F&params=%5B-1%5D")
+                .body(Body::default())
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(
+            body,
+            r#"{"type":"server","message":"Value `-1` of parameter `$1` is not supported. Supported ones are unsigned integers, booleans, strings and null.","request_id":"test-request"}"#
+        );
+    }
+
+    /// Server state with an executor stand-in that returns 10,000 rows for every statement.
+    fn get_test_state_with_large_result() -> Arc<ServerState> {
+        let (executor_tx, mut executor_rx) = mpsc::channel::<ExecutorPayload>(1);
+        tokio::spawn(async move {
+            while let Some((_statements, resp_tx)) = executor_rx.recv().await {
+                let _ = resp_tx.send(Ok(StatementOutcome::Rows(QueryResult {
+                    column_names: vec!["id".into()],
+                    rows: (0..10_000)
+                        .map(|id| Row(vec![DataInstance::Direct(DataInstanceRaw::UInt16(id))]))
+                        .collect(),
+                })));
+            }
+        });
+        Arc::new(ServerState::new(&config::Config::default(), executor_tx))
+    }
+
+    #[tokio::test]
+    async fn large_result_is_streamed_in_chunks() {
+        let response = echo(
+            get_test_state_with_large_result(),
+            Request::post("/")
+                .body(Body::from("SELECT * FROM test"))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(!response.headers().contains_key(header::CONTENT_ENCODING));
+        let mut body = response.into_body();
+        let mut chunk_count = 0;
+        let mut body_bytes = Vec::new();
+        while let Some(chunk) = body.data().await {
+            chunk_count += 1;
+            body_bytes.extend_from_slice(&chunk.unwrap());
+        }
+        assert!(chunk_count > 1);
+        let result: serde_json::Value = serde_json::from_slice(&body_bytes).unwrap();
+        let rows = result["rows"].as_array().unwrap();
+        assert_eq!(rows.len(), 10_000);
+        assert_eq!(rows[9_999], serde_json::json!({ "id": 9_999 }));
+    }
+
+    #[tokio::test]
+    async fn gzip_encoded_result_round_trips() {
+        let response = echo(
+            get_test_state_with_large_result(),
+            Request::post("/")
+                .header(header::ACCEPT_ENCODING, "gzip, deflate")
+                .body(Body::from("SELECT * FROM test"))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::CONTENT_ENCODING], "gzip");
+        let body_bytes = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let mut decompressed = Vec::new();
+        std::io::Read::read_to_end(
+            &mut flate2::read::GzDecoder::new(&body_bytes[..]),
+            &mut decompressed,
+        )
+        .unwrap();
+        assert!(decompressed.len() > body_bytes.len());
+        let result: serde_json::Value = serde_json::from_slice(&decompressed).unwrap();
+        let rows = result["rows"].as_array().unwrap();
+        assert_eq!(rows.len(), 10_000);
+        assert_eq!(rows[9_999], serde_json::json!({ "id": 9_999 }));
+    }
+
+    #[tokio::test]
+    async fn small_result_is_not_compressed() {
+        let response = echo(
+            get_test_state_with_fixed_result(),
+            Request::post("/")
+                .header(header::ACCEPT_ENCODING, "gzip")
+                .body(Body::from("SELECT * FROM test"))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+        assert!(!response.headers().contains_key(header::CONTENT_ENCODING));
+        let body_bytes = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&body_bytes).unwrap(),
+            serde_json::json!({
+                "statement": "select",
+                "columns": ["id", "name"],
+                "rows": [{ "id": 1, "name": "foo" }]
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn get_without_query_is_rejected() {
+        let (status, body) = request(
+            &get_test_state_with_fixed_result(),
+            Request::get("/?format=csv").body(Body::default()).unwrap(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(
+            body,
+            r#"{"type":"server","message":"The `query` parameter is missing.","request_id":"test-request"}"#
+        );
+    }
+
+    /// Server state with a real executor operating on a fresh data directory.
+    async fn get_test_state_with_executor() -> Arc<ServerState> {
+        let config = config::Config {
+            data_directory: std::path::Path::new(env!("TMPDIR"))
+                .join(Ulid::new().to_string())
+                .to_string_lossy()
+                .into(),
+            ..Default::default()
+        };
+        let mut executor = Executor::new(&config);
+        let executor_tx = executor.prepare_channel();
+        executor.bootstrap().await.unwrap();
+        tokio::spawn(async move { executor.start_until(future::pending()).await });
+        Arc::new(ServerState::new(&config, executor_tx))
+    }
+
+    /// Send a query that must succeed, returning the parsed JSON body.
+    async fn post_query(state: &Arc<ServerState>, query: &'static str) -> serde_json::Value {
+        let (status, body) =
+            request(state, Request::post("/").body(Body::from(query)).unwrap()).await;
+        assert_eq!(status, StatusCode::OK);
+        serde_json::from_str(&body).unwrap()
+    }
+
+    #[tokio::test]
+    async fn results_are_wrapped_in_statement_envelope() {
+        let state = get_test_state_with_executor().await;
+        assert_eq!(
+            post_query(
+                &state,
+                "CREATE TABLE test (id UINT8 PRIMARY KEY, name STRING)"
+            )
+            .await,
+            serde_json::json!({ "statement": "create_table", "created": true })
+        );
+        assert_eq!(
+            post_query(&state, "INSERT INTO test (id, name) VALUES (1, 'foo')").await,
+            serde_json::json!({ "statement": "insert", "rows_affected": 1 })
+        );
+        assert_eq!(
+            post_query(&state, "SELECT * FROM test").await,
+            serde_json::json!({
+                "statement": "select",
+                "columns": ["id", "name"],
+                "rows": [{ "id": 1, "name": "foo" }]
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn create_table_if_not_exists_reports_whether_it_created() {
+        let state = get_test_state_with_executor().await;
+        assert_eq!(
+            post_query(
+                &state,
+                "CREATE TABLE IF NOT EXISTS test (id UINT8 PRIMARY KEY)"
+            )
+            .await,
+            serde_json::json!({ "statement": "create_table", "created": true })
+        );
+        assert_eq!(
+            post_query(
+                &state,
+                "CREATE TABLE IF NOT EXISTS test (id UINT8 PRIMARY KEY)"
+            )
+            .await,
+            serde_json::json!({ "statement": "create_table", "created": false })
+        );
+        // Without IF NOT EXISTS, an existing table is still an error
+        let (status, body) = request(
+            &state,
+            Request::post("/")
+                .body(Body::from("CREATE TABLE test (id UINT8 PRIMARY KEY)"))
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(
+            body,
+            r#"{"type":"validation","message":"Table `test` already exists","request_id":"test-request"}"#
+        );
+    }
+
+    #[tokio::test]
+    async fn aliases_name_result_columns() {
+        let state = get_test_state_with_executor().await;
+        post_query(
+            &state,
+            "CREATE TABLE test (id UINT8 PRIMARY KEY, name STRING)",
+        )
+        .await;
+        post_query(&state, "INSERT INTO test (id, name) VALUES (1, 'foo')").await;
+        assert_eq!(
+            post_query(&state, "SELECT id AS user_id, name FROM test").await,
+            serde_json::json!({
+                "statement": "select",
+                "columns": ["user_id", "name"],
+                "rows": [{ "user_id": 1, "name": "foo" }]
+            })
+        );
+        let response = echo(
+            state.clone(),
+            Request::post("/")
+                .header(header::ACCEPT, "text/csv")
+                .body(Body::from("SELECT COUNT(*) AS total FROM test"))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+        let body_bytes = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        assert_eq!(
+            String::from_utf8(body_bytes.to_vec()).unwrap(),
+            "total\r\n1\r\n"
+        );
+    }
+
+    /// Fetch a page of `SELECT id FROM items LIMIT 2` with GET, returning the IDs and the cursor of the next page.
+    async fn get_items_page(state: &Arc<ServerState>, cursor: &str) -> (Vec<u64>, Option<String>) {
+        let query_string = serde_urlencoded::to_string([
+            ("query", "SELECT id FROM items LIMIT 2"),
+            ("cursor", cursor),
+        ])
+        .unwrap();
+        let mut req = Request::get(format!("/?{}", query_string))
+            .body(Body::empty())
+            .unwrap();
+        req.headers_mut()
+            .insert(REQUEST_ID_HEADER, HeaderValue::from_static(TEST_REQUEST_ID));
+        let response = echo(state.clone(), req).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let header_cursor = response
+            .headers()
+            .get(NEXT_CURSOR_HEADER)
+            .map(|cursor| cursor.to_str().unwrap().to_string());
+        let body_bytes = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body_bytes).unwrap();
+        let next_cursor = body["next_cursor"].as_str().map(String::from);
+        assert_eq!(header_cursor, next_cursor);
+        let ids = body["rows"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|row| row["id"].as_u64().unwrap())
+            .collect();
+        (ids, next_cursor)
+    }
+
+    #[tokio::test]
+    async fn cursor_pages_through_table_in_primary_key_order() {
+        let state = get_test_state_with_executor().await;
+        post_query(
+            &state,
+            "CREATE TABLE items (id UINT64 PRIMARY KEY, name STRING);
+            INSERT INTO items (id, name) VALUES (50, 'e');
+            INSERT INTO items (id, name) VALUES (10, 'a');
+            INSERT INTO items (id, name) VALUES (30, 'c')",
+        )
+        .await;
+
+        let (ids, next_cursor) = get_items_page(&state, "").await;
+        assert_eq!(ids, [10, 30]);
+        // Rows inserted between pages show up only if they come after the previous page
+        post_query(
+            &state,
+            "INSERT INTO items (id, name) VALUES (20, 'b');
+            INSERT INTO items (id, name) VALUES (40, 'd')",
+        )
+        .await;
+        // Exactly LIMIT rows remaining make up the last page, with no empty page after it
+        let (ids, next_cursor) = get_items_page(&state, &next_cursor.unwrap()).await;
+        assert_eq!(ids, [40, 50]);
+        assert_eq!(next_cursor, None);
+    }
+
+    #[tokio::test]
+    async fn cursor_is_rejected_outside_single_select() {
+        let state = get_test_state_with_executor().await;
+        post_query(&state, "CREATE TABLE items (id UINT64 PRIMARY KEY)").await;
+        for (query, expected_body) in [
+            (
+                "SELECT id FROM items; SELECT id FROM items",
+                r#"{"type":"server","message":"The `cursor` parameter only applies to a single SELECT statement.","request_id":"test-request"}"#,
+            ),
+            (
+                "SELECT id FROM items ORDER BY id DESC",
+                r#"{"type":"validation","message":"A cursor can't be used with ORDER BY, as pages follow the PRIMARY KEY order","request_id":"test-request"}"#,
+            ),
+            (
+                "SELECT COUNT(*) FROM items",
+                r#"{"type":"validation","message":"A cursor can't be used with aggregate functions or GROUP BY, as they collapse rows","request_id":"test-request"}"#,
+            ),
+        ] {
+            let (status, body) = request(
+                &state,
+                Request::post("/?cursor=").body(Body::from(query)).unwrap(),
+            )
+            .await;
+            assert_eq!(status, StatusCode::BAD_REQUEST);
+            assert_eq!(body, expected_body);
+        }
+        let (status, body) = request(
+            &state,
+            Request::post("/?cursor=bm9wZQ")
+                .body(Body::from("SELECT id FROM items"))
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(
+            body,
+            r#"{"type":"validation","message":"Cursor `bm9wZQ` is invalid for table `items`","request_id":"test-request"}"#
+        );
     }
 }
@@ -0,0 +1,211 @@
+use crate::config;
+use std::fs::File;
+use std::io::{self, BufReader};
+use std::sync::Arc;
+use tokio_rustls::rustls::{sign, Certificate, PrivateKey, ServerConfig, SignatureScheme};
+use tokio_rustls::TlsAcceptor;
+
+/// Signature schemes that can be used to check whether the private key matches the certificate,
+/// paired with their counterparts for verification.
+const KEY_CHECK_SCHEMES: &[(SignatureScheme, &webpki::SignatureAlgorithm)] = &[
+    (SignatureScheme::ED25519, &webpki::ED25519),
+    (
+        SignatureScheme::ECDSA_NISTP256_SHA256,
+        &webpki::ECDSA_P256_SHA256,
+    ),
+    (
+        SignatureScheme::ECDSA_NISTP384_SHA384,
+        &webpki::ECDSA_P384_SHA384,
+    ),
+    (
+        SignatureScheme::RSA_PSS_SHA256,
+        &webpki::RSA_PSS_2048_8192_SHA256_LEGACY_KEY,
+    ),
+];
+
+fn invalid_tls_setup(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+fn read_pem_items(kind: &str, path: &str) -> io::Result<Vec<rustls_pemfile::Item>> {
+    let file = File::open(path).map_err(|error| {
+        invalid_tls_setup(format!(
+            "TLS {} file {} could not be read: {}",
+            kind, path, error
+        ))
+    })?;
+    rustls_pemfile::read_all(&mut BufReader::new(file)).map_err(|error| {
+        invalid_tls_setup(format!(
+            "TLS {} file {} is not valid PEM: {}",
+            kind, path, error
+        ))
+    })
+}
+
+/// Make sure that the private key belongs to the certificate by signing a message with the key
+/// and verifying the signature with the certificate's public key.
+fn check_key_matches_certificate(certificate: &Certificate, key: &PrivateKey) -> io::Result<()> {
+    let signing_key = sign::any_supported_type(key)
+        .map_err(|_| invalid_tls_setup("TLS private key is of an unsupported type".into()))?;
+    let offered_schemes: Vec<SignatureScheme> = KEY_CHECK_SCHEMES
+        .iter()
+        .map(|(scheme, _)| *scheme)
+        .collect();
+    let signer = signing_key
+        .choose_scheme(&offered_schemes)
+        .ok_or_else(|| invalid_tls_setup("TLS private key is of an unsupported type".into()))?;
+    let (_, verification_algorithm) = KEY_CHECK_SCHEMES
+        .iter()
+        .find(|(scheme, _)| *scheme == signer.scheme())
+        .unwrap();
+    const MESSAGE: &[u8] = b"emdrive";
+    let signature = signer
+        .sign(MESSAGE)
+        .map_err(|error| invalid_tls_setup(format!("TLS private key can't sign: {}", error)))?;
+    webpki::EndEntityCert::try_from(certificate.0.as_slice())
+        .and_then(|certificate| {
+            certificate.verify_signature(verification_algorithm, MESSAGE, &signature)
+        })
+        .map_err(|_| invalid_tls_setup("TLS private key doesn't match the certificate".into()))
+}
+
+/// Set up TLS termination if both the certificate and the private key are configured.
+/// Any problem with them is an error, so that the server never silently falls back to plaintext.
+pub fn load_tls_acceptor(config: &config::Config) -> io::Result<Option<TlsAcceptor>> {
+    let (certificate_path, private_key_path) =
+        match (&config.tls_certificate_path, &config.tls_private_key_path) {
+            (Some(certificate_path), Some(private_key_path)) => {
+                (certificate_path, private_key_path)
+            }
+            (None, None) => return Ok(None),
+            _ => return Err(invalid_tls_setup(
+                "Both tls_certificate_path and tls_private_key_path must be set to serve over TLS"
+                    .into(),
+            )),
+        };
+    let certificates: Vec<Certificate> = read_pem_items("certificate", certificate_path)?
+        .into_iter()
+        .filter_map(|item| match item {
+            rustls_pemfile::Item::X509Certificate(certificate) => Some(Certificate(certificate)),
+            _ => None,
+        })
+        .collect();
+    if certificates.is_empty() {
+        return Err(invalid_tls_setup(format!(
+            "TLS certificate file {} contains no certificates",
+            certificate_path
+        )));
+    }
+    let private_key = read_pem_items("private key", private_key_path)?
+        .into_iter()
+        .find_map(|item| match item {
+            rustls_pemfile::Item::PKCS8Key(key)
+            | rustls_pemfile::Item::RSAKey(key)
+            | rustls_pemfile::Item::ECKey(key) => Some(PrivateKey(key)),
+            _ => None,
+        })
+        .ok_or_else(|| {
+            invalid_tls_setup(format!(
+                "TLS private key file {} contains no private key",
+                private_key_path
+            ))
+        })?;
+    // The first certificate is the server's own, any further ones make up the chain
+    check_key_matches_certificate(&certificates[0], &private_key)?;
+    let server_config = ServerConfig::builder()
+        .with_safe_defaults()
+        .with_no_client_auth()
+        .with_single_cert(certificates, private_key)
+        .map_err(|error| invalid_tls_setup(format!("TLS setup failed: {}", error)))?;
+    Ok(Some(TlsAcceptor::from(Arc::new(server_config))))
+}
+
+#[cfg(test)]
+pub mod tls_tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use rand::distributions::Alphanumeric;
+    use rand::{thread_rng, Rng};
+    use std::path::Path;
+
+    /// Write a fresh self-signed certificate for `localhost` and its private key to temporary files,
+    /// returning the certificate in DER along with a config pointing to the files.
+    pub fn write_test_certificate() -> (Certificate, config::Config) {
+        let certificate = rcgen::generate_simple_self_signed(vec!["localhost".into()]).unwrap();
+        let file_name: String = thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(10)
+            .map(char::from)
+            .collect();
+        let certificate_path = Path::new(env!("TMPDIR")).join(format!("{}.crt", file_name));
+        let private_key_path = Path::new(env!("TMPDIR")).join(format!("{}.key", file_name));
+        std::fs::write(&certificate_path, certificate.serialize_pem().unwrap()).unwrap();
+        std::fs::write(&private_key_path, certificate.serialize_private_key_pem()).unwrap();
+        (
+            Certificate(certificate.serialize_der().unwrap()),
+            config::Config {
+                tls_certificate_path: Some(certificate_path.to_string_lossy().into()),
+                tls_private_key_path: Some(private_key_path.to_string_lossy().into()),
+                ..Default::default()
+            },
+        )
+    }
+
+    #[test]
+    fn no_tls_is_set_up_without_paths() {
+        assert!(load_tls_acceptor(&config::Config::default())
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn tls_is_set_up_with_matching_certificate_and_key() {
+        let (_, config) = write_test_certificate();
+        assert!(load_tls_acceptor(&config).unwrap().is_some());
+    }
+
+    #[test]
+    fn mismatched_key_fails() {
+        let (_, config) = write_test_certificate();
+        let (_, other_config) = write_test_certificate();
+        let error = load_tls_acceptor(&config::Config {
+            tls_private_key_path: other_config.tls_private_key_path,
+            ..config
+        })
+        .err()
+        .unwrap();
+        assert_eq!(
+            error.to_string(),
+            "TLS private key doesn't match the certificate"
+        );
+    }
+
+    #[test]
+    fn missing_certificate_file_fails() {
+        let (_, config) = write_test_certificate();
+        let error = load_tls_acceptor(&config::Config {
+            tls_certificate_path: Some("/nonexistent.crt".into()),
+            ..config
+        })
+        .err()
+        .unwrap();
+        assert!(error
+            .to_string()
+            .starts_with("TLS certificate file /nonexistent.crt could not be read: "));
+    }
+
+    #[test]
+    fn only_one_path_fails() {
+        let (_, config) = write_test_certificate();
+        let error = load_tls_acceptor(&config::Config {
+            tls_private_key_path: None,
+            ..config
+        })
+        .err()
+        .unwrap();
+        assert_eq!(
+            error.to_string(),
+            "Both tls_certificate_path and tls_private_key_path must be set to serve over TLS"
+        );
+    }
+}
@@ -0,0 +1,94 @@
+use crate::constructs::components::DataTypeRaw;
+use std::fmt;
+
+/// Flavor of SQL accepted by the parser. The canonical type names work in every dialect.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+#[non_exhaustive]
+pub enum Dialect {
+    /// Only canonical type names, e.g. `UINT64` and `STRING`.
+    #[default]
+    Strict,
+    /// Also familiar ANSI-style type names, mapped onto canonical types. There are no signed integer types,
+    /// so integer aliases map onto unsigned types of the same width:
+    /// `TINYINT` → `UINT8`, `SMALLINT` → `UINT16`, `INT`/`INTEGER` → `UINT32`, `BIGINT` → `UINT64`,
+    /// `BOOLEAN` → `BOOL`, and `VARCHAR`/`CHAR`/`TEXT` → `STRING` (without a length).
+    Aliases,
+}
+
+impl Dialect {
+    /// Dialect names accepted in config, for use in error messages.
+    pub const SUPPORTED_NAMES: &'static str = "strict, aliases";
+
+    /// Type aliases of the [`Dialect::Aliases`] dialect.
+    pub const TYPE_ALIASES: [&'static str; 9] = [
+        "TINYINT", "SMALLINT", "INT", "INTEGER", "BIGINT", "BOOLEAN", "VARCHAR", "CHAR", "TEXT",
+    ];
+
+    /// Dialect by its name, as in config.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "strict" => Some(Self::Strict),
+            "aliases" => Some(Self::Aliases),
+            _ => None,
+        }
+    }
+
+    /// Canonical type that the name is an alias of in this dialect, if any.
+    pub fn resolve_type_alias(&self, candidate: &str) -> Option<DataTypeRaw> {
+        match self {
+            Self::Strict => None,
+            Self::Aliases => match candidate.to_lowercase().as_str() {
+                "tinyint" => Some(DataTypeRaw::UInt8),
+                "smallint" => Some(DataTypeRaw::UInt16),
+                "int" | "integer" => Some(DataTypeRaw::UInt32),
+                "bigint" => Some(DataTypeRaw::UInt64),
+                "boolean" => Some(DataTypeRaw::Bool),
+                "varchar" | "char" | "text" => Some(DataTypeRaw::String),
+                _ => None,
+            },
+        }
+    }
+}
+
+impl fmt::Display for Dialect {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Strict => write!(f, "strict"),
+            Self::Aliases => write!(f, "aliases"),
+        }
+    }
+}
+
+/// Whether a list in parentheses may end with a comma, e.g. `(id, name,)`, as generated SQL often does.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum TrailingCommas {
+    /// A trailing comma is a syntax error.
+    #[default]
+    Strict,
+    /// A single trailing comma right before a closing parenthesis is ignored.
+    /// A list can't consist of a comma only though, e.g. `(,)`, and consecutive commas are still an error.
+    Lenient,
+}
+
+impl TrailingCommas {
+    /// Mode names accepted in config, for use in error messages.
+    pub const SUPPORTED_NAMES: &'static str = "strict, lenient";
+
+    /// Mode by its name, as in config.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "strict" => Some(Self::Strict),
+            "lenient" => Some(Self::Lenient),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for TrailingCommas {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Strict => write!(f, "strict"),
+            Self::Lenient => write!(f, "lenient"),
+        }
+    }
+}
@@ -1,18 +1,104 @@
+use super::tokenizer::Token;
 use serde::{ser::SerializeMap, Serialize, Serializer};
 use thiserror::Error;
 
+/// Location in the statement, with both the line and the column 1-based.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
 #[derive(Error, Debug, PartialEq, Eq)]
-#[error("SyntaxError: {0}")]
-pub struct SyntaxError(pub String);
+#[error("SyntaxError: {message}")]
+pub struct SyntaxError {
+    pub message: String,
+    /// Where the offending token starts. `None` when there's no specific token at fault, e.g. at end of statement.
+    pub position: Option<Position>,
+}
+
+impl SyntaxError {
+    pub fn new(message: String) -> Self {
+        SyntaxError {
+            message,
+            position: None,
+        }
+    }
+
+    /// Error caused by the given token.
+    pub fn at(token: &Token, message: String) -> Self {
+        SyntaxError {
+            message,
+            position: Some(Position {
+                line: token.line_number,
+                column: token.column_number,
+            }),
+        }
+    }
+}
+
+impl SyntaxError {
+    /// Render the error along with the offending line of `source` (the input the error comes from)
+    /// and a caret under the offending token, e.g. for CLI output:
+    ///
+    /// ```text
+    /// SyntaxError: Expected a non-negative integer, instead found arbitrary `x` at line 1.
+    ///  --> line 1, column 26
+    ///   |
+    /// 1 | SELECT * FROM test LIMIT x
+    ///   |                          ^
+    /// ```
+    ///
+    /// Without a position, or if it's outside `source`, this is the same as `Display`.
+    pub fn render(&self, source: &str) -> String {
+        let position = match self.position {
+            Some(position) => position,
+            None => return self.to_string(),
+        };
+        let line = match position
+            .line
+            .checked_sub(1)
+            .and_then(|line_index| source.lines().nth(line_index))
+        {
+            Some(line) => line,
+            None => return self.to_string(),
+        };
+        let gutter_width = position.line.to_string().len();
+        let gutter_padding = " ".repeat(gutter_width);
+        // Tabs are kept, so that the caret is aligned no matter the tab width
+        let caret_padding: String = line
+            .chars()
+            .take(position.column.saturating_sub(1))
+            .map(|character| if character == '\t' { '\t' } else { ' ' })
+            .collect();
+        format!(
+            "{}\n{}--> line {}, column {}\n{} |\n{} | {}\n{} | {}^",
+            self,
+            gutter_padding,
+            position.line,
+            position.column,
+            gutter_padding,
+            position.line,
+            line,
+            gutter_padding,
+            caret_padding
+        )
+    }
+}
 
 impl Serialize for SyntaxError {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
     {
-        let mut map = serializer.serialize_map(Some(2))?;
+        let entry_count = if self.position.is_some() { 4 } else { 2 };
+        let mut map = serializer.serialize_map(Some(entry_count))?;
         map.serialize_entry("type", "syntax")?;
-        map.serialize_entry("message", &self.0)?;
+        map.serialize_entry("message", &self.message)?;
+        if let Some(position) = &self.position {
+            map.serialize_entry("line", &position.line)?;
+            map.serialize_entry("column", &position.column)?;
+        }
         map.end()
     }
 }
@@ -32,3 +118,42 @@ impl Serialize for ValidationError {
         map.end()
     }
 }
+
+#[cfg(test)]
+mod errors_tests {
+    use super::*;
+    use crate::sql::parse_statements;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn render_points_at_offending_token() {
+        const SOURCE: &str = "SELECT * FROM test;\nSELECT * FROM WHERE id = 1";
+        let error = parse_statements(SOURCE).unwrap_err();
+        assert_eq!(
+            error.render(SOURCE),
+            "SyntaxError: Expected an identifier, instead found reserved keyword `WHERE` at line 2. Quote it as `\"WHERE\"` to use it as an identifier.
+ --> line 2, column 15
+  |
+2 | SELECT * FROM WHERE id = 1
+  |               ^"
+        );
+        // The plain form stays the same
+        assert_eq!(
+            error.to_string(),
+            "SyntaxError: Expected an identifier, instead found reserved keyword `WHERE` at line 2. Quote it as `\"WHERE\"` to use it as an identifier."
+        );
+    }
+
+    #[test]
+    fn render_keeps_tabs_for_alignment() {
+        const SOURCE: &str = "\tSELECT\t* FROM WHERE";
+        let error = parse_statements(SOURCE).unwrap_err();
+        assert!(error.render(SOURCE).ends_with("\n  | \t      \t       ^"));
+    }
+
+    #[test]
+    fn render_without_position_is_plain() {
+        let error = SyntaxError::new("Expected a value, instead found end of statement.".into());
+        assert_eq!(error.render("SELECT"), error.to_string());
+    }
+}
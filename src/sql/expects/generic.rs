@@ -47,14 +47,6 @@ pub fn detect<'t, P, M>(
     }
 }
 
-pub fn expect_identity(tokens: &[Token]) -> ExpectResult<Vec<Token>> {
-    Ok(ExpectOk {
-        rest: &[][..],
-        tokens_consumed_count: tokens.len(),
-        outcome: tokens.to_vec(),
-    })
-}
-
 pub fn expect_token_value<'t>(
     tokens: &'t [Token],
     expected_token_value: &TokenValue,
@@ -71,10 +63,13 @@ pub fn expect_token_value<'t>(
             outcome: (),
         })
     } else {
-        Err(SyntaxError(format!(
-            "Expected {}, instead found {}.",
-            expected_token_value, found_token
-        )))
+        Err(SyntaxError::at(
+            found_token,
+            format!(
+                "Expected {}, instead found {}.",
+                expected_token_value, found_token
+            ),
+        ))
     }
 }
 
@@ -93,31 +88,31 @@ pub fn expect_token_values_sequence<'t>(
     })
 }
 
-pub fn expect_end_of_statement(tokens: &[Token]) -> ExpectResult<'_, ()> {
+pub fn expect_end_of_statement<'t>(tokens: &'t [Token]) -> ExpectResult<'t, ()> {
     match tokens.first() {
         None => Ok(ExpectOk {
             rest: tokens,
             tokens_consumed_count: 0,
             outcome: (),
         }),
-        Some(wrong_token) => Err(SyntaxError(format!(
-            "Expected end of statement, instead found {}.",
-            wrong_token
-        ))),
+        Some(wrong_token) => Err(SyntaxError::at(
+            wrong_token,
+            format!("Expected end of statement, instead found {}.", wrong_token),
+        )),
     }
 }
 
 pub fn expect_next_token<'t>(
     tokens: &'t [Token],
     expectation_description: &dyn std::fmt::Display,
-) -> ExpectResult<'t, &'t Token> {
+) -> ExpectResult<'t, &'t Token<'t>> {
     match tokens.first() {
         Some(found_token) => Ok(ExpectOk {
             rest: &tokens[1..],
             tokens_consumed_count: 1,
             outcome: found_token,
         }),
-        None => Err(SyntaxError(format!(
+        None => Err(SyntaxError::new(format!(
             "Expected {}, instead found end of statement.",
             expectation_description
         ))),
@@ -149,41 +144,50 @@ pub fn expect_comma_separated<'t, O>(
     tokens: &'t [Token],
     expect_element: ExpectFn<'t, O>,
 ) -> ExpectResult<'t, Vec<O>> {
+    let mut rest = tokens;
     let mut tokens_consumed_total_count = 0;
     let mut outcomes = Vec::<O>::new();
     loop {
         // Parse next element
         let ExpectOk {
+            rest: rest_element,
             tokens_consumed_count,
             outcome,
-            ..
-        } = expect_element(&tokens[tokens_consumed_total_count..])?;
+        } = expect_element(rest)?;
         tokens_consumed_total_count += tokens_consumed_count;
         outcomes.push(outcome);
-        // Check for the comma (trailing comma disallowed)
-        match expect_token_value(
-            &tokens[tokens_consumed_total_count..],
-            &TokenValue::Delimiting(Delimiter::Comma),
-        ) {
-            Err(_) => break, // If there's no comma after this element, it's time to break out of the loop
-            _ => {
+        // Check for the comma (trailing comma disallowed). The lack of one is how the list normally ends,
+        // so the next token is only peeked at, instead of building an error with `expect_token_value`
+        match rest_element.split_first() {
+            Some((
+                Token {
+                    value: TokenValue::Delimiting(Delimiter::Comma),
+                    ..
+                },
+                rest_comma,
+            )) => {
+                rest = rest_comma;
                 tokens_consumed_total_count += 1;
             }
+            _ => {
+                rest = rest_element;
+                break;
+            }
         }
     }
     Ok(ExpectOk {
-        rest: &tokens[tokens_consumed_total_count..],
+        rest,
         tokens_consumed_count: tokens_consumed_total_count,
         outcome: outcomes,
     })
 }
 
 // Expert an enclosure, the outcome being enclosure contents excluding opening and closing delimiters.
-pub fn expect_enclosure(
-    tokens: &[Token],
+pub fn expect_enclosure<'t>(
+    tokens: &'t [Token],
     opening: Delimiter,
     closing: Delimiter,
-) -> ExpectResult<&[Token]> {
+) -> ExpectResult<'t, &'t [Token<'t>]> {
     let ExpectOk { rest, .. } = expect_token_value(
         tokens,
         &TokenValue::Delimiting(Delimiter::ParenthesisOpening),
@@ -211,10 +215,13 @@ pub fn expect_enclosure(
             tokens_consumed_count: enclosure_size + 2, // +2 to account for parentheses
             outcome: &rest[..enclosure_size],
         }),
-        None => Err(SyntaxError(format!(
-            "Expected to find a matching {} for {}, instead found end of statement.",
-            closing, tokens[0]
-        ))),
+        None => Err(SyntaxError::at(
+            &tokens[0],
+            format!(
+                "Expected to find a matching {} for {}, instead found end of statement.",
+                closing, tokens[0]
+            ),
+        )),
     }
 }
 
@@ -234,16 +241,28 @@ pub fn expect_enclosed_comma_separated<'t, O>(
     )?;
     // Disallow empty enclosures
     if enclosure_tokens.is_empty() {
-        return Err(SyntaxError(format!(
-            "Found an enclosure delimited by {} and {} as expected, but it's empty.",
-            tokens[0],
-            tokens[tokens_consumed_count - 1],
-        )));
+        return Err(SyntaxError::at(
+            &tokens[0],
+            format!(
+                "Found an enclosure delimited by {} and {} as expected, but it's empty.",
+                tokens[0],
+                tokens[tokens_consumed_count - 1],
+            ),
+        ));
     }
     let mut elements = Vec::<O>::new();
     let mut previous_separator_offset: usize = 0;
+    // Only separators outside of nested enclosures split elements, e.g. not the one in `PRIMARY KEY (a, b)`
+    let mut nesting_depth: usize = 0;
     for (current_index, current_token) in enclosure_tokens.iter().enumerate() {
-        if current_token.value == SEPARATOR {
+        match current_token.value {
+            TokenValue::Delimiting(Delimiter::ParenthesisOpening) => nesting_depth += 1,
+            TokenValue::Delimiting(Delimiter::ParenthesisClosing) => {
+                nesting_depth = nesting_depth.saturating_sub(1)
+            }
+            _ => (),
+        }
+        if nesting_depth == 0 && current_token.value == SEPARATOR {
             if previous_separator_offset == current_index {
                 expect_element(&enclosure_tokens[..previous_separator_offset])?;
             }
@@ -257,10 +276,10 @@ pub fn expect_enclosed_comma_separated<'t, O>(
     // If the final vector is empty, it means there was a trailing separator, which is generally disallowed in SQL
     let final_element_tokens = &enclosure_tokens[previous_separator_offset..];
     if final_element_tokens.is_empty() {
-        return Err(SyntaxError(format!(
-            "Found disallowed trailing {}.",
-            &SEPARATOR
-        )));
+        return Err(SyntaxError::at(
+            &enclosure_tokens[previous_separator_offset - 1],
+            format!("Found disallowed trailing {}.", &SEPARATOR),
+        ));
     }
     elements.push(expect_element(&enclosure_tokens[previous_separator_offset..])?.outcome);
     Ok(ExpectOk {
@@ -282,15 +301,18 @@ mod expect_token_sequence_tests {
                 &[
                     Token {
                         value: TokenValue::Const(Keyword::If),
-                        line_number: 1
+                        line_number: 1,
+                        column_number: 1
                     },
                     Token {
                         value: TokenValue::Const(Keyword::Not),
-                        line_number: 1
+                        line_number: 1,
+                        column_number: 4
                     },
                     Token {
                         value: TokenValue::Const(Keyword::Exists),
-                        line_number: 1
+                        line_number: 1,
+                        column_number: 8
                     }
                 ],
                 &[
@@ -314,15 +336,18 @@ mod expect_token_sequence_tests {
                 &[
                     Token {
                         value: TokenValue::Const(Keyword::If),
-                        line_number: 1
+                        line_number: 1,
+                        column_number: 1
                     },
                     Token {
                         value: TokenValue::Const(Keyword::Not),
-                        line_number: 1
+                        line_number: 1,
+                        column_number: 4
                     },
                     Token {
-                        value: TokenValue::Arbitrary("xyz".to_string()),
-                        line_number: 1
+                        value: TokenValue::Arbitrary("xyz".into()),
+                        line_number: 1,
+                        column_number: 8
                     }
                 ],
                 &[
@@ -331,9 +356,11 @@ mod expect_token_sequence_tests {
                     TokenValue::Const(Keyword::Exists),
                 ]
             ),
-            Err(SyntaxError(
-                "Expected keyword `EXISTS`, instead found arbitrary `xyz` at line 1.".to_string()
-            ))
+            Err(SyntaxError {
+                message: "Expected keyword `EXISTS`, instead found arbitrary `xyz` at line 1."
+                    .to_string(),
+                position: Some(Position { line: 1, column: 8 })
+            })
         )
     }
 
@@ -343,7 +370,8 @@ mod expect_token_sequence_tests {
             expect_token_values_sequence(
                 &[Token {
                     value: TokenValue::Const(Keyword::If),
-                    line_number: 1
+                    line_number: 1,
+                    column_number: 1
                 }],
                 &[
                     TokenValue::Const(Keyword::If),
@@ -351,7 +379,7 @@ mod expect_token_sequence_tests {
                     TokenValue::Const(Keyword::Exists),
                 ]
             ),
-            Err(SyntaxError(
+            Err(SyntaxError::new(
                 "Expected keyword `NOT`, instead found end of statement.".to_string()
             ))
         )
@@ -368,7 +396,7 @@ mod expect_token_sequence_tests {
                     TokenValue::Const(Keyword::Exists),
                 ]
             ),
-            Err(SyntaxError(
+            Err(SyntaxError::new(
                 "Expected keyword `IF`, instead found end of statement.".to_string()
             ))
         )
@@ -387,19 +415,22 @@ mod expect_token_single_tests {
                 &[
                     Token {
                         value: TokenValue::Const(Keyword::Primary),
-                        line_number: 1
+                        line_number: 1,
+                        column_number: 1
                     },
                     Token {
-                        value: TokenValue::Arbitrary("foo".to_string()),
-                        line_number: 1
+                        value: TokenValue::Arbitrary("foo".into()),
+                        line_number: 1,
+                        column_number: 9
                     }
                 ],
                 &TokenValue::Const(Keyword::Primary)
             ),
             Ok(ExpectOk {
                 rest: &[Token {
-                    value: TokenValue::Arbitrary("foo".to_string()),
-                    line_number: 1
+                    value: TokenValue::Arbitrary("foo".into()),
+                    line_number: 1,
+                    column_number: 9
                 }][..],
                 tokens_consumed_count: 1,
                 outcome: ()
@@ -413,13 +444,16 @@ mod expect_token_single_tests {
             expect_token_value(
                 &[Token {
                     value: TokenValue::Const(Keyword::Create),
-                    line_number: 1
+                    line_number: 1,
+                    column_number: 1
                 }],
                 &TokenValue::Const(Keyword::Primary)
             ),
-            Err(SyntaxError(
-                "Expected keyword `PRIMARY`, instead found keyword `CREATE` at line 1.".to_string()
-            ))
+            Err(SyntaxError {
+                message: "Expected keyword `PRIMARY`, instead found keyword `CREATE` at line 1."
+                    .to_string(),
+                position: Some(Position { line: 1, column: 1 })
+            })
         )
     }
 
@@ -427,7 +461,7 @@ mod expect_token_single_tests {
     fn returns_error_if_eos() {
         assert_eq!(
             expect_token_value(&[], &TokenValue::Const(Keyword::Primary)),
-            Err(SyntaxError(
+            Err(SyntaxError::new(
                 "Expected keyword `PRIMARY`, instead found end of statement.".to_string()
             ))
         )
@@ -448,35 +482,43 @@ mod expect_enclosure_tests {
                 &[
                     Token {
                         value: TokenValue::Delimiting(Delimiter::ParenthesisOpening),
-                        line_number: 1
+                        line_number: 1,
+                        column_number: 1
                     },
                     Token {
-                        value: TokenValue::Arbitrary("foo".to_string()),
-                        line_number: 1
+                        value: TokenValue::Arbitrary("foo".into()),
+                        line_number: 1,
+                        column_number: 2
                     },
                     Token {
                         value: TokenValue::Const(Keyword::Nullable),
-                        line_number: 1
+                        line_number: 1,
+                        column_number: 6
                     },
                     Token {
                         value: TokenValue::Delimiting(Delimiter::ParenthesisOpening),
-                        line_number: 1
+                        line_number: 1,
+                        column_number: 14
                     },
                     Token {
                         value: TokenValue::Type(DataTypeRaw::UInt64),
-                        line_number: 1
+                        line_number: 1,
+                        column_number: 15
                     },
                     Token {
                         value: TokenValue::Delimiting(Delimiter::ParenthesisClosing),
-                        line_number: 1
+                        line_number: 1,
+                        column_number: 21
                     },
                     Token {
                         value: TokenValue::Delimiting(Delimiter::ParenthesisClosing),
-                        line_number: 1
+                        line_number: 1,
+                        column_number: 22
                     },
                     Token {
                         value: TokenValue::Const(Keyword::Values),
-                        line_number: 1
+                        line_number: 1,
+                        column_number: 24
                     },
                 ],
                 Delimiter::ParenthesisOpening,
@@ -485,32 +527,86 @@ mod expect_enclosure_tests {
             Ok(ExpectOk {
                 rest: &[Token {
                     value: TokenValue::Const(Keyword::Values),
-                    line_number: 1
+                    line_number: 1,
+                    column_number: 24
                 }][..],
                 tokens_consumed_count: 7,
                 outcome: &[
                     Token {
-                        value: TokenValue::Arbitrary("foo".to_string()),
-                        line_number: 1
+                        value: TokenValue::Arbitrary("foo".into()),
+                        line_number: 1,
+                        column_number: 2
                     },
                     Token {
                         value: TokenValue::Const(Keyword::Nullable),
-                        line_number: 1
+                        line_number: 1,
+                        column_number: 6
                     },
                     Token {
                         value: TokenValue::Delimiting(Delimiter::ParenthesisOpening),
-                        line_number: 1
+                        line_number: 1,
+                        column_number: 14
                     },
                     Token {
                         value: TokenValue::Type(DataTypeRaw::UInt64),
-                        line_number: 1
+                        line_number: 1,
+                        column_number: 15
                     },
                     Token {
                         value: TokenValue::Delimiting(Delimiter::ParenthesisClosing),
-                        line_number: 1
+                        line_number: 1,
+                        column_number: 21
                     },
                 ][..]
             })
         )
     }
 }
+
+#[cfg(test)]
+mod expect_comma_separated_tests {
+    use super::*;
+    use crate::sql::expects::semantic::expect_identifier;
+    use crate::sql::tokenize;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn returns_elements_up_to_first_non_comma() {
+        let tokens = tokenize("foo, bar,baz FROM xyz").unwrap();
+        let ExpectOk {
+            rest,
+            tokens_consumed_count,
+            outcome,
+        } = expect_comma_separated(&tokens, expect_identifier).unwrap();
+        assert_eq!(outcome, vec!["foo", "bar", "baz"]);
+        assert_eq!(tokens_consumed_count, 5);
+        assert_eq!(rest, &tokens[5..]);
+    }
+
+    #[test]
+    fn returns_single_element_at_end_of_statement() {
+        let tokens = tokenize("foo").unwrap();
+        let ExpectOk {
+            rest,
+            tokens_consumed_count,
+            outcome,
+        } = expect_comma_separated(&tokens, expect_identifier).unwrap();
+        assert_eq!(outcome, vec!["foo"]);
+        assert_eq!(tokens_consumed_count, 1);
+        assert!(rest.is_empty());
+    }
+
+    #[test]
+    fn fails_on_trailing_comma_and_invalid_element() {
+        let tokens = tokenize("foo, bar, FROM").unwrap();
+        assert_eq!(
+            expect_comma_separated(&tokens, expect_identifier).unwrap_err(),
+            expect_identifier(&tokens[4..]).unwrap_err()
+        );
+        let tokens = tokenize("foo, bar,").unwrap();
+        assert_eq!(
+            expect_comma_separated(&tokens, expect_identifier).unwrap_err(),
+            expect_identifier(&[]).unwrap_err()
+        );
+    }
+}
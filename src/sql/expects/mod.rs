@@ -6,12 +6,11 @@ use crate::sql::errors::*;
 use crate::sql::tokenizer::*;
 
 pub use generic::*;
-pub use semantic::*;
 pub use statements::*;
 
 #[derive(Debug, PartialEq, Eq)]
 pub struct ExpectOk<'t, O> {
-    pub rest: &'t [Token],
+    pub rest: &'t [Token<'t>],
     pub tokens_consumed_count: usize,
     pub outcome: O,
 }
@@ -1,33 +1,144 @@
 use crate::constructs::components::{
-    DataDefinition, DataInstance, DataInstanceRaw, DataType, DataTypeRaw, Expression,
+    is_plain_identifier, DataDefinition, DataInstance, DataInstanceRaw, DataType, DataTypeRaw,
+    Expression,
 };
 use crate::constructs::functions::Function;
 use crate::sql::errors::*;
 use crate::sql::expects::{generic::*, ExpectOk, ExpectResult};
 use crate::sql::tokenizer::*;
 
-pub fn expect_identifier(tokens: &[Token]) -> ExpectResult<String> {
+pub fn expect_identifier<'t>(tokens: &'t [Token]) -> ExpectResult<'t, String> {
     let ExpectOk {
         outcome: found_token,
         ..
     } = expect_next_token(tokens, &"an identifier")?;
     match found_token {
         Token {
-            value: TokenValue::Arbitrary(value),
+            value: TokenValue::QuotedIdentifier(value),
             ..
         } => Ok(ExpectOk {
             rest: &tokens[1..],
             tokens_consumed_count: 1,
-            outcome: value.to_owned(),
+            outcome: value.to_string(),
+        }),
+        Token {
+            value: TokenValue::Arbitrary(value),
+            ..
+        } if is_plain_identifier(value) => Ok(ExpectOk {
+            rest: &tokens[1..],
+            tokens_consumed_count: 1,
+            outcome: value.to_string(),
         }),
-        wrong_token => Err(SyntaxError(format!(
-            "Expected an identifier, instead found {}.",
-            wrong_token
-        ))),
+        // Other characters most likely make the token an identifier too, just one that needs quoting
+        Token {
+            value: TokenValue::Arbitrary(value),
+            ..
+        } if !value.starts_with(|character: char| character.is_ascii_digit()) => {
+            Err(SyntaxError::at(
+                found_token,
+                format!(
+                    "Expected an identifier, instead found {}, which contains characters other than letters, digits and underscores. Quote it as `\"{}\"` to use it as an identifier.",
+                    found_token, value
+                ),
+            ))
+        }
+        wrong_token => match reserved_word_name(&wrong_token.value) {
+            // A reserved word is most likely meant to be an identifier here, so let's point to the fix
+            Some(reserved_word) => Err(SyntaxError::at(
+                wrong_token,
+                format!(
+                    "Expected an identifier, instead found reserved keyword `{}` at line {}. Quote it as `\"{}\"` to use it as an identifier.",
+                    reserved_word, wrong_token.line_number, reserved_word
+                ),
+            )),
+            None => Err(SyntaxError::at(
+                wrong_token,
+                format!("Expected an identifier, instead found {}.", wrong_token),
+            )),
+        },
+    }
+}
+
+/// Expect a column identifier, optionally qualified with a table name, e.g. `users.id`.
+/// The outcome is the table name, if there's one, and the column name.
+pub fn expect_qualified_identifier<'t>(
+    tokens: &'t [Token],
+) -> ExpectResult<'t, (Option<String>, String)> {
+    let ExpectOk {
+        rest,
+        tokens_consumed_count,
+        outcome: identifier,
+    } = expect_identifier(tokens)?;
+    match expect_token_value(rest, &TokenValue::Delimiting(Delimiter::Period)) {
+        Ok(ExpectOk { rest, .. }) => {
+            let ExpectOk {
+                rest,
+                tokens_consumed_count: tokens_consumed_count_column_name,
+                outcome: column_name,
+            } = expect_identifier(rest)?;
+            Ok(ExpectOk {
+                rest,
+                tokens_consumed_count: tokens_consumed_count
+                    + 1
+                    + tokens_consumed_count_column_name,
+                outcome: (Some(identifier), column_name),
+            })
+        }
+        Err(_) => Ok(ExpectOk {
+            rest,
+            tokens_consumed_count,
+            outcome: (None, identifier),
+        }),
+    }
+}
+
+/// Name of the reserved word making up the token, if it is one.
+fn reserved_word_name(token_value: &TokenValue) -> Option<&'static str> {
+    match token_value {
+        TokenValue::Const(keyword) if keyword.is_symbol() => None,
+        TokenValue::Const(keyword) => Some(keyword.name()),
+        TokenValue::Type(data_type) => Some(data_type.name()),
+        TokenValue::Function(function) => Some(function.name()),
+        TokenValue::Aggregate(aggregate) => Some(aggregate.name()),
+        _ => None,
+    }
+}
+
+pub fn expect_unsigned_integer<'t>(tokens: &'t [Token]) -> ExpectResult<'t, u64> {
+    let ExpectOk {
+        rest,
+        tokens_consumed_count,
+        outcome: found_token,
+    } = expect_next_token(tokens, &"a non-negative integer")?;
+    match found_token {
+        Token {
+            value: TokenValue::Arbitrary(found_number_candidate),
+            ..
+        } => match found_number_candidate.parse::<u64>() {
+            Ok(found_number) => Ok(ExpectOk {
+                rest,
+                tokens_consumed_count,
+                outcome: found_number,
+            }),
+            Err(_) => Err(SyntaxError::at(
+                found_token,
+                format!(
+                    "Expected a non-negative integer, instead found {}.",
+                    found_token
+                ),
+            )),
+        },
+        wrong_token => Err(SyntaxError::at(
+            wrong_token,
+            format!(
+                "Expected a non-negative integer, instead found {}.",
+                wrong_token
+            ),
+        )),
     }
 }
 
-pub fn expect_data_type_raw(tokens: &[Token]) -> ExpectResult<DataTypeRaw> {
+pub fn expect_data_type_raw<'t>(tokens: &'t [Token]) -> ExpectResult<'t, DataTypeRaw> {
     let ExpectOk {
         outcome: found_token,
         ..
@@ -41,18 +152,27 @@ pub fn expect_data_type_raw(tokens: &[Token]) -> ExpectResult<DataTypeRaw> {
             tokens_consumed_count: 1,
             outcome: *found_data_type,
         }),
-        wrong_token => Err(SyntaxError(format!(
-            "Expected a data type, instead found {}.",
-            wrong_token
-        ))),
+        wrong_token => {
+            let suggestion = match &wrong_token.value {
+                TokenValue::Arbitrary(candidate) => DataTypeRaw::suggestion_for(candidate),
+                _ => None,
+            };
+            Err(SyntaxError::at(
+                wrong_token,
+                match suggestion {
+                    Some(suggestion) => format!(
+                        "Expected a data type, instead found {}. {}",
+                        wrong_token, suggestion
+                    ),
+                    None => format!("Expected a data type, instead found {}.", wrong_token),
+                },
+            ))
+        }
     }
 }
 
-pub fn expect_data_type(tokens: &[Token]) -> ExpectResult<DataType> {
-    let is_nullable = matches!(
-        expect_token_value(tokens, &TokenValue::Const(Keyword::Nullable)),
-        Ok(_)
-    );
+pub fn expect_data_type<'t>(tokens: &'t [Token]) -> ExpectResult<'t, DataType> {
+    let is_nullable = expect_token_value(tokens, &TokenValue::Const(Keyword::Nullable)).is_ok();
     let ExpectOk {
         rest,
         tokens_consumed_count,
@@ -77,7 +197,7 @@ pub fn expect_data_type(tokens: &[Token]) -> ExpectResult<DataType> {
     })
 }
 
-pub fn expect_data_instance(tokens: &[Token]) -> ExpectResult<DataInstance> {
+pub fn expect_data_instance<'t>(tokens: &'t [Token]) -> ExpectResult<'t, DataInstance> {
     let ExpectOk {
         rest,
         tokens_consumed_count,
@@ -92,37 +212,101 @@ pub fn expect_data_instance(tokens: &[Token]) -> ExpectResult<DataInstance> {
             tokens_consumed_count,
             outcome: DataInstance::Null,
         }),
+        Token {
+            value: TokenValue::Const(keyword @ (Keyword::True | Keyword::False)),
+            ..
+        } => Ok(ExpectOk {
+            rest,
+            tokens_consumed_count,
+            outcome: DataInstance::Direct(DataInstanceRaw::Bool(*keyword == Keyword::True)),
+        }),
         Token {
             value: TokenValue::String(found_string),
             ..
         } => Ok(ExpectOk {
             rest,
             tokens_consumed_count,
-            outcome: DataInstance::Direct(DataInstanceRaw::String(found_string.into())),
+            outcome: DataInstance::Direct(DataInstanceRaw::String(found_string.to_string())),
         }),
         Token {
             value: TokenValue::Arbitrary(found_number_candidate),
             ..
-        } => match found_number_candidate.parse::<u32>() {
-            // UInt32 is the default integer type
+        } => match found_number_candidate.parse::<u128>() {
+            // UInt32 is the default integer type, with wider ones used for larger numbers
             Ok(found_number) => Ok(ExpectOk {
                 rest,
                 tokens_consumed_count,
-                outcome: DataInstance::Direct(DataInstanceRaw::UInt32(found_number)),
+                outcome: DataInstance::Direct(
+                    u32::try_from(found_number)
+                        .map(DataInstanceRaw::UInt32)
+                        .or_else(|_| u64::try_from(found_number).map(DataInstanceRaw::UInt64))
+                        .unwrap_or(DataInstanceRaw::UInt128(found_number)),
+                ),
             }),
-            Err(_) => Err(SyntaxError(format!(
-                "Expected a value, instead found {}.",
-                found_number_candidate
-            ))),
+            Err(_) => Err(SyntaxError::at(
+                found_token,
+                format!(
+                    "Expected a value, instead found {}.",
+                    found_number_candidate
+                ),
+            )),
         },
-        wrong_token => Err(SyntaxError(format!(
-            "Expected a value, instead found {}.",
-            wrong_token
-        ))),
+        wrong_token => Err(SyntaxError::at(
+            wrong_token,
+            format!("Expected a value, instead found {}.", wrong_token),
+        )),
     }
 }
 
-pub fn expect_function_call(tokens: &[Token]) -> ExpectResult<Function> {
+pub fn expect_parameter<'t>(tokens: &'t [Token]) -> ExpectResult<'t, usize> {
+    let ExpectOk {
+        rest,
+        tokens_consumed_count,
+        outcome: found_token,
+    } = expect_next_token(tokens, &"a parameter")?;
+    match found_token {
+        Token {
+            value: TokenValue::Parameter(number),
+            ..
+        } => Ok(ExpectOk {
+            rest,
+            tokens_consumed_count,
+            outcome: *number,
+        }),
+        wrong_token => Err(SyntaxError::at(
+            wrong_token,
+            format!("Expected a parameter, instead found {}.", wrong_token),
+        )),
+    }
+}
+
+/// Expect a value given directly or as a parameter, which is what INSERT accepts.
+pub fn expect_value<'t>(tokens: &'t [Token]) -> ExpectResult<'t, DataDefinition> {
+    if let Ok(ExpectOk {
+        rest,
+        tokens_consumed_count,
+        outcome: number,
+    }) = expect_parameter(tokens)
+    {
+        return Ok(ExpectOk {
+            rest,
+            tokens_consumed_count,
+            outcome: DataDefinition::Parameter(number),
+        });
+    }
+    let ExpectOk {
+        rest,
+        tokens_consumed_count,
+        outcome: data_instance,
+    } = expect_data_instance(tokens)?;
+    Ok(ExpectOk {
+        rest,
+        tokens_consumed_count,
+        outcome: DataDefinition::Const(data_instance),
+    })
+}
+
+pub fn expect_function_call<'t>(tokens: &'t [Token]) -> ExpectResult<'t, Function> {
     let ExpectOk {
         rest,
         tokens_consumed_count: tokens_consumed_count_call,
@@ -151,14 +335,14 @@ pub fn expect_function_call(tokens: &[Token]) -> ExpectResult<Function> {
                 outcome: found_function.to_owned(),
             })
         }
-        wrong_token => Err(SyntaxError(format!(
-            "Expected a function name, instead found {}.",
-            wrong_token
-        ))),
+        wrong_token => Err(SyntaxError::at(
+            wrong_token,
+            format!("Expected a function name, instead found {}.", wrong_token),
+        )),
     }
 }
 
-pub fn expect_data_definition(tokens: &[Token]) -> ExpectResult<DataDefinition> {
+pub fn expect_data_definition<'t>(tokens: &'t [Token]) -> ExpectResult<'t, DataDefinition> {
     if let Ok(ExpectOk {
         rest,
         tokens_consumed_count,
@@ -198,62 +382,311 @@ pub fn expect_data_definition(tokens: &[Token]) -> ExpectResult<DataDefinition>
     if let Ok(ExpectOk {
         rest,
         tokens_consumed_count,
-        outcome: identifier,
-    }) = expect_identifier(tokens)
+        outcome: number,
+    }) = expect_parameter(tokens)
     {
         return Ok(ExpectOk {
             rest,
             tokens_consumed_count,
-            outcome: DataDefinition::Identifier(identifier),
+            outcome: DataDefinition::Parameter(number),
         });
     }
-    Err(SyntaxError(format!(
+    if let Ok(ExpectOk {
+        rest,
+        tokens_consumed_count,
+        outcome: (table_name, column_name),
+    }) = expect_qualified_identifier(tokens)
+    {
+        return Ok(ExpectOk {
+            rest,
+            tokens_consumed_count,
+            outcome: match table_name {
+                Some(table_name) => DataDefinition::QualifiedIdentifier {
+                    table_name,
+                    column_name,
+                },
+                None => DataDefinition::Identifier(column_name),
+            },
+        });
+    }
+    let message = format!(
         "Expected a function call, a constant value or an identifier, instead found {:?}.",
         tokens.first()
-    )))
+    );
+    Err(match tokens.first() {
+        Some(found_token) => SyntaxError::at(found_token, message),
+        None => SyntaxError::new(message),
+    })
+}
+
+/// Expect `IS NULL` or `IS NOT NULL`, with the outcome being whether the check is negated.
+fn expect_null_check<'t>(tokens: &'t [Token]) -> ExpectResult<'t, bool> {
+    let ExpectOk { rest, .. } = expect_token_value(tokens, &TokenValue::Const(Keyword::Is))?;
+    let is_negated = expect_token_value(rest, &TokenValue::Const(Keyword::Not)).is_ok();
+    let rest = if is_negated { &rest[1..] } else { rest };
+    let ExpectOk { rest, .. } = expect_token_value(rest, &TokenValue::Const(Keyword::Null))?;
+    Ok(ExpectOk {
+        rest,
+        tokens_consumed_count: 2 + usize::from(is_negated),
+        outcome: is_negated,
+    })
+}
+
+/// Expect `IN (value, ...)`, with the outcome being the listed values.
+fn expect_in_list<'t>(tokens: &'t [Token]) -> ExpectResult<'t, Vec<Expression>> {
+    let ExpectOk { rest, .. } = expect_token_value(tokens, &TokenValue::Const(Keyword::In))?;
+    if let [Token {
+        value: TokenValue::Delimiting(Delimiter::ParenthesisOpening),
+        ..
+    }, closing_token @ Token {
+        value: TokenValue::Delimiting(Delimiter::ParenthesisClosing),
+        ..
+    }, ..] = rest
+    {
+        return Err(SyntaxError::at(
+            closing_token,
+            "Expected at least one value in the IN list, instead found an empty list.".into(),
+        ));
+    }
+    let ExpectOk {
+        rest,
+        tokens_consumed_count,
+        outcome: values,
+    } = expect_enclosed(
+        rest,
+        |tokens| expect_comma_separated(tokens, expect_value),
+        Delimiter::ParenthesisOpening,
+        Delimiter::ParenthesisClosing,
+    )?;
+    Ok(ExpectOk {
+        rest,
+        tokens_consumed_count: 1 + tokens_consumed_count,
+        outcome: values.into_iter().map(Expression::Atom).collect(),
+    })
+}
+
+/// Expect `BETWEEN low AND high`, with the outcome being the bounds.
+fn expect_between_range<'t>(tokens: &'t [Token]) -> ExpectResult<'t, (Expression, Expression)> {
+    let ExpectOk { rest, .. } = expect_token_value(tokens, &TokenValue::Const(Keyword::Between))?;
+    let ExpectOk {
+        rest,
+        tokens_consumed_count: tokens_consumed_count_low,
+        outcome: low,
+    } = expect_value(rest)?;
+    let ExpectOk { rest, .. } = expect_token_value(rest, &TokenValue::Const(Keyword::And))?;
+    let ExpectOk {
+        rest,
+        tokens_consumed_count: tokens_consumed_count_high,
+        outcome: high,
+    } = expect_value(rest)?;
+    Ok(ExpectOk {
+        rest,
+        tokens_consumed_count: 2 + tokens_consumed_count_low + tokens_consumed_count_high, // +2 for BETWEEN and AND
+        outcome: (Expression::Atom(low), Expression::Atom(high)),
+    })
+}
+
+/// Expect a condition, i.e. predicates combined with AND and OR.
+/// AND binds tighter than OR, while parentheses group predicates explicitly.
+///
+/// Parenthesized groups are tracked with an explicit stack instead of recursion, so that however deeply
+/// the input nests them, parsing can't overflow the call stack.
+pub fn expect_expression<'t>(tokens: &'t [Token]) -> ExpectResult<'t, Expression> {
+    let mut rest = tokens;
+    let mut tokens_consumed_count = 0;
+    // Groups enclosing the current one, the innermost last
+    let mut enclosing_groups = Vec::<ExpressionGroup>::new();
+    let mut current_group = ExpressionGroup::default();
+    loop {
+        // An operand is expected here, which either opens a group or is a predicate
+        if let Some((
+            Token {
+                value: TokenValue::Delimiting(Delimiter::ParenthesisOpening),
+                ..
+            },
+            rest_opening,
+        )) = rest.split_first()
+        {
+            enclosing_groups.push(std::mem::take(&mut current_group));
+            rest = rest_opening;
+            tokens_consumed_count += 1;
+            continue;
+        }
+        let ExpectOk {
+            rest: rest_predicate,
+            tokens_consumed_count: tokens_consumed_count_predicate,
+            outcome: mut operand,
+        } = expect_predicate(rest)?;
+        rest = rest_predicate;
+        tokens_consumed_count += tokens_consumed_count_predicate;
+        // After an operand, the group either continues with a joiner, or ends
+        loop {
+            if let Ok(ExpectOk { rest: rest_and, .. }) =
+                expect_token_value(rest, &TokenValue::Const(Keyword::And))
+            {
+                current_group.push_and(operand);
+                rest = rest_and;
+                tokens_consumed_count += 1;
+                break;
+            }
+            if let Ok(ExpectOk { rest: rest_or, .. }) =
+                expect_token_value(rest, &TokenValue::Const(Keyword::Or))
+            {
+                current_group.push_or(operand);
+                rest = rest_or;
+                tokens_consumed_count += 1;
+                break;
+            }
+            let group_expression = current_group.finish(operand);
+            match enclosing_groups.pop() {
+                Some(enclosing_group) => {
+                    let ExpectOk {
+                        rest: rest_closing, ..
+                    } = expect_token_value(
+                        rest,
+                        &TokenValue::Delimiting(Delimiter::ParenthesisClosing),
+                    )?;
+                    rest = rest_closing;
+                    tokens_consumed_count += 1;
+                    // The closed group is just an operand of the enclosing one
+                    current_group = enclosing_group;
+                    operand = group_expression;
+                }
+                None => {
+                    return Ok(ExpectOk {
+                        rest,
+                        tokens_consumed_count,
+                        outcome: group_expression,
+                    })
+                }
+            }
+        }
+    }
+}
+
+/// Operands of a parenthesized group (or of the whole condition) parsed so far, combined left to right.
+#[derive(Default)]
+struct ExpressionGroup {
+    /// Terms already joined with OR.
+    disjunction: Option<Expression>,
+    /// Operands joined with AND since the last OR.
+    conjunction: Option<Expression>,
+}
+
+impl ExpressionGroup {
+    fn push_and(&mut self, operand: Expression) {
+        self.conjunction = Some(match self.conjunction.take() {
+            Some(conjunction) => Expression::And(Box::new(conjunction), Box::new(operand)),
+            None => operand,
+        });
+    }
+
+    fn push_or(&mut self, operand: Expression) {
+        self.push_and(operand);
+        let term = self.conjunction.take().unwrap();
+        self.disjunction = Some(match self.disjunction.take() {
+            Some(disjunction) => Expression::Or(Box::new(disjunction), Box::new(term)),
+            None => term,
+        });
+    }
+
+    fn finish(mut self, operand: Expression) -> Expression {
+        self.push_or(operand);
+        self.disjunction.unwrap()
+    }
 }
 
-pub fn expect_expression(tokens: &[Token]) -> ExpectResult<Expression> {
+/// Expect a single predicate, e.g. `a = 1` or `a IS NULL`, or just a value.
+fn expect_predicate<'t>(tokens: &'t [Token]) -> ExpectResult<'t, Expression> {
     let ExpectOk {
         rest: rest_atom,
         tokens_consumed_count: tokens_consumed_count_lhs,
         outcome: lhs_raw,
     } = expect_data_definition(tokens)?;
     let lhs = Expression::Atom(lhs_raw);
+    // IS is followed by a keyword rather than a value, so it's not handled like a comparison operator
+    if expect_token_value(rest_atom, &TokenValue::Const(Keyword::Is)).is_ok() {
+        let ExpectOk {
+            rest,
+            tokens_consumed_count: tokens_consumed_count_null_check,
+            outcome: is_negated,
+        } = expect_null_check(rest_atom)?;
+        return Ok(ExpectOk {
+            rest,
+            tokens_consumed_count: tokens_consumed_count_lhs + tokens_consumed_count_null_check,
+            outcome: if is_negated {
+                Expression::IsNotNull(Box::new(lhs))
+            } else {
+                Expression::IsNull(Box::new(lhs))
+            },
+        });
+    }
+    if expect_token_value(rest_atom, &TokenValue::Const(Keyword::In)).is_ok() {
+        let ExpectOk {
+            rest,
+            tokens_consumed_count: tokens_consumed_count_in_list,
+            outcome: values,
+        } = expect_in_list(rest_atom)?;
+        return Ok(ExpectOk {
+            rest,
+            tokens_consumed_count: tokens_consumed_count_lhs + tokens_consumed_count_in_list,
+            outcome: Expression::In {
+                column: Box::new(lhs),
+                values,
+            },
+        });
+    }
+    if let Ok(ExpectOk { rest, .. }) =
+        expect_token_value(rest_atom, &TokenValue::Const(Keyword::Like))
+    {
+        let ExpectOk {
+            rest,
+            tokens_consumed_count: tokens_consumed_count_pattern,
+            outcome: pattern,
+        } = expect_value(rest)?;
+        return Ok(ExpectOk {
+            rest,
+            tokens_consumed_count: tokens_consumed_count_lhs + 1 + tokens_consumed_count_pattern, // +1 for LIKE
+            outcome: Expression::Like {
+                column: Box::new(lhs),
+                pattern: Box::new(Expression::Atom(pattern)),
+            },
+        });
+    }
+    if expect_token_value(rest_atom, &TokenValue::Const(Keyword::Between)).is_ok() {
+        let ExpectOk {
+            rest,
+            tokens_consumed_count: tokens_consumed_count_range,
+            outcome: (low, high),
+        } = expect_between_range(rest_atom)?;
+        return Ok(ExpectOk {
+            rest,
+            tokens_consumed_count: tokens_consumed_count_lhs + tokens_consumed_count_range,
+            outcome: Expression::Between {
+                column: Box::new(lhs),
+                low: Box::new(low),
+                high: Box::new(high),
+            },
+        });
+    }
+    // Anything other than an operator ends the predicate, e.g. AND or ORDER BY
     let ExpectOk {
         rest,
         tokens_consumed_count: tokens_consumed_count_operator_and_rhs,
         outcome: operator_and_rhs,
     } = detect(
         rest_atom,
-        |tokens| expect_next_token(tokens, &"an operator"),
+        |tokens| expect_token_value(tokens, &TokenValue::Delimiting(Delimiter::Equal)),
         expect_data_definition,
         &"the right-hand side of the expression",
     )?;
     match operator_and_rhs {
-        Some((
-            Token {
-                value: TokenValue::Delimiting(Delimiter::Equal),
-                ..
-            },
-            rhs_raw,
-        )) => Ok(ExpectOk {
+        Some(((), rhs_raw)) => Ok(ExpectOk {
             rest,
             tokens_consumed_count: tokens_consumed_count_lhs
                 + tokens_consumed_count_operator_and_rhs,
             outcome: Expression::Equal(Box::new(lhs), Box::new(Expression::Atom(rhs_raw))),
         }),
-        Some((
-            Token {
-                value: unexpected_token,
-                ..
-            },
-            _,
-        )) => Err(SyntaxError(format!(
-            "Expected {}, instead found {}.",
-            Delimiter::Equal,
-            unexpected_token
-        ))),
         None => Ok(ExpectOk {
             rest,
             tokens_consumed_count: tokens_consumed_count_lhs,
@@ -262,6 +695,178 @@ pub fn expect_expression(tokens: &[Token]) -> ExpectResult<Expression> {
     }
 }
 
+/// Expect an arithmetic expression, i.e. values combined with `+`, `-`, `*` and `/`. `*` and `/` bind tighter
+/// than `+` and `-`, operators of the same precedence apply left to right, and parentheses group explicitly.
+/// `CAST(expression AS type)` is an operand too.
+///
+/// Like in `expect_expression`, parenthesized groups (including those of CAST) are tracked with an explicit stack
+/// instead of recursion, so that however deeply the input nests them, parsing can't overflow the call stack.
+pub fn expect_arithmetic_expression<'t>(tokens: &'t [Token]) -> ExpectResult<'t, Expression> {
+    let mut rest = tokens;
+    let mut tokens_consumed_count = 0;
+    // Groups enclosing the current one, the innermost last, along with what kind of group each one opened
+    let mut enclosing_groups = Vec::<(ArithmeticGroup, ArithmeticGroupKind)>::new();
+    let mut current_group = ArithmeticGroup::default();
+    loop {
+        // An operand is expected here, which either opens a group or is a value
+        let opened_group_kind = match rest.first().map(|token| &token.value) {
+            Some(TokenValue::Delimiting(Delimiter::ParenthesisOpening)) => {
+                Some((ArithmeticGroupKind::Parenthesized, 1))
+            }
+            Some(TokenValue::Const(Keyword::Cast)) => {
+                expect_token_value(
+                    &rest[1..],
+                    &TokenValue::Delimiting(Delimiter::ParenthesisOpening),
+                )?;
+                Some((ArithmeticGroupKind::Cast, 2))
+            }
+            _ => None,
+        };
+        if let Some((kind, tokens_consumed_count_opening)) = opened_group_kind {
+            enclosing_groups.push((std::mem::take(&mut current_group), kind));
+            rest = &rest[tokens_consumed_count_opening..];
+            tokens_consumed_count += tokens_consumed_count_opening;
+            continue;
+        }
+        let ExpectOk {
+            rest: rest_value,
+            tokens_consumed_count: tokens_consumed_count_value,
+            outcome: value,
+        } = expect_data_definition(rest)?;
+        rest = rest_value;
+        tokens_consumed_count += tokens_consumed_count_value;
+        let mut operand = Expression::Atom(value);
+        // After an operand, the group either continues with an operator, or ends
+        loop {
+            if let Some((
+                Token {
+                    value: TokenValue::Const(operator),
+                    ..
+                },
+                rest_operator,
+            )) = rest.split_first()
+            {
+                if ArithmeticGroup::is_operator(operator) {
+                    expect_next_token(
+                        rest_operator,
+                        &"the right-hand side of the arithmetic operation",
+                    )?;
+                    current_group.push(operand, operator.clone());
+                    rest = rest_operator;
+                    tokens_consumed_count += 1;
+                    break;
+                }
+            }
+            let group_expression = current_group.finish(operand);
+            let (enclosing_group, kind) = match enclosing_groups.pop() {
+                Some(enclosing_group) => enclosing_group,
+                None => {
+                    return Ok(ExpectOk {
+                        rest,
+                        tokens_consumed_count,
+                        outcome: group_expression,
+                    })
+                }
+            };
+            // The closed group is just an operand of the enclosing one
+            operand = match kind {
+                ArithmeticGroupKind::Parenthesized => group_expression,
+                ArithmeticGroupKind::Cast => {
+                    let ExpectOk {
+                        rest: rest_as,
+                        tokens_consumed_count: tokens_consumed_count_as,
+                        ..
+                    } = expect_token_value(rest, &TokenValue::Const(Keyword::As))?;
+                    let ExpectOk {
+                        rest: rest_data_type,
+                        tokens_consumed_count: tokens_consumed_count_data_type,
+                        outcome: data_type,
+                    } = expect_data_type_raw(rest_as)?;
+                    rest = rest_data_type;
+                    tokens_consumed_count +=
+                        tokens_consumed_count_as + tokens_consumed_count_data_type;
+                    Expression::Cast {
+                        operand: Box::new(group_expression),
+                        data_type,
+                    }
+                }
+            };
+            let ExpectOk {
+                rest: rest_closing, ..
+            } = expect_token_value(rest, &TokenValue::Delimiting(Delimiter::ParenthesisClosing))?;
+            rest = rest_closing;
+            tokens_consumed_count += 1;
+            current_group = enclosing_group;
+        }
+    }
+}
+
+/// What opened a group of an arithmetic expression, which determines how it's closed.
+enum ArithmeticGroupKind {
+    /// `(`, closed by `)`.
+    Parenthesized,
+    /// `CAST(`, closed by `AS type)`.
+    Cast,
+}
+
+/// Operands of a group of an arithmetic expression parsed so far, combined left to right.
+#[derive(Default)]
+struct ArithmeticGroup {
+    /// Operands already joined with `+` or `-`, along with the operator following them.
+    sum: Option<(Expression, Keyword)>,
+    /// Operands joined with `*` or `/` since the last `+` or `-`, along with the operator following them.
+    product: Option<(Expression, Keyword)>,
+}
+
+impl ArithmeticGroup {
+    fn is_operator(keyword: &Keyword) -> bool {
+        matches!(
+            keyword,
+            Keyword::Plus | Keyword::Minus | Keyword::Asterisk | Keyword::Slash
+        )
+    }
+
+    fn apply(operator: Keyword, lhs: Expression, rhs: Expression) -> Expression {
+        let (lhs, rhs) = (Box::new(lhs), Box::new(rhs));
+        match operator {
+            Keyword::Plus => Expression::Add(lhs, rhs),
+            Keyword::Minus => Expression::Subtract(lhs, rhs),
+            Keyword::Asterisk => Expression::Multiply(lhs, rhs),
+            _ => Expression::Divide(lhs, rhs),
+        }
+    }
+
+    /// Add an operand followed by an arithmetic operator.
+    fn push(&mut self, operand: Expression, operator: Keyword) {
+        let term = self.complete_product(operand);
+        // `*` and `/` bind tighter, so a product goes on until the next `+` or `-`
+        if matches!(operator, Keyword::Asterisk | Keyword::Slash) {
+            self.product = Some((term, operator));
+        } else {
+            self.sum = Some((self.complete_sum(term), operator));
+        }
+    }
+
+    fn complete_product(&mut self, operand: Expression) -> Expression {
+        match self.product.take() {
+            Some((product, operator)) => Self::apply(operator, product, operand),
+            None => operand,
+        }
+    }
+
+    fn complete_sum(&mut self, term: Expression) -> Expression {
+        match self.sum.take() {
+            Some((sum, operator)) => Self::apply(operator, sum, term),
+            None => term,
+        }
+    }
+
+    fn finish(mut self, operand: Expression) -> Expression {
+        let term = self.complete_product(operand);
+        self.complete_sum(term)
+    }
+}
+
 #[cfg(test)]
 mod expect_identifier_tests {
     use super::*;
@@ -271,8 +876,9 @@ mod expect_identifier_tests {
     fn returns_ok() {
         assert_eq!(
             expect_identifier(&[Token {
-                value: TokenValue::Arbitrary("foo".to_string()),
-                line_number: 1
+                value: TokenValue::Arbitrary("foo".into()),
+                line_number: 1,
+                column_number: 1
             }]),
             Ok(ExpectOk {
                 rest: &[][..],
@@ -287,11 +893,13 @@ mod expect_identifier_tests {
         assert_eq!(
             expect_identifier(&[Token {
                 value: TokenValue::Const(Keyword::Create),
-                line_number: 1
+                line_number: 1,
+                column_number: 1
             }]),
-            Err(SyntaxError(
-                "Expected an identifier, instead found keyword `CREATE` at line 1.".to_string()
-            ))
+            Err(SyntaxError {
+                message: "Expected an identifier, instead found reserved keyword `CREATE` at line 1. Quote it as `\"CREATE\"` to use it as an identifier.".to_string(),
+                position: Some(Position { line: 1, column: 1 })
+            })
         )
     }
 
@@ -299,7 +907,95 @@ mod expect_identifier_tests {
     fn returns_error_if_eos() {
         assert_eq!(
             expect_identifier(&[]),
-            Err(SyntaxError(
+            Err(SyntaxError::new(
+                "Expected an identifier, instead found end of statement.".to_string()
+            ))
+        )
+    }
+
+    #[test]
+    fn returns_error_if_unquoted_with_other_characters() {
+        assert_eq!(
+            expect_identifier(&[Token {
+                value: TokenValue::Arbitrary("my-table".into()),
+                line_number: 1,
+                column_number: 14
+            }]),
+            Err(SyntaxError {
+                message: "Expected an identifier, instead found arbitrary `my-table` at line 1, which contains characters other than letters, digits and underscores. Quote it as `\"my-table\"` to use it as an identifier.".to_string(),
+                position: Some(Position { line: 1, column: 14 })
+            })
+        );
+        assert_eq!(
+            expect_identifier(&[Token {
+                value: TokenValue::QuotedIdentifier("my-table".into()),
+                line_number: 1,
+                column_number: 14
+            }]),
+            Ok(ExpectOk {
+                rest: &[][..],
+                tokens_consumed_count: 1,
+                outcome: "my-table".to_string()
+            })
+        );
+    }
+}
+
+#[cfg(test)]
+mod expect_qualified_identifier_tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn get_token(value: TokenValue, column_number: usize) -> Token {
+        Token {
+            value,
+            line_number: 1,
+            column_number,
+        }
+    }
+
+    #[test]
+    fn returns_ok_qualified() {
+        let tokens = [
+            get_token(TokenValue::Arbitrary("users".into()), 1),
+            get_token(TokenValue::Delimiting(Delimiter::Period), 6),
+            get_token(TokenValue::QuotedIdentifier("id".into()), 7),
+        ];
+        assert_eq!(
+            expect_qualified_identifier(&tokens),
+            Ok(ExpectOk {
+                rest: &[][..],
+                tokens_consumed_count: 3,
+                outcome: (Some("users".to_string()), "id".to_string())
+            })
+        )
+    }
+
+    #[test]
+    fn returns_ok_unqualified() {
+        let tokens = [
+            get_token(TokenValue::Arbitrary("id".into()), 1),
+            get_token(TokenValue::Delimiting(Delimiter::Comma), 3),
+        ];
+        assert_eq!(
+            expect_qualified_identifier(&tokens),
+            Ok(ExpectOk {
+                rest: &tokens[1..],
+                tokens_consumed_count: 1,
+                outcome: (None, "id".to_string())
+            })
+        )
+    }
+
+    #[test]
+    fn returns_error_if_no_column_after_period() {
+        let tokens = [
+            get_token(TokenValue::Arbitrary("users".into()), 1),
+            get_token(TokenValue::Delimiting(Delimiter::Period), 6),
+        ];
+        assert_eq!(
+            expect_qualified_identifier(&tokens),
+            Err(SyntaxError::new(
                 "Expected an identifier, instead found end of statement.".to_string()
             ))
         )
@@ -316,7 +1012,8 @@ mod expect_data_type_wrapped_tests {
         assert_eq!(
             expect_data_type(&[Token {
                 value: TokenValue::Type(DataTypeRaw::UInt64),
-                line_number: 1
+                line_number: 1,
+                column_number: 1
             }]),
             Ok(ExpectOk {
                 rest: &[][..],
@@ -335,19 +1032,23 @@ mod expect_data_type_wrapped_tests {
             expect_data_type(&[
                 Token {
                     value: TokenValue::Const(Keyword::Nullable),
-                    line_number: 1
+                    line_number: 1,
+                    column_number: 1
                 },
                 Token {
                     value: TokenValue::Delimiting(Delimiter::ParenthesisOpening),
-                    line_number: 1
+                    line_number: 1,
+                    column_number: 9
                 },
                 Token {
                     value: TokenValue::Type(DataTypeRaw::Timestamp),
-                    line_number: 1
+                    line_number: 1,
+                    column_number: 10
                 },
                 Token {
                     value: TokenValue::Delimiting(Delimiter::ParenthesisClosing),
-                    line_number: 1
+                    line_number: 1,
+                    column_number: 19
                 }
             ]),
             Ok(ExpectOk {
@@ -367,24 +1068,33 @@ mod expect_data_type_wrapped_tests {
             expect_data_type(&[
                 Token {
                     value: TokenValue::Const(Keyword::Nullable),
-                    line_number: 1
+                    line_number: 1,
+                    column_number: 1
                 },
                 Token {
                     value: TokenValue::Delimiting(Delimiter::ParenthesisOpening),
-                    line_number: 1
+                    line_number: 1,
+                    column_number: 9
                 },
                 Token {
                     value: TokenValue::Type(DataTypeRaw::Timestamp),
-                    line_number: 1
+                    line_number: 1,
+                    column_number: 10
                 },
                 Token {
                     value: TokenValue::Delimiting(Delimiter::Comma),
-                    line_number: 1
+                    line_number: 1,
+                    column_number: 19
                 }
             ]),
-            Err(SyntaxError(
-                "Expected closing parenthesis `)`, instead found comma `,` at line 1.".to_string()
-            ))
+            Err(SyntaxError {
+                message: "Expected closing parenthesis `)`, instead found comma `,` at line 1."
+                    .to_string(),
+                position: Some(Position {
+                    line: 1,
+                    column: 19
+                })
+            })
         )
     }
 
@@ -392,12 +1102,15 @@ mod expect_data_type_wrapped_tests {
     fn returns_error_if_no_type() {
         assert_eq!(
             expect_data_type(&[Token {
-                value: TokenValue::Arbitrary("foo".to_string()),
-                line_number: 1
+                value: TokenValue::Arbitrary("foo".into()),
+                line_number: 1,
+                column_number: 1
             }]),
-            Err(SyntaxError(
-                "Expected a data type, instead found arbitrary `foo` at line 1.".to_string()
-            ))
+            Err(SyntaxError {
+                message: "Expected a data type, instead found arbitrary `foo` at line 1."
+                    .to_string(),
+                position: Some(Position { line: 1, column: 1 })
+            })
         )
     }
 
@@ -405,7 +1118,7 @@ mod expect_data_type_wrapped_tests {
     fn returns_error_if_neos() {
         assert_eq!(
             expect_data_type(&[]),
-            Err(SyntaxError(
+            Err(SyntaxError::new(
                 "Expected a data type, instead found end of statement.".to_string()
             ))
         )
@@ -417,20 +1130,28 @@ mod expect_data_type_wrapped_tests {
             expect_data_type(&[
                 Token {
                     value: TokenValue::Const(Keyword::Nullable),
-                    line_number: 1
+                    line_number: 1,
+                    column_number: 1
                 },
                 Token {
                     value: TokenValue::Delimiting(Delimiter::ParenthesisOpening),
-                    line_number: 1
+                    line_number: 1,
+                    column_number: 9
                 },
                 Token {
-                    value: TokenValue::Arbitrary("bar".to_string()),
-                    line_number: 1
+                    value: TokenValue::Arbitrary("bar".into()),
+                    line_number: 1,
+                    column_number: 10
                 }
             ]),
-            Err(SyntaxError(
-                "Expected a data type, instead found arbitrary `bar` at line 1.".to_string()
-            ))
+            Err(SyntaxError {
+                message: "Expected a data type, instead found arbitrary `bar` at line 1."
+                    .to_string(),
+                position: Some(Position {
+                    line: 1,
+                    column: 10
+                })
+            })
         )
     }
 
@@ -440,14 +1161,16 @@ mod expect_data_type_wrapped_tests {
             expect_data_type(&[
                 Token {
                     value: TokenValue::Const(Keyword::Nullable),
-                    line_number: 1
+                    line_number: 1,
+                    column_number: 1
                 },
                 Token {
                     value: TokenValue::Delimiting(Delimiter::ParenthesisOpening),
-                    line_number: 1
+                    line_number: 1,
+                    column_number: 9
                 }
             ]),
-            Err(SyntaxError(
+            Err(SyntaxError::new(
                 "Expected a data type, instead found end of statement.".to_string()
             ))
         )
@@ -463,8 +1186,9 @@ mod expect_data_instance_tests {
     fn returns_ok_string() {
         assert_eq!(
             expect_data_instance(&[Token {
-                value: TokenValue::String("foo".to_string()),
-                line_number: 1
+                value: TokenValue::String("foo".into()),
+                line_number: 1,
+                column_number: 1
             },]),
             Ok(ExpectOk {
                 rest: &[][..],
@@ -478,8 +1202,9 @@ mod expect_data_instance_tests {
     fn returns_ok_number() {
         assert_eq!(
             expect_data_instance(&[Token {
-                value: TokenValue::Arbitrary("1227".to_string()),
-                line_number: 1
+                value: TokenValue::Arbitrary("1227".into()),
+                line_number: 1,
+                column_number: 1
             }]),
             Ok(ExpectOk {
                 rest: &[][..],
@@ -488,6 +1213,38 @@ mod expect_data_instance_tests {
             })
         )
     }
+
+    #[test]
+    fn returns_ok_wider_number() {
+        assert_eq!(
+            expect_data_instance(&[Token {
+                value: TokenValue::Arbitrary("4294967296".into()),
+                line_number: 1,
+                column_number: 1
+            }]),
+            Ok(ExpectOk {
+                rest: &[][..],
+                tokens_consumed_count: 1,
+                outcome: DataInstance::Direct(DataInstanceRaw::UInt64(4_294_967_296))
+            })
+        )
+    }
+
+    #[test]
+    fn returns_ok_bool() {
+        assert_eq!(
+            expect_data_instance(&[Token {
+                value: TokenValue::Const(Keyword::False),
+                line_number: 1,
+                column_number: 1
+            }]),
+            Ok(ExpectOk {
+                rest: &[][..],
+                tokens_consumed_count: 1,
+                outcome: DataInstance::Direct(DataInstanceRaw::Bool(false))
+            })
+        )
+    }
 }
 
 #[cfg(test)]
@@ -501,15 +1258,18 @@ mod expect_function_call_tests {
             expect_function_call(&[
                 Token {
                     value: TokenValue::Function(Function::Ulid),
-                    line_number: 1
+                    line_number: 1,
+                    column_number: 1
                 },
                 Token {
                     value: TokenValue::Delimiting(Delimiter::ParenthesisOpening),
-                    line_number: 1
+                    line_number: 1,
+                    column_number: 5
                 },
                 Token {
                     value: TokenValue::Delimiting(Delimiter::ParenthesisClosing),
-                    line_number: 1
+                    line_number: 1,
+                    column_number: 6
                 }
             ]),
             Ok(ExpectOk {
@@ -525,9 +1285,10 @@ mod expect_function_call_tests {
         assert_eq!(
             expect_function_call(&[Token {
                 value: TokenValue::Function(Function::Ulid),
-                line_number: 1
+                line_number: 1,
+                column_number: 1
             }]),
-            Err(SyntaxError(
+            Err(SyntaxError::new(
                 "Expected opening parenthesis `(`, instead found end of statement.".to_string()
             ))
         )
@@ -0,0 +1,97 @@
+use crate::constructs::statements::{AlterTableAction, AlterTableStatement};
+use crate::sql::expects::{generic::*, semantic::*, ExpectOk, ExpectResult};
+use crate::sql::{tokenizer::*, SyntaxError};
+
+use super::expect_column_definition;
+
+pub fn expect_alter_table_action<'t>(tokens: &'t [Token]) -> ExpectResult<'t, AlterTableAction> {
+    let ExpectOk {
+        rest,
+        tokens_consumed_count: tokens_consumed_count_action,
+        outcome: found_token,
+    } = expect_next_token(tokens, &format!("{} or {}", Keyword::Add, Keyword::Drop))?;
+    match found_token {
+        // ADD [COLUMN]
+        Token {
+            value: TokenValue::Const(Keyword::Add),
+            ..
+        } => {
+            let (rest, tokens_consumed_count_column) =
+                match expect_token_value(rest, &TokenValue::Const(Keyword::Column)) {
+                    Ok(ExpectOk {
+                        rest,
+                        tokens_consumed_count,
+                        ..
+                    }) => (rest, tokens_consumed_count),
+                    Err(_) => (rest, 0),
+                };
+            let ExpectOk {
+                rest,
+                tokens_consumed_count: tokens_consumed_count_column_definition,
+                outcome: column,
+            } = expect_column_definition(rest)?;
+            Ok(ExpectOk {
+                rest,
+                tokens_consumed_count: tokens_consumed_count_action
+                    + tokens_consumed_count_column
+                    + tokens_consumed_count_column_definition,
+                outcome: AlterTableAction::AddColumn(column),
+            })
+        }
+        // DROP [COLUMN]
+        Token {
+            value: TokenValue::Const(Keyword::Drop),
+            ..
+        } => {
+            let (rest, tokens_consumed_count_column) =
+                match expect_token_value(rest, &TokenValue::Const(Keyword::Column)) {
+                    Ok(ExpectOk {
+                        rest,
+                        tokens_consumed_count,
+                        ..
+                    }) => (rest, tokens_consumed_count),
+                    Err(_) => (rest, 0),
+                };
+            let ExpectOk {
+                rest,
+                tokens_consumed_count: tokens_consumed_count_column_name,
+                outcome: column_name,
+            } = expect_identifier(rest)?;
+            Ok(ExpectOk {
+                rest,
+                tokens_consumed_count: tokens_consumed_count_action
+                    + tokens_consumed_count_column
+                    + tokens_consumed_count_column_name,
+                outcome: AlterTableAction::DropColumn(column_name),
+            })
+        }
+        wrong_token => Err(SyntaxError::at(
+            wrong_token,
+            format!(
+                "Expected {} or {}, instead found {}.",
+                Keyword::Add,
+                Keyword::Drop,
+                wrong_token
+            ),
+        )),
+    }
+}
+
+/// Conjure an AlterTableStatement from tokens following ALTER TABLE.
+pub fn expect_alter_table<'t>(tokens: &'t [Token]) -> ExpectResult<'t, AlterTableStatement> {
+    let ExpectOk {
+        rest,
+        tokens_consumed_count: tokens_consumed_count_table_name,
+        outcome: table_name,
+    } = expect_identifier(tokens)?;
+    let ExpectOk {
+        rest,
+        tokens_consumed_count: tokens_consumed_count_action,
+        outcome: action,
+    } = expect_alter_table_action(rest)?;
+    Ok(ExpectOk {
+        rest,
+        tokens_consumed_count: tokens_consumed_count_table_name + tokens_consumed_count_action,
+        outcome: AlterTableStatement { table_name, action },
+    })
+}
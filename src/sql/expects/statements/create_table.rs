@@ -1,9 +1,19 @@
 use crate::constructs::{
-    components::{ColumnDefinition, TableDefinition},
+    components::{normalize_identifier, ColumnDefinition, DataDefinition, TableDefinition},
     statements::CreateTableStatement,
 };
 use crate::sql::expects::{generic::*, semantic::*, ExpectOk, ExpectResult};
-use crate::sql::tokenizer::*;
+use crate::sql::{tokenizer::*, SyntaxError};
+
+/// Column modifiers which may follow the data type, in any order.
+#[derive(Default)]
+struct ColumnModifiers {
+    primary_key: bool,
+    unique: bool,
+    not_null: bool,
+    default: Option<DataDefinition>,
+    comment: Option<String>,
+}
 
 pub fn expect_column_definition<'t>(tokens: &'t [Token]) -> ExpectResult<'t, ColumnDefinition> {
     let ExpectOk {
@@ -12,46 +22,261 @@ pub fn expect_column_definition<'t>(tokens: &'t [Token]) -> ExpectResult<'t, Col
         outcome: name,
     } = expect_identifier(tokens)?;
     let ExpectOk {
-        rest,
+        mut rest,
         tokens_consumed_count: tokens_consumed_count_data_type,
         outcome: data_type,
     } = expect_data_type(rest)?;
-    let ExpectOk {
-        rest,
-        tokens_consumed_count: tokens_consumed_count_primary_key,
-        outcome: primary_key_option,
-    } = detect(
-        rest,
-        |tokens| expect_token_value(tokens, &TokenValue::Const(Keyword::Primary)),
-        |tokens| expect_token_value(tokens, &TokenValue::Const(Keyword::Key)),
-        &TokenValue::Const(Keyword::Key),
-    )?;
-    let ExpectOk {
-        rest,
-        tokens_consumed_count: tokens_consumed_count_default,
-        outcome: maybe_default,
-    } = detect(
-        rest,
-        |tokens| expect_token_value(tokens, &TokenValue::Const(Keyword::Default)),
-        expect_data_definition,
-        &"DEFAULT definition",
-    )?;
+    let mut tokens_consumed_count_modifiers = 0;
+    let mut modifiers = ColumnModifiers::default();
+    while let Some(modifier_token) = rest.first() {
+        let (modifier_keyword, is_repeated) = match modifier_token {
+            Token {
+                value: TokenValue::Const(Keyword::Primary),
+                ..
+            } => {
+                let ExpectOk {
+                    rest: rest_modifier,
+                    tokens_consumed_count,
+                    ..
+                } = expect_token_values_sequence(
+                    rest,
+                    &[
+                        TokenValue::Const(Keyword::Primary),
+                        TokenValue::Const(Keyword::Key),
+                    ],
+                )?;
+                rest = rest_modifier;
+                tokens_consumed_count_modifiers += tokens_consumed_count;
+                (
+                    Keyword::Primary,
+                    std::mem::replace(&mut modifiers.primary_key, true),
+                )
+            }
+            Token {
+                value: TokenValue::Const(Keyword::Unique),
+                ..
+            } => {
+                rest = &rest[1..];
+                tokens_consumed_count_modifiers += 1;
+                (
+                    Keyword::Unique,
+                    std::mem::replace(&mut modifiers.unique, true),
+                )
+            }
+            Token {
+                value: TokenValue::Const(Keyword::Not),
+                ..
+            } => {
+                let ExpectOk {
+                    rest: rest_modifier,
+                    tokens_consumed_count,
+                    ..
+                } = expect_token_values_sequence(
+                    rest,
+                    &[
+                        TokenValue::Const(Keyword::Not),
+                        TokenValue::Const(Keyword::Null),
+                    ],
+                )?;
+                rest = rest_modifier;
+                tokens_consumed_count_modifiers += tokens_consumed_count;
+                (
+                    Keyword::Not,
+                    std::mem::replace(&mut modifiers.not_null, true),
+                )
+            }
+            Token {
+                value: TokenValue::Const(Keyword::Default),
+                ..
+            } => {
+                let ExpectOk {
+                    rest: rest_modifier,
+                    tokens_consumed_count,
+                    outcome: default,
+                } = expect_data_definition(&rest[1..])?;
+                rest = rest_modifier;
+                tokens_consumed_count_modifiers += 1 + tokens_consumed_count;
+                (
+                    Keyword::Default,
+                    modifiers.default.replace(default).is_some(),
+                )
+            }
+            Token {
+                value: TokenValue::Const(Keyword::Comment),
+                ..
+            } => {
+                let comment = match rest.get(1) {
+                    Some(Token {
+                        value: TokenValue::String(comment),
+                        ..
+                    }) => comment.to_string(),
+                    Some(wrong_token) => {
+                        return Err(SyntaxError::at(
+                            wrong_token,
+                            format!(
+                                "Expected a string with the comment on column `{}`, instead found {}.",
+                                name, wrong_token
+                            ),
+                        ))
+                    }
+                    None => {
+                        return Err(SyntaxError::new(format!(
+                            "Expected a string with the comment on column `{}`, instead found end of statement.",
+                            name
+                        )))
+                    }
+                };
+                rest = &rest[2..];
+                tokens_consumed_count_modifiers += 2;
+                (
+                    Keyword::Comment,
+                    modifiers.comment.replace(comment).is_some(),
+                )
+            }
+            _ => break,
+        };
+        if is_repeated {
+            return Err(SyntaxError::at(
+                modifier_token,
+                format!(
+                    "Column `{}` has {} specified more than once.",
+                    name, modifier_keyword
+                ),
+            ));
+        }
+    }
+    // Types are non-nullable by default, so NOT NULL only makes that explicit, and contradicts NULLABLE(...)
+    if modifiers.not_null && data_type.is_nullable {
+        return Err(SyntaxError::new(format!(
+            "Column `{}` cannot be both NULLABLE and NOT NULL.",
+            name
+        )));
+    }
     // TODO: Test against types like UINT16(8)
     Ok(ExpectOk {
         rest,
         tokens_consumed_count: tokens_consumed_count_name
             + tokens_consumed_count_data_type
-            + tokens_consumed_count_primary_key
-            + tokens_consumed_count_default,
+            + tokens_consumed_count_modifiers,
         outcome: ColumnDefinition {
             name,
             data_type,
-            primary_key: primary_key_option.is_some(),
-            default: maybe_default.and_then(|(_, default)| Some(default)),
+            primary_key: modifiers.primary_key,
+            unique: modifiers.unique,
+            default: modifiers.default,
+            comment: modifiers.comment,
         },
     })
 }
 
+/// Element of the parenthesized list following the table name.
+enum TableElement<'t> {
+    Column(ColumnDefinition),
+    /// Table-level `PRIMARY KEY (column, ...)` clause, along with the token it starts with.
+    PrimaryKey {
+        column_names: Vec<String>,
+        token: &'t Token<'t>,
+    },
+}
+
+fn expect_table_element<'t>(tokens: &'t [Token]) -> ExpectResult<'t, TableElement<'t>> {
+    match tokens.first() {
+        Some(
+            token @ Token {
+                value: TokenValue::Const(Keyword::Primary),
+                ..
+            },
+        ) => {
+            let ExpectOk {
+                rest,
+                tokens_consumed_count: tokens_consumed_count_keywords,
+                ..
+            } = expect_token_values_sequence(
+                tokens,
+                &[
+                    TokenValue::Const(Keyword::Primary),
+                    TokenValue::Const(Keyword::Key),
+                ],
+            )?;
+            let ExpectOk {
+                rest,
+                tokens_consumed_count: tokens_consumed_count_columns,
+                outcome: column_names,
+            } = expect_enclosed_comma_separated(rest, expect_identifier)?;
+            Ok(ExpectOk {
+                rest,
+                tokens_consumed_count: tokens_consumed_count_keywords
+                    + tokens_consumed_count_columns,
+                outcome: TableElement::PrimaryKey {
+                    column_names,
+                    token,
+                },
+            })
+        }
+        _ => {
+            let ExpectOk {
+                rest,
+                tokens_consumed_count,
+                outcome: column,
+            } = expect_column_definition(tokens)?;
+            Ok(ExpectOk {
+                rest,
+                tokens_consumed_count,
+                outcome: TableElement::Column(column),
+            })
+        }
+    }
+}
+
+/// Mark the columns named in the table-level PRIMARY KEY clause as the PRIMARY KEY, making sure that they exist
+/// and don't conflict with an inline PRIMARY KEY declaration.
+fn apply_primary_key_clause(
+    columns: &mut [ColumnDefinition],
+    column_names: &[String],
+    token: &Token,
+) -> Result<(), SyntaxError> {
+    let mut normalized_column_names: Vec<String> = Vec::with_capacity(column_names.len());
+    for column_name in column_names {
+        let normalized_column_name = normalize_identifier(column_name);
+        if normalized_column_names.contains(&normalized_column_name) {
+            return Err(SyntaxError::at(
+                token,
+                format!(
+                    "PRIMARY KEY clause specifies column `{}` more than once.",
+                    column_name
+                ),
+            ));
+        }
+        if !columns
+            .iter()
+            .any(|column| normalize_identifier(&column.name) == normalized_column_name)
+        {
+            return Err(SyntaxError::at(
+                token,
+                format!(
+                    "PRIMARY KEY clause specifies column `{}`, which is not defined in the table.",
+                    column_name
+                ),
+            ));
+        }
+        normalized_column_names.push(normalized_column_name);
+    }
+    for column in columns {
+        let is_in_clause = normalized_column_names.contains(&normalize_identifier(&column.name));
+        if column.primary_key && !is_in_clause {
+            return Err(SyntaxError::at(
+                token,
+                format!(
+                    "PRIMARY KEY clause doesn't specify column `{}`, which is already declared PRIMARY KEY.",
+                    column.name
+                ),
+            ));
+        }
+        column.primary_key = is_in_clause;
+    }
+    Ok(())
+}
+
 pub fn expect_table_definition<'t>(tokens: &'t [Token]) -> ExpectResult<'t, TableDefinition> {
     let ExpectOk {
         rest,
@@ -60,13 +285,51 @@ pub fn expect_table_definition<'t>(tokens: &'t [Token]) -> ExpectResult<'t, Tabl
     } = expect_identifier(tokens)?;
     let ExpectOk {
         rest,
-        tokens_consumed_count: tokens_consumed_count_columns,
-        outcome: columns,
-    } = expect_enclosed_comma_separated(rest, expect_column_definition)?;
+        tokens_consumed_count: tokens_consumed_count_elements,
+        outcome: elements,
+    } = expect_enclosed_comma_separated(rest, expect_table_element)?;
+    let mut columns: Vec<ColumnDefinition> = Vec::with_capacity(elements.len());
+    let mut primary_key_clause: Option<(Vec<String>, &Token)> = None;
+    for element in elements {
+        match element {
+            TableElement::Column(column) => {
+                // Composite keys are only declared with the clause, so that inline declarations are unambiguous
+                if column.primary_key {
+                    if let Some(primary_key) = columns.iter().find(|column| column.primary_key) {
+                        return Err(SyntaxError::at(
+                            &tokens[0],
+                            format!(
+                                "Columns `{}` and `{}` are both declared PRIMARY KEY. A composite key is declared with a PRIMARY KEY ({}, {}) clause instead.",
+                                primary_key.name, column.name, primary_key.name, column.name
+                            ),
+                        ));
+                    }
+                }
+                columns.push(column)
+            }
+            TableElement::PrimaryKey {
+                column_names,
+                token,
+            } => {
+                if primary_key_clause.is_some() {
+                    return Err(SyntaxError::at(
+                        token,
+                        "PRIMARY KEY clause specified more than once.".into(),
+                    ));
+                }
+                primary_key_clause = Some((column_names, token));
+            }
+        }
+    }
+    if let Some((column_names, token)) = primary_key_clause {
+        apply_primary_key_clause(&mut columns, &column_names, token)?;
+    }
+    // A missing PRIMARY KEY is a validation error rather than a syntax error
+    let table = TableDefinition::new_unchecked(name, columns);
     Ok(ExpectOk {
         rest,
-        tokens_consumed_count: tokens_consumed_count_name + tokens_consumed_count_columns,
-        outcome: TableDefinition::new(name, columns),
+        tokens_consumed_count: tokens_consumed_count_name + tokens_consumed_count_elements,
+        outcome: table,
     })
 }
 
@@ -103,3 +366,112 @@ pub fn expect_create_table<'t>(tokens: &'t [Token]) -> ExpectResult<'t, CreateTa
         },
     })
 }
+
+#[cfg(test)]
+mod expect_column_definition_tests {
+    use super::*;
+    use crate::constructs::components::{DataType, DataTypeRaw};
+    use crate::sql::tokenizer::tokenize_statement;
+    use crate::sql::Position;
+    use pretty_assertions::assert_eq;
+
+    fn expect_column_definition_from_str(input: &str) -> Result<ColumnDefinition, SyntaxError> {
+        consume_all(&tokenize_statement(input), expect_column_definition)
+    }
+
+    #[test]
+    fn returns_ok_with_not_null() {
+        assert_eq!(
+            expect_column_definition_from_str("id UINT64 NOT NULL PRIMARY KEY"),
+            Ok(ColumnDefinition {
+                name: "id".to_string(),
+                data_type: DataType {
+                    raw_type: DataTypeRaw::UInt64,
+                    is_nullable: false
+                },
+                primary_key: true,
+                unique: false,
+                default: None,
+                comment: None,
+            })
+        )
+    }
+
+    #[test]
+    fn returns_ok_with_plain_type() {
+        assert_eq!(
+            expect_column_definition_from_str("id UINT64"),
+            Ok(ColumnDefinition {
+                name: "id".to_string(),
+                data_type: DataType {
+                    raw_type: DataTypeRaw::UInt64,
+                    is_nullable: false
+                },
+                primary_key: false,
+                unique: false,
+                default: None,
+                comment: None,
+            })
+        )
+    }
+
+    #[test]
+    fn returns_ok_with_comment() {
+        assert_eq!(
+            expect_column_definition_from_str(
+                "seen_at TIMESTAMP COMMENT 'When the user was last active' DEFAULT NOW()"
+            ),
+            Ok(ColumnDefinition {
+                name: "seen_at".to_string(),
+                data_type: DataType {
+                    raw_type: DataTypeRaw::Timestamp,
+                    is_nullable: false
+                },
+                primary_key: false,
+                unique: false,
+                default: Some(DataDefinition::FunctionCall(
+                    crate::constructs::functions::Function::Now
+                )),
+                comment: Some("When the user was last active".to_string()),
+            })
+        )
+    }
+
+    #[test]
+    fn returns_error_if_comment_is_not_a_string() {
+        assert_eq!(
+            expect_column_definition_from_str("id UINT64 COMMENT 42"),
+            Err(SyntaxError {
+                message: "Expected a string with the comment on column `id`, instead found arbitrary `42` at line 1.".to_string(),
+                position: Some(Position {
+                    line: 1,
+                    column: 19
+                })
+            })
+        )
+    }
+
+    #[test]
+    fn returns_error_if_nullable_and_not_null() {
+        assert_eq!(
+            expect_column_definition_from_str("id NULLABLE(UINT64) NOT NULL"),
+            Err(SyntaxError::new(
+                "Column `id` cannot be both NULLABLE and NOT NULL.".to_string()
+            ))
+        )
+    }
+
+    #[test]
+    fn returns_error_if_modifier_repeated() {
+        assert_eq!(
+            expect_column_definition_from_str("id UINT64 UNIQUE UNIQUE"),
+            Err(SyntaxError {
+                message: "Column `id` has keyword `UNIQUE` specified more than once.".to_string(),
+                position: Some(Position {
+                    line: 1,
+                    column: 18
+                })
+            })
+        )
+    }
+}
@@ -0,0 +1,17 @@
+use crate::constructs::statements::DescribeStatement;
+use crate::sql::expects::{semantic::*, ExpectOk, ExpectResult};
+use crate::sql::tokenizer::*;
+
+/// Conjure a DescribeStatement from tokens following DESCRIBE.
+pub fn expect_describe<'t>(tokens: &'t [Token]) -> ExpectResult<'t, DescribeStatement> {
+    let ExpectOk {
+        rest,
+        tokens_consumed_count,
+        outcome: table_name,
+    } = expect_identifier(tokens)?;
+    Ok(ExpectOk {
+        rest,
+        tokens_consumed_count,
+        outcome: DescribeStatement { table_name },
+    })
+}
@@ -20,7 +20,7 @@ pub fn expect_insert<'t>(tokens: &'t [Token]) -> ExpectResult<'t, InsertStatemen
         rest,
         tokens_consumed_count: tokens_consumed_count_values,
         outcome: values,
-    } = expect_enclosed_comma_separated(rest, expect_data_instance)?;
+    } = expect_enclosed_comma_separated(rest, expect_value)?;
     Ok(ExpectOk {
         rest,
         tokens_consumed_count: 2 // +2 to account for INTO + VALUES
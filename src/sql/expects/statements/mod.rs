@@ -1,7 +1,19 @@
+mod alter_table;
 mod create_table;
+mod delete;
+mod describe;
 mod insert;
 mod select;
+mod show;
+mod truncate_table;
+mod update;
 
+pub use alter_table::*;
 pub use create_table::*;
+pub use delete::*;
+pub use describe::*;
 pub use insert::*;
 pub use select::*;
+pub use show::*;
+pub use truncate_table::*;
+pub use update::*;
@@ -1,21 +1,41 @@
-use crate::constructs::statements::{SelectColumn, SelectStatement};
+use crate::constructs::components::{DataDefinition, Expression};
+use crate::constructs::statements::{
+    AggregateArgument, Join, OrderBy, OrderDirection, SelectColumn, SelectItem, SelectStatement,
+};
 use crate::sql::expects::{generic::*, semantic::*, ExpectOk, ExpectResult};
 use crate::sql::{tokenizer::*, SyntaxError};
 
+pub fn expect_aggregate_argument<'t>(tokens: &'t [Token]) -> ExpectResult<'t, AggregateArgument> {
+    if let Ok(ExpectOk {
+        rest,
+        tokens_consumed_count,
+        ..
+    }) = expect_token_value(tokens, &TokenValue::Const(Keyword::Asterisk))
+    {
+        return Ok(ExpectOk {
+            rest,
+            tokens_consumed_count,
+            outcome: AggregateArgument::All,
+        });
+    }
+    let ExpectOk {
+        rest,
+        tokens_consumed_count,
+        outcome: column_name,
+    } = expect_identifier(tokens)?;
+    Ok(ExpectOk {
+        rest,
+        tokens_consumed_count,
+        outcome: AggregateArgument::Identifier(column_name),
+    })
+}
+
 pub fn expect_select_column<'t>(tokens: &'t [Token]) -> ExpectResult<'t, SelectColumn> {
     let ExpectOk {
         outcome: found_token,
         ..
     } = expect_next_token(tokens, &"a SELECT column")?;
     match found_token {
-        Token {
-            value: TokenValue::Arbitrary(value),
-            ..
-        } => Ok(ExpectOk {
-            rest: &tokens[1..],
-            tokens_consumed_count: 1,
-            outcome: SelectColumn::Identifier(value.to_owned()),
-        }),
         Token {
             value: TokenValue::Const(Keyword::Asterisk),
             ..
@@ -24,26 +44,200 @@ pub fn expect_select_column<'t>(tokens: &'t [Token]) -> ExpectResult<'t, SelectC
             tokens_consumed_count: 1,
             outcome: SelectColumn::All,
         }),
-        wrong_token => Err(SyntaxError(format!(
-            "Expected a SELECT column, instead found {}.",
-            wrong_token
-        ))),
+        Token {
+            value: TokenValue::Aggregate(function),
+            ..
+        } => {
+            let ExpectOk {
+                rest,
+                tokens_consumed_count,
+                outcome: argument,
+            } = expect_enclosed(
+                &tokens[1..],
+                expect_aggregate_argument,
+                Delimiter::ParenthesisOpening,
+                Delimiter::ParenthesisClosing,
+            )?;
+            Ok(ExpectOk {
+                rest,
+                tokens_consumed_count: 1 + tokens_consumed_count,
+                outcome: SelectColumn::Aggregate {
+                    function: *function,
+                    argument,
+                },
+            })
+        }
+        wrong_token if !can_start_expression(&wrong_token.value) => Err(SyntaxError::at(
+            wrong_token,
+            format!("Expected a SELECT column, instead found {}.", wrong_token),
+        )),
+        _ => {
+            let ExpectOk {
+                rest,
+                tokens_consumed_count,
+                outcome: expression,
+            } = expect_arithmetic_expression(tokens)?;
+            Ok(ExpectOk {
+                rest,
+                tokens_consumed_count,
+                outcome: match expression {
+                    Expression::Atom(DataDefinition::Identifier(column_name)) => {
+                        SelectColumn::Identifier(column_name)
+                    }
+                    Expression::Atom(DataDefinition::QualifiedIdentifier {
+                        table_name,
+                        column_name,
+                    }) => SelectColumn::QualifiedIdentifier {
+                        table_name,
+                        column_name,
+                    },
+                    expression => SelectColumn::Expression(expression),
+                },
+            })
+        }
+    }
+}
+
+/// SELECT column, optionally followed by `AS alias`.
+pub fn expect_select_item<'t>(tokens: &'t [Token]) -> ExpectResult<'t, SelectItem> {
+    let ExpectOk {
+        rest,
+        tokens_consumed_count: tokens_consumed_count_column,
+        outcome: column,
+    } = expect_select_column(tokens)?;
+    let ExpectOk {
+        rest,
+        tokens_consumed_count: tokens_consumed_count_alias,
+        outcome: maybe_alias,
+    } = detect(
+        rest,
+        |tokens| expect_token_value(tokens, &TokenValue::Const(Keyword::As)),
+        expect_identifier,
+        &"column alias",
+    )?;
+    Ok(ExpectOk {
+        rest,
+        tokens_consumed_count: tokens_consumed_count_column + tokens_consumed_count_alias,
+        outcome: SelectItem {
+            column,
+            alias: maybe_alias.map(|(_, alias)| alias),
+        },
+    })
+}
+
+/// Whether the token can start a column reference or an expression, i.e. it's a value or an opening parenthesis.
+fn can_start_expression(token_value: &TokenValue) -> bool {
+    match token_value {
+        TokenValue::Const(keyword) => {
+            matches!(
+                keyword,
+                Keyword::Null | Keyword::True | Keyword::False | Keyword::Cast
+            )
+        }
+        TokenValue::Type(_) => false,
+        TokenValue::Delimiting(delimiter) => *delimiter == Delimiter::ParenthesisOpening,
+        _ => true,
     }
 }
 
+pub fn expect_order_by<'t>(tokens: &'t [Token]) -> ExpectResult<'t, OrderBy> {
+    let ExpectOk {
+        rest,
+        tokens_consumed_count: tokens_consumed_count_column_name,
+        outcome: column_name,
+    } = expect_identifier(tokens)?;
+    let (direction, rest, tokens_consumed_count_direction) = match rest.first() {
+        Some(Token {
+            value: TokenValue::Const(Keyword::Asc),
+            ..
+        }) => (OrderDirection::Asc, &rest[1..], 1),
+        Some(Token {
+            value: TokenValue::Const(Keyword::Desc),
+            ..
+        }) => (OrderDirection::Desc, &rest[1..], 1),
+        _ => (OrderDirection::Asc, rest, 0),
+    };
+    Ok(ExpectOk {
+        rest,
+        tokens_consumed_count: tokens_consumed_count_column_name + tokens_consumed_count_direction,
+        outcome: OrderBy {
+            column_name,
+            direction,
+        },
+    })
+}
+
+/// `JOIN table ON condition`, optionally starting with INNER.
+pub fn expect_join<'t>(tokens: &'t [Token]) -> ExpectResult<'t, Join> {
+    let (rest, tokens_consumed_count_inner) =
+        match expect_token_value(tokens, &TokenValue::Const(Keyword::Inner)) {
+            Ok(ExpectOk { rest, .. }) => (rest, 1),
+            Err(_) => (tokens, 0),
+        };
+    let ExpectOk { rest, .. } = expect_token_value(rest, &TokenValue::Const(Keyword::Join))?;
+    let ExpectOk {
+        rest,
+        tokens_consumed_count: tokens_consumed_count_table_name,
+        outcome: table_name,
+    } = expect_identifier(rest)?;
+    let ExpectOk { rest, .. } = expect_token_value(rest, &TokenValue::Const(Keyword::On))?;
+    let ExpectOk {
+        rest,
+        tokens_consumed_count: tokens_consumed_count_condition,
+        outcome: condition,
+    } = expect_expression(rest)?;
+    Ok(ExpectOk {
+        rest,
+        tokens_consumed_count: tokens_consumed_count_inner
+            + 2 // +2 to account for JOIN and ON
+            + tokens_consumed_count_table_name
+            + tokens_consumed_count_condition,
+        outcome: Join {
+            table_name,
+            condition,
+        },
+    })
+}
+
+/// Names of the columns following GROUP BY.
+pub fn expect_group_by<'t>(tokens: &'t [Token]) -> ExpectResult<'t, Vec<String>> {
+    expect_comma_separated(tokens, expect_identifier)
+}
+
 /// Conjure an SelectStatement from tokens following SELECT.
 pub fn expect_select<'t>(tokens: &'t [Token]) -> ExpectResult<'t, SelectStatement> {
+    let (distinct, rest, tokens_consumed_count_distinct) = match tokens.first() {
+        Some(Token {
+            value: TokenValue::Const(Keyword::Distinct),
+            ..
+        }) => (true, &tokens[1..], 1),
+        _ => (false, tokens, 0),
+    };
     let ExpectOk {
         rest,
         tokens_consumed_count: tokens_consumed_columns,
         outcome: columns,
-    } = expect_comma_separated(tokens, expect_select_column)?;
+    } = expect_comma_separated(rest, expect_select_item)?;
     let ExpectOk { rest, .. } = expect_token_value(rest, &TokenValue::Const(Keyword::From))?;
     let ExpectOk {
         rest,
         tokens_consumed_count: tokens_consumed_count_table_name,
         outcome: table_name,
     } = expect_identifier(rest)?;
+    let (join, rest, tokens_consumed_count_join) = match rest.first() {
+        Some(Token {
+            value: TokenValue::Const(Keyword::Inner | Keyword::Join),
+            ..
+        }) => {
+            let ExpectOk {
+                rest,
+                tokens_consumed_count,
+                outcome: join,
+            } = expect_join(rest)?;
+            (Some(join), rest, tokens_consumed_count)
+        }
+        _ => (None, rest, 0),
+    };
     let ExpectOk {
         rest,
         tokens_consumed_count: tokens_consumed_count_where_clause,
@@ -54,15 +248,97 @@ pub fn expect_select<'t>(tokens: &'t [Token]) -> ExpectResult<'t, SelectStatemen
         expect_expression,
         &"WHERE conditions",
     )?;
+    let ExpectOk {
+        rest,
+        tokens_consumed_count: tokens_consumed_count_group_by,
+        outcome: maybe_group_by,
+    } = detect(
+        rest,
+        |tokens| {
+            expect_token_values_sequence(
+                tokens,
+                &[
+                    TokenValue::Const(Keyword::Group),
+                    TokenValue::Const(Keyword::By),
+                ],
+            )
+        },
+        expect_group_by,
+        &"GROUP BY columns",
+    )?;
+    let ExpectOk {
+        rest,
+        tokens_consumed_count: tokens_consumed_count_order_by,
+        outcome: maybe_order_by,
+    } = detect(
+        rest,
+        |tokens| {
+            expect_token_values_sequence(
+                tokens,
+                &[
+                    TokenValue::Const(Keyword::Order),
+                    TokenValue::Const(Keyword::By),
+                ],
+            )
+        },
+        |tokens| expect_comma_separated(tokens, expect_order_by),
+        &"ORDER BY columns",
+    )?;
+    let ExpectOk {
+        rest,
+        tokens_consumed_count: tokens_consumed_count_limit,
+        outcome: maybe_limit,
+    } = detect(
+        rest,
+        |tokens| expect_token_value(tokens, &TokenValue::Const(Keyword::Limit)),
+        expect_unsigned_integer,
+        &"LIMIT value",
+    )?;
+    let ExpectOk {
+        rest,
+        tokens_consumed_count: tokens_consumed_count_offset,
+        outcome: maybe_offset,
+    } = if maybe_limit.is_some() {
+        detect(
+            rest,
+            |tokens| expect_token_value(tokens, &TokenValue::Const(Keyword::Offset)),
+            expect_unsigned_integer,
+            &"OFFSET value",
+        )?
+    } else {
+        ExpectOk {
+            rest,
+            tokens_consumed_count: 0,
+            outcome: None,
+        }
+    };
     Ok(ExpectOk {
         rest,
         tokens_consumed_count: 1 // +1 to account for FROM
+            + tokens_consumed_count_distinct
             + tokens_consumed_columns
-            + tokens_consumed_count_table_name + tokens_consumed_count_where_clause,
+            + tokens_consumed_count_table_name
+            + tokens_consumed_count_join
+            + tokens_consumed_count_where_clause
+            + tokens_consumed_count_group_by
+            + tokens_consumed_count_order_by
+            + tokens_consumed_count_limit
+            + tokens_consumed_count_offset,
         outcome: SelectStatement {
+            distinct,
             columns,
             source: table_name,
-            where_clause: maybe_where_clause.and_then(|(_, where_clause)| Some(where_clause)),
+            join,
+            where_clause: maybe_where_clause.map(|(_, where_clause)| where_clause),
+            group_by: maybe_group_by
+                .map(|(_, group_by)| group_by)
+                .unwrap_or_default(),
+            order_by: maybe_order_by
+                .map(|(_, order_by)| order_by)
+                .unwrap_or_default(),
+            limit: maybe_limit.map(|(_, limit)| limit),
+            offset: maybe_offset.map(|(_, offset)| offset),
+            cursor: None,
         },
     })
 }
@@ -0,0 +1,35 @@
+use crate::constructs::statements::DescribeStatement;
+use crate::sql::expects::{generic::*, ExpectOk, ExpectResult};
+use crate::sql::tokenizer::*;
+
+use super::expect_describe;
+
+/// Expect the rest of SHOW TABLES, i.e. tokens following SHOW.
+pub fn expect_show_tables<'t>(tokens: &'t [Token]) -> ExpectResult<'t, ()> {
+    expect_token_value(tokens, &TokenValue::Const(Keyword::Tables))
+}
+
+/// Conjure a DescribeStatement from tokens following SHOW, as SHOW COLUMNS FROM is equivalent to DESCRIBE.
+pub fn expect_show_columns<'t>(tokens: &'t [Token]) -> ExpectResult<'t, DescribeStatement> {
+    let ExpectOk {
+        rest,
+        tokens_consumed_count: tokens_consumed_count_columns_from,
+        ..
+    } = expect_token_values_sequence(
+        tokens,
+        &[
+            TokenValue::Const(Keyword::Columns),
+            TokenValue::Const(Keyword::From),
+        ],
+    )?;
+    let ExpectOk {
+        rest,
+        tokens_consumed_count: tokens_consumed_count_describe,
+        outcome,
+    } = expect_describe(rest)?;
+    Ok(ExpectOk {
+        rest,
+        tokens_consumed_count: tokens_consumed_count_columns_from + tokens_consumed_count_describe,
+        outcome,
+    })
+}
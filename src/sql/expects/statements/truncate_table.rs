@@ -0,0 +1,17 @@
+use crate::constructs::statements::TruncateTableStatement;
+use crate::sql::expects::{semantic::*, ExpectOk, ExpectResult};
+use crate::sql::tokenizer::*;
+
+/// Conjure a TruncateTableStatement from tokens following TRUNCATE TABLE.
+pub fn expect_truncate_table<'t>(tokens: &'t [Token]) -> ExpectResult<'t, TruncateTableStatement> {
+    let ExpectOk {
+        rest,
+        tokens_consumed_count,
+        outcome: table_name,
+    } = expect_identifier(tokens)?;
+    Ok(ExpectOk {
+        rest,
+        tokens_consumed_count,
+        outcome: TruncateTableStatement { table_name },
+    })
+}
@@ -0,0 +1,66 @@
+use crate::constructs::statements::{Assignment, UpdateStatement};
+use crate::sql::expects::{generic::*, semantic::*, ExpectOk, ExpectResult};
+use crate::sql::tokenizer::*;
+
+/// Expect a SET assignment, i.e. `column = value`.
+pub fn expect_assignment<'t>(tokens: &'t [Token]) -> ExpectResult<'t, Assignment> {
+    let ExpectOk {
+        rest,
+        tokens_consumed_count: tokens_consumed_count_column_name,
+        outcome: column_name,
+    } = expect_identifier(tokens)?;
+    let ExpectOk {
+        rest,
+        tokens_consumed_count: tokens_consumed_count_equal,
+        ..
+    } = expect_token_value(rest, &TokenValue::Delimiting(Delimiter::Equal))?;
+    let ExpectOk {
+        rest,
+        tokens_consumed_count: tokens_consumed_count_value,
+        outcome: value,
+    } = expect_data_definition(rest)?;
+    Ok(ExpectOk {
+        rest,
+        tokens_consumed_count: tokens_consumed_count_column_name
+            + tokens_consumed_count_equal
+            + tokens_consumed_count_value,
+        outcome: Assignment { column_name, value },
+    })
+}
+
+/// Conjure an UpdateStatement from tokens following UPDATE.
+pub fn expect_update_statement<'t>(tokens: &'t [Token]) -> ExpectResult<'t, UpdateStatement> {
+    let ExpectOk {
+        rest,
+        tokens_consumed_count: tokens_consumed_count_table_name,
+        outcome: table_name,
+    } = expect_identifier(tokens)?;
+    let ExpectOk { rest, .. } = expect_token_value(rest, &TokenValue::Const(Keyword::Set))?;
+    let ExpectOk {
+        rest,
+        tokens_consumed_count: tokens_consumed_count_assignments,
+        outcome: assignments,
+    } = expect_comma_separated(rest, expect_assignment)?;
+    let ExpectOk {
+        rest,
+        tokens_consumed_count: tokens_consumed_count_where_clause,
+        outcome: maybe_where_clause,
+    } = detect(
+        rest,
+        |tokens| expect_token_value(tokens, &TokenValue::Const(Keyword::Where)),
+        expect_expression,
+        &"WHERE conditions",
+    )?;
+    Ok(ExpectOk {
+        rest,
+        tokens_consumed_count: 1 // +1 to account for SET
+            + tokens_consumed_count_table_name
+            + tokens_consumed_count_assignments
+            + tokens_consumed_count_where_clause,
+        outcome: UpdateStatement {
+            table_name,
+            assignments,
+            where_clause: maybe_where_clause.map(|(_, where_clause)| where_clause),
+        },
+    })
+}
@@ -1,7 +1,18 @@
+mod dialect;
 mod errors;
 mod expects;
 mod parser;
 mod tokenizer;
 
+pub use dialect::{Dialect, TrailingCommas};
 pub use errors::*;
-pub use parser::{parse_statement, Statement};
+pub use parser::{
+    bind_parameters, parse_statement, parse_statement_in_dialect, parse_statements,
+    parse_statements_in_dialect, parse_statements_recovering,
+    parse_statements_recovering_in_dialect, parse_statements_with_limits, ParseLimits,
+    RecoveredStatements, Statement,
+};
+pub use tokenizer::{
+    is_keyword, keywords, tokenize, tokenize_collect_errors, tokenize_statement,
+    tokenize_statements, type_names, Delimiter, Keyword, Token, TokenValue,
+};
@@ -1,18 +1,234 @@
+use super::dialect::{Dialect, TrailingCommas};
 use super::expects::*;
 use super::tokenizer::*;
-use crate::constructs::components::Validatable;
+use crate::constructs::components::{DataDefinition, DataInstance, NamePolicy, Validatable};
 use crate::constructs::statements::SelectStatement;
-use crate::constructs::statements::{CreateTableStatement, InsertStatement};
+use crate::constructs::statements::{
+    AlterTableAction, AlterTableStatement, CreateTableStatement, DeleteStatement,
+    DescribeStatement, InsertStatement, SelectColumn, TruncateTableStatement, UpdateStatement,
+};
 use crate::sql::errors::*;
 
 pub fn parse_statement(input: &str) -> Result<Statement, SyntaxError> {
-    let tokens = tokenize_statement(input);
+    parse_statement_in_dialect(input, Dialect::Strict)
+}
+
+/// Like `parse_statement`, but in the specified dialect.
+pub fn parse_statement_in_dialect(input: &str, dialect: Dialect) -> Result<Statement, SyntaxError> {
+    // Empty statements are left out, so that semicolons before or after the statement don't matter
+    let mut statements_tokens = tokenize_statements_in_dialect(input, dialect).into_iter();
+    // Only the first lexical error is reported, as the parser stops at the first error anyway
+    let tokens = match statements_tokens.next() {
+        Some(tokens) => tokens.map_err(|mut errors| errors.remove(0))?,
+        None => return Err(empty_statement_error()),
+    };
+    check_nesting_depth(&tokens, ParseLimits::default().max_nesting_depth)?;
+    let statement = parse_tokens(&tokens)?;
+    // Anything past the semicolon would be silently dropped otherwise
+    if let Some(next_tokens) = statements_tokens.next() {
+        let next_tokens = next_tokens.map_err(|mut errors| errors.remove(0))?;
+        return Err(SyntaxError::at(
+            &next_tokens[0],
+            format!(
+                "Expected end of input, instead found {} in another statement. Only a single statement is allowed here.",
+                next_tokens[0]
+            ),
+        ));
+    }
+    Ok(statement)
+}
+
+/// Parse all semicolon-separated statements of the input, e.g. of a transaction.
+pub fn parse_statements(input: &str) -> Result<Vec<Statement>, SyntaxError> {
+    parse_statements_in_dialect(input, Dialect::Strict)
+}
+
+/// Like `parse_statements`, but in the specified dialect.
+pub fn parse_statements_in_dialect(
+    input: &str,
+    dialect: Dialect,
+) -> Result<Vec<Statement>, SyntaxError> {
+    parse_statements_with_limits(
+        input,
+        dialect,
+        TrailingCommas::Strict,
+        &ParseLimits::default(),
+    )
+}
+
+/// Bounds on the work of parsing, so that untrusted input can't exhaust CPU, memory or the stack.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseLimits {
+    /// Maximum number of tokens across all statements of the input.
+    pub max_token_count: usize,
+    /// Maximum depth of nested parentheses, e.g. 2 for `(a = 1 OR (b = 2))`.
+    pub max_nesting_depth: usize,
+}
+
+impl ParseLimits {
+    /// Highest nesting depth that a query can be allowed, as nested groups are evaluated and dropped recursively.
+    /// Even debug builds handle about twice as deep queries on the 2 MiB stack of a Tokio worker thread.
+    pub const MAX_SAFE_NESTING_DEPTH: usize = 128;
+}
+
+impl Default for ParseLimits {
+    /// No limit on the token count, but at most 64 levels of nested parentheses. Parsing itself doesn't recurse
+    /// into parentheses – both conditions and arithmetic expressions track their groups with explicit stacks – but
+    /// the expression trees that nested groups produce (e.g. with CAST) are evaluated and dropped recursively.
+    fn default() -> Self {
+        ParseLimits {
+            max_token_count: usize::MAX,
+            max_nesting_depth: 64,
+        }
+    }
+}
+
+/// Like `parse_statements_in_dialect`, but failing if the input exceeds the limits,
+/// and with trailing commas treated according to the mode.
+pub fn parse_statements_with_limits(
+    input: &str,
+    dialect: Dialect,
+    trailing_commas: TrailingCommas,
+    limits: &ParseLimits,
+) -> Result<Vec<Statement>, SyntaxError> {
+    // The token limit is enforced already while tokenizing, with tokens past the excess one never produced
+    let statements_tokens = tokenize_statements_with_limit(input, dialect, limits.max_token_count);
+    if statements_tokens.is_empty() {
+        return Err(empty_statement_error());
+    }
+    let mut remaining_token_count = limits.max_token_count;
+    statements_tokens
+        .into_iter()
+        .map(|tokens| {
+            let tokens = tokens.map_err(|mut errors| errors.remove(0))?;
+            // Limits are checked before parsing, as parsing is what they protect
+            if let Some(excess_token) = tokens.get(remaining_token_count) {
+                return Err(SyntaxError::at(
+                    excess_token,
+                    format!(
+                        "Found {} past the limit of {} tokens.",
+                        excess_token, limits.max_token_count
+                    ),
+                ));
+            }
+            remaining_token_count -= tokens.len();
+            check_nesting_depth(&tokens, limits.max_nesting_depth)?;
+            match trailing_commas {
+                TrailingCommas::Strict => parse_tokens(&tokens),
+                TrailingCommas::Lenient => parse_tokens(&drop_trailing_commas(tokens)),
+            }
+        })
+        .collect()
+}
+
+/// Error for input without any statement, e.g. only whitespace, comments or semicolons, as sent from a blank editor.
+fn empty_statement_error() -> SyntaxError {
+    SyntaxError {
+        message:
+            "Found no statement to run, as the input is empty or only has whitespace and comments."
+                .to_string(),
+        position: Some(Position { line: 1, column: 1 }),
+    }
+}
+
+/// Remove commas that end a list in parentheses. A comma right after an opening parenthesis or another comma
+/// isn't trailing a list element, so it's kept for the parser to reject.
+fn drop_trailing_commas(tokens: Vec<Token>) -> Vec<Token> {
+    let mut kept_tokens: Vec<Token> = Vec::with_capacity(tokens.len());
+    let mut tokens = tokens.into_iter().peekable();
+    while let Some(token) = tokens.next() {
+        let is_trailing_comma = token.value == TokenValue::Delimiting(Delimiter::Comma)
+            && matches!(
+                tokens.peek(),
+                Some(Token {
+                    value: TokenValue::Delimiting(Delimiter::ParenthesisClosing),
+                    ..
+                })
+            )
+            && !matches!(
+                kept_tokens.last(),
+                None | Some(Token {
+                    value: TokenValue::Delimiting(Delimiter::ParenthesisOpening | Delimiter::Comma),
+                    ..
+                })
+            );
+        if !is_trailing_comma {
+            kept_tokens.push(token);
+        }
+    }
+    kept_tokens
+}
+
+fn check_nesting_depth(tokens: &[Token], max_nesting_depth: usize) -> Result<(), SyntaxError> {
+    let mut nesting_depth: usize = 0;
+    for token in tokens {
+        match token.value {
+            TokenValue::Delimiting(Delimiter::ParenthesisOpening) => {
+                nesting_depth += 1;
+                if nesting_depth > max_nesting_depth {
+                    return Err(SyntaxError::at(
+                        token,
+                        format!(
+                            "Found {} past the limit of {} levels of nested parentheses.",
+                            token, max_nesting_depth
+                        ),
+                    ));
+                }
+            }
+            TokenValue::Delimiting(Delimiter::ParenthesisClosing) => {
+                nesting_depth = nesting_depth.saturating_sub(1)
+            }
+            _ => (),
+        }
+    }
+    Ok(())
+}
+
+/// Statements which parsed, along with errors of those which didn't.
+#[derive(Debug, PartialEq, Eq, Default)]
+pub struct RecoveredStatements {
+    pub statements: Vec<Statement>,
+    pub errors: Vec<SyntaxError>,
+}
+
+/// Parse all semicolon-separated statements of the input, recovering from errors instead of stopping at the first one.
+/// Useful for editors, which can then point out all problems at once.
+///
+/// Recovery resyncs at the statement separator (a semicolon outside of strings and quoted identifiers):
+/// a statement with an error is skipped entirely and parsing continues with the statement after its semicolon.
+/// As strings can't span lines, after an unterminated string it's the first semicolon on a following line.
+/// Every lexical error of a broken statement is reported, but within a statement, parsing stops at the first
+/// syntax error. Empty statements, e.g. after a trailing semicolon, are ignored.
+pub fn parse_statements_recovering(input: &str) -> RecoveredStatements {
+    parse_statements_recovering_in_dialect(input, Dialect::Strict)
+}
+
+/// Like `parse_statements_recovering`, but in the specified dialect.
+pub fn parse_statements_recovering_in_dialect(
+    input: &str,
+    dialect: Dialect,
+) -> RecoveredStatements {
+    let mut recovered = RecoveredStatements::default();
+    for tokens in tokenize_statements_in_dialect(input, dialect) {
+        match tokens.map(|tokens| {
+            check_nesting_depth(&tokens, ParseLimits::default().max_nesting_depth)?;
+            parse_tokens(&tokens)
+        }) {
+            Ok(Ok(statement)) => recovered.statements.push(statement),
+            Ok(Err(error)) => recovered.errors.push(error),
+            Err(errors) => recovered.errors.extend(errors),
+        }
+    }
+    recovered
+}
+
+fn parse_tokens(tokens: &[Token]) -> Result<Statement, SyntaxError> {
     let ExpectOk {
         rest,
         outcome: found_token_first,
         ..
     } = expect_next_token(
-        &tokens,
+        tokens,
         &format!("{} or {}", Keyword::Create, Keyword::Insert),
     )?;
     match found_token_first {
@@ -36,11 +252,74 @@ pub fn parse_statement(input: &str) -> Result<Statement, SyntaxError> {
                     expect_create_table,
                 )?)),
                 // CREATE ???
-                wrong_token => Err(SyntaxError(format!(
-                    "Expected {}, instead found {}.",
-                    Keyword::Table,
-                    wrong_token
-                ))),
+                wrong_token => Err(SyntaxError::at(
+                    wrong_token,
+                    format!(
+                        "Expected {}, instead found {}.",
+                        Keyword::Table,
+                        wrong_token
+                    ),
+                )),
+            }
+        }
+        // ALTER
+        Token {
+            value: TokenValue::Const(Keyword::Alter),
+            ..
+        } => {
+            let ExpectOk {
+                rest,
+                outcome: found_token_second,
+                ..
+            } = expect_next_token(rest, &Keyword::Table.to_string())?;
+            match found_token_second {
+                // ALTER TABLE
+                Token {
+                    value: TokenValue::Const(Keyword::Table),
+                    ..
+                } => Ok(Statement::AlterTable(consume_all(
+                    rest,
+                    expect_alter_table,
+                )?)),
+                // ALTER ???
+                wrong_token => Err(SyntaxError::at(
+                    wrong_token,
+                    format!(
+                        "Expected {}, instead found {}.",
+                        Keyword::Table,
+                        wrong_token
+                    ),
+                )),
+            }
+        }
+        // TRUNCATE
+        Token {
+            value: TokenValue::Const(Keyword::Truncate),
+            ..
+        } => {
+            let ExpectOk {
+                rest,
+                outcome: found_token_second,
+                ..
+            } = expect_next_token(rest, &Keyword::Table.to_string())?;
+            match found_token_second {
+                // TRUNCATE TABLE
+                Token {
+                    value: TokenValue::Const(Keyword::Table),
+                    ..
+                } => Ok(Statement::TruncateTable(consume_all(
+                    rest,
+                    expect_truncate_table,
+                )?)),
+                // TRUNCATE ???
+                wrong_token => Err(SyntaxError::at(
+                    wrong_token,
+                    format!(
+                        "Expected {}, instead found {}.",
+                        Keyword::Table,
+                        wrong_token
+                    ),
+                )),
             }
         }
         // INSERT
@@ -48,34 +327,291 @@ pub fn parse_statement(input: &str) -> Result<Statement, SyntaxError> {
             value: TokenValue::Const(Keyword::Insert),
             ..
         } => Ok(Statement::Insert(consume_all(rest, expect_insert)?)),
+        // UPDATE
+        Token {
+            value: TokenValue::Const(Keyword::Update),
+            ..
+        } => Ok(Statement::Update(consume_all(
+            rest,
+            expect_update_statement,
+        )?)),
+        // DELETE
+        Token {
+            value: TokenValue::Const(Keyword::Delete),
+            ..
+        } => Ok(Statement::Delete(consume_all(
+            rest,
+            expect_delete_statement,
+        )?)),
         // SELECT
         Token {
             value: TokenValue::Const(Keyword::Select),
             ..
         } => Ok(Statement::Select(consume_all(rest, expect_select)?)),
+        // SHOW
+        Token {
+            value: TokenValue::Const(Keyword::Show),
+            ..
+        } => match rest.first() {
+            // SHOW COLUMNS
+            Some(Token {
+                value: TokenValue::Const(Keyword::Columns),
+                ..
+            }) => Ok(Statement::Describe(consume_all(rest, expect_show_columns)?)),
+            // SHOW TABLES
+            _ => {
+                consume_all(rest, expect_show_tables)?;
+                Ok(Statement::ShowTables)
+            }
+        },
+        // BEGIN
+        Token {
+            value: TokenValue::Const(Keyword::Begin),
+            ..
+        } => {
+            expect_end_of_statement(rest)?;
+            Ok(Statement::Begin)
+        }
+        // COMMIT
+        Token {
+            value: TokenValue::Const(Keyword::Commit),
+            ..
+        } => {
+            expect_end_of_statement(rest)?;
+            Ok(Statement::Commit)
+        }
+        // ROLLBACK
+        Token {
+            value: TokenValue::Const(Keyword::Rollback),
+            ..
+        } => {
+            expect_end_of_statement(rest)?;
+            Ok(Statement::Rollback)
+        }
+        // DESCRIBE
+        Token {
+            value: TokenValue::Const(Keyword::Describe),
+            ..
+        } => Ok(Statement::Describe(consume_all(rest, expect_describe)?)),
+        // EXPLAIN
+        Token {
+            value: TokenValue::Const(Keyword::Explain),
+            ..
+        } => match rest.first() {
+            // Rejected right away, so that a long chain of EXPLAINs can't nest deeply
+            Some(
+                nested_token @ Token {
+                    value: TokenValue::Const(Keyword::Explain),
+                    ..
+                },
+            ) => Err(SyntaxError::at(
+                nested_token,
+                format!(
+                    "Expected a statement to explain, instead found {}.",
+                    nested_token
+                ),
+            )),
+            _ => Ok(Statement::Explain(Box::new(parse_tokens(rest)?))),
+        },
         // Something else
-        wrong_token => Err(SyntaxError(format!(
-            "Expected {} or {}, instead found {}.",
-            Keyword::Create,
-            Keyword::Insert,
-            wrong_token
-        ))),
+        wrong_token => Err(SyntaxError::at(
+            wrong_token,
+            format!(
+                "Expected {} or {}, instead found {}.",
+                Keyword::Create,
+                Keyword::Insert,
+                wrong_token
+            ),
+        )),
     }
 }
 
 #[derive(Debug, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum Statement {
     CreateTable(CreateTableStatement),
+    AlterTable(AlterTableStatement),
+    TruncateTable(TruncateTableStatement),
     Insert(InsertStatement),
     Select(SelectStatement),
+    Update(UpdateStatement),
+    Delete(DeleteStatement),
+    ShowTables,
+    Describe(DescribeStatement),
+    Begin,
+    Commit,
+    Rollback,
+    /// The statement's plan, instead of its execution.
+    Explain(Box<Statement>),
+}
+
+impl Statement {
+    /// Name of the kind of statement, e.g. for logging.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Statement::CreateTable(_) => "create_table",
+            Statement::AlterTable(_) => "alter_table",
+            Statement::TruncateTable(_) => "truncate_table",
+            Statement::Insert(_) => "insert",
+            Statement::Select(_) => "select",
+            Statement::Update(_) => "update",
+            Statement::Delete(_) => "delete",
+            Statement::ShowTables => "show_tables",
+            Statement::Describe(_) => "describe",
+            Statement::Begin => "begin",
+            Statement::Commit => "commit",
+            Statement::Rollback => "rollback",
+            Statement::Explain(_) => "explain",
+        }
+    }
+
+    /// Whether the statement modifies the schema or data. Transaction control doesn't modify anything by itself.
+    pub fn is_mutation(&self) -> bool {
+        match self {
+            Statement::CreateTable(_)
+            | Statement::AlterTable(_)
+            | Statement::TruncateTable(_)
+            | Statement::Insert(_)
+            | Statement::Update(_)
+            | Statement::Delete(_) => true,
+            Statement::Select(_)
+            | Statement::ShowTables
+            | Statement::Describe(_)
+            | Statement::Begin
+            | Statement::Commit
+            | Statement::Rollback
+            | Statement::Explain(_) => false,
+        }
+    }
+
+    /// Whether the statement only reads. Transaction control isn't read-only, as it locks out all other
+    /// statements, and ROLLBACK writes the state from before the transaction back to disk.
+    pub fn is_read_only(&self) -> bool {
+        match self {
+            Statement::Select(_)
+            | Statement::ShowTables
+            | Statement::Describe(_)
+            | Statement::Explain(_) => true,
+            Statement::CreateTable(_)
+            | Statement::AlterTable(_)
+            | Statement::TruncateTable(_)
+            | Statement::Insert(_)
+            | Statement::Update(_)
+            | Statement::Delete(_)
+            | Statement::Begin
+            | Statement::Commit
+            | Statement::Rollback => false,
+        }
+    }
+
+    /// Data definitions of the statement which can be parameter placeholders.
+    fn data_definitions_mut(&mut self) -> Vec<&mut DataDefinition> {
+        let mut data_definitions = Vec::new();
+        match self {
+            Statement::CreateTable(create_table) => data_definitions.extend(
+                create_table
+                    .table
+                    .columns
+                    .iter_mut()
+                    .filter_map(|column| column.default.as_mut()),
+            ),
+            Statement::AlterTable(AlterTableStatement {
+                action: AlterTableAction::AddColumn(column),
+                ..
+            }) => data_definitions.extend(column.default.as_mut()),
+            Statement::Insert(insert) => data_definitions.extend(insert.values.iter_mut()),
+            Statement::Select(select) => {
+                for item in &mut select.columns {
+                    if let SelectColumn::Expression(expression) = &mut item.column {
+                        expression.collect_atoms_mut(&mut data_definitions);
+                    }
+                }
+                if let Some(join) = &mut select.join {
+                    join.condition.collect_atoms_mut(&mut data_definitions);
+                }
+                if let Some(where_clause) = &mut select.where_clause {
+                    where_clause.collect_atoms_mut(&mut data_definitions);
+                }
+            }
+            Statement::Update(update) => {
+                data_definitions.extend(
+                    update
+                        .assignments
+                        .iter_mut()
+                        .map(|assignment| &mut assignment.value),
+                );
+                if let Some(where_clause) = &mut update.where_clause {
+                    where_clause.collect_atoms_mut(&mut data_definitions);
+                }
+            }
+            Statement::Delete(delete) => {
+                if let Some(where_clause) = &mut delete.where_clause {
+                    where_clause.collect_atoms_mut(&mut data_definitions);
+                }
+            }
+            Statement::Explain(explained) => return explained.data_definitions_mut(),
+            _ => (),
+        }
+        data_definitions
+    }
+}
+
+/// Replace parameter placeholders in the statements with the values, `$1` being the first value.
+/// Values are coerced to column types on execution, just like literals.
+pub fn bind_parameters(
+    statements: &mut [Statement],
+    values: &[DataInstance],
+) -> Result<(), ValidationError> {
+    let placeholders: Vec<&mut DataDefinition> = statements
+        .iter_mut()
+        .flat_map(Statement::data_definitions_mut)
+        .filter(|data_definition| matches!(data_definition, DataDefinition::Parameter(_)))
+        .collect();
+    let parameter_count = placeholders
+        .iter()
+        .map(|placeholder| match placeholder {
+            DataDefinition::Parameter(number) => *number,
+            _ => 0,
+        })
+        .max()
+        .unwrap_or(0);
+    if parameter_count != values.len() {
+        return Err(ValidationError(format!(
+            "Number of parameter values ({}) doesn't match the number of parameters in the query ({})",
+            values.len(),
+            parameter_count
+        )));
+    }
+    for placeholder in placeholders {
+        if let DataDefinition::Parameter(number) = *placeholder {
+            *placeholder = DataDefinition::Const(values[number - 1].clone());
+        }
+    }
+    Ok(())
 }
 
 impl Validatable for Statement {
     fn validate(&self) -> Result<(), ValidationError> {
+        self.validate_with_name_policy(&NamePolicy::default())
+    }
+
+    fn validate_with_name_policy(&self, name_policy: &NamePolicy) -> Result<(), ValidationError> {
         match self {
-            Statement::CreateTable(create_table) => create_table.validate(),
+            Statement::CreateTable(create_table) => {
+                create_table.validate_with_name_policy(name_policy)
+            }
+            Statement::AlterTable(alter_table) => {
+                alter_table.validate_with_name_policy(name_policy)
+            }
+            Statement::TruncateTable(truncate_table) => truncate_table.validate(),
             Statement::Insert(insert) => insert.validate(),
             Statement::Select(select) => select.validate(),
+            Statement::Update(update) => update.validate(),
+            Statement::Delete(delete) => delete.validate(),
+            Statement::ShowTables => Ok(()),
+            Statement::Describe(describe) => describe.validate(),
+            Statement::Begin | Statement::Commit | Statement::Rollback => Ok(()),
+            Statement::Explain(explained) => explained.validate_with_name_policy(name_policy),
         }
     }
 }
@@ -87,8 +623,11 @@ mod tests {
             ColumnDefinition, DataDefinition, DataInstance, DataInstanceRaw, DataType, DataTypeRaw,
             Expression, TableDefinition,
         },
-        functions::Function,
-        statements::SelectColumn,
+        functions::{Aggregate, Function},
+        statements::{
+            AggregateArgument, AlterTableAction, Assignment, Join, OrderBy, OrderDirection,
+            SelectColumn, SelectItem,
+        },
     };
 
     use super::*;
@@ -118,7 +657,9 @@ mod tests {
                                 is_nullable: false
                             },
                             primary_key: true,
+                            unique: false,
                             default: None,
+                            comment: None,
                         },
                         ColumnDefinition {
                             name: "server_id".to_string(),
@@ -127,7 +668,9 @@ mod tests {
                                 is_nullable: true
                             },
                             primary_key: false,
+                            unique: false,
                             default: None,
+                            comment: None,
                         },
                         ColumnDefinition {
                             name: "hash".to_string(),
@@ -136,10 +679,12 @@ mod tests {
                                 is_nullable: false
                             },
                             primary_key: false,
+                            unique: false,
                             default: Some(DataDefinition::Const(DataInstance::Direct(
                                 // TODO: Infer number size from context
                                 DataInstanceRaw::UInt32(666)
                             ))),
+                            comment: None,
                         },
                         ColumnDefinition {
                             name: "sent_at".to_string(),
@@ -148,10 +693,13 @@ mod tests {
                                 is_nullable: false
                             },
                             primary_key: false,
+                            unique: false,
                             default: Some(DataDefinition::FunctionCall(Function::Now)),
+                            comment: None,
                         },
                     ]
-                ),
+                )
+                .unwrap(),
                 if_not_exists: true
             })
         )
@@ -170,13 +718,153 @@ mod tests {
                 table_name: "xyz".to_string(),
                 column_names: vec!["foo".to_string(), "bar".to_string(),],
                 values: vec![
-                    DataInstance::Direct(DataInstanceRaw::UInt32(1815)),
-                    DataInstance::Direct(DataInstanceRaw::String("Waterloo".into())),
+                    DataDefinition::Const(DataInstance::Direct(DataInstanceRaw::UInt32(1815))),
+                    DataDefinition::Const(DataInstance::Direct(DataInstanceRaw::String(
+                        "Waterloo".into()
+                    ))),
                 ]
             })
         )
     }
 
+    #[test]
+    fn parsing_works_with_parameters() {
+        const STATEMENT: &str = "UPDATE xyz SET foo = ? WHERE bar = $1";
+
+        let detected_statement = parse_statement(STATEMENT).unwrap();
+
+        assert_eq!(
+            detected_statement,
+            Statement::Update(UpdateStatement {
+                table_name: "xyz".to_string(),
+                assignments: vec![Assignment {
+                    column_name: "foo".to_string(),
+                    value: DataDefinition::Parameter(1),
+                }],
+                where_clause: Some(Expression::Equal(
+                    Box::new(Expression::Atom(DataDefinition::Identifier("bar".into()))),
+                    Box::new(Expression::Atom(DataDefinition::Parameter(1)))
+                )),
+            })
+        )
+    }
+
+    #[test]
+    fn parsing_works_with_insert_parameters() {
+        const STATEMENT: &str = "INSERT INTO xyz (foo, bar) VALUES (?, $1)";
+
+        let detected_statement = parse_statement(STATEMENT).unwrap();
+
+        assert_eq!(
+            detected_statement,
+            Statement::Insert(InsertStatement {
+                table_name: "xyz".to_string(),
+                column_names: vec!["foo".to_string(), "bar".to_string(),],
+                values: vec![DataDefinition::Parameter(1), DataDefinition::Parameter(1)]
+            })
+        )
+    }
+
+    #[test]
+    fn binding_parameters_works() {
+        let mut statements =
+            parse_statements("INSERT INTO xyz (foo) VALUES (?); DELETE FROM xyz WHERE foo = ?")
+                .unwrap();
+        let values = [
+            DataInstance::Direct(DataInstanceRaw::UInt32(1)),
+            DataInstance::Null,
+        ];
+
+        bind_parameters(&mut statements, &values).unwrap();
+
+        assert_eq!(
+            statements,
+            parse_statements("INSERT INTO xyz (foo) VALUES (1); DELETE FROM xyz WHERE foo = NULL")
+                .unwrap()
+        );
+        assert_eq!(
+            bind_parameters(&mut parse_statements("SELECT * FROM xyz WHERE foo = $2").unwrap(), &values[..1]),
+            Err(ValidationError(
+                "Number of parameter values (1) doesn't match the number of parameters in the query (2)".into()
+            ))
+        );
+    }
+
+    #[test]
+    fn type_aliases_are_recognized_in_aliases_dialect() {
+        const STATEMENT: &str =
+            "CREATE TABLE xyz (id BIGINT PRIMARY KEY, age int, name nullable(VARCHAR), \"text\" boolean)";
+
+        let detected_statement = parse_statement_in_dialect(STATEMENT, Dialect::Aliases).unwrap();
+
+        assert_eq!(
+            detected_statement,
+            parse_statement(
+                "CREATE TABLE xyz (id UINT64 PRIMARY KEY, age UINT32, name nullable(STRING), \"text\" BOOL)"
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn type_aliases_are_not_recognized_in_strict_dialect() {
+        const STATEMENT: &str = "CREATE TABLE xyz (id BIGINT PRIMARY KEY)";
+
+        assert_eq!(
+            parse_statement_in_dialect(STATEMENT, Dialect::Strict),
+            parse_statement(STATEMENT)
+        );
+        assert_eq!(
+            parse_statement(STATEMENT),
+            Err(SyntaxError {
+                message: "Expected a data type, instead found arbitrary `BIGINT` at line 1. Did you mean `UINT64`? `BIGINT` is only an alias of it in the `aliases` dialect.".to_string(),
+                position: Some(Position {
+                    line: 1,
+                    column: 22
+                })
+            })
+        );
+    }
+
+    #[test]
+    fn misspelled_type_gets_suggestion() {
+        assert_eq!(
+            parse_statement("CREATE TABLE xyz (id uint_64 PRIMARY KEY)"),
+            Err(SyntaxError {
+                message: "Expected a data type, instead found arbitrary `uint_64` at line 1. Did you mean `UINT64`?".to_string(),
+                position: Some(Position {
+                    line: 1,
+                    column: 22
+                })
+            })
+        );
+    }
+
+    #[test]
+    fn mutations_are_classified() {
+        let statements = parse_statements(
+            "CREATE TABLE xyz (id uint32 PRIMARY KEY); INSERT INTO xyz (id) VALUES (1); \
+            UPDATE xyz SET id = 2; DELETE FROM xyz; TRUNCATE TABLE xyz; \
+            SELECT * FROM xyz; SHOW TABLES; DESCRIBE xyz; BEGIN; COMMIT",
+        )
+        .unwrap();
+
+        assert_eq!(
+            statements
+                .iter()
+                .map(Statement::is_mutation)
+                .collect::<Vec<bool>>(),
+            vec![true, true, true, true, true, false, false, false, false, false]
+        );
+        assert_eq!(
+            statements
+                .iter()
+                .map(Statement::is_read_only)
+                .collect::<Vec<bool>>(),
+            vec![false, false, false, false, false, true, true, true, false, false]
+        );
+    }
+
     #[test]
     fn parsing_works_with_select() {
         const STATEMENT: &str = "SELECT *, foo FROM xyz WHERE foo = 'bar';";
@@ -186,11 +874,13 @@ mod tests {
         assert_eq!(
             detected_statement,
             Statement::Select(SelectStatement {
+                distinct: false,
                 columns: vec![
-                    SelectColumn::All,
-                    SelectColumn::Identifier("foo".to_string()),
+                    SelectColumn::All.into(),
+                    SelectColumn::Identifier("foo".to_string()).into(),
                 ],
                 source: "xyz".to_string(),
+                join: None,
                 where_clause: Some(Expression::Equal(
                     Box::new(Expression::Atom(DataDefinition::Identifier(
                         "foo".to_string()
@@ -198,8 +888,1377 @@ mod tests {
                     Box::new(Expression::Atom(DataDefinition::Const(
                         DataInstance::Direct(DataInstanceRaw::String("bar".into()))
                     )))
-                ))
+                )),
+                group_by: vec![],
+                order_by: vec![],
+                limit: None,
+                offset: None,
+                cursor: None
+            })
+        )
+    }
+
+    #[test]
+    fn parsing_works_with_null_checks() {
+        let where_clause = |statement: &str| match parse_statement(statement).unwrap() {
+            Statement::Select(SelectStatement { where_clause, .. }) => where_clause,
+            other => panic!("Expected a SELECT statement, instead found {:?}", other),
+        };
+        let foo = || Box::new(Expression::Atom(DataDefinition::Identifier("foo".into())));
+
+        assert_eq!(
+            where_clause("SELECT * FROM xyz WHERE foo IS NULL"),
+            Some(Expression::IsNull(foo()))
+        );
+        assert_eq!(
+            where_clause("SELECT * FROM xyz WHERE foo IS NOT NULL"),
+            Some(Expression::IsNotNull(foo()))
+        );
+        // Equality with NULL is still just equality, which never holds
+        assert_eq!(
+            where_clause("SELECT * FROM xyz WHERE foo = NULL"),
+            Some(Expression::Equal(
+                foo(),
+                Box::new(Expression::Atom(DataDefinition::Const(DataInstance::Null)))
+            ))
+        );
+        assert_eq!(
+            parse_statement("SELECT * FROM xyz WHERE foo IS 1")
+                .unwrap_err()
+                .message,
+            "Expected keyword `NULL`, instead found arbitrary `1` at line 1."
+        );
+    }
+
+    #[test]
+    fn parsing_works_with_in_list() {
+        const STATEMENT: &str = "SELECT * FROM xyz WHERE foo IN (1, 'bar', $1)";
+
+        let detected_statement = parse_statement(STATEMENT).unwrap();
+
+        assert_eq!(
+            detected_statement,
+            Statement::Select(SelectStatement {
+                distinct: false,
+                columns: vec![SelectColumn::All.into()],
+                source: "xyz".to_string(),
+                join: None,
+                where_clause: Some(Expression::In {
+                    column: Box::new(Expression::Atom(DataDefinition::Identifier(
+                        "foo".to_string()
+                    ))),
+                    values: vec![
+                        Expression::Atom(DataDefinition::Const(DataInstance::Direct(
+                            DataInstanceRaw::UInt32(1)
+                        ))),
+                        Expression::Atom(DataDefinition::Const(DataInstance::Direct(
+                            DataInstanceRaw::String("bar".into())
+                        ))),
+                        Expression::Atom(DataDefinition::Parameter(1)),
+                    ]
+                }),
+                group_by: vec![],
+                order_by: vec![],
+                limit: None,
+                offset: None,
+                cursor: None
+            })
+        )
+    }
+
+    #[test]
+    fn parsing_fails_with_empty_in_list() {
+        assert_eq!(
+            parse_statement("SELECT * FROM xyz WHERE foo IN ()")
+                .unwrap_err()
+                .message,
+            "Expected at least one value in the IN list, instead found an empty list."
+        );
+    }
+
+    #[test]
+    fn parsing_works_with_between() {
+        const STATEMENT: &str = "SELECT * FROM xyz WHERE foo BETWEEN 1 AND $1";
+
+        let detected_statement = parse_statement(STATEMENT).unwrap();
+
+        assert_eq!(
+            detected_statement,
+            Statement::Select(SelectStatement {
+                distinct: false,
+                columns: vec![SelectColumn::All.into()],
+                source: "xyz".to_string(),
+                join: None,
+                where_clause: Some(Expression::Between {
+                    column: Box::new(Expression::Atom(DataDefinition::Identifier(
+                        "foo".to_string()
+                    ))),
+                    low: Box::new(Expression::Atom(DataDefinition::Const(
+                        DataInstance::Direct(DataInstanceRaw::UInt32(1))
+                    ))),
+                    high: Box::new(Expression::Atom(DataDefinition::Parameter(1))),
+                }),
+                group_by: vec![],
+                order_by: vec![],
+                limit: None,
+                offset: None,
+                cursor: None
             })
         )
     }
+
+    #[test]
+    fn parsing_works_with_and_or_precedence() {
+        let where_clause = |statement: &str| match parse_statement(statement).unwrap() {
+            Statement::Select(SelectStatement { where_clause, .. }) => where_clause,
+            other => panic!("Expected a SELECT statement, instead found {:?}", other),
+        };
+        let equal = |column_name: &str, value: u32| {
+            Box::new(Expression::Equal(
+                Box::new(Expression::Atom(DataDefinition::Identifier(
+                    column_name.into(),
+                ))),
+                Box::new(Expression::Atom(DataDefinition::Const(
+                    DataInstance::Direct(DataInstanceRaw::UInt32(value)),
+                ))),
+            ))
+        };
+
+        assert_eq!(
+            where_clause("SELECT * FROM xyz WHERE a = 1 AND b = 2"),
+            Some(Expression::And(equal("a", 1), equal("b", 2)))
+        );
+        assert_eq!(
+            where_clause("SELECT * FROM xyz WHERE a = 1 OR b = 2"),
+            Some(Expression::Or(equal("a", 1), equal("b", 2)))
+        );
+        assert_eq!(
+            where_clause("SELECT * FROM xyz WHERE a = 1 OR b = 2 AND c = 3"),
+            Some(Expression::Or(
+                equal("a", 1),
+                Box::new(Expression::And(equal("b", 2), equal("c", 3)))
+            ))
+        );
+        assert_eq!(
+            where_clause("SELECT * FROM xyz WHERE a LIKE 'x%' AND b = 2"),
+            Some(Expression::And(
+                Box::new(Expression::Like {
+                    column: Box::new(Expression::Atom(DataDefinition::Identifier("a".into()))),
+                    pattern: Box::new(Expression::Atom(DataDefinition::Const(
+                        DataInstance::Direct(DataInstanceRaw::String("x%".into()))
+                    ))),
+                }),
+                equal("b", 2)
+            ))
+        );
+        assert_eq!(
+            where_clause("SELECT * FROM xyz WHERE (a = 1 OR b = 2) AND c = 3 ORDER BY a"),
+            Some(Expression::And(
+                Box::new(Expression::Or(equal("a", 1), equal("b", 2))),
+                equal("c", 3)
+            ))
+        );
+        assert_eq!(
+            where_clause(
+                "SELECT * FROM xyz WHERE a = 1 AND (b = 2 OR ((c = 3) AND d = 4)) OR e = 5"
+            ),
+            Some(Expression::Or(
+                Box::new(Expression::And(
+                    equal("a", 1),
+                    Box::new(Expression::Or(
+                        equal("b", 2),
+                        Box::new(Expression::And(equal("c", 3), equal("d", 4)))
+                    ))
+                )),
+                equal("e", 5)
+            ))
+        );
+        assert_eq!(
+            parse_statement("SELECT * FROM xyz WHERE (a = 1 OR (b = 2) LIMIT 1"),
+            Err(SyntaxError {
+                message:
+                    "Expected closing parenthesis `)`, instead found keyword `LIMIT` at line 1."
+                        .to_string(),
+                position: Some(Position {
+                    line: 1,
+                    column: 43
+                })
+            })
+        );
+    }
+
+    #[test]
+    fn parsing_works_with_quoted_identifiers() {
+        const STATEMENT: &str = "CREATE TABLE \"SELECT\" (\"order\" UINT8 PRIMARY KEY);";
+
+        let detected_statement = parse_statement(STATEMENT).unwrap();
+
+        assert_eq!(
+            detected_statement,
+            Statement::CreateTable(CreateTableStatement {
+                table: TableDefinition::new(
+                    "SELECT".to_string(),
+                    vec![ColumnDefinition {
+                        name: "order".to_string(),
+                        data_type: DataType {
+                            raw_type: DataTypeRaw::UInt8,
+                            is_nullable: false
+                        },
+                        primary_key: true,
+                        unique: false,
+                        default: None,
+                        comment: None,
+                    }]
+                )
+                .unwrap(),
+                if_not_exists: false
+            })
+        )
+    }
+
+    #[test]
+    fn parsing_works_with_alter_table_add_column() {
+        const STATEMENT: &str = "ALTER TABLE xyz ADD COLUMN note NULLABLE(STRING);";
+
+        let detected_statement = parse_statement(STATEMENT).unwrap();
+
+        assert_eq!(
+            detected_statement,
+            Statement::AlterTable(AlterTableStatement {
+                table_name: "xyz".to_string(),
+                action: AlterTableAction::AddColumn(ColumnDefinition {
+                    name: "note".to_string(),
+                    data_type: DataType {
+                        raw_type: DataTypeRaw::String,
+                        is_nullable: true
+                    },
+                    primary_key: false,
+                    unique: false,
+                    default: None,
+                    comment: None,
+                })
+            })
+        )
+    }
+
+    #[test]
+    fn parsing_works_with_alter_table_drop_column() {
+        const STATEMENT: &str = "ALTER TABLE xyz DROP COLUMN note;";
+
+        let detected_statement = parse_statement(STATEMENT).unwrap();
+
+        assert_eq!(
+            detected_statement,
+            Statement::AlterTable(AlterTableStatement {
+                table_name: "xyz".to_string(),
+                action: AlterTableAction::DropColumn("note".to_string())
+            })
+        )
+    }
+
+    #[test]
+    fn parsing_works_with_truncate_table() {
+        const STATEMENT: &str = "TRUNCATE TABLE xyz;";
+
+        let detected_statement = parse_statement(STATEMENT).unwrap();
+
+        assert_eq!(
+            detected_statement,
+            Statement::TruncateTable(TruncateTableStatement {
+                table_name: "xyz".to_string()
+            })
+        )
+    }
+
+    #[test]
+    fn parsing_works_with_show_tables() {
+        const STATEMENT: &str = "SHOW TABLES;";
+
+        let detected_statement = parse_statement(STATEMENT).unwrap();
+
+        assert_eq!(detected_statement, Statement::ShowTables)
+    }
+
+    #[test]
+    fn parsing_works_with_describe() {
+        let expected_statement = Statement::Describe(DescribeStatement {
+            table_name: "xyz".to_string(),
+        });
+
+        assert_eq!(
+            parse_statement("DESCRIBE xyz;").unwrap(),
+            expected_statement
+        );
+        assert_eq!(
+            parse_statement("SHOW COLUMNS FROM xyz;").unwrap(),
+            expected_statement
+        );
+    }
+
+    #[test]
+    fn parsing_works_with_explain() {
+        assert_eq!(
+            parse_statement("EXPLAIN DESCRIBE xyz;").unwrap(),
+            Statement::Explain(Box::new(Statement::Describe(DescribeStatement {
+                table_name: "xyz".to_string(),
+            })))
+        );
+        assert_eq!(
+            parse_statement("EXPLAIN EXPLAIN SHOW TABLES;"),
+            Err(SyntaxError {
+                message:
+                    "Expected a statement to explain, instead found keyword `EXPLAIN` at line 1."
+                        .to_string(),
+                position: Some(Position { line: 1, column: 9 })
+            })
+        );
+    }
+
+    #[test]
+    fn parsing_works_with_delete() {
+        assert_eq!(
+            parse_statement("DELETE FROM xyz WHERE id = 5;").unwrap(),
+            Statement::Delete(DeleteStatement {
+                table_name: "xyz".to_string(),
+                where_clause: Some(Expression::Equal(
+                    Box::new(Expression::Atom(DataDefinition::Identifier(
+                        "id".to_string()
+                    ))),
+                    Box::new(Expression::Atom(DataDefinition::Const(
+                        DataInstance::Direct(DataInstanceRaw::UInt32(5))
+                    )))
+                )),
+            })
+        );
+        assert_eq!(
+            parse_statement("DELETE FROM xyz;").unwrap(),
+            Statement::Delete(DeleteStatement {
+                table_name: "xyz".to_string(),
+                where_clause: None,
+            })
+        );
+    }
+
+    #[test]
+    fn parsing_works_with_update() {
+        assert_eq!(
+            parse_statement("UPDATE xyz SET note = 'x', created_at = NOW() WHERE id = 5;").unwrap(),
+            Statement::Update(UpdateStatement {
+                table_name: "xyz".to_string(),
+                assignments: vec![
+                    Assignment {
+                        column_name: "note".to_string(),
+                        value: DataDefinition::Const(DataInstance::Direct(
+                            DataInstanceRaw::String("x".to_string())
+                        )),
+                    },
+                    Assignment {
+                        column_name: "created_at".to_string(),
+                        value: DataDefinition::FunctionCall(Function::Now),
+                    }
+                ],
+                where_clause: Some(Expression::Equal(
+                    Box::new(Expression::Atom(DataDefinition::Identifier(
+                        "id".to_string()
+                    ))),
+                    Box::new(Expression::Atom(DataDefinition::Const(
+                        DataInstance::Direct(DataInstanceRaw::UInt32(5))
+                    )))
+                )),
+            })
+        );
+    }
+
+    #[test]
+    fn parsing_works_with_multiple_statements() {
+        const STATEMENTS: &str = "BEGIN; TRUNCATE TABLE xyz;
+            SHOW TABLES; ROLLBACK;";
+
+        assert_eq!(
+            parse_statements(STATEMENTS).unwrap(),
+            vec![
+                Statement::Begin,
+                Statement::TruncateTable(TruncateTableStatement {
+                    table_name: "xyz".to_string()
+                }),
+                Statement::ShowTables,
+                Statement::Rollback
+            ]
+        );
+    }
+
+    #[test]
+    fn parsing_multiple_statements_reports_error_position() {
+        const STATEMENTS: &str = "BEGIN;
+            SHOW TABLES; SHOW xyz;";
+
+        assert_eq!(
+            parse_statements(STATEMENTS).unwrap_err(),
+            SyntaxError {
+                message: "Expected keyword `TABLES`, instead found arbitrary `xyz` at line 2."
+                    .to_string(),
+                position: Some(Position {
+                    line: 2,
+                    column: 31
+                })
+            }
+        );
+    }
+
+    #[test]
+    fn parsing_with_recovery_skips_broken_statement() {
+        const STATEMENTS: &str = "SHOW TABLES;
+            SELECT FROM WHERE;
+            TRUNCATE TABLE xyz;";
+
+        assert_eq!(
+            parse_statements_recovering(STATEMENTS),
+            RecoveredStatements {
+                statements: vec![
+                    Statement::ShowTables,
+                    Statement::TruncateTable(TruncateTableStatement {
+                        table_name: "xyz".to_string()
+                    })
+                ],
+                errors: vec![SyntaxError {
+                    message: "Expected a SELECT column, instead found keyword `FROM` at line 2."
+                        .to_string(),
+                    position: Some(Position {
+                        line: 2,
+                        column: 20
+                    })
+                }]
+            }
+        );
+        // A semicolon inside an unterminated string doesn't end the statement, but one on the next line does
+        assert_eq!(
+            parse_statements_recovering("DESCRIBE 'xyz; SHOW TABLES;\n; BEGIN"),
+            RecoveredStatements {
+                statements: vec![Statement::Begin],
+                errors: vec![SyntaxError {
+                    message: "Found an unterminated string `'xyz; SHOW TABLES;` at line 1."
+                        .to_string(),
+                    position: Some(Position {
+                        line: 1,
+                        column: 10
+                    })
+                }]
+            }
+        );
+        assert_eq!(
+            parse_statements_recovering(" ; "),
+            RecoveredStatements::default()
+        );
+    }
+
+    #[test]
+    fn parsing_fails_past_token_count_limit() {
+        let limits = ParseLimits {
+            max_token_count: 6,
+            ..ParseLimits::default()
+        };
+        assert_eq!(
+            parse_statements_with_limits(
+                "SHOW TABLES; SELECT * FROM xyz",
+                Dialect::Strict,
+                TrailingCommas::Strict,
+                &limits
+            )
+            .unwrap()
+            .len(),
+            2
+        );
+        assert_eq!(
+            parse_statements_with_limits(
+                "SHOW TABLES; SELECT * FROM xyz LIMIT 1",
+                Dialect::Strict,
+                TrailingCommas::Strict,
+                &limits
+            ),
+            Err(SyntaxError {
+                message: "Found keyword `LIMIT` at line 1 past the limit of 6 tokens.".to_string(),
+                position: Some(Position {
+                    line: 1,
+                    column: 32
+                })
+            })
+        );
+    }
+
+    #[test]
+    fn parsing_fails_past_nesting_depth_limit() {
+        let limits = ParseLimits {
+            max_nesting_depth: 2,
+            ..ParseLimits::default()
+        };
+        assert!(parse_statements_with_limits(
+            "SELECT * FROM xyz WHERE (a = 1 OR (b = 2)) AND (c = 3)",
+            Dialect::Strict,
+            TrailingCommas::Strict,
+            &limits
+        )
+        .is_ok());
+        assert_eq!(
+            parse_statements_with_limits(
+                "SELECT * FROM xyz WHERE (a = 1 OR (b = 2 AND (c = 3)))",
+                Dialect::Strict,
+                TrailingCommas::Strict,
+                &limits
+            ),
+            Err(SyntaxError {
+                message: "Found opening parenthesis `(` at line 1 past the limit of 2 levels of nested parentheses."
+                    .to_string(),
+                position: Some(Position {
+                    line: 1,
+                    column: 46
+                })
+            })
+        );
+        // Parentheses nested this deep used to overflow the stack, as each level was parsed recursively
+        let deeply_nested = format!(
+            "SELECT * FROM xyz WHERE {}a = 1{}",
+            "(".repeat(100_000),
+            ")".repeat(100_000)
+        );
+        assert_eq!(
+            parse_statement(&deeply_nested),
+            Err(SyntaxError {
+                message: "Found opening parenthesis `(` at line 1 past the limit of 64 levels of nested parentheses."
+                    .to_string(),
+                position: Some(Position {
+                    line: 1,
+                    column: 89
+                })
+            })
+        );
+        assert!(parse_statements_with_limits(
+            &deeply_nested,
+            Dialect::Strict,
+            TrailingCommas::Strict,
+            &ParseLimits {
+                max_nesting_depth: usize::MAX,
+                ..ParseLimits::default()
+            }
+        )
+        .is_ok());
+        // The same goes for arithmetic expressions
+        let deeply_nested_arithmetic = format!(
+            "SELECT {}a + 1{} FROM xyz",
+            "(".repeat(100_000),
+            ")".repeat(100_000)
+        );
+        assert!(parse_statements_with_limits(
+            &deeply_nested_arithmetic,
+            Dialect::Strict,
+            TrailingCommas::Strict,
+            &ParseLimits {
+                max_nesting_depth: usize::MAX,
+                ..ParseLimits::default()
+            }
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn parsing_works_with_unique_column() {
+        const STATEMENT: &str =
+            "CREATE TABLE xyz (id UINT64 PRIMARY KEY, email STRING UNIQUE DEFAULT 'x');";
+
+        let detected_statement = parse_statement(STATEMENT).unwrap();
+
+        assert_eq!(
+            detected_statement,
+            Statement::CreateTable(CreateTableStatement {
+                table: TableDefinition::new(
+                    "xyz".to_string(),
+                    vec![
+                        ColumnDefinition {
+                            name: "id".to_string(),
+                            data_type: DataType {
+                                raw_type: DataTypeRaw::UInt64,
+                                is_nullable: false
+                            },
+                            primary_key: true,
+                            unique: false,
+                            default: None,
+                            comment: None,
+                        },
+                        ColumnDefinition {
+                            name: "email".to_string(),
+                            data_type: DataType {
+                                raw_type: DataTypeRaw::String,
+                                is_nullable: false
+                            },
+                            primary_key: false,
+                            unique: true,
+                            default: Some(DataDefinition::Const(DataInstance::Direct(
+                                DataInstanceRaw::String("x".into())
+                            ))),
+                            comment: None,
+                        }
+                    ]
+                )
+                .unwrap(),
+                if_not_exists: false
+            })
+        )
+    }
+
+    #[test]
+    fn parsing_works_with_select_order_by() {
+        const STATEMENT: &str = "SELECT * FROM xyz ORDER BY foo DESC, bar;";
+
+        let detected_statement = parse_statement(STATEMENT).unwrap();
+
+        assert_eq!(
+            detected_statement,
+            Statement::Select(SelectStatement {
+                distinct: false,
+                columns: vec![SelectColumn::All.into()],
+                source: "xyz".to_string(),
+                join: None,
+                where_clause: None,
+                group_by: vec![],
+                order_by: vec![
+                    OrderBy {
+                        column_name: "foo".to_string(),
+                        direction: OrderDirection::Desc
+                    },
+                    OrderBy {
+                        column_name: "bar".to_string(),
+                        direction: OrderDirection::Asc
+                    }
+                ],
+                limit: None,
+                offset: None,
+                cursor: None
+            })
+        )
+    }
+
+    #[test]
+    fn parsing_works_with_select_distinct() {
+        const STATEMENT: &str = "SELECT DISTINCT foo, bar FROM xyz;";
+
+        let detected_statement = parse_statement(STATEMENT).unwrap();
+
+        assert_eq!(
+            detected_statement,
+            Statement::Select(SelectStatement {
+                distinct: true,
+                columns: vec![
+                    SelectColumn::Identifier("foo".to_string()).into(),
+                    SelectColumn::Identifier("bar".to_string()).into(),
+                ],
+                source: "xyz".to_string(),
+                join: None,
+                where_clause: None,
+                group_by: vec![],
+                order_by: vec![],
+                limit: None,
+                offset: None,
+                cursor: None
+            })
+        )
+    }
+
+    #[test]
+    fn parsing_works_with_select_group_by() {
+        const STATEMENT: &str =
+            "SELECT country, COUNT(*) FROM xyz WHERE foo = 1 GROUP BY country, city ORDER BY country;";
+
+        let detected_statement = parse_statement(STATEMENT).unwrap();
+
+        assert_eq!(
+            detected_statement,
+            Statement::Select(SelectStatement {
+                distinct: false,
+                columns: vec![
+                    SelectColumn::Identifier("country".to_string()).into(),
+                    SelectColumn::Aggregate {
+                        function: Aggregate::Count,
+                        argument: AggregateArgument::All
+                    }
+                    .into(),
+                ],
+                source: "xyz".to_string(),
+                join: None,
+                where_clause: Some(Expression::Equal(
+                    Box::new(Expression::Atom(DataDefinition::Identifier(
+                        "foo".to_string()
+                    ))),
+                    Box::new(Expression::Atom(DataDefinition::Const(
+                        DataInstance::Direct(DataInstanceRaw::UInt32(1))
+                    )))
+                )),
+                group_by: vec!["country".to_string(), "city".to_string()],
+                order_by: vec![OrderBy {
+                    column_name: "country".to_string(),
+                    direction: OrderDirection::Asc
+                }],
+                limit: None,
+                offset: None,
+                cursor: None
+            })
+        )
+    }
+
+    #[test]
+    fn parsing_works_with_select_join() {
+        const STATEMENT: &str =
+            "SELECT users.name, amount FROM users INNER JOIN orders ON users.id = orders.user_id;";
+
+        let detected_statement = parse_statement(STATEMENT).unwrap();
+
+        assert_eq!(
+            detected_statement,
+            Statement::Select(SelectStatement {
+                distinct: false,
+                columns: vec![
+                    SelectColumn::QualifiedIdentifier {
+                        table_name: "users".to_string(),
+                        column_name: "name".to_string()
+                    }
+                    .into(),
+                    SelectColumn::Identifier("amount".to_string()).into(),
+                ],
+                source: "users".to_string(),
+                join: Some(Join {
+                    table_name: "orders".to_string(),
+                    condition: Expression::Equal(
+                        Box::new(Expression::Atom(DataDefinition::QualifiedIdentifier {
+                            table_name: "users".to_string(),
+                            column_name: "id".to_string()
+                        })),
+                        Box::new(Expression::Atom(DataDefinition::QualifiedIdentifier {
+                            table_name: "orders".to_string(),
+                            column_name: "user_id".to_string()
+                        }))
+                    )
+                }),
+                where_clause: None,
+                group_by: vec![],
+                order_by: vec![],
+                limit: None,
+                offset: None,
+                cursor: None
+            })
+        );
+        // INNER is optional
+        assert!(matches!(
+            parse_statement("SELECT * FROM users JOIN orders ON users.id = orders.user_id"),
+            Ok(Statement::Select(SelectStatement { join: Some(_), .. }))
+        ));
+    }
+
+    #[test]
+    fn parsing_works_with_select_aliases() {
+        const STATEMENT: &str = "SELECT id AS user_id, name, COUNT(*) AS \"count\" FROM xyz";
+
+        let detected_statement = parse_statement(STATEMENT).unwrap();
+
+        assert_eq!(
+            detected_statement,
+            Statement::Select(SelectStatement {
+                distinct: false,
+                columns: vec![
+                    SelectItem {
+                        column: SelectColumn::Identifier("id".to_string()),
+                        alias: Some("user_id".to_string())
+                    },
+                    SelectItem {
+                        column: SelectColumn::Identifier("name".to_string()),
+                        alias: None
+                    },
+                    SelectItem {
+                        column: SelectColumn::Aggregate {
+                            function: Aggregate::Count,
+                            argument: AggregateArgument::All
+                        },
+                        alias: Some("count".to_string())
+                    },
+                ],
+                source: "xyz".to_string(),
+                join: None,
+                where_clause: None,
+                group_by: vec![],
+                order_by: vec![],
+                limit: None,
+                offset: None,
+                cursor: None
+            })
+        );
+        assert_eq!(detected_statement.validate(), Ok(()));
+    }
+
+    #[test]
+    fn invalid_aliases_are_rejected() {
+        assert_eq!(
+            parse_statement("SELECT * AS everything FROM xyz")
+                .unwrap()
+                .validate(),
+            Err(ValidationError(
+                "`*` cannot be given an alias, as it stands for multiple columns".into()
+            ))
+        );
+        assert_eq!(
+            parse_statement("SELECT id AS x, name AS X FROM xyz")
+                .unwrap()
+                .validate(),
+            Err(ValidationError(
+                "Alias `X` is given to more than one SELECT column".into()
+            ))
+        );
+    }
+
+    #[test]
+    fn parsing_works_with_select_arithmetic() {
+        const STATEMENT: &str = "SELECT price * quantity, (a + 1) / 2 - b AS x FROM orders";
+
+        let detected_statement = parse_statement(STATEMENT).unwrap();
+
+        let column =
+            |name: &str| Box::new(Expression::Atom(DataDefinition::Identifier(name.into())));
+        let constant = |value: u32| {
+            Box::new(Expression::Atom(DataDefinition::Const(
+                DataInstance::Direct(DataInstanceRaw::UInt32(value)),
+            )))
+        };
+        let product = Expression::Multiply(column("price"), column("quantity"));
+        let difference = Expression::Subtract(
+            Box::new(Expression::Divide(
+                Box::new(Expression::Add(column("a"), constant(1))),
+                constant(2),
+            )),
+            column("b"),
+        );
+        assert_eq!(product.column_name(), "price * quantity");
+        assert_eq!(difference.column_name(), "(a + 1) / 2 - b");
+        assert_eq!(
+            detected_statement,
+            Statement::Select(SelectStatement {
+                distinct: false,
+                columns: vec![
+                    SelectColumn::Expression(product).into(),
+                    SelectItem {
+                        column: SelectColumn::Expression(difference),
+                        alias: Some("x".to_string())
+                    },
+                ],
+                source: "orders".to_string(),
+                join: None,
+                where_clause: None,
+                group_by: vec![],
+                order_by: vec![],
+                limit: None,
+                offset: None,
+                cursor: None
+            })
+        );
+    }
+
+    #[test]
+    fn parsing_works_with_select_cast() {
+        const STATEMENT: &str = "SELECT CAST(price AS STRING), CAST((a + 1) AS uint8) FROM orders";
+
+        let detected_statement = parse_statement(STATEMENT).unwrap();
+
+        let column =
+            |name: &str| Box::new(Expression::Atom(DataDefinition::Identifier(name.into())));
+        let cast_price = Expression::Cast {
+            operand: column("price"),
+            data_type: DataTypeRaw::String,
+        };
+        let cast_sum = Expression::Cast {
+            operand: Box::new(Expression::Add(
+                column("a"),
+                Box::new(Expression::Atom(DataDefinition::Const(
+                    DataInstance::Direct(DataInstanceRaw::UInt32(1)),
+                ))),
+            )),
+            data_type: DataTypeRaw::UInt8,
+        };
+        assert_eq!(cast_price.column_name(), "CAST(price AS STRING)");
+        assert_eq!(cast_sum.column_name(), "CAST(a + 1 AS UINT8)");
+        assert_eq!(
+            detected_statement,
+            Statement::Select(SelectStatement {
+                distinct: false,
+                columns: vec![
+                    SelectColumn::Expression(cast_price).into(),
+                    SelectColumn::Expression(cast_sum).into(),
+                ],
+                source: "orders".to_string(),
+                join: None,
+                where_clause: None,
+                group_by: vec![],
+                order_by: vec![],
+                limit: None,
+                offset: None,
+                cursor: None
+            })
+        );
+    }
+
+    #[test]
+    fn arithmetic_operators_bind_by_precedence() {
+        for (statement, expected_column_name) in [
+            ("SELECT a + b * c FROM xyz", "a + b * c"),
+            ("SELECT (a + b) * c FROM xyz", "(a + b) * c"),
+            ("SELECT a - (b - c) FROM xyz", "a - (b - c)"),
+            ("SELECT a - b - c FROM xyz", "a - b - c"),
+            ("SELECT a / (b * c) FROM xyz", "a / (b * c)"),
+            ("SELECT a * b + c * d FROM xyz", "a * b + c * d"),
+            ("SELECT a - b * c / d + e FROM xyz", "a - b * c / d + e"),
+            ("SELECT a / b / c FROM xyz", "a / b / c"),
+            ("SELECT ((a)) * (b - (c + d)) FROM xyz", "a * (b - (c + d))"),
+            (
+                "SELECT CAST(a * (b + c) AS STRING) FROM xyz",
+                "CAST(a * (b + c) AS STRING)",
+            ),
+        ] {
+            match parse_statement(statement).unwrap() {
+                Statement::Select(SelectStatement { columns, .. }) => match &columns[0].column {
+                    SelectColumn::Expression(expression) => {
+                        assert_eq!(expression.column_name(), expected_column_name)
+                    }
+                    column => panic!("Unexpected column {:?}", column),
+                },
+                statement => panic!("Unexpected statement {:?}", statement),
+            }
+        }
+    }
+
+    #[test]
+    fn parsing_works_with_select_limit_offset() {
+        const STATEMENT: &str = "SELECT * FROM xyz LIMIT 10 OFFSET 20;";
+
+        let detected_statement = parse_statement(STATEMENT).unwrap();
+
+        assert_eq!(
+            detected_statement,
+            Statement::Select(SelectStatement {
+                distinct: false,
+                columns: vec![SelectColumn::All.into()],
+                source: "xyz".to_string(),
+                join: None,
+                where_clause: None,
+                group_by: vec![],
+                order_by: vec![],
+                limit: Some(10),
+                offset: Some(20),
+                cursor: None
+            })
+        )
+    }
+
+    #[test]
+    fn parsing_fails_with_negative_limit() {
+        const STATEMENT: &str = "SELECT * FROM xyz LIMIT -1;";
+
+        assert_eq!(
+            parse_statement(STATEMENT),
+            Err(SyntaxError {
+                message: "Expected a non-negative integer, instead found arbitrary `-1` at line 1."
+                    .to_string(),
+                position: Some(Position {
+                    line: 1,
+                    column: 25
+                })
+            })
+        )
+    }
+
+    #[test]
+    fn parsing_works_with_create_table_with_primary_key_clause() {
+        const STATEMENT: &str = "CREATE TABLE test (
+            id UINT64,
+            name STRING,
+            PRIMARY KEY (ID)
+        );";
+
+        assert_eq!(
+            parse_statement(STATEMENT),
+            Ok(Statement::CreateTable(CreateTableStatement {
+                table: TableDefinition::new(
+                    "test".to_string(),
+                    vec![
+                        ColumnDefinition {
+                            name: "id".to_string(),
+                            data_type: DataType {
+                                raw_type: DataTypeRaw::UInt64,
+                                is_nullable: false
+                            },
+                            primary_key: true,
+                            unique: false,
+                            default: None,
+                            comment: None
+                        },
+                        ColumnDefinition {
+                            name: "name".to_string(),
+                            data_type: DataType {
+                                raw_type: DataTypeRaw::String,
+                                is_nullable: false
+                            },
+                            primary_key: false,
+                            unique: false,
+                            default: None,
+                            comment: None
+                        },
+                    ]
+                )
+                .unwrap(),
+                if_not_exists: false
+            }))
+        );
+        // Declaring the same column PRIMARY KEY inline as well is redundant, but not contradictory
+        assert_eq!(
+            parse_statement(
+                "CREATE TABLE test (id UINT64 PRIMARY KEY, name STRING, PRIMARY KEY (id));"
+            ),
+            parse_statement(STATEMENT)
+        );
+    }
+
+    #[test]
+    fn parsing_works_with_create_table_with_composite_primary_key() {
+        const STATEMENT: &str =
+            "CREATE TABLE test (user_id UINT64, day UINT16, note STRING, PRIMARY KEY (user_id, day));";
+
+        match parse_statement(STATEMENT).unwrap() {
+            Statement::CreateTable(CreateTableStatement { table, .. }) => assert_eq!(
+                table
+                    .columns
+                    .iter()
+                    .map(|column| column.primary_key)
+                    .collect::<Vec<_>>(),
+                vec![true, true, false]
+            ),
+            statement => panic!("Expected CREATE TABLE, got {:?}", statement),
+        }
+        assert_eq!(
+            parse_statement(
+                "CREATE TABLE test (user_id UINT64, day UINT16, PRIMARY KEY (user_id, USER_ID));"
+            ),
+            Err(SyntaxError {
+                message: "PRIMARY KEY clause specifies column `USER_ID` more than once."
+                    .to_string(),
+                position: Some(Position {
+                    line: 1,
+                    column: 48
+                })
+            })
+        );
+    }
+
+    #[test]
+    fn parsing_fails_with_conflicting_primary_key_declarations() {
+        const STATEMENT: &str =
+            "CREATE TABLE test (id UINT64 PRIMARY KEY, name STRING, PRIMARY KEY (name));";
+
+        assert_eq!(
+            parse_statement(STATEMENT),
+            Err(SyntaxError {
+                message: "PRIMARY KEY clause doesn't specify column `id`, which is already declared PRIMARY KEY.".to_string(),
+                position: Some(Position {
+                    line: 1,
+                    column: 56
+                })
+            })
+        )
+    }
+
+    #[test]
+    fn parsing_fails_with_primary_key_clause_of_undefined_column() {
+        const STATEMENT: &str = "CREATE TABLE test (id UINT64, PRIMARY KEY (xyz));";
+
+        assert_eq!(
+            parse_statement(STATEMENT),
+            Err(SyntaxError {
+                message:
+                    "PRIMARY KEY clause specifies column `xyz`, which is not defined in the table."
+                        .to_string(),
+                position: Some(Position {
+                    line: 1,
+                    column: 31
+                })
+            })
+        )
+    }
+
+    #[test]
+    fn parsing_fails_with_create_table_without_primary_key() {
+        const STATEMENT: &str = "CREATE TABLE test (id UINT8, name STRING);";
+
+        assert_eq!(
+            parse_statement(STATEMENT).unwrap().validate(),
+            Err(ValidationError(
+                "A table must have at least 1 PRIMARY KEY column".to_string()
+            ))
+        )
+    }
+
+    #[test]
+    fn parsing_fails_with_create_table_with_two_primary_keys() {
+        const STATEMENT: &str =
+            "CREATE TABLE test (id UINT8 PRIMARY KEY, other_id UINT8 PRIMARY KEY);";
+
+        assert_eq!(
+            parse_statement(STATEMENT),
+            Err(SyntaxError {
+                message: "Columns `id` and `other_id` are both declared PRIMARY KEY. A composite key is declared with a PRIMARY KEY (id, other_id) clause instead.".to_string(),
+                position: Some(Position {
+                    line: 1,
+                    column: 14
+                })
+            })
+        )
+    }
+
+    #[test]
+    fn parsing_fails_with_reserved_keyword_as_table_name() {
+        const STATEMENT: &str = "CREATE TABLE select (id UINT8 PRIMARY KEY);";
+
+        assert_eq!(
+            parse_statement(STATEMENT),
+            Err(SyntaxError {
+                message: "Expected an identifier, instead found reserved keyword `SELECT` at line 1. Quote it as `\"SELECT\"` to use it as an identifier.".to_string(),
+                position: Some(Position { line: 1, column: 14 })
+            })
+        )
+    }
+
+    #[test]
+    fn parsing_fails_with_reserved_keyword_as_column_name() {
+        const STATEMENT: &str = "CREATE TABLE test (id UINT8 PRIMARY KEY, uint64 UINT64);";
+
+        assert_eq!(
+            parse_statement(STATEMENT),
+            Err(SyntaxError {
+                message: "Expected an identifier, instead found reserved keyword `UINT64` at line 1. Quote it as `\"UINT64\"` to use it as an identifier.".to_string(),
+                position: Some(Position { line: 1, column: 42 })
+            })
+        )
+    }
+
+    #[test]
+    fn parsing_error_has_position() {
+        const STATEMENT: &str = "SELECT *
+FROM xyz
+  LIMIT foo;";
+
+        assert_eq!(
+            parse_statement(STATEMENT),
+            Err(SyntaxError {
+                message:
+                    "Expected a non-negative integer, instead found arbitrary `foo` at line 3."
+                        .to_string(),
+                position: Some(Position { line: 3, column: 9 })
+            })
+        )
+    }
+
+    #[test]
+    fn parsing_works_with_select_count() {
+        const STATEMENT: &str = "SELECT COUNT(*) FROM xyz WHERE foo = 1;";
+
+        let detected_statement = parse_statement(STATEMENT).unwrap();
+
+        assert_eq!(
+            detected_statement,
+            Statement::Select(SelectStatement {
+                distinct: false,
+                columns: vec![SelectColumn::Aggregate {
+                    function: Aggregate::Count,
+                    argument: AggregateArgument::All
+                }
+                .into()],
+                source: "xyz".to_string(),
+                join: None,
+                where_clause: Some(Expression::Equal(
+                    Box::new(Expression::Atom(DataDefinition::Identifier(
+                        "foo".to_string()
+                    ))),
+                    Box::new(Expression::Atom(DataDefinition::Const(
+                        DataInstance::Direct(DataInstanceRaw::UInt32(1))
+                    )))
+                )),
+                group_by: vec![],
+                order_by: vec![],
+                limit: None,
+                offset: None,
+                cursor: None
+            })
+        )
+    }
+
+    fn parse_with_trailing_commas(
+        input: &str,
+        trailing_commas: TrailingCommas,
+    ) -> Result<Vec<Statement>, SyntaxError> {
+        parse_statements_with_limits(
+            input,
+            Dialect::Strict,
+            trailing_commas,
+            &ParseLimits::default(),
+        )
+    }
+
+    #[test]
+    fn trailing_comma_is_accepted_in_lenient_mode() {
+        assert_eq!(
+            parse_with_trailing_commas(
+                "INSERT INTO xyz (id, name,) VALUES (1, 'foo',); CREATE TABLE t (id UINT64 PRIMARY KEY,)",
+                TrailingCommas::Lenient
+            )
+            .unwrap(),
+            parse_statements(
+                "INSERT INTO xyz (id, name) VALUES (1, 'foo'); CREATE TABLE t (id UINT64 PRIMARY KEY)"
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn trailing_comma_is_rejected_in_strict_mode() {
+        assert_eq!(
+            parse_with_trailing_commas(
+                "INSERT INTO xyz (id, name,) VALUES (1, 'foo')",
+                TrailingCommas::Strict
+            ),
+            Err(SyntaxError {
+                message: "Found disallowed trailing comma `,`.".to_string(),
+                position: Some(Position {
+                    line: 1,
+                    column: 26
+                })
+            })
+        );
+    }
+
+    #[test]
+    fn lone_or_doubled_commas_are_rejected_in_lenient_mode() {
+        assert_eq!(
+            parse_with_trailing_commas("INSERT INTO xyz (,) VALUES (1)", TrailingCommas::Lenient),
+            Err(SyntaxError {
+                message: "Expected an identifier, instead found end of statement.".to_string(),
+                position: None
+            })
+        );
+        assert_eq!(
+            parse_with_trailing_commas(
+                "INSERT INTO xyz (id, name,,) VALUES (1, 'foo')",
+                TrailingCommas::Lenient
+            ),
+            Err(SyntaxError {
+                message: "Expected an identifier, instead found end of statement.".to_string(),
+                position: None
+            })
+        );
+        // A trailing comma outside of parentheses stays an error as well
+        assert_eq!(
+            parse_with_trailing_commas("SELECT id, FROM xyz", TrailingCommas::Lenient),
+            Err(SyntaxError {
+                message: "Expected a SELECT column, instead found keyword `FROM` at line 1."
+                    .to_string(),
+                position: Some(Position {
+                    line: 1,
+                    column: 12
+                })
+            })
+        );
+    }
+
+    #[test]
+    fn trailing_semicolon_is_the_same_as_end_of_input() {
+        for statement in [
+            "SHOW TABLES",
+            "SELECT * FROM xyz WHERE id = 1 ORDER BY id LIMIT 1",
+            "CREATE TABLE xyz (id NULLABLE(UINT8) PRIMARY KEY)",
+            "INSERT INTO xyz (id) VALUES (1)",
+            "UPDATE xyz SET id = 2",
+            "DESCRIBE xyz",
+            // Incomplete statements fail the same way with and without a semicolon
+            "",
+            "SELECT * FROM",
+            "SELECT * FROM xyz LIMIT",
+            "CREATE TABLE xyz (id NULLABLE",
+            "CREATE TABLE xyz (id NULLABLE(",
+            "CREATE TABLE xyz (id NULLABLE(UINT8",
+            "INSERT INTO xyz (id) VALUES",
+            "UPDATE xyz SET id =",
+            "DELETE FROM xyz WHERE",
+            "ALTER TABLE xyz ADD COLUMN",
+        ] {
+            for terminated_statement in [
+                format!("{};", statement),
+                format!("{} ;\n", statement),
+                format!("{};;", statement),
+                format!("{}\n;", statement),
+            ] {
+                assert_eq!(
+                    parse_statement(&terminated_statement),
+                    parse_statement(statement),
+                    "{:?}",
+                    terminated_statement
+                );
+                assert_eq!(
+                    parse_statements(&terminated_statement),
+                    parse_statements(statement),
+                    "{:?}",
+                    terminated_statement
+                );
+                assert_eq!(
+                    parse_statements_recovering(&terminated_statement),
+                    parse_statements_recovering(statement),
+                    "{:?}",
+                    terminated_statement
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn parsing_single_statement_fails_on_another_one() {
+        assert_eq!(
+            parse_statement("SHOW TABLES; DROP TABLE xyz"),
+            Err(SyntaxError {
+                message: "Expected end of input, instead found keyword `DROP` at line 1 in another statement. Only a single statement is allowed here.".to_string(),
+                position: Some(Position {
+                    line: 1,
+                    column: 14
+                })
+            })
+        );
+    }
+
+    #[test]
+    fn parsing_fails_without_any_statement() {
+        for input in [
+            "",
+            "  \n\t ",
+            "-- SELECT * FROM xyz",
+            "\n-- Nothing here yet\n;  ;",
+        ] {
+            let expected_error = SyntaxError {
+                message: "Found no statement to run, as the input is empty or only has whitespace and comments.".to_string(),
+                position: Some(Position { line: 1, column: 1 }),
+            };
+            assert_eq!(
+                parse_statements(input).unwrap_err(),
+                expected_error,
+                "{:?}",
+                input
+            );
+            assert_eq!(parse_statement(input), Err(expected_error), "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn parsing_works_with_comments() {
+        assert_eq!(
+            parse_statement("-- Tables of the database\nSHOW -- as in list\nTABLES"),
+            parse_statement("SHOW TABLES")
+        );
+    }
 }
@@ -1,22 +1,37 @@
+use std::borrow::Cow;
 use std::str::FromStr;
 
-use crate::constructs::{components::DataTypeRaw, functions::Function};
+use super::dialect::Dialect;
+use super::errors::{Position, SyntaxError};
+use crate::constructs::{
+    components::DataTypeRaw,
+    functions::{Aggregate, Function},
+};
 use std::fmt::{self, Debug};
 
 #[derive(Debug, PartialEq, Eq, Clone)]
+#[non_exhaustive]
 pub enum Delimiter {
     Comma,
     ParenthesisOpening,
     ParenthesisClosing,
     Equal,
+    /// Separates a table name from a column name in a qualified column reference, e.g. `users.id`.
+    Period,
 }
 
 impl Delimiter {
     /// Delimiting characters that affect statement meaning. Each one is a Delimiter variant.
-    const MEANINGFUL_CHARS: &'static [char] = &[',', '(', ')', '='];
+    const MEANINGFUL_CHARS: &'static [char] = &[',', '(', ')', '=', '.'];
     const STATEMENT_SEPARATOR: char = ';';
+    /// Starts a comment running to the end of the line.
+    const COMMENT_MARKER: &'static str = "--";
     const STRING_MARKER: char = '\'';
+    const IDENTIFIER_MARKER: char = '"';
     const ESCAPE_CHARACTER: char = '\\';
+    /// Placeholder for a parameter value, numbered by its position. `$1`-style numbered placeholders are also supported.
+    const PARAMETER_MARKER: &'static str = "?";
+    const NUMBERED_PARAMETER_PREFIX: char = '$';
 }
 
 impl fmt::Display for Delimiter {
@@ -29,6 +44,7 @@ impl fmt::Display for Delimiter {
                 Self::ParenthesisOpening => "opening parenthesis `(`",
                 Self::ParenthesisClosing => "closing parenthesis `)`",
                 Self::Equal => "equality sign `=`",
+                Self::Period => "period `.`",
             }
         )
     }
@@ -43,6 +59,7 @@ impl FromStr for Delimiter {
             "(" => Ok(Self::ParenthesisOpening),
             ")" => Ok(Self::ParenthesisClosing),
             "=" => Ok(Self::Equal),
+            "." => Ok(Self::Period),
             _ => Err(format!(
                 "`{}` does not refer to a meaningful delimiter",
                 candidate
@@ -52,10 +69,15 @@ impl FromStr for Delimiter {
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
+#[non_exhaustive]
 pub enum Keyword {
     Create,
     Select,
+    Distinct,
     From,
+    Inner,
+    Join,
+    On,
     Where,
     As,
     Insert,
@@ -65,42 +87,195 @@ pub enum Keyword {
     If,
     Not,
     Exists,
+    Is,
+    In,
+    Between,
+    And,
+    Or,
+    Like,
     Nullable,
     Primary,
     Metric,
     Key,
     Null,
+    True,
+    False,
     Default,
+    Alter,
+    Add,
+    Column,
+    Drop,
+    Unique,
+    Comment,
+    Group,
+    Order,
+    By,
+    Asc,
+    Desc,
+    Limit,
+    Offset,
+    Truncate,
+    Show,
+    Tables,
+    Describe,
+    Columns,
+    Delete,
+    Update,
+    Set,
+    Begin,
+    Commit,
+    Rollback,
+    Explain,
+    Cast,
     Asterisk,
+    Plus,
+    Minus,
+    Slash,
+}
+
+impl Keyword {
+    /// Array of all keywords, which is what they're recognized from.
+    pub const ALL: [Self; 60] = [
+        Self::Create,
+        Self::Select,
+        Self::Distinct,
+        Self::From,
+        Self::Inner,
+        Self::Join,
+        Self::On,
+        Self::Where,
+        Self::As,
+        Self::Insert,
+        Self::Into,
+        Self::Values,
+        Self::Table,
+        Self::If,
+        Self::Not,
+        Self::Exists,
+        Self::Is,
+        Self::In,
+        Self::Between,
+        Self::And,
+        Self::Or,
+        Self::Like,
+        Self::Nullable,
+        Self::Primary,
+        Self::Metric,
+        Self::Key,
+        Self::Null,
+        Self::True,
+        Self::False,
+        Self::Default,
+        Self::Alter,
+        Self::Add,
+        Self::Column,
+        Self::Drop,
+        Self::Unique,
+        Self::Comment,
+        Self::Group,
+        Self::Order,
+        Self::By,
+        Self::Asc,
+        Self::Desc,
+        Self::Limit,
+        Self::Offset,
+        Self::Truncate,
+        Self::Show,
+        Self::Tables,
+        Self::Describe,
+        Self::Columns,
+        Self::Delete,
+        Self::Update,
+        Self::Set,
+        Self::Begin,
+        Self::Commit,
+        Self::Rollback,
+        Self::Explain,
+        Self::Cast,
+        Self::Asterisk,
+        Self::Plus,
+        Self::Minus,
+        Self::Slash,
+    ];
+
+    /// Whether this is a symbol rather than a word, i.e. an operator.
+    pub fn is_symbol(&self) -> bool {
+        matches!(
+            self,
+            Self::Asterisk | Self::Plus | Self::Minus | Self::Slash
+        )
+    }
+
+    /// The keyword as written in SQL.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Keyword::Create => "CREATE",
+            Keyword::Select => "SELECT",
+            Keyword::Distinct => "DISTINCT",
+            Keyword::From => "FROM",
+            Keyword::Inner => "INNER",
+            Keyword::Join => "JOIN",
+            Keyword::On => "ON",
+            Keyword::Where => "WHERE",
+            Keyword::As => "AS",
+            Keyword::Insert => "INSERT",
+            Keyword::Into => "INTO",
+            Keyword::Values => "VALUES",
+            Keyword::Table => "TABLE",
+            Keyword::If => "IF",
+            Keyword::Not => "NOT",
+            Keyword::Exists => "EXISTS",
+            Keyword::Is => "IS",
+            Keyword::In => "IN",
+            Keyword::Between => "BETWEEN",
+            Keyword::And => "AND",
+            Keyword::Or => "OR",
+            Keyword::Like => "LIKE",
+            Keyword::Nullable => "NULLABLE",
+            Keyword::Primary => "PRIMARY",
+            Keyword::Metric => "METRIC",
+            Keyword::Key => "KEY",
+            Keyword::Null => "NULL",
+            Keyword::True => "TRUE",
+            Keyword::False => "FALSE",
+            Keyword::Default => "DEFAULT",
+            Keyword::Alter => "ALTER",
+            Keyword::Add => "ADD",
+            Keyword::Column => "COLUMN",
+            Keyword::Drop => "DROP",
+            Keyword::Unique => "UNIQUE",
+            Keyword::Comment => "COMMENT",
+            Keyword::Group => "GROUP",
+            Keyword::Order => "ORDER",
+            Keyword::By => "BY",
+            Keyword::Asc => "ASC",
+            Keyword::Desc => "DESC",
+            Keyword::Limit => "LIMIT",
+            Keyword::Offset => "OFFSET",
+            Keyword::Truncate => "TRUNCATE",
+            Keyword::Show => "SHOW",
+            Keyword::Tables => "TABLES",
+            Keyword::Describe => "DESCRIBE",
+            Keyword::Columns => "COLUMNS",
+            Keyword::Delete => "DELETE",
+            Keyword::Update => "UPDATE",
+            Keyword::Set => "SET",
+            Keyword::Begin => "BEGIN",
+            Keyword::Commit => "COMMIT",
+            Keyword::Rollback => "ROLLBACK",
+            Keyword::Explain => "EXPLAIN",
+            Keyword::Cast => "CAST",
+            Keyword::Asterisk => "*",
+            Keyword::Plus => "+",
+            Keyword::Minus => "-",
+            Keyword::Slash => "/",
+        }
+    }
 }
 
 impl fmt::Display for Keyword {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            "keyword `{}`",
-            match self {
-                Keyword::Create => "CREATE",
-                Keyword::Select => "SELECT",
-                Keyword::From => "FROM",
-                Keyword::Where => "WHERE",
-                Keyword::As => "AS",
-                Keyword::Insert => "INSERT",
-                Keyword::Into => "INTO",
-                Keyword::Values => "VALUES",
-                Keyword::Table => "TABLE",
-                Keyword::If => "IF",
-                Keyword::Not => "NOT",
-                Keyword::Exists => "EXISTS",
-                Keyword::Nullable => "NULLABLE",
-                Keyword::Primary => "PRIMARY",
-                Keyword::Metric => "METRIC",
-                Keyword::Key => "KEY",
-                Keyword::Null => "NULL",
-                Keyword::Default => "DEFAULT",
-                Keyword::Asterisk => "*",
-            }
-        )
+        write!(f, "keyword `{}`", self.name())
     }
 }
 
@@ -108,113 +283,345 @@ impl FromStr for Keyword {
     type Err = String;
 
     fn from_str(candidate: &str) -> std::result::Result<Self, Self::Err> {
-        match candidate.to_lowercase().as_str() {
-            "create" => Ok(Self::Create),
-            "select" => Ok(Self::Select),
-            "from" => Ok(Self::From),
-            "where" => Ok(Self::Where),
-            "as" => Ok(Self::As),
-            "insert" => Ok(Self::Insert),
-            "into" => Ok(Self::Into),
-            "values" => Ok(Self::Values),
-            "table" => Ok(Self::Table),
-            "if" => Ok(Self::If),
-            "not" => Ok(Self::Not),
-            "exists" => Ok(Self::Exists),
-            "nullable" => Ok(Self::Nullable),
-            "primary" => Ok(Self::Primary),
-            "metric" => Ok(Self::Metric),
-            "key" => Ok(Self::Key),
-            "null" => Ok(Self::Null),
-            "default" => Ok(Self::Default),
-            "*" => Ok(Self::Asterisk),
-            _ => Err(format!("`{}` does not refer to a keyword", candidate)),
-        }
+        Self::ALL
+            .iter()
+            .find(|keyword| keyword.name().eq_ignore_ascii_case(candidate))
+            .cloned()
+            .ok_or_else(|| format!("`{}` does not refer to a keyword", candidate))
     }
 }
 
+/// Names of all keywords, e.g. for autocompletion. Type names are listed separately by [`type_names`].
+pub fn keywords() -> impl Iterator<Item = &'static str> {
+    Keyword::ALL
+        .iter()
+        .filter(|keyword| !keyword.is_symbol())
+        .map(Keyword::name)
+}
+
+/// Whether the word is a keyword (case-insensitively).
+pub fn is_keyword(candidate: &str) -> bool {
+    keywords().any(|keyword| keyword.eq_ignore_ascii_case(candidate))
+}
+
+/// Canonical names of all data types. Aliases of the [`Dialect::Aliases`] dialect aren't included.
+pub fn type_names() -> impl Iterator<Item = &'static str> {
+    DataTypeRaw::ALL.iter().map(DataTypeRaw::name)
+}
+
+/// What a token is, along with its content. Content is borrowed from the input,
+/// unless it differs from the input's text, e.g. due to escapes.
 #[derive(Debug, PartialEq, Eq, Clone)]
-pub enum TokenValue {
+#[non_exhaustive]
+pub enum TokenValue<'t> {
     Delimiting(Delimiter),
     Const(Keyword),
     Type(DataTypeRaw),
     Function(Function),
-    String(String),
-    Arbitrary(String),
+    Aggregate(Aggregate),
+    String(Cow<'t, str>),
+    /// Placeholder of a parameter value, by 1-based parameter number.
+    Parameter(usize),
+    /// Identifier written in double quotes, contents only. It may contain any characters and is never a keyword.
+    QuotedIdentifier(Cow<'t, str>),
+    Arbitrary(Cow<'t, str>),
 }
 
-impl fmt::Display for TokenValue {
+impl fmt::Display for TokenValue<'_> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Self::Delimiting(value) => fmt::Display::fmt(&value, f),
             Self::Const(value) => fmt::Display::fmt(&value, f),
-            Self::Type(value) => value.fmt(f),
+            Self::Type(value) => write!(f, "type `{}`", value),
             Self::Function(value) => fmt::Display::fmt(&value, f),
+            Self::Aggregate(value) => fmt::Display::fmt(&value, f),
             Self::String(value) => write!(f, "string `\"{}\"`", value),
+            Self::Parameter(number) => write!(f, "parameter `${}`", number),
+            Self::QuotedIdentifier(value) => write!(f, "identifier `\"{}\"`", value),
             Self::Arbitrary(value) => write!(f, "arbitrary `{}`", value),
         }
     }
 }
 
-impl FromStr for TokenValue {
+impl FromStr for TokenValue<'static> {
     type Err = ();
 
     fn from_str(candidate: &str) -> std::result::Result<Self, Self::Err> {
-        if let Ok(delimiter) = Delimiter::from_str(candidate) {
-            Ok(Self::Delimiting(delimiter))
-        } else if let Ok(keyword) = Keyword::from_str(candidate) {
-            Ok(Self::Const(keyword))
-        } else if let Ok(data_type_raw) = DataTypeRaw::from_str(candidate) {
-            Ok(Self::Type(data_type_raw))
-        } else if let Ok(function) = Function::from_str(candidate) {
-            Ok(Self::Function(function))
+        Ok(TokenValue::from_candidate(Cow::Owned(
+            candidate.to_string(),
+        )))
+    }
+}
+
+/// Part of the candidate between its first and last character, e.g. contents of a quoted identifier,
+/// borrowed if the candidate is.
+fn strip_markers(candidate: Cow<'_, str>) -> Cow<'_, str> {
+    // Markers are always ASCII, so they're 1 byte long
+    match candidate {
+        Cow::Borrowed(candidate) => Cow::Borrowed(&candidate[1..candidate.len() - 1]),
+        Cow::Owned(candidate) => Cow::Owned(candidate[1..candidate.len() - 1].to_string()),
+    }
+}
+
+impl<'t> TokenValue<'t> {
+    fn from_candidate(candidate: Cow<'t, str>) -> Self {
+        let mut candidate_chars = candidate.chars();
+        // Quoted identifiers are never keywords, types or functions, even if their contents match one
+        if candidate.len() >= 2 {
+            if let (Some(Delimiter::IDENTIFIER_MARKER), Some(Delimiter::IDENTIFIER_MARKER)) =
+                (candidate_chars.next(), candidate_chars.next_back())
+            {
+                return Self::QuotedIdentifier(strip_markers(candidate));
+            }
+        }
+        if let Ok(delimiter) = Delimiter::from_str(&candidate) {
+            Self::Delimiting(delimiter)
+        } else if let Ok(keyword) = Keyword::from_str(&candidate) {
+            Self::Const(keyword)
+        } else if let Ok(data_type_raw) = DataTypeRaw::from_str(&candidate) {
+            Self::Type(data_type_raw)
+        } else if let Ok(function) = Function::from_str(&candidate) {
+            Self::Function(function)
+        } else if let Ok(aggregate) = Aggregate::from_str(&candidate) {
+            Self::Aggregate(aggregate)
+        } else if let Some(number) = candidate
+            .strip_prefix(Delimiter::NUMBERED_PARAMETER_PREFIX)
+            .and_then(|number| number.parse::<usize>().ok())
+            .filter(|number| *number >= 1)
+        {
+            Self::Parameter(number)
         } else {
             let mut candidate_chars = candidate.chars();
             if let (Some(Delimiter::STRING_MARKER), Some(Delimiter::STRING_MARKER)) =
                 (candidate_chars.next(), candidate_chars.next_back())
             {
-                // We only need to use as_str() here as the first and last chars have been consumed by nth()s
-                Ok(Self::String(candidate_chars.as_str().to_string()))
+                Self::String(strip_markers(candidate))
             } else {
-                Ok(Self::Arbitrary(candidate.to_string()))
+                Self::Arbitrary(candidate)
             }
         }
     }
 }
 
+/// Token of SQL, positioned within the input. Displayed the way syntax errors refer to it,
+/// e.g. "arbitrary `xyz` at line 1".
 #[derive(Debug, PartialEq, Eq, Clone)]
-pub struct Token {
-    pub value: TokenValue,
+pub struct Token<'t> {
+    pub value: TokenValue<'t>,
     pub line_number: usize,
+    /// 1-based character offset of the token's start within its line.
+    pub column_number: usize,
 }
 
-impl fmt::Display for Token {
+impl fmt::Display for Token<'_> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{} at line {}", self.value, self.line_number)
     }
 }
 
-pub fn tokenize_statement(input: &str) -> Vec<Token> {
-    let mut tokens = Vec::<Token>::new();
+/// Tokenize the input, failing on the first lexical error. Tokens of all the statements are returned in order,
+/// statement separators (semicolons) themselves not being tokens. Useful for tools inspecting the token stream,
+/// e.g. for syntax highlighting.
+///
+/// ```
+/// use emdrive::constructs::components::DataTypeRaw;
+/// use emdrive::{tokenize, Keyword, TokenValue};
+///
+/// let tokens = tokenize("SELECT id FROM users WHERE name = 'Alice'").unwrap();
+/// assert_eq!(tokens[0].value, TokenValue::Const(Keyword::Select));
+/// assert_eq!(tokens[1].value, TokenValue::Arbitrary("id".into()));
+/// assert_eq!(tokens[7].value, TokenValue::String("Alice".into()));
+/// assert_eq!(tokens[1].to_string(), "arbitrary `id` at line 1");
+/// assert_eq!((tokens[1].line_number, tokens[1].column_number), (1, 8));
+///
+/// assert!(tokenize("SELECT 'unterminated").is_err());
+/// ```
+pub fn tokenize(input: &str) -> Result<Vec<Token<'_>>, SyntaxError> {
+    let mut tokens = Vec::new();
+    for (statement_tokens, errors) in tokenize_by_statement(input, Dialect::Strict, usize::MAX) {
+        if let Some(error) = errors.into_iter().next() {
+            return Err(error);
+        }
+        tokens.extend(statement_tokens);
+    }
+    Ok(tokens)
+}
+
+/// Tokenize the statement leniently, with any malformed tokens treated as arbitrary ones.
+pub fn tokenize_statement(input: &str) -> Vec<Token<'_>> {
+    tokenize_by_statement(input, Dialect::Strict, usize::MAX)
+        .swap_remove(0)
+        .0
+}
+
+/// Tokenize the statement, collecting every lexical error instead of stopping at the first one.
+/// Useful for linters, which can then point out all problems at once.
+pub fn tokenize_collect_errors(input: &str) -> Result<Vec<Token<'_>>, Vec<SyntaxError>> {
+    tokenize_collect_errors_in_dialect(input, Dialect::Strict)
+}
+
+/// Like `tokenize_collect_errors`, with type names of the dialect recognized.
+pub(crate) fn tokenize_collect_errors_in_dialect(
+    input: &str,
+    dialect: Dialect,
+) -> Result<Vec<Token<'_>>, Vec<SyntaxError>> {
+    let (tokens, errors) = tokenize_by_statement(input, dialect, usize::MAX).swap_remove(0);
+    if errors.is_empty() {
+        Ok(tokens)
+    } else {
+        Err(errors)
+    }
+}
+
+/// Tokenize each of the semicolon-separated statements, collecting their lexical errors.
+/// Statements with neither tokens nor errors (e.g. after a trailing semicolon) are left out.
+pub fn tokenize_statements(input: &str) -> Vec<Result<Vec<Token<'_>>, Vec<SyntaxError>>> {
+    tokenize_statements_in_dialect(input, Dialect::Strict)
+}
+
+/// Like `tokenize_statements`, with type names of the dialect recognized.
+pub(crate) fn tokenize_statements_in_dialect(
+    input: &str,
+    dialect: Dialect,
+) -> Vec<Result<Vec<Token<'_>>, Vec<SyntaxError>>> {
+    tokenize_statements_with_limit(input, dialect, usize::MAX)
+}
+
+/// Like `tokenize_statements_in_dialect`, but stopping once more than `max_token_count` tokens have been found,
+/// so that oversized input isn't tokenized in full just to be rejected. The excess token is kept for the error.
+pub(crate) fn tokenize_statements_with_limit(
+    input: &str,
+    dialect: Dialect,
+    max_token_count: usize,
+) -> Vec<Result<Vec<Token<'_>>, Vec<SyntaxError>>> {
+    tokenize_by_statement(input, dialect, max_token_count)
+        .into_iter()
+        .filter(|(tokens, errors)| !tokens.is_empty() || !errors.is_empty())
+        .map(|(tokens, errors)| {
+            if errors.is_empty() {
+                Ok(tokens)
+            } else {
+                Err(errors)
+            }
+        })
+        .collect()
+}
+
+/// Token candidate being collected from a line. While it's verbatim, i.e. no characters of the line have been
+/// skipped (as escapes are), it's borrowed from the line instead of being copied.
+#[derive(Default)]
+struct Candidate {
+    /// Characters collected, which are only needed if the candidate isn't verbatim, or for error messages.
+    text: String,
+    /// Byte range of the candidate within the line.
+    start_byte: usize,
+    end_byte: usize,
+    is_skipping: bool,
+}
+
+impl Candidate {
+    fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+
+    fn push(&mut self, byte_index: usize, character: char) {
+        if self.text.is_empty() {
+            self.start_byte = byte_index;
+        }
+        self.text.push(character);
+        self.end_byte = byte_index + character.len_utf8();
+    }
+
+    /// Note that a character of the line has been left out of the candidate.
+    fn skip(&mut self) {
+        self.is_skipping = true;
+    }
+
+    /// Finish the candidate, leaving an empty one (with the buffer kept for reuse).
+    fn take<'t>(&mut self, line: &'t str) -> Cow<'t, str> {
+        let candidate = if self.is_skipping {
+            Cow::Owned(self.text.clone())
+        } else {
+            Cow::Borrowed(&line[self.start_byte..self.end_byte])
+        };
+        self.text.clear();
+        self.is_skipping = false;
+        candidate
+    }
+}
+
+/// Tokens and lexical errors of each semicolon-separated statement. There's always at least one statement.
+/// Parameters are numbered across all statements of the input. Tokenization stops early once more than
+/// `max_token_count` tokens have been found, in which case the rest of the input is left out.
+fn tokenize_by_statement(
+    input: &str,
+    dialect: Dialect,
+    max_token_count: usize,
+) -> Vec<(Vec<Token<'_>>, Vec<SyntaxError>)> {
+    let mut statements = vec![(Vec::<Token>::new(), Vec::<SyntaxError>::new())];
+    let mut highest_parameter_number = 0;
+    // Tokens of all the statements so far, not counting candidates of the current line
+    let mut token_count = 0;
+    let mut is_over_limit = false;
     for (line_index, line) in input.lines().enumerate() {
-        let mut token_candidates = Vec::<String>::new();
-        let mut current_candidate: String = "".to_string();
+        let line_number = line_index + 1;
+        // Candidates are paired with the column at which they start
+        let mut token_candidates = Vec::<(Cow<str>, usize)>::new();
+        let mut current_candidate = Candidate::default();
+        let mut current_candidate_column_number = 1;
         let mut is_current_character_escaped = false;
         let mut is_current_character_inside_string = false;
-        for character in line.chars() {
+        let mut is_current_character_inside_identifier = false;
+        let mut characters = line.char_indices().enumerate().peekable();
+        while let Some((character_index, (byte_index, character))) = characters.next() {
+            if token_count + token_candidates.len() > max_token_count {
+                is_over_limit = true;
+                break;
+            }
+            // An escaped character's token starts at the preceding backslash
+            if current_candidate.is_empty() && !is_current_character_escaped {
+                current_candidate_column_number = character_index + 1;
+            }
+            // Inside a quoted identifier everything is taken literally, except for the closing quote
+            if is_current_character_inside_identifier {
+                if character == Delimiter::IDENTIFIER_MARKER {
+                    if characters
+                        .peek()
+                        .map(|(_, (_, next_character))| *next_character)
+                        == Some(Delimiter::IDENTIFIER_MARKER)
+                    {
+                        // A doubled quote is an escaped quote
+                        characters.next();
+                        current_candidate.skip();
+                    } else {
+                        current_candidate.push(byte_index, character);
+                        token_candidates.push((
+                            current_candidate.take(line),
+                            current_candidate_column_number,
+                        ));
+                        is_current_character_inside_identifier = false;
+                        continue;
+                    }
+                }
+                current_candidate.push(byte_index, character);
+                continue;
+            }
             // Act upon tokenization-level semantics, but only if the current character is not escaped with a backslash
             if !is_current_character_escaped {
                 // Detect if the next character is escaped
                 if character == Delimiter::ESCAPE_CHARACTER {
                     is_current_character_escaped = true;
+                    current_candidate.skip();
                     continue;
                 }
                 // Detect if this character starts/ends a string
                 if character == Delimiter::STRING_MARKER {
-                    current_candidate.push(character);
+                    current_candidate.push(byte_index, character);
                     if is_current_character_inside_string {
-                        token_candidates.push(current_candidate.clone());
-                        current_candidate.clear();
+                        token_candidates.push((
+                            current_candidate.take(line),
+                            current_candidate_column_number,
+                        ));
                         is_current_character_inside_string = false;
                     } else {
                         is_current_character_inside_string = true;
@@ -222,24 +629,63 @@ pub fn tokenize_statement(input: &str) -> Vec<Token> {
                     continue;
                 }
                 if !is_current_character_inside_string {
-                    // End tokenization when a statement separator (semicolon) is encountered
-                    if character == Delimiter::STATEMENT_SEPARATOR {
+                    // Detect if this character starts a quoted identifier
+                    if character == Delimiter::IDENTIFIER_MARKER {
+                        if !current_candidate.is_empty() {
+                            token_candidates.push((
+                                current_candidate.take(line),
+                                current_candidate_column_number,
+                            ));
+                        }
+                        current_candidate_column_number = character_index + 1;
+                        current_candidate.push(byte_index, character);
+                        is_current_character_inside_identifier = true;
+                        continue;
+                    }
+                    // Skip the rest of the line when a comment starts
+                    if line[byte_index..].starts_with(Delimiter::COMMENT_MARKER) {
                         break;
                     }
+                    // Start a new statement when a statement separator (semicolon) is encountered
+                    if character == Delimiter::STATEMENT_SEPARATOR {
+                        if !current_candidate.is_empty() {
+                            token_candidates.push((
+                                current_candidate.take(line),
+                                current_candidate_column_number,
+                            ));
+                        }
+                        let (tokens, _) = statements.last_mut().unwrap();
+                        token_count += token_candidates.len();
+                        tokens.extend(candidates_into_tokens(
+                            token_candidates.drain(..),
+                            line_number,
+                            &mut highest_parameter_number,
+                            dialect,
+                        ));
+                        statements.push((Vec::new(), Vec::new()));
+                        continue;
+                    }
                     // Recognize delimiters earlier, as they don't have to be separated by whitespace from other tokens
                     if Delimiter::MEANINGFUL_CHARS.contains(&character) {
                         if !current_candidate.is_empty() {
-                            token_candidates.push(current_candidate.clone());
-                            current_candidate.clear();
+                            token_candidates.push((
+                                current_candidate.take(line),
+                                current_candidate_column_number,
+                            ));
                         }
-                        token_candidates.push(character.to_string());
+                        token_candidates.push((
+                            Cow::Borrowed(&line[byte_index..byte_index + character.len_utf8()]),
+                            character_index + 1,
+                        ));
                         continue;
                     }
                     // Break up non-delimiter tokens on whitespace
                     if character.is_ascii_whitespace() {
                         if !current_candidate.is_empty() {
-                            token_candidates.push(current_candidate.clone());
-                            current_candidate.clear();
+                            token_candidates.push((
+                                current_candidate.take(line),
+                                current_candidate_column_number,
+                            ));
                         }
                         continue;
                     }
@@ -249,19 +695,101 @@ pub fn tokenize_statement(input: &str) -> Vec<Token> {
                 is_current_character_escaped = false;
             }
             // The default case for a character is just being appended to the working token candidate string
-            current_candidate.push(character);
+            current_candidate.push(byte_index, character);
+        }
+        // Strings and quoted identifiers can't span lines, and there's nothing to escape past the line's end,
+        // though the line may also just have been cut short by the token limit
+        let lexical_error = if is_over_limit {
+            None
+        } else if is_current_character_inside_string {
+            Some((
+                format!(
+                    "Found an unterminated string `{}` at line {}.",
+                    current_candidate.text, line_number
+                ),
+                current_candidate_column_number,
+            ))
+        } else if is_current_character_inside_identifier {
+            Some((
+                format!(
+                    "Found an unterminated quoted identifier `{}` at line {}.",
+                    current_candidate.text, line_number
+                ),
+                current_candidate_column_number,
+            ))
+        } else if is_current_character_escaped {
+            Some((
+                format!(
+                    "Found a dangling escape character `{}` at the end of line {}.",
+                    Delimiter::ESCAPE_CHARACTER,
+                    line_number
+                ),
+                line.chars().count(),
+            ))
+        } else {
+            None
+        };
+        let (tokens, errors) = statements.last_mut().unwrap();
+        if let Some((message, column)) = lexical_error {
+            errors.push(SyntaxError {
+                message,
+                position: Some(Position {
+                    line: line_number,
+                    column,
+                }),
+            });
         }
         // Add line remainded to token candidates
         if !current_candidate.is_empty() {
-            token_candidates.push(current_candidate);
+            token_candidates.push((
+                current_candidate.take(line),
+                current_candidate_column_number,
+            ));
         }
         // Process token candidates found on this line
-        tokens.extend(token_candidates.iter().map(|candidate| Token {
-            value: TokenValue::from_str(candidate).unwrap(),
-            line_number: line_index + 1,
-        }))
+        token_count += token_candidates.len();
+        tokens.extend(candidates_into_tokens(
+            token_candidates,
+            line_number,
+            &mut highest_parameter_number,
+            dialect,
+        ));
+        if is_over_limit {
+            break;
+        }
     }
-    tokens
+    statements
+}
+
+/// Turn token candidates, paired with the columns at which they start, into tokens.
+/// Like in SQLite, a `?` placeholder is numbered one higher than the highest parameter number so far.
+fn candidates_into_tokens<'t>(
+    candidates: impl IntoIterator<Item = (Cow<'t, str>, usize)>,
+    line_number: usize,
+    highest_parameter_number: &mut usize,
+    dialect: Dialect,
+) -> Vec<Token<'t>> {
+    candidates
+        .into_iter()
+        .map(|(candidate, column_number)| {
+            let value = if candidate == Delimiter::PARAMETER_MARKER {
+                TokenValue::Parameter(*highest_parameter_number + 1)
+            } else if let Some(data_type_raw) = dialect.resolve_type_alias(&candidate) {
+                // Quoted identifiers keep their quotes at this point, so they're never taken for aliases
+                TokenValue::Type(data_type_raw)
+            } else {
+                TokenValue::from_candidate(candidate)
+            };
+            if let TokenValue::Parameter(number) = value {
+                *highest_parameter_number = (*highest_parameter_number).max(number);
+            }
+            Token {
+                value,
+                line_number,
+                column_number,
+            }
+        })
+        .collect()
 }
 
 #[cfg(test)]
@@ -269,6 +797,30 @@ mod tests {
     use super::*;
     use pretty_assertions::assert_eq;
 
+    #[test]
+    fn keywords_are_listed() {
+        let keywords: Vec<&str> = keywords().collect();
+        for keyword in ["SELECT", "WHERE", "NULLABLE", "BETWEEN", "ROLLBACK"] {
+            assert!(keywords.contains(&keyword));
+        }
+        for operator in ["*", "+", "-", "/"] {
+            assert!(!keywords.contains(&operator));
+        }
+        assert!(is_keyword("select"));
+        assert!(is_keyword("Order"));
+        assert!(!is_keyword("users"));
+        assert!(!is_keyword("*"));
+        // Type names aren't keywords, even though they're reserved too
+        assert!(!is_keyword("UINT64"));
+        assert!(type_names().any(|type_name| type_name == "UINT64"));
+        for keyword in Keyword::ALL {
+            assert_eq!(
+                Keyword::from_str(&keyword.name().to_lowercase()),
+                Ok(keyword)
+            );
+        }
+    }
+
     #[test]
     fn tokenization_works_with_create_table() {
         const STATEMENT: &str = "CREATE TABLE IF NOT EXISTS test (
@@ -283,106 +835,131 @@ mod tests {
             Token {
                 value: TokenValue::Const(Keyword::Create),
                 line_number: 1,
+                column_number: 1,
             },
             Token {
                 value: TokenValue::Const(Keyword::Table),
                 line_number: 1,
+                column_number: 8,
             },
             Token {
                 value: TokenValue::Const(Keyword::If),
                 line_number: 1,
+                column_number: 14,
             },
             Token {
                 value: TokenValue::Const(Keyword::Not),
                 line_number: 1,
+                column_number: 17,
             },
             Token {
                 value: TokenValue::Const(Keyword::Exists),
                 line_number: 1,
+                column_number: 21,
             },
             Token {
-                value: TokenValue::Arbitrary("test".to_string()),
+                value: TokenValue::Arbitrary("test".into()),
                 line_number: 1,
+                column_number: 28,
             },
             Token {
                 value: TokenValue::Delimiting(Delimiter::ParenthesisOpening),
                 line_number: 1,
+                column_number: 33,
             },
             // New line
             Token {
-                value: TokenValue::Arbitrary("server_id".to_string()),
+                value: TokenValue::Arbitrary("server_id".into()),
                 line_number: 2,
+                column_number: 13,
             },
             Token {
                 value: TokenValue::Const(Keyword::Nullable),
                 line_number: 2,
+                column_number: 23,
             },
             Token {
                 value: TokenValue::Delimiting(Delimiter::ParenthesisOpening),
                 line_number: 2,
+                column_number: 31,
             },
             Token {
                 value: TokenValue::Type(DataTypeRaw::UInt64),
                 line_number: 2,
+                column_number: 32,
             },
             Token {
                 value: TokenValue::Delimiting(Delimiter::ParenthesisClosing),
                 line_number: 2,
+                column_number: 38,
             },
             Token {
                 value: TokenValue::Delimiting(Delimiter::Comma),
                 line_number: 2,
+                column_number: 39,
             },
             // New line
             Token {
-                value: TokenValue::Arbitrary("hash".to_string()),
+                value: TokenValue::Arbitrary("hash".into()),
                 line_number: 3,
+                column_number: 13,
             },
             Token {
                 value: TokenValue::Type(DataTypeRaw::UInt128),
                 line_number: 3,
+                column_number: 18,
             },
             Token {
                 value: TokenValue::Const(Keyword::Metric),
                 line_number: 3,
+                column_number: 26,
             },
             Token {
                 value: TokenValue::Const(Keyword::Key),
                 line_number: 3,
+                column_number: 33,
             },
             Token {
                 value: TokenValue::Delimiting(Delimiter::Comma),
                 line_number: 3,
+                column_number: 36,
             },
             // New line
             Token {
-                value: TokenValue::Arbitrary("sent_at".to_string()),
+                value: TokenValue::Arbitrary("sent_at".into()),
                 line_number: 4,
+                column_number: 13,
             },
             Token {
                 value: TokenValue::Type(DataTypeRaw::Timestamp),
                 line_number: 4,
+                column_number: 21,
             },
             Token {
                 value: TokenValue::Const(Keyword::Default),
                 line_number: 4,
+                column_number: 31,
             },
             Token {
                 value: TokenValue::Function(Function::Now),
                 line_number: 4,
+                column_number: 39,
             },
             Token {
                 value: TokenValue::Delimiting(Delimiter::ParenthesisOpening),
                 line_number: 4,
+                column_number: 42,
             },
             Token {
                 value: TokenValue::Delimiting(Delimiter::ParenthesisClosing),
                 line_number: 4,
+                column_number: 43,
             },
             // New line
             Token {
                 value: TokenValue::Delimiting(Delimiter::ParenthesisClosing),
                 line_number: 5,
+                column_number: 9,
             },
         ];
         assert_eq!(&detected_tokens, &expected_tokens)
@@ -400,54 +977,67 @@ mod tests {
             Token {
                 value: TokenValue::Const(Keyword::Create),
                 line_number: 1,
+                column_number: 1,
             },
             Token {
                 value: TokenValue::Const(Keyword::Table),
                 line_number: 1,
+                column_number: 8,
             },
             Token {
                 value: TokenValue::Const(Keyword::If),
                 line_number: 1,
+                column_number: 14,
             },
             Token {
                 value: TokenValue::Const(Keyword::Not),
                 line_number: 1,
+                column_number: 17,
             },
             Token {
                 value: TokenValue::Const(Keyword::Exists),
                 line_number: 1,
+                column_number: 21,
             },
             Token {
-                value: TokenValue::Arbitrary("TEST".to_string()),
+                value: TokenValue::Arbitrary("TEST".into()),
                 line_number: 1,
+                column_number: 28,
             },
             Token {
                 value: TokenValue::Delimiting(Delimiter::ParenthesisOpening),
                 line_number: 1,
+                column_number: 33,
             },
             Token {
-                value: TokenValue::Arbitrary("serverId".to_string()),
+                value: TokenValue::Arbitrary("serverId".into()),
                 line_number: 2,
+                column_number: 13,
             },
             Token {
                 value: TokenValue::Const(Keyword::Nullable),
                 line_number: 2,
+                column_number: 22,
             },
             Token {
                 value: TokenValue::Delimiting(Delimiter::ParenthesisOpening),
                 line_number: 2,
+                column_number: 30,
             },
             Token {
                 value: TokenValue::Type(DataTypeRaw::UInt64),
                 line_number: 2,
+                column_number: 31,
             },
             Token {
                 value: TokenValue::Delimiting(Delimiter::ParenthesisClosing),
                 line_number: 2,
+                column_number: 37,
             },
             Token {
                 value: TokenValue::Delimiting(Delimiter::ParenthesisClosing),
                 line_number: 3,
+                column_number: 9,
             },
         ];
         assert_eq!(&detected_tokens, &expected_tokens)
@@ -465,76 +1055,266 @@ mod tests {
             Token {
                 value: TokenValue::Const(Keyword::Insert),
                 line_number: 1,
+                column_number: 1,
             },
             Token {
                 value: TokenValue::Const(Keyword::Into),
                 line_number: 1,
+                column_number: 8,
             },
             Token {
-                value: TokenValue::Arbitrary("test".to_string()),
+                value: TokenValue::Arbitrary("test".into()),
                 line_number: 1,
+                column_number: 13,
             },
             Token {
                 value: TokenValue::Delimiting(Delimiter::ParenthesisOpening),
                 line_number: 2,
+                column_number: 13,
             },
             Token {
-                value: TokenValue::Arbitrary("foo".to_string()),
+                value: TokenValue::Arbitrary("foo".into()),
                 line_number: 2,
+                column_number: 14,
             },
             Token {
                 value: TokenValue::Delimiting(Delimiter::Comma),
                 line_number: 2,
+                column_number: 17,
             },
             Token {
-                value: TokenValue::Arbitrary("bar".to_string()),
+                value: TokenValue::Arbitrary("bar".into()),
                 line_number: 2,
+                column_number: 19,
             },
             Token {
                 value: TokenValue::Delimiting(Delimiter::Comma),
                 line_number: 2,
+                column_number: 22,
             },
             Token {
-                value: TokenValue::Arbitrary("baz".to_string()),
+                value: TokenValue::Arbitrary("baz".into()),
                 line_number: 2,
+                column_number: 24,
             },
             Token {
                 value: TokenValue::Delimiting(Delimiter::ParenthesisClosing),
                 line_number: 2,
+                column_number: 27,
             },
             Token {
                 value: TokenValue::Const(Keyword::Values),
                 line_number: 3,
+                column_number: 13,
             },
             Token {
                 value: TokenValue::Delimiting(Delimiter::ParenthesisOpening),
                 line_number: 3,
+                column_number: 20,
             },
             Token {
-                value: TokenValue::String("123".to_string()),
+                value: TokenValue::String("123".into()),
                 line_number: 3,
+                column_number: 21,
             },
             Token {
                 value: TokenValue::Delimiting(Delimiter::Comma),
                 line_number: 3,
+                column_number: 26,
             },
             Token {
-                value: TokenValue::String("   x ".to_string()),
+                value: TokenValue::String("   x ".into()),
                 line_number: 3,
+                column_number: 28,
             },
             Token {
                 value: TokenValue::Delimiting(Delimiter::Comma),
                 line_number: 3,
+                column_number: 35,
             },
             Token {
-                value: TokenValue::String("The \'Moon\'".to_string()),
+                value: TokenValue::String("The \'Moon\'".into()),
                 line_number: 3,
+                column_number: 37,
             },
             Token {
                 value: TokenValue::Delimiting(Delimiter::ParenthesisClosing),
                 line_number: 3,
+                column_number: 51,
+            },
+        ];
+        assert_eq!(&detected_tokens, &expected_tokens)
+    }
+
+    #[test]
+    fn tokenization_supports_quoted_identifiers() {
+        const STATEMENT: &str = "SELECT \"select\", \"my \"\"quoted\"\" column\" FROM \"Table\"";
+
+        let detected_tokens = tokenize_statement(STATEMENT);
+
+        let expected_tokens = [
+            Token {
+                value: TokenValue::Const(Keyword::Select),
+                line_number: 1,
+                column_number: 1,
+            },
+            Token {
+                value: TokenValue::QuotedIdentifier("select".into()),
+                line_number: 1,
+                column_number: 8,
+            },
+            Token {
+                value: TokenValue::Delimiting(Delimiter::Comma),
+                line_number: 1,
+                column_number: 16,
+            },
+            Token {
+                value: TokenValue::QuotedIdentifier("my \"quoted\" column".into()),
+                line_number: 1,
+                column_number: 18,
+            },
+            Token {
+                value: TokenValue::Const(Keyword::From),
+                line_number: 1,
+                column_number: 41,
+            },
+            Token {
+                value: TokenValue::QuotedIdentifier("Table".into()),
+                line_number: 1,
+                column_number: 46,
             },
         ];
         assert_eq!(&detected_tokens, &expected_tokens)
     }
+
+    #[test]
+    fn tokenization_collects_all_errors() {
+        const STATEMENT: &str = "INSERT INTO test (name, alias)
+            VALUES ('foo,
+            \"bar);";
+
+        assert_eq!(
+            tokenize_collect_errors(STATEMENT),
+            Err(vec![
+                SyntaxError {
+                    message: "Found an unterminated string `'foo,` at line 2.".to_string(),
+                    position: Some(Position {
+                        line: 2,
+                        column: 21
+                    })
+                },
+                SyntaxError {
+                    message: "Found an unterminated quoted identifier `\"bar);` at line 3."
+                        .to_string(),
+                    position: Some(Position {
+                        line: 3,
+                        column: 13
+                    })
+                }
+            ])
+        )
+    }
+
+    #[test]
+    fn tokenization_numbers_parameters() {
+        const STATEMENTS: &str =
+            "SELECT * FROM test WHERE id=?; INSERT INTO test (id, name) VALUES ($3,?);
+            DELETE FROM test WHERE name = \"?\"";
+
+        let parameter_numbers: Vec<usize> = tokenize_statements(STATEMENTS)
+            .into_iter()
+            .flat_map(Result::unwrap)
+            .filter_map(|token| match token.value {
+                TokenValue::Parameter(number) => Some(number),
+                _ => None,
+            })
+            .collect();
+
+        assert_eq!(parameter_numbers, vec![1, 3, 4])
+    }
+
+    #[test]
+    fn tokenization_collects_no_errors_if_valid() {
+        const STATEMENT: &str = "SELECT \"my column\" FROM test WHERE name = 'foo'";
+
+        assert_eq!(
+            tokenize_collect_errors(STATEMENT),
+            Ok(tokenize_statement(STATEMENT))
+        )
+    }
+
+    #[test]
+    fn tokens_borrow_from_input_unless_escaped() {
+        let tokens = tokenize("SELECT \"my col\", 'it\\'s', 'plain', \"a\"\"b\" FROM tbl").unwrap();
+        let contents: Vec<(&str, bool)> = tokens
+            .iter()
+            .filter_map(|token| match &token.value {
+                TokenValue::QuotedIdentifier(value)
+                | TokenValue::String(value)
+                | TokenValue::Arbitrary(value) => {
+                    Some((value.as_ref(), matches!(value, Cow::Borrowed(_))))
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            contents,
+            vec![
+                ("my col", true),
+                ("it's", false),
+                ("plain", true),
+                ("a\"b", false),
+                ("tbl", true)
+            ]
+        );
+    }
+
+    #[test]
+    fn tokenization_spans_statements_and_fails_on_first_error() {
+        assert_eq!(
+            tokenize("SHOW TABLES; SHOW TABLES").map(|tokens| tokens.len()),
+            Ok(4)
+        );
+        assert_eq!(
+            tokenize("SHOW TABLES;\nSELECT 'foo\nSELECT \"bar"),
+            Err(SyntaxError {
+                message: "Found an unterminated string `'foo` at line 2.".into(),
+                position: Some(Position { line: 2, column: 8 }),
+            })
+        );
+    }
+
+    #[test]
+    fn tokenization_skips_comments() {
+        let detected_values: Vec<TokenValue> =
+            tokenize("SELECT a-- the rest; is ignored\n  FROM '--not a comment' -- ;")
+                .unwrap()
+                .into_iter()
+                .map(|token| token.value)
+                .collect();
+        assert_eq!(
+            detected_values,
+            [
+                TokenValue::Const(Keyword::Select),
+                TokenValue::Arbitrary("a".into()),
+                TokenValue::Const(Keyword::From),
+                TokenValue::String("--not a comment".into()),
+            ]
+        );
+        assert!(tokenize_statements("-- only a comment;\n  ").is_empty());
+    }
+
+    #[test]
+    fn tokenization_stops_past_token_limit() {
+        let input = format!(
+            "SELECT 1;\nSELECT a, b{} 'unterminated",
+            ", c".repeat(100_000)
+        );
+        let statements = tokenize_statements_with_limit(&input, Dialect::Strict, 4);
+        assert_eq!(statements.len(), 2);
+        let tokens = statements[1].as_ref().unwrap();
+        // The first token past the limit (the fifth) is kept, but hardly any others
+        assert_eq!(tokens[2].value, TokenValue::Delimiting(Delimiter::Comma));
+        assert!(tokens.len() < 8);
+    }
 }
@@ -0,0 +1,339 @@
+//! Persistence of table definitions, so that the catalog survives restarts.
+//! Rows live in table files already, so only definitions are stored here.
+
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use tokio::fs;
+use uuid::Uuid;
+
+use crate::config;
+use crate::constructs::components::{
+    ColumnDefinition, DataDefinition, DataInstance, DataInstanceRaw, DataType, TableDefinition,
+};
+
+/// Version of the catalog file format. Bump it on any change to the format, migrating older files on load.
+pub const CATALOG_FORMAT_VERSION: u32 = 2;
+
+const CATALOG_FILE_NAME: &str = "catalog.json";
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CatalogFile {
+    version: u32,
+    tables: Vec<TableRecord>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct TableRecord {
+    name: String,
+    columns: Vec<ColumnRecord>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ColumnRecord {
+    name: String,
+    data_type: String,
+    is_nullable: bool,
+    primary_key: bool,
+    unique: bool,
+    default: Option<DefaultRecord>,
+    /// Added in version 2, so missing from catalogs of version 1.
+    #[serde(default)]
+    comment: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum DefaultRecord {
+    Const(ValueRecord),
+    FunctionCall(String),
+}
+
+/// Self-describing counterpart of `DataInstance`, as its own serialization is untagged.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(super) enum ValueRecord {
+    Null,
+    UInt8(u8),
+    UInt16(u16),
+    UInt32(u32),
+    UInt64(u64),
+    UInt128(u128),
+    Bool(bool),
+    Timestamp(OffsetDateTime),
+    Uuid(Uuid),
+    String(String),
+}
+
+impl From<&DataInstance> for ValueRecord {
+    fn from(value: &DataInstance) -> Self {
+        match value.as_raw() {
+            None => Self::Null,
+            Some(DataInstanceRaw::UInt8(value)) => Self::UInt8(*value),
+            Some(DataInstanceRaw::UInt16(value)) => Self::UInt16(*value),
+            Some(DataInstanceRaw::UInt32(value)) => Self::UInt32(*value),
+            Some(DataInstanceRaw::UInt64(value)) => Self::UInt64(*value),
+            Some(DataInstanceRaw::UInt128(value)) => Self::UInt128(*value),
+            Some(DataInstanceRaw::Bool(value)) => Self::Bool(*value),
+            Some(DataInstanceRaw::Timestamp(value)) => Self::Timestamp(*value),
+            Some(DataInstanceRaw::Uuid(value)) => Self::Uuid(*value),
+            Some(DataInstanceRaw::String(value)) => Self::String(value.clone()),
+        }
+    }
+}
+
+impl From<ValueRecord> for DataInstance {
+    fn from(value: ValueRecord) -> Self {
+        // Nullability is restored by coercion to the column's type
+        DataInstance::Direct(match value {
+            ValueRecord::Null => return DataInstance::Null,
+            ValueRecord::UInt8(value) => DataInstanceRaw::UInt8(value),
+            ValueRecord::UInt16(value) => DataInstanceRaw::UInt16(value),
+            ValueRecord::UInt32(value) => DataInstanceRaw::UInt32(value),
+            ValueRecord::UInt64(value) => DataInstanceRaw::UInt64(value),
+            ValueRecord::UInt128(value) => DataInstanceRaw::UInt128(value),
+            ValueRecord::Bool(value) => DataInstanceRaw::Bool(value),
+            ValueRecord::Timestamp(value) => DataInstanceRaw::Timestamp(value),
+            ValueRecord::Uuid(value) => DataInstanceRaw::Uuid(value),
+            ValueRecord::String(value) => DataInstanceRaw::String(value),
+        })
+    }
+}
+
+impl From<&TableDefinition> for TableRecord {
+    fn from(table: &TableDefinition) -> Self {
+        TableRecord {
+            name: table.name.clone(),
+            columns: table
+                .columns
+                .iter()
+                .map(|column| ColumnRecord {
+                    name: column.name.clone(),
+                    data_type: column.data_type.raw_type.name().to_string(),
+                    is_nullable: column.data_type.is_nullable,
+                    primary_key: column.primary_key,
+                    unique: column.unique,
+                    default: column.default.as_ref().map(|default| match default {
+                        DataDefinition::Const(value) => DefaultRecord::Const(value.into()),
+                        DataDefinition::FunctionCall(function) => {
+                            DefaultRecord::FunctionCall(function.name().to_string())
+                        }
+                        DataDefinition::Identifier(_)
+                        | DataDefinition::QualifiedIdentifier { .. }
+                        | DataDefinition::Parameter(_) => {
+                            unreachable!("Identifier and parameter defaults are rejected")
+                        }
+                    }),
+                    comment: column.comment.clone(),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl TryFrom<TableRecord> for TableDefinition {
+    type Error = String;
+
+    fn try_from(table: TableRecord) -> Result<Self, Self::Error> {
+        let columns = table
+            .columns
+            .into_iter()
+            .map(|column| {
+                let data_type = DataType {
+                    raw_type: column.data_type.parse()?,
+                    is_nullable: column.is_nullable,
+                };
+                let default = match column.default {
+                    None => None,
+                    Some(DefaultRecord::Const(value)) => Some(DataDefinition::Const(
+                        DataInstance::from(value)
+                            .coerce(&data_type)
+                            .map_err(|error| error.0)?,
+                    )),
+                    Some(DefaultRecord::FunctionCall(function)) => {
+                        Some(DataDefinition::FunctionCall(function.parse()?))
+                    }
+                };
+                Ok(ColumnDefinition {
+                    name: column.name,
+                    data_type,
+                    primary_key: column.primary_key,
+                    unique: column.unique,
+                    default,
+                    comment: column.comment,
+                })
+            })
+            .collect::<Result<_, String>>()?;
+        TableDefinition::new(table.name, columns).map_err(|error| error.0)
+    }
+}
+
+fn determine_catalog_path(config: &config::Config) -> PathBuf {
+    Path::new(&config.data_directory).join(CATALOG_FILE_NAME) // <$EMDRIVE_DATA_DIRECTORY>/catalog.json
+}
+
+fn corrupt_catalog_error(path: &Path, problem: impl std::fmt::Display) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("Catalog file {} is corrupt: {}", path.display(), problem),
+    )
+}
+
+/// Load table definitions saved with `save_catalog`. No catalog file means no tables yet.
+pub async fn load_catalog(config: &config::Config) -> io::Result<Vec<TableDefinition>> {
+    let path = determine_catalog_path(config);
+    let data = match fs::read(&path).await {
+        Ok(data) => data,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(error) => return Err(error),
+    };
+    let catalog: CatalogFile =
+        serde_json::from_slice(&data).map_err(|error| corrupt_catalog_error(&path, error))?;
+    // Version 1 differs only by lacking column comments, which deserialize as none
+    if !(1..=CATALOG_FORMAT_VERSION).contains(&catalog.version) {
+        return Err(corrupt_catalog_error(
+            &path,
+            format!(
+                "format version {} is not supported (expected 1 to {})",
+                catalog.version, CATALOG_FORMAT_VERSION
+            ),
+        ));
+    }
+    catalog
+        .tables
+        .into_iter()
+        .map(TableDefinition::try_from)
+        .collect::<Result<_, _>>()
+        .map_err(|error| corrupt_catalog_error(&path, error))
+}
+
+/// Save table definitions, replacing the previous catalog file atomically.
+pub async fn save_catalog(config: &config::Config, tables: &[TableDefinition]) -> io::Result<()> {
+    let path = determine_catalog_path(config);
+    let catalog = CatalogFile {
+        version: CATALOG_FORMAT_VERSION,
+        tables: tables.iter().map(TableRecord::from).collect(),
+    };
+    let data = serde_json::to_vec_pretty(&catalog)?;
+    fs::create_dir_all(&config.data_directory).await?;
+    // Writing to a temporary file first, so that a crash mid-write leaves the previous catalog intact
+    let temporary_path = path.with_extension("json.tmp");
+    fs::write(&temporary_path, data).await?;
+    fs::rename(&temporary_path, &path).await
+}
+
+#[cfg(test)]
+mod catalog_tests {
+    use super::*;
+    use crate::constructs::components::DataTypeRaw;
+    use crate::constructs::functions::Function;
+    use pretty_assertions::assert_eq;
+    use rand::distributions::Alphanumeric;
+    use rand::{thread_rng, Rng};
+
+    fn get_test_config() -> config::Config {
+        let data_directory_name: String = thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(10)
+            .map(char::from)
+            .collect();
+        config::Config {
+            data_directory: Path::new(env!("TMPDIR"))
+                .join(data_directory_name)
+                .to_string_lossy()
+                .into(),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn catalog_round_trip_works() {
+        let config = get_test_config();
+        let tables = vec![TableDefinition::new(
+            "test".into(),
+            vec![
+                ColumnDefinition {
+                    name: "id".into(),
+                    data_type: DataType {
+                        raw_type: DataTypeRaw::Uuid,
+                        is_nullable: false,
+                    },
+                    primary_key: true,
+                    unique: false,
+                    default: Some(DataDefinition::FunctionCall(Function::Ulid)),
+                    comment: Some("Sortable, thanks to ULID".into()),
+                },
+                ColumnDefinition {
+                    name: "size".into(),
+                    data_type: DataType {
+                        raw_type: DataTypeRaw::UInt16,
+                        is_nullable: true,
+                    },
+                    primary_key: false,
+                    unique: true,
+                    default: Some(DataDefinition::Const(DataInstance::Nullable(
+                        DataInstanceRaw::UInt16(42),
+                    ))),
+                    comment: None,
+                },
+            ],
+        )
+        .unwrap()];
+        save_catalog(&config, &tables).await.unwrap();
+        assert_eq!(load_catalog(&config).await.unwrap(), tables);
+    }
+
+    #[tokio::test]
+    async fn catalog_of_version_1_loads_without_comments() {
+        let config = get_test_config();
+        fs::create_dir_all(&config.data_directory).await.unwrap();
+        fs::write(
+            determine_catalog_path(&config),
+            r#"{"version":1,"tables":[{"name":"test","columns":[{"name":"id","data_type":"UINT8","is_nullable":false,"primary_key":true,"unique":false,"default":null}]}]}"#,
+        )
+        .await
+        .unwrap();
+        let tables = load_catalog(&config).await.unwrap();
+        assert_eq!(tables[0].columns[0].name, "id");
+        assert_eq!(tables[0].columns[0].comment, None);
+    }
+
+    #[tokio::test]
+    async fn missing_catalog_means_no_tables() {
+        assert_eq!(load_catalog(&get_test_config()).await.unwrap(), vec![]);
+    }
+
+    #[tokio::test]
+    async fn corrupt_catalog_fails_to_load() {
+        let config = get_test_config();
+        fs::create_dir_all(&config.data_directory).await.unwrap();
+        let path = determine_catalog_path(&config);
+        fs::write(&path, r#"{"version":1,"tables":[{"name":"#)
+            .await
+            .unwrap();
+        let error = load_catalog(&config).await.unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert!(error
+            .to_string()
+            .starts_with(&format!("Catalog file {} is corrupt: ", path.display())));
+    }
+
+    #[tokio::test]
+    async fn catalog_of_unknown_version_fails_to_load() {
+        let config = get_test_config();
+        fs::create_dir_all(&config.data_directory).await.unwrap();
+        let path = determine_catalog_path(&config);
+        fs::write(&path, r#"{"version":999,"tables":[]}"#)
+            .await
+            .unwrap();
+        assert_eq!(
+            load_catalog(&config).await.unwrap_err().to_string(),
+            format!(
+                "Catalog file {} is corrupt: format version 999 is not supported (expected 1 to 2)",
+                path.display()
+            )
+        );
+    }
+}
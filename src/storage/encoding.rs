@@ -1,4 +1,6 @@
-use crate::constructs::components::{DataInstance, DataInstanceRaw, DataType, DataTypeRaw};
+use crate::constructs::components::{
+    timestamp_micros, DataInstance, DataInstanceRaw, DataType, DataTypeRaw,
+};
 use serde::{ser::SerializeMap, Serialize, Serializer};
 use std::{
     convert::{From, TryFrom},
@@ -141,10 +143,11 @@ impl Encodable for String {
     }
 }
 
+// Timestamps are stored as microseconds since Unix epoch.
 impl Encodable for OffsetDateTime {
     fn try_decode(blob: ReadBlob) -> Result<(Self, ReadBlob), String> {
-        let (unix_timestamp_raw, rest) = i64::try_decode(blob)?;
-        match Self::from_unix_timestamp(unix_timestamp_raw) {
+        let (unix_timestamp_micros, rest) = i64::try_decode(blob)?;
+        match Self::from_unix_timestamp_nanos(i128::from(unix_timestamp_micros) * 1000) {
             Ok(unix_timestamp) => Ok((unix_timestamp, rest)),
             Err(err) => Err(err.to_string()),
         }
@@ -154,7 +157,7 @@ impl Encodable for OffsetDateTime {
         let advanced_position = position + self.encoded_size();
         blob.splice(
             position..advanced_position,
-            self.unix_timestamp().to_be_bytes(),
+            timestamp_micros(self).to_be_bytes(),
         );
         advanced_position
     }
@@ -306,10 +309,10 @@ impl<'b> EncodableWithAssumption<'b> for DataInstance {
         if assumption.is_nullable {
             let (null_marker, rest) = bool::try_decode(blob)?;
             if null_marker {
+                Ok((DataInstance::Null, rest))
+            } else {
                 let (value, rest) = DataInstanceRaw::try_decode_assume(rest, assumption.raw_type)?;
                 Ok((DataInstance::Nullable(value), rest))
-            } else {
-                Ok((DataInstance::Null, rest))
             }
         } else {
             let (value, rest) = DataInstanceRaw::try_decode_assume(blob, assumption.raw_type)?;
@@ -322,7 +325,7 @@ impl<'b> EncodableWithAssumption<'b> for DataInstance {
 pub struct Row(pub Vec<DataInstance>);
 
 #[derive(Debug)]
-pub struct NamedRow<'a>(pub &'a Vec<String>, pub &'a Vec<DataInstance>);
+pub struct NamedRow<'a>(pub &'a [String], pub &'a [DataInstance]);
 
 impl Serialize for NamedRow<'_> {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
@@ -377,7 +380,9 @@ mod encoding_tests {
 
     #[test]
     fn timestamp_encoding() {
-        let timestamp = OffsetDateTime::from_unix_timestamp(1_546_300_800).unwrap();
+        // With microsecond precision, which is what's stored
+        let timestamp =
+            OffsetDateTime::from_unix_timestamp_nanos(1_546_300_800_123_456_000).unwrap();
         let mut blob: WriteBlob = vec![0; timestamp.encoded_size()];
         let position = timestamp.encode(&mut blob, 0);
         assert_eq!(position, timestamp.encoded_size());
@@ -396,4 +401,23 @@ mod encoding_tests {
         assert_eq!(decoded_uuid, uuid);
         assert_eq!(rest.len(), 0);
     }
+
+    #[test]
+    fn nullable_encoding() {
+        let data_type = DataType {
+            raw_type: DataTypeRaw::UInt16,
+            is_nullable: true,
+        };
+        for instance in [
+            DataInstance::Null,
+            DataInstance::Nullable(DataInstanceRaw::UInt16(7)),
+        ] {
+            let mut blob: WriteBlob = vec![0; instance.encoded_size()];
+            instance.encode(&mut blob, 0);
+            let (decoded_instance, rest) =
+                DataInstance::try_decode_assume(&blob, &data_type).unwrap();
+            assert_eq!(decoded_instance, instance);
+            assert_eq!(rest.len(), 0);
+        }
+    }
 }
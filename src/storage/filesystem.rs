@@ -77,6 +77,7 @@ pub async fn seek_write_page(
     let mut file = fs::OpenOptions::new()
         .write(true)
         .create(true)
+        .truncate(false)
         .open(path)
         .await?;
     file.seek(SeekFrom::Start(page_index as u64 * PAGE_SIZE as u64))
@@ -85,6 +86,16 @@ pub async fn seek_write_page(
     Ok(())
 }
 
+/// Make sure that everything written to the table's file is actually on disk.
+pub async fn sync_table_file(
+    config: &config::Config,
+    schema: &str,
+    table_name: &str,
+) -> Result<(), std::io::Error> {
+    let path = determine_table_dir_path(config, schema, table_name).join("0");
+    fs::File::open(path).await?.sync_all().await
+}
+
 #[cfg(test)]
 mod filesystem_tests {
     use super::*;
@@ -110,7 +121,9 @@ mod filesystem_tests {
                         is_nullable: false,
                     },
                     primary_key: true,
+                    unique: false,
                     default: None,
+                    comment: None,
                 },
                 ColumnDefinition {
                     name: "table_name".into(),
@@ -119,10 +132,13 @@ mod filesystem_tests {
                         is_nullable: false,
                     },
                     primary_key: false,
+                    unique: false,
                     default: None,
+                    comment: None,
                 },
             ],
         )
+        .unwrap()
     }
 
     #[tokio::test]
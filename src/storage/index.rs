@@ -9,12 +9,14 @@ use tracing::*;
 
 // Massively work-in-progress!
 
+#[allow(dead_code)]
 pub struct Index<'a> {
     collection_name: String,
     data: Vec<u128>,
     config: &'a Config,
 }
 
+#[allow(dead_code)]
 impl<'a> Index<'a> {
     pub fn new(collection_name: &str, config: &'a Config) -> Self {
         let mut index = Index {
@@ -52,7 +54,7 @@ impl<'a> Index<'a> {
     fn parse_index_raw_data(&self, raw_data: Vec<u8>) -> Vec<u128> {
         let entry_size = mem::size_of::<u128>();
         let raw_data_size = raw_data.len();
-        if raw_data_size % entry_size != 0 {
+        if !raw_data_size.is_multiple_of(entry_size) {
             panic!("Size of index data for collection `{}` must be a multiple of {} bytes, instead found {} bytes!", &self.collection_name, entry_size, raw_data_size);
         }
         raw_data
@@ -94,7 +96,7 @@ impl<'a> Index<'a> {
             .data
             .iter()
             .flat_map(|x| {
-                let bytes: Vec<u8> = x.to_be_bytes().iter().copied().collect();
+                let bytes: Vec<u8> = x.to_be_bytes().to_vec();
                 bytes
             })
             .collect();
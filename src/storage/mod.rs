@@ -1,7 +1,9 @@
+pub mod catalog;
 pub mod encoding;
 pub mod filesystem;
 mod index;
 pub mod paging;
 pub mod system;
+pub mod wal;
 
 pub use encoding::{NamedRow, Row};
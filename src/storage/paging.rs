@@ -35,6 +35,47 @@ pub fn construct_blank_table() -> WriteBlob {
     core_blob
 }
 
+/// Whether a B+ tree leaf with these rows can be encoded into a single page.
+pub fn does_leaf_fit(rows: &[Row]) -> bool {
+    // 1. page type marker, 2. next leaf page index, 3. row count
+    let metadata_size = 1 + std::mem::size_of::<PageIndex>() + std::mem::size_of::<LocalCount>();
+    let rows_size: usize = rows
+        .iter()
+        .map(|row| std::mem::size_of::<LocalCount>() + row.encoded_size())
+        .sum();
+    metadata_size + rows_size <= PAGE_SIZE
+}
+
+/// Decode only the row at the position within a B+ tree leaf page, without the leaf's other rows.
+/// `None` means that the leaf doesn't have that many rows.
+pub fn decode_leaf_row(
+    blob: ReadBlob,
+    table_definition: &TableDefinition,
+    position: usize,
+) -> Result<Option<Row>, String> {
+    if blob[0] != 0x21 {
+        return Err(format!(
+            "Invalid page type marker byte {:#04x} - expected a B+ tree leaf (0x21)",
+            blob[0]
+        ));
+    }
+    let (_next_leaf_page_index, rest) = PageIndex::try_decode(&blob[1..])?;
+    let (row_count, rest) = LocalCount::try_decode(rest)?;
+    if position >= row_count as usize {
+        return Ok(None);
+    }
+    // Row addresses follow the row count, in row order
+    let (row_address, _rest) =
+        LocalCount::try_decode(&rest[position * std::mem::size_of::<LocalCount>()..])?;
+    let row_data_types: Vec<_> = table_definition
+        .columns
+        .iter()
+        .map(|column| &column.data_type)
+        .collect();
+    let (row, _rest_back) = Row::try_decode_assume(&blob[row_address as usize..], &row_data_types)?;
+    Ok(Some(row))
+}
+
 /// Possible core page types.
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub enum Page {
@@ -47,8 +88,8 @@ pub enum Page {
     },
     /// B+ tree node.
     BTreeNode {
-        /// N primary keys.
-        primary_keys: Vec<DataInstanceRaw>,
+        /// N primary keys, each being values of the PRIMARY KEY columns in column order.
+        primary_keys: Vec<Vec<DataInstanceRaw>>,
         /// N+1 pointers to child pages.
         child_page_indexes: Vec<PageIndex>,
     },
@@ -89,7 +130,9 @@ impl From<Page> for WriteBlob {
                 // 3. Primary keys
                 assert_eq!(primary_keys.len(), child_page_indexes.len() - 1);
                 for primary_key in primary_keys {
-                    position = primary_key.encode(&mut page_blob, position);
+                    for value in primary_key {
+                        position = value.encode(&mut page_blob, position);
+                    }
                 }
                 // 4. Child page indexes
                 for child_page_index in child_page_indexes {
@@ -153,13 +196,17 @@ impl<'b> EncodableWithAssumption<'b> for Page {
             0x20 => {
                 let (arity, rest) = LocalCount::try_decode(&blob[1..])?;
                 let mut rest = rest;
-                let mut primary_keys: Vec<DataInstanceRaw> = Vec::with_capacity(arity as usize - 1);
+                let primary_key_columns = assumption.get_primary_key_columns();
+                let mut primary_keys: Vec<Vec<DataInstanceRaw>> =
+                    Vec::with_capacity(arity as usize - 1);
                 for _ in 0..(arity as usize - 1) {
-                    let (primary_key, iteration_rest) = DataInstanceRaw::try_decode_assume(
-                        rest,
-                        assumption.get_primary_key().data_type.raw_type,
-                    )?;
-                    rest = iteration_rest;
+                    let mut primary_key = Vec::with_capacity(primary_key_columns.len());
+                    for column in &primary_key_columns {
+                        let (value, iteration_rest) =
+                            DataInstanceRaw::try_decode_assume(rest, column.data_type.raw_type)?;
+                        rest = iteration_rest;
+                        primary_key.push(value);
+                    }
                     primary_keys.push(primary_key);
                 }
                 let mut child_page_indexes: Vec<PageIndex> = Vec::with_capacity(arity as usize);
@@ -232,7 +279,9 @@ mod core_serialization_tests {
                         is_nullable: false,
                     },
                     primary_key: true,
+                    unique: false,
                     default: None,
+                    comment: None,
                 },
                 ColumnDefinition {
                     name: "table_name".into(),
@@ -241,10 +290,13 @@ mod core_serialization_tests {
                         is_nullable: false,
                     },
                     primary_key: false,
+                    unique: false,
                     default: None,
+                    comment: None,
                 },
             ],
         )
+        .unwrap()
     }
 
     #[test]
@@ -353,10 +405,42 @@ mod core_serialization_tests {
         );
     }
 
+    #[test]
+    fn single_leaf_row_is_decoded() {
+        let rows = vec![
+            Row(vec![
+                DataInstance::Direct(DataInstanceRaw::Uuid(Uuid::from_u128(1))),
+                DataInstance::Direct(DataInstanceRaw::String("Foo 🧐".into())),
+            ]),
+            Row(vec![
+                DataInstance::Direct(DataInstanceRaw::Uuid(Uuid::from_u128(2))),
+                DataInstance::Direct(DataInstanceRaw::String("Здравствуйте".into())),
+            ]),
+        ];
+        let leaf_blob: WriteBlob = Page::BTreeLeaf {
+            next_leaf_page_index: 0,
+            rows: rows.clone(),
+        }
+        .into();
+        let table_definition = get_test_table();
+        assert_eq!(
+            decode_leaf_row(&leaf_blob, &table_definition, 1).unwrap(),
+            Some(rows[1].clone())
+        );
+        assert_eq!(
+            decode_leaf_row(&leaf_blob, &table_definition, 0).unwrap(),
+            Some(rows[0].clone())
+        );
+        assert_eq!(
+            decode_leaf_row(&leaf_blob, &table_definition, 2).unwrap(),
+            None
+        );
+    }
+
     #[test]
     fn node_de_serialization_works() {
         let leaf_blob: WriteBlob = Page::BTreeNode {
-            primary_keys: vec![DataInstanceRaw::Uuid(Uuid::from_u128(123))],
+            primary_keys: vec![vec![DataInstanceRaw::Uuid(Uuid::from_u128(123))]],
             child_page_indexes: vec![3u32, 4u32],
         }
         .into();
@@ -364,7 +448,7 @@ mod core_serialization_tests {
         assert_eq!(
             leaf_page,
             Page::BTreeNode {
-                primary_keys: vec![DataInstanceRaw::Uuid(Uuid::from_u128(123))],
+                primary_keys: vec![vec![DataInstanceRaw::Uuid(Uuid::from_u128(123))]],
                 child_page_indexes: vec![3u32, 4u32],
             }
         );
@@ -23,7 +23,9 @@ impl SystemTable {
                             is_nullable: false,
                         },
                         primary_key: true,
+                        unique: false,
                         default: None,
+                        comment: None,
                     },
                     ColumnDefinition {
                         name: "schema_name".into(),
@@ -32,7 +34,9 @@ impl SystemTable {
                             is_nullable: false,
                         },
                         primary_key: false,
+                        unique: false,
                         default: None,
+                        comment: None,
                     },
                     ColumnDefinition {
                         name: "table_name".into(),
@@ -41,7 +45,9 @@ impl SystemTable {
                             is_nullable: false,
                         },
                         primary_key: false,
+                        unique: false,
                         default: None,
+                        comment: None,
                     },
                 ],
             ),
@@ -55,7 +61,9 @@ impl SystemTable {
                             is_nullable: false,
                         },
                         primary_key: true,
+                        unique: false,
                         default: None,
+                        comment: None,
                     },
                     ColumnDefinition {
                         name: "table_id".into(),
@@ -64,7 +72,9 @@ impl SystemTable {
                             is_nullable: false,
                         },
                         primary_key: false,
+                        unique: false,
                         default: None,
+                        comment: None,
                     },
                     ColumnDefinition {
                         name: "raw_type".into(),
@@ -73,7 +83,9 @@ impl SystemTable {
                             is_nullable: false,
                         },
                         primary_key: false,
+                        unique: false,
                         default: None,
+                        comment: None,
                     },
                     ColumnDefinition {
                         name: "is_nullable".into(),
@@ -82,10 +94,13 @@ impl SystemTable {
                             is_nullable: false,
                         },
                         primary_key: false,
+                        unique: false,
                         default: None,
+                        comment: None,
                     },
                 ],
             ),
         }
+        .expect("A system table must have exactly 1 PRIMARY KEY column")
     }
 }
@@ -0,0 +1,320 @@
+//! Write-ahead log of INSERTs, so that they survive a crash before table files are flushed to disk.
+//! Each record is a line of JSON. The log is emptied at checkpoints, once table files are synced.
+
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+use tokio::io::AsyncWriteExt;
+
+use super::catalog::ValueRecord;
+use super::Row;
+use crate::config::{self, WalSyncMode};
+use crate::constructs::components::{DataInstance, TableDefinition};
+
+/// Row inserted into a table.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WalRecord {
+    table_name: String,
+    values: Vec<ValueRecord>,
+}
+
+impl WalRecord {
+    pub fn new(table_name: &str, row: &Row) -> Self {
+        WalRecord {
+            table_name: table_name.to_string(),
+            values: row.0.iter().map(ValueRecord::from).collect(),
+        }
+    }
+
+    pub fn table_name(&self) -> &str {
+        &self.table_name
+    }
+
+    /// Restore the logged row, coercing values to the table's column types.
+    pub fn into_row(self, table_definition: &TableDefinition) -> Result<Row, String> {
+        if self.values.len() != table_definition.columns.len() {
+            return Err(format!(
+                "Logged row has {} values, but table `{}` has {} columns",
+                self.values.len(),
+                table_definition.name,
+                table_definition.columns.len()
+            ));
+        }
+        self.values
+            .into_iter()
+            .zip(&table_definition.columns)
+            .map(|(value, column)| {
+                DataInstance::from(value)
+                    .coerce(&column.data_type)
+                    .map_err(|error| error.0)
+            })
+            .collect::<Result<_, _>>()
+            .map(Row)
+    }
+}
+
+pub struct WriteAheadLog {
+    path: PathBuf,
+    file: fs::File,
+    sync_mode: WalSyncMode,
+    sync_interval: Duration,
+    last_synced_at: Instant,
+}
+
+impl WriteAheadLog {
+    /// Open the log at the configured path for appending, creating it if needed.
+    pub async fn open(config: &config::Config, path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).await?;
+        }
+        let file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .await?;
+        Ok(WriteAheadLog {
+            path: path.to_path_buf(),
+            file,
+            sync_mode: config.wal_sync_mode,
+            sync_interval: Duration::from_millis(config.wal_sync_interval_ms),
+            last_synced_at: Instant::now(),
+        })
+    }
+
+    /// Read all records of the log at the path. No log file means no records.
+    /// A partially written last record (from a crash mid-write) is ignored, while any other damage is an error.
+    pub async fn read_records(path: impl AsRef<Path>) -> io::Result<Vec<WalRecord>> {
+        let path = path.as_ref();
+        let data = match fs::read_to_string(path).await {
+            Ok(data) => data,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(error) => return Err(error),
+        };
+        // Every complete record ends with a newline, so anything after the last one is a torn write
+        let complete_part = match data.rfind('\n') {
+            Some(last_newline_index) => &data[..last_newline_index],
+            None => "",
+        };
+        complete_part
+            .lines()
+            .enumerate()
+            .map(|(line_index, line)| {
+                serde_json::from_str(line).map_err(|error| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!(
+                            "Write-ahead log {} is corrupt at line {}: {}",
+                            path.display(),
+                            line_index + 1,
+                            error
+                        ),
+                    )
+                })
+            })
+            .collect()
+    }
+
+    /// Append the record, flushing it to disk according to the sync mode.
+    /// Returns the length of the log before the record, from which the record can be discarded.
+    pub async fn append(&mut self, record: &WalRecord) -> io::Result<u64> {
+        let mut line = serde_json::to_vec(record)?;
+        line.push(b'\n');
+        let length_before = self.file.metadata().await?.len();
+        if let Err(error) = self.file.write_all(&line).await {
+            // A partially written record would make the records appended after it unreadable
+            let _ = self.file.set_len(length_before).await;
+            return Err(error);
+        }
+        let is_sync_due = match self.sync_mode {
+            WalSyncMode::Always => true,
+            WalSyncMode::Periodic => self.last_synced_at.elapsed() >= self.sync_interval,
+        };
+        if is_sync_due {
+            self.file.sync_data().await?;
+            self.last_synced_at = Instant::now();
+        }
+        Ok(length_before)
+    }
+
+    /// Discard records appended since the log had the given length, e.g. the record of a write that failed.
+    pub async fn discard_from(&mut self, length: u64) -> io::Result<()> {
+        self.file.set_len(length).await?;
+        self.file.sync_data().await?;
+        self.last_synced_at = Instant::now();
+        Ok(())
+    }
+
+    /// Flush records appended since the last sync to disk, e.g. before shutting down in `periodic` sync mode.
+    pub async fn sync(&mut self) -> io::Result<()> {
+        self.file.sync_data().await?;
+        self.last_synced_at = Instant::now();
+        Ok(())
+    }
+
+    /// Discard all records. Only to be done once their effects are safely on disk.
+    pub async fn truncate(&mut self) -> io::Result<()> {
+        self.file.set_len(0).await?;
+        self.file.sync_all().await?;
+        self.last_synced_at = Instant::now();
+        Ok(())
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+#[cfg(test)]
+mod wal_tests {
+    use super::*;
+    use crate::constructs::components::{ColumnDefinition, DataInstanceRaw, DataType, DataTypeRaw};
+    use pretty_assertions::assert_eq;
+    use rand::distributions::Alphanumeric;
+    use rand::{thread_rng, Rng};
+
+    fn get_test_path() -> PathBuf {
+        let file_name: String = thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(10)
+            .map(char::from)
+            .collect();
+        Path::new(env!("TMPDIR")).join(format!("{}.wal", file_name))
+    }
+
+    fn get_test_table() -> TableDefinition {
+        TableDefinition::new(
+            "test".into(),
+            vec![
+                ColumnDefinition {
+                    name: "id".into(),
+                    data_type: DataType {
+                        raw_type: DataTypeRaw::UInt8,
+                        is_nullable: false,
+                    },
+                    primary_key: true,
+                    unique: false,
+                    default: None,
+                    comment: None,
+                },
+                ColumnDefinition {
+                    name: "note".into(),
+                    data_type: DataType {
+                        raw_type: DataTypeRaw::String,
+                        is_nullable: true,
+                    },
+                    primary_key: false,
+                    unique: false,
+                    default: None,
+                    comment: None,
+                },
+            ],
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn records_round_trip() {
+        let path = get_test_path();
+        let rows = vec![
+            Row(vec![
+                DataInstance::Direct(DataInstanceRaw::UInt8(1)),
+                DataInstance::Nullable(DataInstanceRaw::String("x".into())),
+            ]),
+            Row(vec![
+                DataInstance::Direct(DataInstanceRaw::UInt8(2)),
+                DataInstance::Null,
+            ]),
+        ];
+        let mut wal = WriteAheadLog::open(&config::Config::default(), &path)
+            .await
+            .unwrap();
+        for row in &rows {
+            wal.append(&WalRecord::new("test", row)).await.unwrap();
+        }
+        let table_definition = get_test_table();
+        let restored_rows: Vec<Row> = WriteAheadLog::read_records(&path)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|record| record.into_row(&table_definition).unwrap())
+            .collect();
+        assert_eq!(restored_rows, rows);
+        wal.truncate().await.unwrap();
+        assert_eq!(WriteAheadLog::read_records(&path).await.unwrap().len(), 0);
+    }
+
+    #[tokio::test]
+    async fn appended_record_can_be_discarded() {
+        let path = get_test_path();
+        let mut wal = WriteAheadLog::open(&config::Config::default(), &path)
+            .await
+            .unwrap();
+        let kept_row = Row(vec![
+            DataInstance::Direct(DataInstanceRaw::UInt8(1)),
+            DataInstance::Null,
+        ]);
+        let discarded_row = Row(vec![
+            DataInstance::Direct(DataInstanceRaw::UInt8(2)),
+            DataInstance::Null,
+        ]);
+        assert_eq!(
+            wal.append(&WalRecord::new("test", &kept_row))
+                .await
+                .unwrap(),
+            0
+        );
+        let length_before = wal
+            .append(&WalRecord::new("test", &discarded_row))
+            .await
+            .unwrap();
+        wal.discard_from(length_before).await.unwrap();
+        let table_definition = get_test_table();
+        let restored_rows: Vec<Row> = WriteAheadLog::read_records(&path)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|record| record.into_row(&table_definition).unwrap())
+            .collect();
+        assert_eq!(restored_rows, vec![kept_row]);
+    }
+
+    #[tokio::test]
+    async fn torn_last_record_is_ignored() {
+        let path = get_test_path();
+        let mut wal = WriteAheadLog::open(&config::Config::default(), &path)
+            .await
+            .unwrap();
+        wal.append(&WalRecord::new(
+            "test",
+            &Row(vec![
+                DataInstance::Direct(DataInstanceRaw::UInt8(1)),
+                DataInstance::Null,
+            ]),
+        ))
+        .await
+        .unwrap();
+        wal.file
+            .write_all(br#"{"table_name":"test","val"#)
+            .await
+            .unwrap();
+        assert_eq!(WriteAheadLog::read_records(&path).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn corrupt_record_fails_to_read() {
+        let path = get_test_path();
+        fs::write(&path, "{\"table_name\":\"test\"}\n")
+            .await
+            .unwrap();
+        let error = WriteAheadLog::read_records(&path).await.unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert!(error.to_string().starts_with(&format!(
+            "Write-ahead log {} is corrupt at line 1: ",
+            path.display()
+        )));
+    }
+}